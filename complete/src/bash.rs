@@ -0,0 +1,377 @@
+use crate::{Arg, Command, Flag, Value, ValueHint};
+
+/// Create completion script for `bash`
+///
+/// The script defines a completion function and registers it with
+/// `complete -F`. Values are completed for options that take a value, both
+/// when the value is given as a separate word (`--width 80`, only for
+/// required values) and when it is attached with `=` (`--width=80`).
+pub fn render(c: &Command) -> String {
+    let name = c.name;
+    let function = format!("_{}", sanitize(name));
+
+    let mut attached_arms = Vec::new();
+    let mut separate_arms = Vec::new();
+    let mut words = Vec::new();
+
+    for arg in c.completed_args() {
+        let action = render_value_hint(arg);
+
+        let mut attached = Vec::new();
+        let mut separate = Vec::new();
+        for Flag { flag, value } in &arg.short {
+            let flag = escape_word(&format!("-{flag}"));
+            if let Value::Required(_) = value {
+                separate.push(flag.clone());
+            }
+            words.push(flag);
+        }
+        for Flag { flag, value } in &arg.long {
+            let flag = escape_word(&format!("--{flag}"));
+            match value {
+                Value::Required(_) => {
+                    attached.push(flag.clone());
+                    separate.push(flag.clone());
+                }
+                Value::Optional(_) => attached.push(flag.clone()),
+                Value::No => {}
+            }
+            words.push(flag);
+        }
+
+        if !attached.is_empty() {
+            attached_arms.push(render_arm(&attached, &action));
+        }
+        if !separate.is_empty() {
+            separate_arms.push(render_arm(&separate, &action));
+        }
+    }
+
+    let attached_arms = attached_arms.concat();
+    let separate_arms = separate_arms.concat();
+    let attached_arms: String = attached_arms
+        .lines()
+        .map(|l| format!("    {l}\n"))
+        .collect();
+    let words = double_quote(&words.join(" "));
+    let name = escape_word(name);
+
+    format!(
+        "\
+{function}() {{
+    local cur prev opt prefix
+    COMPREPLY=()
+    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    opt=\"\"
+    prefix=\"\"
+
+    # With the default COMP_WORDBREAKS, \"--opt=value\" is split into the
+    # words \"--opt\", \"=\" and \"value\".
+    if [[ \"${{cur}}\" == \"=\" ]]; then
+        opt=\"${{prev}}\"
+        cur=\"\"
+    elif [[ \"${{prev}}\" == \"=\" && ${{COMP_CWORD}} -ge 2 ]]; then
+        opt=\"${{COMP_WORDS[COMP_CWORD-2]}}\"
+    elif [[ \"${{cur}}\" == --*=* ]]; then
+        opt=\"${{cur%%=*}}\"
+        prefix=\"${{opt}}=\"
+        cur=\"${{cur#*=}}\"
+    fi
+
+    if [[ -n \"${{opt}}\" ]]; then
+        case \"${{opt}}\" in
+{attached_arms}        esac
+        return 0
+    fi
+
+    case \"${{prev}}\" in
+{separate_arms}    esac
+
+    COMPREPLY=($(compgen -W {words} -- \"${{cur}}\"))
+}}
+complete -F {function} {name}
+"
+    )
+}
+
+fn render_arm(flags: &[String], action: &str) -> String {
+    let pattern = flags.join("|");
+    format!("        {pattern})\n{action}            return 0\n            ;;\n")
+}
+
+fn render_value_hint(arg: &Arg) -> String {
+    let compgen = match &arg.value {
+        Some(ValueHint::Strings(_) | ValueHint::Described(_)) => {
+            let values = arg
+                .value
+                .as_ref()
+                .and_then(ValueHint::values)
+                .unwrap_or_default();
+            let values: Vec<String> = values.iter().map(|v| escape_word(v)).collect();
+            format!("-W {}", double_quote(&values.join(" ")))
+        }
+        Some(ValueHint::AnyPath | ValueHint::FilePath | ValueHint::ExecutablePath) => "-f".into(),
+        Some(ValueHint::DirPath) => "-d".into(),
+        Some(ValueHint::Username) => "-u".into(),
+        Some(ValueHint::Hostname) => "-A hostname".into(),
+        Some(ValueHint::Unknown) | None => return String::new(),
+    };
+    format!("            COMPREPLY=($(compgen {compgen} -P \"${{prefix}}\" -- \"${{cur}}\"))\n")
+}
+
+/// Escape the characters of a word that the shell would expand
+///
+/// This is needed for the word list of `compgen -W`, which is expanded after
+/// splitting it into words. Whitespace cannot be part of such a word, so it is
+/// replaced by a space, which ends up splitting the word.
+fn escape_word(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            c if c.is_whitespace() => out.push(' '),
+            c if c.is_alphanumeric() || "-_./,+=:@%".contains(c) => out.push(c),
+            c => {
+                out.push('\\');
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// Quote a string as a double-quoted bash string
+fn double_quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Turn the command name into a valid bash function name
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::{
+        test_util::{split_words, word_after, Dialect, ADVERSARIAL},
+        Arg, Command, Flag, Value, ValueHint,
+    };
+
+    fn command() -> Command<'static> {
+        Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![
+                Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::No,
+                    }],
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::No,
+                    }],
+                    help: "show all",
+                    value: None,
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "quiet",
+                        value: Value::No,
+                    }],
+                    help: "be quiet",
+                    ..Arg::default()
+                },
+                Arg {
+                    short: vec![Flag {
+                        flag: "f",
+                        value: Value::Required("FILE"),
+                    }],
+                    long: vec![Flag {
+                        flag: "file",
+                        value: Value::Required("FILE"),
+                    }],
+                    help: "read from FILE",
+                    value: Some(ValueHint::FilePath),
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "dir",
+                        value: Value::Required("DIR"),
+                    }],
+                    help: "change to DIR",
+                    value: Some(ValueHint::DirPath),
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Optional("WHEN"),
+                    }],
+                    help: "use color",
+                    value: Some(ValueHint::Strings(vec![
+                        "always".into(),
+                        "auto".into(),
+                        "never".into(),
+                    ])),
+                    ..Arg::default()
+                },
+                Arg {
+                    short: vec![Flag {
+                        flag: "w",
+                        value: Value::Required("COLS"),
+                    }],
+                    help: "set width",
+                    value: Some(ValueHint::Unknown),
+                    ..Arg::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        assert_eq!(
+            render(&command()),
+            r#"_test() {
+    local cur prev opt prefix
+    COMPREPLY=()
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    opt=""
+    prefix=""
+
+    # With the default COMP_WORDBREAKS, "--opt=value" is split into the
+    # words "--opt", "=" and "value".
+    if [[ "${cur}" == "=" ]]; then
+        opt="${prev}"
+        cur=""
+    elif [[ "${prev}" == "=" && ${COMP_CWORD} -ge 2 ]]; then
+        opt="${COMP_WORDS[COMP_CWORD-2]}"
+    elif [[ "${cur}" == --*=* ]]; then
+        opt="${cur%%=*}"
+        prefix="${opt}="
+        cur="${cur#*=}"
+    fi
+
+    if [[ -n "${opt}" ]]; then
+        case "${opt}" in
+            --file)
+                COMPREPLY=($(compgen -f -P "${prefix}" -- "${cur}"))
+                return 0
+                ;;
+            --dir)
+                COMPREPLY=($(compgen -d -P "${prefix}" -- "${cur}"))
+                return 0
+                ;;
+            --color)
+                COMPREPLY=($(compgen -W "always auto never" -P "${prefix}" -- "${cur}"))
+                return 0
+                ;;
+        esac
+        return 0
+    fi
+
+    case "${prev}" in
+        -f|--file)
+            COMPREPLY=($(compgen -f -P "${prefix}" -- "${cur}"))
+            return 0
+            ;;
+        --dir)
+            COMPREPLY=($(compgen -d -P "${prefix}" -- "${cur}"))
+            return 0
+            ;;
+        -w)
+            return 0
+            ;;
+    esac
+
+    COMPREPLY=($(compgen -W "-a --all --quiet -f --file --dir --color -w" -- "${cur}"))
+}
+complete -F _test test
+"#
+        );
+    }
+
+    #[test]
+    fn flags_without_value_do_not_take_the_next_word() {
+        let out = render(&command());
+        assert!(!out.contains("-a|"));
+        assert!(!out.contains("--all)"));
+        assert!(!out.contains("--quiet)"));
+    }
+
+    #[test]
+    fn optional_value_only_attached() {
+        // `--color WHEN` is not valid, so `--color` only appears in the
+        // arms for attached values.
+        let out = render(&command());
+        assert_eq!(out.matches("--color)").count(), 1);
+    }
+
+    #[test]
+    fn sanitized_function_name() {
+        let c = Command {
+            name: "ls-extra.1",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![],
+        };
+        assert!(render(&c).contains("_ls_extra_1() {"));
+        assert!(render(&c).ends_with("complete -F _ls_extra_1 ls-extra.1\n"));
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let c = Command {
+                name: "test",
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                summary: "",
+                version: "",
+                after_options: "",
+                args: vec![Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Required("WHEN"),
+                    }],
+                    help: s,
+                    value: Some(ValueHint::Described(vec![
+                        (s.to_string(), s.to_string()),
+                        ("plain".into(), "".into()),
+                    ])),
+                    ..Arg::default()
+                }],
+            };
+            let words = split_words(&render(&c), Dialect::Posix).unwrap();
+            let values = word_after(&words, "-W").unwrap();
+            let mut expected: Vec<&str> = s.split_whitespace().collect();
+            expected.push("plain");
+            assert_eq!(split_words(values, Dialect::Posix).unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{Command, Flag, Value, ValueHint};
+
+/// Create completion script for `fish`
+pub fn render(c: &Command) -> String {
+    let mut out = String::new();
+    let name = &c.name;
+    for arg in c.completed_args() {
+        let mut line = format!("complete -c {name}");
+        for Flag { flag, .. } in &arg.short {
+            line.push_str(&format!(" -s {flag}"));
+        }
+        for Flag { flag, .. } in &arg.long {
+            line.push_str(&format!(" -l {flag}"));
+        }
+        let help = arg.help.lines().next().unwrap_or_default();
+        line.push_str(&format!(" -d {}", quote(help)));
+        let requires_value = arg
+            .short
+            .iter()
+            .chain(&arg.long)
+            .any(|f| matches!(f.value, Value::Required(_)));
+        if requires_value {
+            line.push_str(" -r");
+        }
+        if let Some(value) = &arg.value {
+            line.push_str(&render_value_hint(value));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    for arg in &c.positional {
+        let mut line = format!("complete -c {name}");
+        let help = arg.help.lines().next().unwrap_or_default();
+        if !help.is_empty() {
+            line.push_str(&format!(" -d {}", quote(help)));
+        }
+        // Without a hint, we assume that the arguments are files.
+        match &arg.value {
+            Some(value) => line.push_str(&render_value_hint(value)),
+            None => line.push_str(" -F"),
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+fn render_value_hint(value: &ValueHint) -> String {
+    match value {
+        ValueHint::Strings(s) => {
+            let values: Vec<String> = s.iter().map(|s| escape_value(s)).collect();
+            format!(" -f -a '{}'", values.join(" "))
+        }
+        ValueHint::Described(s) => {
+            let values: Vec<String> = s
+                .iter()
+                .map(|(value, description)| {
+                    let value = escape_value(value);
+                    if description.is_empty() {
+                        value
+                    } else {
+                        format!("{value}\\t\"{}\"", escape_description(description))
+                    }
+                })
+                .collect();
+            format!(" -f -a '{}'", values.join(" "))
+        }
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::ExecutablePath => String::from(" -F"),
+        ValueHint::DirPath => " -f -a '(__fish_complete_directories)'".into(),
+        ValueHint::Unknown => " -f".into(),
+        ValueHint::Username => " -f -a '(__fish_complete_users)'".into(),
+        ValueHint::Hostname => " -f -a '(__fish_print_hostnames)'".into(),
+    }
+}
+
+/// Quote a string as a single-quoted fish string
+///
+/// Within single quotes only `\'` and `\\` are escapes. Newlines are replaced
+/// by spaces, because the string is a one-line description.
+fn quote(s: &str) -> String {
+    let mut out = String::from("'");
+    for c in s.chars() {
+        match c {
+            '\\' | '\'' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// Escape a value for the single-quoted argument of `-a`
+///
+/// Fish splits that argument into words again, so values with characters
+/// other than letters, digits and some punctuation are put in double quotes.
+fn escape_value(s: &str) -> String {
+    let is_plain = |c: char| c.is_alphanumeric() || "-_.,/+=:@".contains(c);
+    if !s.is_empty() && s.chars().all(is_plain) {
+        s.to_string()
+    } else {
+        format!("\"{}\"", escape_description(s))
+    }
+}
+
+/// Escape a description for a double-quoted string within the single-quoted
+/// argument of `-a`, which fish splits into words again.
+fn escape_description(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '\n' | '\r' => out.push(' '),
+            '\\' => out.push_str("\\\\\\\\"),
+            '"' | '$' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\'' => out.push_str("\\'"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::{
+        test_util::{split_words, word_after, Dialect, ADVERSARIAL},
+        Arg, Command, Flag, PositionalArg, Value, ValueHint,
+    };
+
+    #[test]
+    fn positional() {
+        let c = Command {
+            name: "test",
+            positional: vec![
+                PositionalArg {
+                    name: "MODE",
+                    num_args: 1..=1,
+                    help: "the mode",
+                    value: Some(ValueHint::Strings(vec!["fast".into(), "slow".into()])),
+                    last: false,
+                },
+                PositionalArg {
+                    name: "FILES",
+                    num_args: 0..=usize::MAX,
+                    help: "",
+                    value: None,
+                    last: true,
+                },
+            ],
+            usage: "[OPTION]... MODE [FILES]...",
+            extra: vec![],
+            args: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(
+            render(&c),
+            "complete -c test -d 'the mode' -f -a 'fast slow'\ncomplete -c test -F\n"
+        )
+    }
+
+    #[test]
+    fn short() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                short: vec![Flag {
+                    flag: "a",
+                    value: Value::No,
+                }],
+                help: "some flag",
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(render(&c), "complete -c test -s a -d 'some flag'\n",)
+    }
+
+    #[test]
+    fn long() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                long: vec![Flag {
+                    flag: "all",
+                    value: Value::No,
+                }],
+                help: "some flag",
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(render(&c), "complete -c test -l all -d 'some flag'\n",)
+    }
+
+    #[test]
+    fn deprecated() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![
+                Arg {
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::No,
+                    }],
+                    help: "some flag",
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "old",
+                        value: Value::No,
+                    }],
+                    help: "an old flag",
+                    deprecated: true,
+                    ..Arg::default()
+                },
+            ],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(render(&c), "complete -c test -l all -d 'some flag'\n",)
+    }
+
+    #[test]
+    fn value_hints() {
+        let args = [
+            (
+                ValueHint::Strings(vec!["all".into(), "none".into()]),
+                "-f -a 'all none'",
+            ),
+            (
+                ValueHint::Described(vec![
+                    ("all".into(), "show \"all\" of it's $items".into()),
+                    ("none".into(), "".into()),
+                ]),
+                r#"-f -a 'all\t"show \"all\" of it\'s \$items" none'"#,
+            ),
+            (ValueHint::Unknown, "-f"),
+            (ValueHint::AnyPath, "-F"),
+            (ValueHint::FilePath, "-F"),
+            (ValueHint::DirPath, "-f -a '(__fish_complete_directories)'"),
+            (ValueHint::ExecutablePath, "-F"),
+            (ValueHint::Username, "-f -a '(__fish_complete_users)'"),
+            (ValueHint::Hostname, "-f -a '(__fish_print_hostnames)'"),
+        ];
+        for (hint, expected) in args {
+            let c = Command {
+                name: "test",
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                args: vec![Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::Required("VALUE"),
+                    }],
+                    long: vec![],
+                    help: "some flag",
+                    value: Some(hint),
+                    ..Arg::default()
+                }],
+                summary: "",
+                version: "",
+                after_options: "",
+            };
+            assert_eq!(
+                render(&c),
+                format!("complete -c test -s a -d 'some flag' -r {expected}\n")
+            )
+        }
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let c = Command {
+                name: "test",
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                args: vec![
+                    Arg {
+                        long: vec![Flag {
+                            flag: "all",
+                            value: Value::Required("VALUE"),
+                        }],
+                        help: s,
+                        value: Some(ValueHint::Strings(vec![s.to_string(), "plain".into()])),
+                        ..Arg::default()
+                    },
+                    Arg {
+                        long: vec![Flag {
+                            flag: "color",
+                            value: Value::Required("WHEN"),
+                        }],
+                        help: "some flag",
+                        value: Some(ValueHint::Described(vec![
+                            (s.to_string(), s.to_string()),
+                            ("plain".into(), "".into()),
+                        ])),
+                        ..Arg::default()
+                    },
+                ],
+                summary: "",
+                version: "",
+                after_options: "",
+            };
+            let out = render(&c);
+            let lines: Vec<&str> = out.lines().collect();
+            assert_eq!(lines.len(), 2, "{out}");
+
+            let words = split_words(lines[0], Dialect::Fish).unwrap();
+            let help = s.lines().next().unwrap();
+            assert_eq!(word_after(&words, "-d"), Some(help));
+            let values = word_after(&words, "-a").unwrap();
+            assert_eq!(
+                split_words(values, Dialect::Fish).unwrap(),
+                [s.replace('\n', " ").as_str(), "plain"]
+            );
+
+            let words = split_words(lines[1], Dialect::Fish).unwrap();
+            let values = word_after(&words, "-a").unwrap();
+            assert_eq!(split_words(values, Dialect::Fish).unwrap().len(), 2);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Conventional locations for installed completion scripts.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{render_to, Command};
+
+/// The shells that [`write_completions`] writes a completion script for
+pub const SHELLS: &[&str] = &["bash", "fish", "zsh", "powershell", "nu"];
+
+/// The name of the file that the completion script for `name` is loaded from
+///
+/// Besides shells, `"man"`, `"md"` and `"json"` are accepted, like in
+/// [`render`](crate::render).
+///
+/// Panics if the shell is not supported.
+pub fn completion_file_name(name: &str, shell: &str) -> PathBuf {
+    let file_name = match shell {
+        // bash-completion loads completions by the name of the command
+        "bash" => name.to_string(),
+        "fish" => format!("{name}.fish"),
+        "zsh" => format!("_{name}"),
+        "powershell" | "pwsh" => format!("_{name}.ps1"),
+        "nu" | "nushell" => format!("{name}.nu"),
+        "man" => format!("{name}.1"),
+        "md" => format!("{name}.md"),
+        "json" => format!("{name}.json"),
+        _ => panic!("unknown shell '{shell}'!"),
+    };
+    PathBuf::from(file_name)
+}
+
+/// The directory below `prefix` where the shell looks for completion scripts
+///
+/// For example, with a prefix of `/usr/local`, fish completions go into
+/// `/usr/local/share/fish/vendor_completions.d`. PowerShell and nushell do
+/// not load completions from a directory, so their scripts are put in a
+/// directory of this crate's choosing that the user has to source.
+///
+/// Panics if the shell is not supported.
+pub fn default_install_dir(shell: &str, prefix: &Path) -> PathBuf {
+    let dir = match shell {
+        "bash" => "share/bash-completion/completions",
+        "fish" => "share/fish/vendor_completions.d",
+        "zsh" => "share/zsh/site-functions",
+        "powershell" | "pwsh" => "share/powershell/completions",
+        "nu" | "nushell" => "share/nushell/completions",
+        "man" => "share/man/man1",
+        "md" | "json" => "share/doc",
+        _ => panic!("unknown shell '{shell}'!"),
+    };
+    prefix.join(dir)
+}
+
+/// Write the completion scripts of all [`SHELLS`] into `dir`
+///
+/// The directory is created if it does not exist yet. The files are named
+/// with [`completion_file_name`]. Returns the paths of the written files.
+pub fn write_completions(c: &Command, dir: &Path) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for shell in SHELLS {
+        let path = dir.join(completion_file_name(c.name, shell));
+        let mut w = BufWriter::new(File::create(&path)?);
+        render_to(c, shell, &mut w)?;
+        w.flush()?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{completion_file_name, default_install_dir, write_completions, SHELLS};
+    use crate::{render, Arg, Command, Flag, Value};
+
+    #[test]
+    fn file_names() {
+        let name = |shell| completion_file_name("ls", shell);
+        assert_eq!(name("bash"), Path::new("ls"));
+        assert_eq!(name("fish"), Path::new("ls.fish"));
+        assert_eq!(name("zsh"), Path::new("_ls"));
+        assert_eq!(name("powershell"), Path::new("_ls.ps1"));
+        assert_eq!(name("pwsh"), Path::new("_ls.ps1"));
+        assert_eq!(name("nu"), Path::new("ls.nu"));
+        assert_eq!(name("man"), Path::new("ls.1"));
+        assert_eq!(name("md"), Path::new("ls.md"));
+        assert_eq!(name("json"), Path::new("ls.json"));
+    }
+
+    #[test]
+    fn install_dirs() {
+        let dir = |shell| default_install_dir(shell, Path::new("/usr/local"));
+        assert_eq!(
+            dir("bash"),
+            Path::new("/usr/local/share/bash-completion/completions")
+        );
+        assert_eq!(
+            dir("fish"),
+            Path::new("/usr/local/share/fish/vendor_completions.d")
+        );
+        assert_eq!(dir("zsh"), Path::new("/usr/local/share/zsh/site-functions"));
+        assert_eq!(dir("man"), Path::new("/usr/local/share/man/man1"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn unknown_shell() {
+        completion_file_name("ls", "cmd");
+    }
+
+    #[test]
+    fn write_to_dir() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![Arg {
+                short: vec![Flag {
+                    flag: "a",
+                    value: Value::No,
+                }],
+                help: "some flag",
+                ..Arg::default()
+            }],
+        };
+
+        let dir: PathBuf = std::env::temp_dir()
+            .join(format!("complete-test-{}", std::process::id()))
+            .join("completions");
+        let paths = write_completions(&c, &dir).unwrap();
+        assert_eq!(paths.len(), SHELLS.len());
+        for (path, shell) in paths.iter().zip(SHELLS) {
+            assert_eq!(path, &dir.join(completion_file_name("test", shell)));
+            assert_eq!(std::fs::read_to_string(path).unwrap(), render(&c, shell));
+        }
+
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,381 @@
+use crate::{Arg, Command, Flag, PositionalArg, Value, ValueHint};
+
+/// Create a JSON description of the command
+///
+/// The arguments are in the order they are declared, so the output only
+/// changes when the command does. A value that is not known, like the
+/// default of an option without one or the end of an unbounded range, is
+/// `null`.
+pub fn render(c: &Command) -> String {
+    let json = Json::Object(vec![
+        ("name", Json::String(c.name)),
+        ("version", Json::String(c.version)),
+        ("summary", Json::String(c.summary)),
+        ("usage", Json::String(c.usage)),
+        ("after_options", Json::String(c.after_options)),
+        ("args", Json::Array(c.args.iter().map(arg).collect())),
+        (
+            "positional",
+            Json::Array(c.positional.iter().map(positional).collect()),
+        ),
+    ]);
+    let mut out = String::new();
+    json.write(&mut out, 0);
+    out.push('\n');
+    out
+}
+
+fn arg<'a>(arg: &'a Arg) -> Json<'a> {
+    Json::Object(vec![
+        ("short", Json::Array(arg.short.iter().map(flag).collect())),
+        ("long", Json::Array(arg.long.iter().map(flag).collect())),
+        ("help", Json::String(arg.help)),
+        ("value", value_hint(&arg.value)),
+        ("default", arg.default.map_or(Json::Null, Json::String)),
+        ("category", arg.category.map_or(Json::Null, Json::String)),
+        ("deprecated", Json::Bool(arg.deprecated)),
+        ("hidden_help", Json::Bool(arg.hidden_help)),
+        ("hidden_complete", Json::Bool(arg.hidden_complete)),
+    ])
+}
+
+fn flag<'a>(flag: &'a Flag) -> Json<'a> {
+    let (takes_value, name) = match flag.value {
+        Value::No => ("no", Json::Null),
+        Value::Required(name) => ("required", Json::String(name)),
+        Value::Optional(name) => ("optional", Json::String(name)),
+    };
+    Json::Object(vec![
+        ("flag", Json::String(flag.flag)),
+        ("takes_value", Json::String(takes_value)),
+        ("value_name", name),
+    ])
+}
+
+fn positional<'a>(arg: &'a PositionalArg) -> Json<'a> {
+    let max = match *arg.num_args.end() {
+        usize::MAX => Json::Null,
+        end => Json::Number(end),
+    };
+    Json::Object(vec![
+        ("name", Json::String(arg.name)),
+        ("min", Json::Number(*arg.num_args.start())),
+        ("max", max),
+        ("help", Json::String(arg.help)),
+        ("value", value_hint(&arg.value)),
+        ("last", Json::Bool(arg.last)),
+    ])
+}
+
+fn value_hint(hint: &Option<ValueHint>) -> Json<'_> {
+    let Some(hint) = hint else {
+        return Json::Null;
+    };
+    let kind = match hint {
+        ValueHint::Strings(_) => "strings",
+        ValueHint::Described(_) => "described",
+        ValueHint::Unknown => "unknown",
+        ValueHint::AnyPath => "any_path",
+        ValueHint::FilePath => "file_path",
+        ValueHint::ExecutablePath => "executable_path",
+        ValueHint::DirPath => "dir_path",
+        ValueHint::Username => "username",
+        ValueHint::Hostname => "hostname",
+    };
+    let mut fields = vec![("kind", Json::String(kind))];
+    match hint {
+        ValueHint::Strings(values) => fields.push((
+            "values",
+            Json::Array(values.iter().map(|v| Json::String(v)).collect()),
+        )),
+        ValueHint::Described(values) => fields.push((
+            "values",
+            Json::Array(
+                values
+                    .iter()
+                    .map(|(value, description)| {
+                        Json::Object(vec![
+                            ("value", Json::String(value)),
+                            ("description", Json::String(description)),
+                        ])
+                    })
+                    .collect(),
+            ),
+        )),
+        _ => {}
+    }
+    Json::Object(fields)
+}
+
+/// A JSON value, with the fields of objects in the order they are written
+enum Json<'a> {
+    Null,
+    Bool(bool),
+    Number(usize),
+    String(&'a str),
+    Array(Vec<Json<'a>>),
+    Object(Vec<(&'static str, Json<'a>)>),
+}
+
+impl Json<'_> {
+    /// Write the value indented by `depth` levels of two spaces
+    fn write(&self, out: &mut String, depth: usize) {
+        let indent = |out: &mut String, depth: usize| {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+        };
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(&b.to_string()),
+            Json::Number(n) => out.push_str(&n.to_string()),
+            Json::String(s) => escape(out, s),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    indent(out, depth + 1);
+                    item.write(out, depth + 1);
+                }
+                indent(out, depth);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    indent(out, depth + 1);
+                    escape(out, key);
+                    out.push_str(": ");
+                    value.write(out, depth + 1);
+                }
+                indent(out, depth);
+                out.push('}');
+            }
+        }
+    }
+}
+
+/// Write a string literal, with the characters escaped that must be
+fn escape(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::{test_util::ADVERSARIAL, Arg, Command, Flag, PositionalArg, Value, ValueHint};
+
+    #[test]
+    fn snapshot() {
+        let c = Command {
+            name: "test",
+            summary: "Do some things.",
+            version: "1.0.0",
+            after_options: "Some \"more\" text.",
+            usage: "[OPTION]... [FILE]...",
+            extra: vec![],
+            args: vec![
+                Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::No,
+                    }],
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::No,
+                    }],
+                    help: "show all",
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Optional("WHEN"),
+                    }],
+                    help: "use color\nwhen wanted",
+                    value: Some(ValueHint::Strings(vec!["always".into(), "never".into()])),
+                    default: Some("always"),
+                    category: Some("Output"),
+                    ..Arg::default()
+                },
+                Arg {
+                    short: vec![Flag {
+                        flag: "s",
+                        value: Value::Required("SIGNAL"),
+                    }],
+                    help: "send SIGNAL",
+                    value: Some(ValueHint::Described(vec![("HUP".into(), "hang up".into())])),
+                    ..Arg::default()
+                },
+            ],
+            positional: vec![PositionalArg {
+                name: "FILE",
+                num_args: 0..=usize::MAX,
+                help: "",
+                value: Some(ValueHint::AnyPath),
+                last: false,
+            }],
+        };
+
+        assert_eq!(
+            render(&c),
+            r#"{
+  "name": "test",
+  "version": "1.0.0",
+  "summary": "Do some things.",
+  "usage": "[OPTION]... [FILE]...",
+  "after_options": "Some \"more\" text.",
+  "args": [
+    {
+      "short": [
+        {
+          "flag": "a",
+          "takes_value": "no",
+          "value_name": null
+        }
+      ],
+      "long": [
+        {
+          "flag": "all",
+          "takes_value": "no",
+          "value_name": null
+        }
+      ],
+      "help": "show all",
+      "value": null,
+      "default": null,
+      "category": null,
+      "deprecated": false,
+      "hidden_help": false,
+      "hidden_complete": false
+    },
+    {
+      "short": [],
+      "long": [
+        {
+          "flag": "color",
+          "takes_value": "optional",
+          "value_name": "WHEN"
+        }
+      ],
+      "help": "use color\nwhen wanted",
+      "value": {
+        "kind": "strings",
+        "values": [
+          "always",
+          "never"
+        ]
+      },
+      "default": "always",
+      "category": "Output",
+      "deprecated": false,
+      "hidden_help": false,
+      "hidden_complete": false
+    },
+    {
+      "short": [
+        {
+          "flag": "s",
+          "takes_value": "required",
+          "value_name": "SIGNAL"
+        }
+      ],
+      "long": [],
+      "help": "send SIGNAL",
+      "value": {
+        "kind": "described",
+        "values": [
+          {
+            "value": "HUP",
+            "description": "hang up"
+          }
+        ]
+      },
+      "default": null,
+      "category": null,
+      "deprecated": false,
+      "hidden_help": false,
+      "hidden_complete": false
+    }
+  ],
+  "positional": [
+    {
+      "name": "FILE",
+      "min": 0,
+      "max": null,
+      "help": "",
+      "value": {
+        "kind": "any_path"
+      },
+      "last": false
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn adversarial() {
+        let command = |s| Command {
+            name: s,
+            summary: s,
+            version: "",
+            after_options: "",
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                long: vec![Flag {
+                    flag: s,
+                    value: Value::Required(s),
+                }],
+                help: s,
+                ..Arg::default()
+            }],
+            positional: vec![],
+        };
+        let plain = render(&command("plain")).lines().count();
+        for s in ADVERSARIAL {
+            let out = render(&command(s));
+            // Every string stays on its line and only the quotes around it
+            // are not escaped.
+            assert_eq!(out.lines().count(), plain, "{s:?}");
+            for line in out.lines() {
+                let unescaped_quotes = line
+                    .replace("\\\\", "")
+                    .replace("\\\"", "")
+                    .matches('"')
+                    .count();
+                assert!(unescaped_quotes % 2 == 0, "{line}");
+            }
+        }
+        let c = Command {
+            name: "\u{1b}[1m\u{7f}",
+            summary: "",
+            version: "",
+            after_options: "",
+            usage: "",
+            extra: vec![],
+            args: vec![],
+            positional: vec![],
+        };
+        assert!(render(&c).contains(r#""name": "\u001b[1m\u007f","#));
+    }
+}
@@ -0,0 +1,188 @@
+//! Generation of shell completions from the structure of a command.
+//!
+//! The `Arguments` derive generates a [`Command`] describing all the options
+//! of a utility, which can then be rendered for a specific shell with
+//! [`render`]. The [`install`] module has helpers for writing the scripts to
+//! the locations where the shells find them.
+
+mod bash;
+mod fish;
+pub mod install;
+mod json;
+mod man;
+mod md;
+mod nu;
+mod powershell;
+#[cfg(test)]
+mod test_util;
+mod zsh;
+
+use std::{io, io::Write, ops::RangeInclusive};
+
+/// A description of a command and its arguments
+pub struct Command<'a> {
+    pub name: &'a str,
+    pub summary: &'a str,
+    pub version: &'a str,
+    pub after_options: &'a str,
+    pub args: Vec<Arg<'a>>,
+    pub positional: Vec<PositionalArg<'a>>,
+    /// The arguments in the usage line, like `[OPTION]... [FILE]...`, without
+    /// the name
+    pub usage: &'a str,
+    /// Hand-written additions to the completion script of a shell, as the
+    /// name of the shell and the snippet that is appended to the script
+    pub extra: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Command<'a> {
+    /// The options that are completed by the shells
+    ///
+    /// Deprecated options still work, but are not suggested.
+    pub(crate) fn completed_args(&self) -> impl Iterator<Item = &Arg<'a>> {
+        self.args
+            .iter()
+            .filter(|arg| !arg.deprecated && !arg.hidden_complete)
+    }
+
+    /// The options that are documented in the man page and markdown
+    pub(crate) fn documented_args(&self) -> impl Iterator<Item = &Arg<'a>> {
+        self.args.iter().filter(|arg| !arg.hidden_help)
+    }
+
+    /// The documented options grouped by category
+    ///
+    /// The categories are in the order of their first option. The options
+    /// without a category come last, in a group without a name.
+    pub(crate) fn args_by_category(&self) -> Vec<(Option<&'a str>, Vec<&Arg<'a>>)> {
+        let mut groups: Vec<(Option<&str>, Vec<&Arg>)> = Vec::new();
+        for arg in self.documented_args() {
+            let Some(category) = arg.category else {
+                continue;
+            };
+            match groups.iter_mut().find(|(c, _)| *c == Some(category)) {
+                Some((_, args)) => args.push(arg),
+                None => groups.push((Some(category), vec![arg])),
+            }
+        }
+        let uncategorized: Vec<_> = self
+            .documented_args()
+            .filter(|a| a.category.is_none())
+            .collect();
+        if !uncategorized.is_empty() {
+            groups.push((None, uncategorized));
+        }
+        groups
+    }
+}
+
+/// A single option, which may have multiple short and long spellings
+#[derive(Default)]
+pub struct Arg<'a> {
+    pub short: Vec<Flag<'a>>,
+    pub long: Vec<Flag<'a>>,
+    pub help: &'a str,
+    pub value: Option<ValueHint>,
+    /// The value used when an optional value is not given
+    pub default: Option<&'a str>,
+    /// The heading the option is listed under, if any
+    pub category: Option<&'a str>,
+    /// Whether the option is deprecated, in which case it is only documented
+    /// and not completed
+    pub deprecated: bool,
+    /// Whether the option is left out of the documentation, but still
+    /// completed
+    pub hidden_help: bool,
+    /// Whether the option is left out of the completions, but still
+    /// documented
+    pub hidden_complete: bool,
+}
+
+/// A positional argument
+pub struct PositionalArg<'a> {
+    /// The name of the value, like `FILE`
+    pub name: &'a str,
+    /// The number of values, where an end of `usize::MAX` means unbounded
+    pub num_args: RangeInclusive<usize>,
+    pub help: &'a str,
+    /// The kind of values, if known
+    pub value: Option<ValueHint>,
+    /// Whether this argument takes all remaining arguments as is
+    pub last: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Flag<'a> {
+    pub flag: &'a str,
+    pub value: Value<'a>,
+}
+
+/// Whether a flag takes a value and, if so, the name of that value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Value<'a> {
+    Required(&'a str),
+    Optional(&'a str),
+    No,
+}
+
+/// The kind of values an option accepts, used to determine how to complete it
+pub enum ValueHint {
+    Strings(Vec<String>),
+    /// Values with a description of each value
+    Described(Vec<(String, String)>),
+    Unknown,
+    AnyPath,
+    FilePath,
+    ExecutablePath,
+    DirPath,
+    Username,
+    Hostname,
+}
+
+impl ValueHint {
+    /// The possible values, if they are known
+    pub fn values(&self) -> Option<Vec<&str>> {
+        match self {
+            Self::Strings(s) => Some(s.iter().map(String::as_str).collect()),
+            Self::Described(s) => Some(s.iter().map(|(v, _)| v.as_str()).collect()),
+            _ => None,
+        }
+    }
+}
+
+/// Render the completion script for the given shell
+///
+/// Besides shells, `"man"` and `"md"` are accepted to render a man page
+/// or markdown documentation, and `"json"` to describe the command for
+/// other tools. The [extra snippet](Command::extra) for the shell, if any,
+/// is appended to the generated script.
+///
+/// Panics if the shell is not supported.
+pub fn render(c: &Command, shell: &str) -> String {
+    let (mut script, shell) = match shell {
+        "fish" => (fish::render(c), "fish"),
+        "bash" => (bash::render(c), "bash"),
+        "zsh" => (zsh::render(c), "zsh"),
+        "powershell" | "pwsh" => (powershell::render(c), "powershell"),
+        "nu" | "nushell" => (nu::render(c), "nu"),
+        "man" => return man::render(c),
+        "md" => return md::render(c),
+        "json" => return json::render(c),
+        "sh" | "csh" | "elvish" => {
+            panic!("shell '{shell}' completion not supported yet!")
+        }
+        _ => panic!("unknown shell '{shell}'!"),
+    };
+    for (_, extra) in c.extra.iter().filter(|(s, _)| *s == shell) {
+        script.push_str(extra);
+    }
+    script
+}
+
+/// Write the completion script for the given shell to `w`
+///
+/// This accepts the same shells as [`render`] and panics if the shell is not
+/// supported.
+pub fn render_to(c: &Command, shell: &str, w: &mut impl Write) -> io::Result<()> {
+    w.write_all(render(c, shell).as_bytes())
+}
@@ -0,0 +1,386 @@
+use crate::{Arg, Command, Flag, PositionalArg, Value};
+
+/// Create a man page in roff format
+///
+/// The page has the NAME, SYNOPSIS, DESCRIPTION and OPTIONS sections. The
+/// text after the options in the help file is added as paragraphs at the end
+/// of the OPTIONS section, just like in the help output.
+pub fn render(c: &Command) -> String {
+    let name = escape(c.name);
+    let title = escape_arg(&c.name.to_uppercase());
+    let footer = escape_arg(&format!("{} {}", c.name, c.version));
+    let summary = c.summary.lines().next().unwrap_or_default();
+
+    let mut out = format!(".TH {title} 1 \"\" \"{footer}\" \"User Commands\"\n");
+
+    out.push_str(".SH NAME\n");
+    if summary.is_empty() {
+        out.push_str(&format!("{name}\n"));
+    } else {
+        out.push_str(&format!("{name} \\- {}\n", escape(summary)));
+    }
+
+    out.push_str(".SH SYNOPSIS\n");
+    out.push_str(&format!(
+        "\\fB{name}\\fR [\\fIOPTIONS\\fR] {}\n",
+        render_synopsis_positional(&c.positional)
+    ));
+
+    if !c.summary.is_empty() {
+        out.push_str(".SH DESCRIPTION\n");
+        out.push_str(&render_paragraphs(c.summary));
+    }
+
+    if c.documented_args().next().is_some() || !c.after_options.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        let groups = c.args_by_category();
+        // Subsections are only needed to separate categories.
+        let has_categories = groups.iter().any(|(category, _)| category.is_some());
+        for (category, args) in groups {
+            if has_categories {
+                let heading = escape_arg(category.unwrap_or("Options"));
+                out.push_str(&format!(".SS {heading}\n"));
+            }
+            for arg in args {
+                out.push_str(&render_arg(arg));
+            }
+        }
+        if !c.after_options.is_empty() {
+            out.push_str(&render_paragraphs(c.after_options));
+        }
+    }
+
+    out
+}
+
+fn render_arg(arg: &Arg) -> String {
+    let short = arg.short.iter().map(|Flag { flag, value }| {
+        let flag = format!("\\fB\\-{}\\fR", escape(flag));
+        match value {
+            Value::No => flag,
+            Value::Required(name) => format!("{flag} \\fI{}\\fR", escape(name)),
+            Value::Optional(name) => format!("{flag}[\\fI{}\\fR]", escape(name)),
+        }
+    });
+    let long = arg.long.iter().map(|Flag { flag, value }| {
+        let flag = format!("\\fB\\-\\-{}\\fR", escape(flag));
+        match value {
+            Value::No => flag,
+            Value::Required(name) => format!("{flag}=\\fI{}\\fR", escape(name)),
+            Value::Optional(name) => format!("{flag}[=\\fI{}\\fR]", escape(name)),
+        }
+    });
+    let flags: Vec<String> = short.chain(long).collect();
+    let marker = if arg.deprecated { " (deprecated)" } else { "" };
+
+    let mut out = format!(".TP\n{}{marker}\n", flags.join(", "));
+    for line in arg.help.lines() {
+        if !line.is_empty() {
+            out.push_str(&escape_line(line));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Render the positional arguments for the synopsis
+///
+/// Without any known positional arguments, any arguments are allowed.
+fn render_synopsis_positional(args: &[PositionalArg]) -> String {
+    if args.is_empty() {
+        return "[\\fIARGS\\fR]".into();
+    }
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            let mut s = format!("\\fI{}\\fR", escape(arg.name));
+            if *arg.num_args.start() == 0 {
+                s = format!("[{s}]");
+            }
+            // The last argument takes all remaining arguments.
+            if *arg.num_args.end() > 1 || arg.last {
+                s.push_str("...");
+            }
+            s
+        })
+        .collect();
+    args.join(" ")
+}
+
+/// Render text with paragraphs separated by blank lines
+fn render_paragraphs(text: &str) -> String {
+    let mut out = String::new();
+    for paragraph in text.split("\n\n") {
+        let paragraph = paragraph.trim();
+        if paragraph.is_empty() {
+            continue;
+        }
+        out.push_str(".PP\n");
+        for line in paragraph.lines() {
+            out.push_str(&escape_line(line.trim()));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Escape a line of text, making sure it is not interpreted as a request
+fn escape_line(line: &str) -> String {
+    let line = escape(line);
+    if line.starts_with('.') || line.starts_with('\'') {
+        format!("\\&{line}")
+    } else {
+        line
+    }
+}
+
+/// Escape backslashes and dashes for roff
+///
+/// Newlines are replaced by spaces, because the text must stay on one line.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\e")
+        .replace('-', "\\-")
+        .replace(['\n', '\r'], " ")
+}
+
+/// Escape text for an argument of a macro, which may be quoted
+fn escape_arg(s: &str) -> String {
+    escape(s).replace('"', "\\(dq")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render, render_synopsis_positional};
+    use crate::{test_util::ADVERSARIAL, Arg, Command, Flag, PositionalArg, Value};
+
+    #[test]
+    fn snapshot() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "Do some things.\n\nAnd some more things.",
+            version: "1.0.0",
+            after_options:
+                "The WHEN is one of 'always' or 'never'.\n.dotted line\n\nUse \\ and -- carefully.",
+            args: vec![
+                Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::No,
+                    }],
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::No,
+                    }],
+                    help: "show all",
+                    ..Arg::default()
+                },
+                Arg {
+                    short: vec![Flag {
+                        flag: "w",
+                        value: Value::Required("N"),
+                    }],
+                    long: vec![Flag {
+                        flag: "width",
+                        value: Value::Required("N"),
+                    }],
+                    help: "set the width\nto N columns",
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Optional("WHEN"),
+                    }],
+                    help: "use color",
+                    ..Arg::default()
+                },
+            ],
+        };
+
+        assert_eq!(
+            render(&c),
+            r#".TH TEST 1 "" "test 1.0.0" "User Commands"
+.SH NAME
+test \- Do some things.
+.SH SYNOPSIS
+\fBtest\fR [\fIOPTIONS\fR] [\fIARGS\fR]
+.SH DESCRIPTION
+.PP
+Do some things.
+.PP
+And some more things.
+.SH OPTIONS
+.TP
+\fB\-a\fR, \fB\-\-all\fR
+show all
+.TP
+\fB\-w\fR \fIN\fR, \fB\-\-width\fR=\fIN\fR
+set the width
+to N columns
+.TP
+\fB\-\-color\fR[=\fIWHEN\fR]
+use color
+.PP
+The WHEN is one of 'always' or 'never'.
+\&.dotted line
+.PP
+Use \e and \-\- carefully.
+"#
+        );
+    }
+
+    #[test]
+    fn synopsis() {
+        let positional = |name, num_args, last| PositionalArg {
+            name,
+            num_args,
+            help: "",
+            value: None,
+            last,
+        };
+        assert_eq!(
+            render_synopsis_positional(&[
+                positional("SOURCE", 1..=1, false),
+                positional("DEST", 0..=1, false),
+                positional("FILES", 0..=usize::MAX, false),
+                positional("ARGS", 1..=1, true),
+            ]),
+            r"\fISOURCE\fR [\fIDEST\fR] [\fIFILES\fR]... \fIARGS\fR..."
+        );
+    }
+
+    #[test]
+    fn minimal() {
+        let c = Command {
+            name: "my-test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![],
+        };
+        assert_eq!(
+            render(&c),
+            r#".TH MY\-TEST 1 "" "my\-test " "User Commands"
+.SH NAME
+my\-test
+.SH SYNOPSIS
+\fBmy\-test\fR [\fIOPTIONS\fR] [\fIARGS\fR]
+"#
+        );
+    }
+
+    #[test]
+    fn categories() {
+        let arg = |flag, category| Arg {
+            long: vec![Flag {
+                flag,
+                value: Value::No,
+            }],
+            category,
+            ..Arg::default()
+        };
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![
+                arg("sort", Some("Sorting")),
+                arg("all", None),
+                arg("long", Some("Format")),
+                arg("reverse", Some("Sorting")),
+            ],
+        };
+        let out = render(&c);
+        let (_, options) = out.split_once(".SH OPTIONS\n").unwrap();
+        assert_eq!(
+            options,
+            r".SS Sorting
+.TP
+\fB\-\-sort\fR
+.TP
+\fB\-\-reverse\fR
+.SS Format
+.TP
+\fB\-\-long\fR
+.SS Options
+.TP
+\fB\-\-all\fR
+"
+        );
+    }
+
+    #[test]
+    fn deprecated() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![Arg {
+                long: vec![Flag {
+                    flag: "retry",
+                    value: Value::No,
+                }],
+                help: "keep trying to open a file",
+                deprecated: true,
+                ..Arg::default()
+            }],
+        };
+        let out = render(&c);
+        let (_, options) = out.split_once(".SH OPTIONS\n").unwrap();
+        assert_eq!(
+            options,
+            r".TP
+\fB\-\-retry\fR (deprecated)
+keep trying to open a file
+"
+        );
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let c = Command {
+                name: s,
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                summary: s,
+                version: s,
+                after_options: s,
+                args: vec![Arg {
+                    long: vec![Flag {
+                        flag: s,
+                        value: Value::Required(s),
+                    }],
+                    help: s,
+                    category: Some(s),
+                    ..Arg::default()
+                }],
+            };
+            for line in render(&c).lines() {
+                // Every line is either text or a known request.
+                if let Some(request) = line.strip_prefix('.') {
+                    let (request, args) = request.split_once(' ').unwrap_or((request, ""));
+                    assert!(["TH", "SH", "SS", "TP", "PP"].contains(&request), "{line}");
+                    assert_eq!(args.matches('"').count() % 2, 0, "{line}");
+                } else {
+                    assert!(!line.starts_with('\''), "{line}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{Arg, Command, Flag, Value, ValueHint};
+
+/// Create a markdown page documenting the command
+///
+/// The page contains the summary, a table of all options and the text
+/// after the options from the help file.
+pub fn render(c: &Command) -> String {
+    let mut out = format!("# {}\n\n", c.name);
+
+    if !c.version.is_empty() {
+        out.push_str(&format!("Version: {}\n\n", c.version));
+    }
+
+    if !c.summary.is_empty() {
+        out.push_str(c.summary);
+        out.push_str("\n\n");
+    }
+
+    if c.documented_args().next().is_some() {
+        out.push_str("## Options\n\n");
+        out.push_str("| Flags | Value | Default | Description |\n");
+        out.push_str("|-------|-------|---------|-------------|\n");
+        for arg in c.documented_args() {
+            out.push_str(&render_arg(arg));
+        }
+        out.push('\n');
+    }
+
+    if !c.after_options.is_empty() {
+        out.push_str(c.after_options);
+        out.push('\n');
+    }
+
+    out
+}
+
+fn render_arg(arg: &Arg) -> String {
+    let short = arg
+        .short
+        .iter()
+        .map(|Flag { flag, .. }| code(&format!("-{flag}")));
+    let long = arg
+        .long
+        .iter()
+        .map(|Flag { flag, .. }| code(&format!("--{flag}")));
+    let flags: Vec<String> = short.chain(long).collect();
+
+    let value = render_value(arg);
+    let default = match arg.default {
+        Some(default) => code(default),
+        None => String::new(),
+    };
+    let help: Vec<&str> = arg.help.lines().map(str::trim).collect();
+    let mut help = escape(&help.join(" "));
+    if arg.deprecated {
+        help.push_str(" (deprecated)");
+    }
+
+    format!("| {} | {value} | {default} | {help} |\n", flags.join(", "))
+}
+
+fn render_value(arg: &Arg) -> String {
+    let value = arg
+        .short
+        .iter()
+        .chain(&arg.long)
+        .find_map(|Flag { value, .. }| match value {
+            Value::Required(name) => Some(code(name)),
+            Value::Optional(name) => Some(code(&format!("[{name}]"))),
+            Value::No => None,
+        });
+
+    let Some(mut value) = value else {
+        return String::new();
+    };
+
+    if let Some(s) = arg.value.as_ref().and_then(ValueHint::values) {
+        let alternatives: Vec<String> = s.iter().map(|s| code(s)).collect();
+        value.push_str(&format!(": {}", alternatives.join(", ")));
+    }
+    value
+}
+
+/// Escape characters that would break the table
+fn escape(s: &str) -> String {
+    s.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// Format text as inline code within the table
+///
+/// If the text contains backticks, it is delimited with double backticks and
+/// padded with spaces, so that the backticks are part of the code.
+fn code(s: &str) -> String {
+    let s = escape(s);
+    if s.contains('`') {
+        format!("`` {s} ``")
+    } else {
+        format!("`{s}`")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::{test_util::ADVERSARIAL, Arg, Command, Flag, Value, ValueHint};
+
+    #[test]
+    fn snapshot() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "Do some things.",
+            version: "1.0.0",
+            after_options: "Some more text.",
+            args: vec![
+                Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::No,
+                    }],
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::No,
+                    }],
+                    help: "show all",
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Optional("WHEN"),
+                    }],
+                    help: "use color\nwhen | if wanted",
+                    value: Some(ValueHint::Strings(vec!["always".into(), "never".into()])),
+                    default: Some("always"),
+                    ..Arg::default()
+                },
+                Arg {
+                    short: vec![Flag {
+                        flag: "f",
+                        value: Value::Required("FILE"),
+                    }],
+                    help: "read FILE",
+                    value: Some(ValueHint::FilePath),
+                    ..Arg::default()
+                },
+            ],
+        };
+
+        assert_eq!(
+            render(&c),
+            "\
+# test
+
+Version: 1.0.0
+
+Do some things.
+
+## Options
+
+| Flags | Value | Default | Description |
+|-------|-------|---------|-------------|
+| `-a`, `--all` |  |  | show all |
+| `--color` | `[WHEN]`: `always`, `never` | `always` | use color when \\| if wanted |
+| `-f` | `FILE` |  | read FILE |
+
+Some more text.
+"
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![],
+        };
+        assert_eq!(render(&c), "# test\n\n");
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let c = Command {
+                name: "test",
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                summary: "",
+                version: "",
+                after_options: "",
+                args: vec![Arg {
+                    long: vec![Flag {
+                        flag: s,
+                        value: Value::Required(s),
+                    }],
+                    help: s,
+                    value: Some(ValueHint::Strings(vec![s.to_string()])),
+                    default: Some(s),
+                    ..Arg::default()
+                }],
+            };
+            let out = render(&c);
+            let row = out.lines().find(|l| l.starts_with("| `")).unwrap();
+            // The row has 4 columns, so 5 unescaped pipes.
+            let pipes = row
+                .match_indices('|')
+                .filter(|(i, _)| !row[..*i].ends_with('\\'));
+            assert_eq!(pipes.count(), 5, "{row}");
+        }
+    }
+}
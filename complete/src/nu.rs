@@ -0,0 +1,347 @@
+use crate::{Arg, Command, Flag, Value, ValueHint};
+
+/// Create completion script for `nushell`
+///
+/// The script is an `export extern` definition, which gives Nushell the
+/// signature of the external command. The help of each flag is added as a
+/// comment, which Nushell shows as its description.
+pub fn render(c: &Command) -> String {
+    let mut out = format!("export extern \"{}\" [\n", escape_name(c.name));
+    for arg in c.completed_args() {
+        // Nushell adds `--help` to every signature itself.
+        if arg.long.iter().any(|f| f.flag == "help") {
+            continue;
+        }
+        for line in render_arg(arg) {
+            out.push_str(&format!("    {line}\n"));
+        }
+    }
+    out.push_str("    ...args\n]\n");
+    out
+}
+
+fn render_arg(arg: &Arg) -> Vec<String> {
+    let help = arg.help.lines().next().unwrap_or_default();
+    let ty = render_value_hint(&arg.value);
+
+    // Short flags in Nushell must be a single letter or digit.
+    let mut short = arg
+        .short
+        .iter()
+        .filter(|f| f.flag.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    let mut lines = Vec::new();
+    for Flag { flag, value } in &arg.long {
+        let mut line = format!("--{}", sanitize(flag));
+        if lines.is_empty() {
+            if let Some(Flag { flag, .. }) = short.next() {
+                line.push_str(&format!("(-{flag})"));
+            }
+        }
+        line.push_str(&render_type(value, ty));
+        lines.push(line);
+    }
+    for Flag { flag, value } in short {
+        lines.push(format!("-{flag}{}", render_type(value, ty)));
+    }
+
+    if !help.is_empty() {
+        for line in &mut lines {
+            line.push_str(&format!(" # {help}"));
+        }
+    }
+    lines
+}
+
+fn render_type(value: &Value, ty: &str) -> String {
+    match value {
+        Value::No => String::new(),
+        Value::Required(_) | Value::Optional(_) => format!(": {ty}"),
+    }
+}
+
+fn render_value_hint(value: &Option<ValueHint>) -> &'static str {
+    match value {
+        Some(
+            ValueHint::AnyPath
+            | ValueHint::FilePath
+            | ValueHint::ExecutablePath
+            | ValueHint::DirPath,
+        ) => "path",
+        _ => "string",
+    }
+}
+
+/// Replace characters that are not allowed in a Nushell flag name
+fn sanitize(flag: &str) -> String {
+    flag.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Escape the command name for use in a double-quoted Nushell string
+fn escape_name(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::{
+        test_util::{split_words, Dialect, ADVERSARIAL},
+        Arg, Command, Flag, Value, ValueHint,
+    };
+
+    #[test]
+    fn short() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                short: vec![Flag {
+                    flag: "a",
+                    value: Value::No,
+                }],
+                help: "some flag",
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(
+            render(&c),
+            "export extern \"test\" [\n    -a # some flag\n    ...args\n]\n"
+        )
+    }
+
+    #[test]
+    fn long() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                long: vec![Flag {
+                    flag: "all",
+                    value: Value::No,
+                }],
+                help: "some flag",
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(
+            render(&c),
+            "export extern \"test\" [\n    --all # some flag\n    ...args\n]\n"
+        )
+    }
+
+    #[test]
+    fn short_and_long() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                short: vec![
+                    Flag {
+                        flag: "a",
+                        value: Value::No,
+                    },
+                    Flag {
+                        flag: "A",
+                        value: Value::No,
+                    },
+                ],
+                long: vec![
+                    Flag {
+                        flag: "all",
+                        value: Value::No,
+                    },
+                    Flag {
+                        flag: "almost-all",
+                        value: Value::No,
+                    },
+                ],
+                help: "some flag",
+                value: None,
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(
+            render(&c),
+            "\
+export extern \"test\" [
+    --all(-a) # some flag
+    --almost-all # some flag
+    -A # some flag
+    ...args
+]
+"
+        )
+    }
+
+    #[test]
+    fn value_hints() {
+        let args = [
+            (
+                ValueHint::Strings(vec!["all".into(), "none".into()]),
+                "string",
+            ),
+            (ValueHint::Unknown, "string"),
+            (ValueHint::AnyPath, "path"),
+            (ValueHint::FilePath, "path"),
+            (ValueHint::DirPath, "path"),
+            (ValueHint::ExecutablePath, "path"),
+            (ValueHint::Username, "string"),
+            (ValueHint::Hostname, "string"),
+        ];
+        for (hint, expected) in args {
+            let c = Command {
+                name: "test",
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                args: vec![Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::Required("VALUE"),
+                    }],
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::Optional("VALUE"),
+                    }],
+                    help: "some flag",
+                    value: Some(hint),
+                    ..Arg::default()
+                }],
+                summary: "",
+                version: "",
+                after_options: "",
+            };
+            assert_eq!(
+                render(&c),
+                format!(
+                    "export extern \"test\" [\n    --all(-a): {expected} # some flag\n    ...args\n]\n"
+                )
+            )
+        }
+    }
+
+    #[test]
+    fn sanitize() {
+        let c = Command {
+            name: "my \"test\"",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                short: vec![
+                    Flag {
+                        flag: "@",
+                        value: Value::No,
+                    },
+                    Flag {
+                        flag: "1",
+                        value: Value::No,
+                    },
+                ],
+                long: vec![Flag {
+                    flag: "a.b",
+                    value: Value::No,
+                }],
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(
+            render(&c),
+            "export extern \"my \\\"test\\\"\" [\n    --a_b(-1)\n    ...args\n]\n"
+        )
+    }
+
+    #[test]
+    fn skip_help() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            args: vec![Arg {
+                long: vec![Flag {
+                    flag: "help",
+                    value: Value::No,
+                }],
+                help: "Display this help message",
+                ..Arg::default()
+            }],
+            summary: "",
+            version: "",
+            after_options: "",
+        };
+        assert_eq!(render(&c), "export extern \"test\" [\n    ...args\n]\n")
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let c = Command {
+                name: s,
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                summary: "",
+                version: "",
+                after_options: "",
+                args: vec![Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Required("WHEN"),
+                    }],
+                    help: s,
+                    value: Some(ValueHint::Strings(vec![s.to_string()])),
+                    ..Arg::default()
+                }],
+            };
+            let out = render(&c);
+            let words = split_words(&out, Dialect::Posix).unwrap();
+            // Nushell turns the escaped newline back into a newline.
+            let name = s.replace('\n', "\\n");
+            assert_eq!(
+                words,
+                ["export", "extern", &name, "[", "--color:", "string", "...args", "]"]
+            );
+            let help = s.lines().next().unwrap();
+            assert!(out.contains(&format!("--color: string # {help}\n")));
+        }
+    }
+}
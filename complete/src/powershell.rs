@@ -0,0 +1,273 @@
+use crate::{Command, Flag, Value, ValueHint};
+
+/// Create completion script for `powershell`
+///
+/// The script registers a native argument completer for the command. Values
+/// from [`ValueHint::Strings`] and [`ValueHint::Described`] are completed
+/// after an option that requires a value and after `--option=`. For other
+/// hints nothing is returned, so PowerShell falls back to completing paths.
+pub fn render(c: &Command) -> String {
+    let mut separate_arms = Vec::new();
+    let mut attached_arms = Vec::new();
+    let mut results = Vec::new();
+
+    for arg in c.completed_args() {
+        let help = arg.help.lines().next().unwrap_or_default();
+        let values = render_value_hint(&arg.value);
+
+        for Flag { flag, value } in &arg.short {
+            let flag = format!("-{flag}");
+            if let Value::Required(_) = value {
+                separate_arms.push(format!("        {} {{ {values} }}\n", quote(&flag)));
+            }
+            results.push(completion_result(&flag, "ParameterName", help));
+        }
+        for Flag { flag, value } in &arg.long {
+            let flag = format!("--{flag}");
+            match value {
+                Value::Required(_) => {
+                    separate_arms.push(format!("        {} {{ {values} }}\n", quote(&flag)));
+                    attached_arms.push(format!(
+                        "            {} {{ {values} }}\n",
+                        quote(&format!("{flag}="))
+                    ));
+                }
+                Value::Optional(_) => {
+                    attached_arms.push(format!(
+                        "            {} {{ {values} }}\n",
+                        quote(&format!("{flag}="))
+                    ));
+                }
+                Value::No => {}
+            }
+            results.push(completion_result(&flag, "ParameterName", help));
+        }
+    }
+
+    let name = quote(c.name);
+    let separate_arms = separate_arms.concat();
+    let attached_arms = attached_arms.concat();
+    let results = results.concat();
+
+    format!(
+        "\
+Register-ArgumentCompleter -Native -CommandName {name} -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $prev = ''
+    foreach ($element in $commandAst.CommandElements) {{
+        if ($element.Extent.EndOffset -lt $cursorPosition) {{
+            $prev = $element.ToString()
+        }}
+    }}
+
+    $prefix = ''
+    $values = switch -CaseSensitive ($prev) {{
+{separate_arms}    }}
+    if ($null -eq $values -and $wordToComplete -match '^(--[^=]+=)') {{
+        $prefix = $Matches[1]
+        $values = switch -CaseSensitive ($prefix) {{
+{attached_arms}        }}
+    }}
+
+    if ($null -ne $values) {{
+        $values | Where-Object {{ \"$prefix$_\".StartsWith($wordToComplete) }} | ForEach-Object {{
+            [System.Management.Automation.CompletionResult]::new(\"$prefix$_\", $_, 'ParameterValue', $_)
+        }}
+        return
+    }}
+
+    @(
+{results}    ) | Where-Object {{ $_.CompletionText.StartsWith($wordToComplete) }}
+}}
+"
+    )
+}
+
+/// The body of a `switch` arm producing the values for an option
+///
+/// An empty `return` makes PowerShell fall back to its default completion.
+fn render_value_hint(value: &Option<ValueHint>) -> String {
+    match value {
+        Some(hint) => match hint.values() {
+            Some(s) if !s.is_empty() => {
+                let values: Vec<String> = s.iter().map(|s| quote(s)).collect();
+                format!("{}; break", values.join(", "))
+            }
+            _ => "return".into(),
+        },
+        None => "return".into(),
+    }
+}
+
+fn completion_result(text: &str, kind: &str, help: &str) -> String {
+    // The tooltip of a completion result cannot be empty.
+    let tooltip = if help.is_empty() { text } else { help };
+    format!(
+        "        [System.Management.Automation.CompletionResult]::new({}, {}, '{kind}', {})\n",
+        quote(text),
+        quote(text),
+        quote(tooltip)
+    )
+}
+
+/// Quote a string as a single-quoted PowerShell string literal
+///
+/// Within single quotes only quote characters are special; they are escaped
+/// by doubling them. Backticks and `$` are taken literally. PowerShell also
+/// treats the typographic single quotes as quotes, so those are doubled too.
+fn quote(s: &str) -> String {
+    let mut out = String::from("'");
+    for c in s.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            out.push(c);
+        }
+        out.push(c);
+    }
+    out.push('\'');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{quote, render};
+    use crate::{
+        test_util::{split_words, Dialect, ADVERSARIAL},
+        Arg, Command, Flag, Value, ValueHint,
+    };
+
+    #[test]
+    fn snapshot() {
+        let c = Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args: vec![
+                Arg {
+                    short: vec![Flag {
+                        flag: "a",
+                        value: Value::No,
+                    }],
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::No,
+                    }],
+                    help: "show all",
+                    value: None,
+                    ..Arg::default()
+                },
+                Arg {
+                    short: vec![Flag {
+                        flag: "f",
+                        value: Value::Required("FILE"),
+                    }],
+                    help: "read from FILE",
+                    value: Some(ValueHint::FilePath),
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Optional("WHEN"),
+                    }],
+                    help: "don't use `color`",
+                    value: Some(ValueHint::Strings(vec!["always".into(), "never".into()])),
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "format",
+                        value: Value::Required("FORMAT"),
+                    }],
+                    value: Some(ValueHint::Strings(vec!["long".into(), "short".into()])),
+                    ..Arg::default()
+                },
+            ],
+        };
+
+        assert_eq!(
+            render(&c),
+            r#"Register-ArgumentCompleter -Native -CommandName 'test' -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $prev = ''
+    foreach ($element in $commandAst.CommandElements) {
+        if ($element.Extent.EndOffset -lt $cursorPosition) {
+            $prev = $element.ToString()
+        }
+    }
+
+    $prefix = ''
+    $values = switch -CaseSensitive ($prev) {
+        '-f' { return }
+        '--format' { 'long', 'short'; break }
+    }
+    if ($null -eq $values -and $wordToComplete -match '^(--[^=]+=)') {
+        $prefix = $Matches[1]
+        $values = switch -CaseSensitive ($prefix) {
+            '--color=' { 'always', 'never'; break }
+            '--format=' { 'long', 'short'; break }
+        }
+    }
+
+    if ($null -ne $values) {
+        $values | Where-Object { "$prefix$_".StartsWith($wordToComplete) } | ForEach-Object {
+            [System.Management.Automation.CompletionResult]::new("$prefix$_", $_, 'ParameterValue', $_)
+        }
+        return
+    }
+
+    @(
+        [System.Management.Automation.CompletionResult]::new('-a', '-a', 'ParameterName', 'show all')
+        [System.Management.Automation.CompletionResult]::new('--all', '--all', 'ParameterName', 'show all')
+        [System.Management.Automation.CompletionResult]::new('-f', '-f', 'ParameterName', 'read from FILE')
+        [System.Management.Automation.CompletionResult]::new('--color', '--color', 'ParameterName', 'don''t use `color`')
+        [System.Management.Automation.CompletionResult]::new('--format', '--format', 'ParameterName', '--format')
+    ) | Where-Object { $_.CompletionText.StartsWith($wordToComplete) }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("plain"), "'plain'");
+        assert_eq!(quote("it's"), "'it''s'");
+        assert_eq!(quote("`$x`"), "'`$x`'");
+        assert_eq!(quote("\u{2019}"), "'\u{2019}\u{2019}'");
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let c = Command {
+                name: s,
+                positional: vec![],
+                usage: "",
+                extra: vec![],
+                summary: "",
+                version: "",
+                after_options: "",
+                args: vec![Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Required("WHEN"),
+                    }],
+                    help: s,
+                    value: Some(ValueHint::Strings(vec![s.to_string()])),
+                    ..Arg::default()
+                }],
+            };
+            let words = split_words(&render(&c), Dialect::PowerShell).unwrap();
+            assert_eq!(words[3], *s);
+            // The value is followed by `; break` and the tooltip ends the
+            // arguments of the constructor.
+            assert!(words.contains(&format!("{s};")));
+            assert!(words.contains(&format!("{})", s.lines().next().unwrap())));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Helpers for checking the quoting of the generated scripts in tests.
+
+/// Strings that are likely to break the quoting of a script
+pub const ADVERSARIAL: &[&str] = &[
+    "can't --- see?",
+    "say \"hi\"",
+    "back\\slash",
+    "trailing\\",
+    "$HOME and ${PATH}",
+    "`whoami` and $(whoami)",
+    "line\nbreak",
+    "[x]:y (z) {a,b} | c; d & e",
+    "100% *.rs ~ #!",
+    "emoji 🦀, ünïcödé and ‘typographic’ quotes",
+];
+
+/// The quoting rules of a shell
+#[derive(Clone, Copy)]
+pub enum Dialect {
+    /// bash, zsh and nushell: backslashes escape outside of quotes and in
+    /// double quotes, single quotes are literal
+    Posix,
+    /// fish: like [`Dialect::Posix`], but `\'` and `\\` are escapes within
+    /// single quotes
+    Fish,
+    /// PowerShell: backticks escape, quotes are escaped by doubling them and
+    /// typographic single quotes are single quotes too
+    PowerShell,
+}
+
+/// Split a script into words, removing quotes and escapes
+///
+/// This is a small approximation of the shells' tokenizers: it only knows
+/// about whitespace, quotes, escapes and comments. Expansions are kept as is.
+/// An error is returned if a quote or escape is left open.
+pub fn split_words(script: &str, dialect: Dialect) -> Result<Vec<String>, String> {
+    let escape = match dialect {
+        Dialect::Posix | Dialect::Fish => '\\',
+        Dialect::PowerShell => '`',
+    };
+    // The characters that can be escaped within double quotes
+    let escaped_in_double_quotes = |c: char| match dialect {
+        Dialect::Posix => "$`\"\\\n".contains(c),
+        Dialect::Fish => "$\"\\\n".contains(c),
+        Dialect::PowerShell => true,
+    };
+    let is_single_quote = |c: char| match dialect {
+        Dialect::Posix | Dialect::Fish => c == '\'',
+        Dialect::PowerShell => {
+            matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}')
+        }
+    };
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = script.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '#' if word.is_none() => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if is_single_quote(c) => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("unclosed single quote in {script:?}")),
+                        Some(c) if is_single_quote(c) && matches!(dialect, Dialect::PowerShell) => {
+                            match chars.peek() {
+                                Some(&next) if is_single_quote(next) => {
+                                    chars.next();
+                                    w.push(next);
+                                }
+                                _ => break,
+                            }
+                        }
+                        Some('\'') => break,
+                        Some('\\') if matches!(dialect, Dialect::Fish) => match chars.peek() {
+                            Some(&c @ ('\'' | '\\')) => {
+                                chars.next();
+                                w.push(c);
+                            }
+                            _ => w.push('\\'),
+                        },
+                        Some(c) => w.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("unclosed double quote in {script:?}")),
+                        Some('"') => break,
+                        Some(c) if c == escape => match chars.next() {
+                            Some(c) if escaped_in_double_quotes(c) => w.push(c),
+                            Some(c) => {
+                                w.push(escape);
+                                w.push(c);
+                            }
+                            None => return Err(format!("unfinished escape in {script:?}")),
+                        },
+                        Some(c) => w.push(c),
+                    }
+                }
+            }
+            c if c == escape => match chars.next() {
+                // A line continuation
+                Some('\n') => {}
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err(format!("unfinished escape in {script:?}")),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// The word after the first occurrence of `word`, if any
+pub fn word_after<'a>(words: &'a [String], word: &str) -> Option<&'a str> {
+    let idx = words.iter().position(|w| w == word)?;
+    words.get(idx + 1).map(String::as_str)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_words, Dialect};
+
+    #[test]
+    fn posix() {
+        let words =
+            split_words("a 'b c' \"d \\\" \\e\" f\\ g \\\n h # i 'j", Dialect::Posix).unwrap();
+        assert_eq!(words, ["a", "b c", "d \" \\e", "f g", "h"]);
+        assert!(split_words("'a", Dialect::Posix).is_err());
+        assert!(split_words("\"a\\\"", Dialect::Posix).is_err());
+        assert!(split_words("a\\", Dialect::Posix).is_err());
+    }
+
+    #[test]
+    fn fish() {
+        let words = split_words(r"'a\'b\\c\d'", Dialect::Fish).unwrap();
+        assert_eq!(words, [r"a'b\c\d"]);
+        assert!(split_words(r"'a\'", Dialect::Fish).is_err());
+    }
+
+    #[test]
+    fn powershell() {
+        let words = split_words("'it''s' \"a`\"b\" c\\d", Dialect::PowerShell).unwrap();
+        assert_eq!(words, ["it's", "a\"b", "c\\d"]);
+        assert!(split_words("'it's'", Dialect::PowerShell).is_err());
+        let words = split_words("'it\u{2019}\u{2019}s'", Dialect::PowerShell).unwrap();
+        assert_eq!(words, ["it\u{2019}s"]);
+    }
+}
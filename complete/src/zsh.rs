@@ -0,0 +1,387 @@
+use crate::{Arg, Command, Flag, Value, ValueHint};
+
+/// Create completion script for `zsh`
+///
+/// The script is a `#compdef` file that calls `_arguments` with one spec per
+/// spelling of each option. The spellings of one option exclude each other.
+pub fn render(c: &Command) -> String {
+    let mut specs = Vec::new();
+    for arg in c.completed_args() {
+        specs.extend(render_arg(arg));
+    }
+
+    let mut out = format!("#compdef {}\n\n_arguments -s -S", c.name);
+    for spec in specs {
+        out.push_str(" \\\n    ");
+        out.push_str(&spec);
+    }
+    out.push('\n');
+    out
+}
+
+fn render_arg(arg: &Arg) -> Vec<String> {
+    let help = escape_help(arg.help.lines().next().unwrap_or_default());
+    let action = render_value_hint(&arg.value);
+
+    let flags: Vec<(String, &Value)> = arg
+        .short
+        .iter()
+        .map(|Flag { flag, value }| (format!("-{flag}"), value))
+        .chain(
+            arg.long
+                .iter()
+                .map(|Flag { flag, value }| (format!("--{flag}"), value)),
+        )
+        .collect();
+
+    // Each spec consists of the flag with a suffix to indicate how the value
+    // is given and a tail with the help and the value spec.
+    let specs: Vec<(String, String)> = flags
+        .iter()
+        .map(|(flag, value)| {
+            let is_long = flag.starts_with("--");
+            match value {
+                Value::No => (flag.clone(), format!("[{help}]")),
+                Value::Required(name) => {
+                    let suffix = if is_long { "=" } else { "+" };
+                    (
+                        format!("{flag}{suffix}"),
+                        format!("[{help}]:{}:{action}", escape_help(name)),
+                    )
+                }
+                Value::Optional(name) => {
+                    let suffix = if is_long { "=-" } else { "-" };
+                    (
+                        format!("{flag}{suffix}"),
+                        format!("[{help}]::{}:{action}", escape_help(name)),
+                    )
+                }
+            }
+        })
+        .collect();
+
+    match &specs[..] {
+        [] => vec![],
+        [(flag, tail)] => vec![format!("'{flag}{tail}'")],
+        [(_, first_tail), rest @ ..] => {
+            let exclusions: Vec<&str> = flags.iter().map(|(f, _)| f.as_str()).collect();
+            let exclusions = exclusions.join(" ");
+            if rest.iter().all(|(_, tail)| tail == first_tail) {
+                let flags: Vec<&str> = specs.iter().map(|(f, _)| f.as_str()).collect();
+                vec![format!(
+                    "'({exclusions})'{{{}}}'{first_tail}'",
+                    flags.join(",")
+                )]
+            } else {
+                specs
+                    .iter()
+                    .map(|(flag, tail)| format!("'({exclusions}){flag}{tail}'"))
+                    .collect()
+            }
+        }
+    }
+}
+
+fn render_value_hint(value: &Option<ValueHint>) -> String {
+    match value {
+        Some(ValueHint::Strings(s)) => {
+            let values: Vec<String> = s.iter().map(|s| escape_value(s)).collect();
+            format!("({})", values.join(" "))
+        }
+        Some(ValueHint::Described(s)) => {
+            let values: Vec<String> = s
+                .iter()
+                .map(|(value, description)| {
+                    // The colon separating the value from the description
+                    // must survive the evaluation of the list.
+                    let value = escape_value(value).replace("\\:", "\\\\\\:");
+                    format!("{value}\\:\"{}\"", escape_description(description))
+                })
+                .collect();
+            format!("(({}))", values.join(" "))
+        }
+        Some(ValueHint::AnyPath | ValueHint::FilePath | ValueHint::ExecutablePath) => {
+            "_files".into()
+        }
+        Some(ValueHint::DirPath) => "_directories".into(),
+        Some(ValueHint::Username) => "_users".into(),
+        Some(ValueHint::Hostname) => "_hosts".into(),
+        Some(ValueHint::Unknown) | None => String::new(),
+    }
+}
+
+/// Escape a string for use in a single-quoted `_arguments` spec
+fn escape_help(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("'\\''"),
+            '[' | ']' | ':' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape a value in the list of an action in a single-quoted spec
+///
+/// `_arguments` evaluates the list, so all characters that are special to the
+/// shell are escaped with a backslash.
+fn escape_value(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("\\'\\''"),
+            '\n' | '\r' => out.push_str("\\ "),
+            c if c.is_alphanumeric() || "-_./,+=@%".contains(c) => out.push(c),
+            c => {
+                out.push('\\');
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// Escape a description of a value for a double-quoted string in the list
+/// of an action in a single-quoted spec
+fn escape_description(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '\'' => out.push_str("'\\''"),
+            '\\' | '"' | '$' | '`' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::render;
+    use crate::{
+        test_util::{split_words, Dialect, ADVERSARIAL},
+        Arg, Command, Flag, Value, ValueHint,
+    };
+
+    fn command(args: Vec<Arg<'static>>) -> Command<'static> {
+        Command {
+            name: "test",
+            positional: vec![],
+            usage: "",
+            extra: vec![],
+            summary: "",
+            version: "",
+            after_options: "",
+            args,
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        let c = command(vec![
+            Arg {
+                short: vec![Flag {
+                    flag: "a",
+                    value: Value::No,
+                }],
+                long: vec![Flag {
+                    flag: "all",
+                    value: Value::No,
+                }],
+                help: "show all",
+                value: None,
+                ..Arg::default()
+            },
+            Arg {
+                long: vec![Flag {
+                    flag: "quiet",
+                    value: Value::No,
+                }],
+                help: "be quiet",
+                ..Arg::default()
+            },
+            Arg {
+                short: vec![Flag {
+                    flag: "f",
+                    value: Value::Required("FILE"),
+                }],
+                long: vec![Flag {
+                    flag: "file",
+                    value: Value::Required("FILE"),
+                }],
+                help: "read from FILE",
+                value: Some(ValueHint::FilePath),
+                ..Arg::default()
+            },
+            Arg {
+                long: vec![Flag {
+                    flag: "dir",
+                    value: Value::Required("DIR"),
+                }],
+                help: "change to DIR",
+                value: Some(ValueHint::DirPath),
+                ..Arg::default()
+            },
+            Arg {
+                long: vec![Flag {
+                    flag: "color",
+                    value: Value::Optional("WHEN"),
+                }],
+                help: "use color",
+                value: Some(ValueHint::Strings(vec![
+                    "always".into(),
+                    "auto".into(),
+                    "never".into(),
+                ])),
+                ..Arg::default()
+            },
+            Arg {
+                short: vec![Flag {
+                    flag: "F",
+                    value: Value::No,
+                }],
+                long: vec![Flag {
+                    flag: "classify",
+                    value: Value::Optional("WHEN"),
+                }],
+                help: "classify entries",
+                value: Some(ValueHint::Strings(vec!["always".into(), "never".into()])),
+                ..Arg::default()
+            },
+            Arg {
+                long: vec![Flag {
+                    flag: "user",
+                    value: Value::Required("USER"),
+                }],
+                help: "run as USER",
+                value: Some(ValueHint::Username),
+                ..Arg::default()
+            },
+            Arg {
+                long: vec![Flag {
+                    flag: "host",
+                    value: Value::Required("HOST"),
+                }],
+                help: "connect to HOST",
+                value: Some(ValueHint::Hostname),
+                ..Arg::default()
+            },
+        ]);
+
+        assert_eq!(
+            render(&c),
+            r#"#compdef test
+
+_arguments -s -S \
+    '(-a --all)'{-a,--all}'[show all]' \
+    '--quiet[be quiet]' \
+    '(-f --file)'{-f+,--file=}'[read from FILE]:FILE:_files' \
+    '--dir=[change to DIR]:DIR:_directories' \
+    '--color=-[use color]::WHEN:(always auto never)' \
+    '(-F --classify)-F[classify entries]' \
+    '(-F --classify)--classify=-[classify entries]::WHEN:(always never)' \
+    '--user=[run as USER]:USER:_users' \
+    '--host=[connect to HOST]:HOST:_hosts'
+"#
+        );
+    }
+
+    #[test]
+    fn escape_quotes() {
+        let c = command(vec![Arg {
+            long: vec![Flag {
+                flag: "foo",
+                value: Value::No,
+            }],
+            help: "don't [really] do it",
+            ..Arg::default()
+        }]);
+        assert_eq!(
+            render(&c),
+            "#compdef test\n\n_arguments -s -S \\\n    '--foo[don'\\''t \\[really\\] do it]'\n"
+        );
+    }
+
+    #[test]
+    fn no_args() {
+        assert_eq!(
+            render(&command(vec![])),
+            "#compdef test\n\n_arguments -s -S\n"
+        );
+    }
+
+    /// Split an unquoted spec into the help in brackets and the action
+    fn split_spec(spec: &str) -> (String, &str) {
+        let start = spec.find('[').unwrap();
+        let mut help = String::new();
+        let mut chars = spec[start + 1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => help.push(chars.next().unwrap().1),
+                ']' => {
+                    let rest = &spec[start + i + 2..];
+                    let action = rest.splitn(3, ':').nth(2).unwrap();
+                    return (help, action);
+                }
+                c => help.push(c),
+            }
+        }
+        panic!("unclosed help in {spec:?}");
+    }
+
+    #[test]
+    fn adversarial() {
+        for s in ADVERSARIAL {
+            let line = s.replace('\n', " ");
+            let c = command(vec![
+                Arg {
+                    long: vec![Flag {
+                        flag: "all",
+                        value: Value::Required("VALUE"),
+                    }],
+                    help: s,
+                    value: Some(ValueHint::Strings(vec![s.to_string(), "plain".into()])),
+                    ..Arg::default()
+                },
+                Arg {
+                    long: vec![Flag {
+                        flag: "color",
+                        value: Value::Required("WHEN"),
+                    }],
+                    help: "some flag",
+                    value: Some(ValueHint::Described(vec![(s.to_string(), s.to_string())])),
+                    ..Arg::default()
+                },
+            ]);
+            let words = split_words(&render(&c), Dialect::Posix).unwrap();
+            assert_eq!(words.len(), 5, "{words:?}");
+
+            let (help, action) = split_spec(&words[3]);
+            assert_eq!(help, s.lines().next().unwrap());
+            let values = action.strip_prefix('(').unwrap().strip_suffix(')').unwrap();
+            assert_eq!(
+                split_words(values, Dialect::Posix).unwrap(),
+                [line.as_str(), "plain"]
+            );
+
+            let (_, action) = split_spec(&words[4]);
+            let values = action
+                .strip_prefix("((")
+                .unwrap()
+                .strip_suffix("))")
+                .unwrap();
+            let expected = format!("{}:{line}", line.replace(':', "\\:"));
+            assert_eq!(split_words(values, Dialect::Posix).unwrap(), [expected]);
+        }
+    }
+}
@@ -13,46 +13,97 @@ pub(crate) struct ActionAttr {
 pub(crate) enum ActionType {
     Set(Vec<syn::Path>),
     Map(Vec<syn::Arm>),
+    /// Sets the field to the first value only
+    First(Vec<syn::Path>),
+    /// Collects all values except the first
+    Rest(Vec<syn::Path>),
+    /// Maps arguments without a value to variants of the type of the field
+    Choice(Choice),
 }
 
-fn parse_paths(attr: &Attribute) -> Vec<syn::Path> {
-    attr.parse_args_with(Punctuated::<syn::Path, Token![|]>::parse_terminated)
-        .into_iter()
-        .flatten()
-        .collect()
+pub(crate) struct Choice {
+    /// Whether every variant of the type of the field must be listed
+    pub(crate) exhaustive: bool,
+    /// The arguments and the variants they map to
+    pub(crate) pairs: Vec<(syn::Path, syn::Path)>,
 }
 
-pub(crate) fn parse_action_attr(attr: &Attribute) -> Option<ActionAttr> {
-    if attr.path.is_ident("collect") {
-        let inner: ActionType = attr.parse_args().unwrap();
-        Some(ActionAttr {
-            action_type: inner,
+impl Parse for Choice {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let exhaustive = input.peek(syn::Ident) && input.peek2(Token![,]);
+        if exhaustive {
+            let ident: syn::Ident = input.parse()?;
+            if ident != "exhaustive" {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("Unrecognized argument `{ident}`, expected `exhaustive`"),
+                ));
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        let mut pairs = Vec::new();
+        while !input.is_empty() {
+            let arg: syn::Path = input.parse()?;
+            input.parse::<Token![=>]>()?;
+            let variant: syn::Path = input.parse()?;
+            pairs.push((arg, variant));
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(Self { exhaustive, pairs })
+    }
+}
+
+fn parse_paths(attr: &Attribute) -> syn::Result<Vec<syn::Path>> {
+    let paths = attr.parse_args_with(Punctuated::<syn::Path, Token![|]>::parse_terminated)?;
+    Ok(paths.into_iter().collect())
+}
+
+pub(crate) fn parse_action_attr(attr: &Attribute) -> syn::Result<Option<ActionAttr>> {
+    let action_attr = if attr.path.is_ident("collect") {
+        ActionAttr {
+            action_type: attr.parse_args()?,
             collect: true,
-        })
+        }
     } else if attr.path.is_ident("map") {
-        Some(ActionAttr {
-            action_type: ActionType::Map(
-                attr.parse_args_with(Punctuated::<syn::Arm, Nothing>::parse_terminated)
-                    .into_iter()
-                    .flatten()
-                    .collect(),
-            ),
+        let arms = attr.parse_args_with(Punctuated::<syn::Arm, Nothing>::parse_terminated)?;
+        ActionAttr {
+            action_type: ActionType::Map(arms.into_iter().collect()),
             collect: false,
-        })
+        }
     } else if attr.path.is_ident("set") {
-        Some(ActionAttr {
-            action_type: ActionType::Set(parse_paths(attr)),
+        ActionAttr {
+            action_type: ActionType::Set(parse_paths(attr)?),
+            collect: false,
+        }
+    } else if attr.path.is_ident("first") {
+        ActionAttr {
+            action_type: ActionType::First(parse_paths(attr)?),
+            collect: false,
+        }
+    } else if attr.path.is_ident("choice") {
+        ActionAttr {
+            action_type: ActionType::Choice(attr.parse_args()?),
             collect: false,
-        })
+        }
+    } else if attr.path.is_ident("rest") {
+        ActionAttr {
+            action_type: ActionType::Rest(parse_paths(attr)?),
+            collect: true,
+        }
     } else {
-        None
-    }
+        return Ok(None);
+    };
+    Ok(Some(action_attr))
 }
 
 impl Parse for ActionType {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let action: syn::Ident = input.parse()?;
-        let action = action.to_string();
+        let ident: syn::Ident = input.parse()?;
+        let action = ident.to_string();
         let content;
         parenthesized!(content in input);
         if action == "map" {
@@ -63,7 +114,10 @@ impl Parse for ActionType {
             let pat = pat.into_iter().collect();
             match &action[..] {
                 "set" => Ok(ActionType::Set(pat)),
-                _ => panic!("Unexpected action type in collect {}", action),
+                _ => Err(syn::Error::new(
+                    ident.span(),
+                    format!("Unexpected action type in collect {}", action),
+                )),
             }
         }
     }
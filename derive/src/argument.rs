@@ -1,12 +1,15 @@
 use std::ops::RangeInclusive;
 
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Attribute, Fields, FieldsUnnamed, Ident, Lit, Meta, Variant};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    Attribute, Expr, Fields, FieldsUnnamed, GenericArgument, Ident, Lit, Meta, Path, PathArguments,
+    Type, Variant,
+};
 
 use crate::{
-    attributes::{parse_argument_attribute, ArgAttr, ArgumentsAttr},
-    flags::{Flags, Value},
+    attributes::{parse_argument_attribute, ArgAttr, ArgumentsAttr, Delimiter},
+    flags::{number_spelling, Flag, Flags, Value},
 };
 
 pub(crate) struct Argument {
@@ -14,314 +17,1803 @@ pub(crate) struct Argument {
     pub(crate) name: String,
     pub(crate) arg_type: ArgType,
     pub(crate) help: String,
+    pub(crate) field: Option<Type>,
 }
 
 pub(crate) enum ArgType {
     Option {
         flags: Flags,
-        hidden: bool,
+        /// Whether the option is left out of the help and the man page
+        hidden_help: bool,
+        /// Whether the option is left out of the shell completions
+        hidden_complete: bool,
         takes_value: bool,
-        default: TokenStream,
+        default: Option<Box<Expr>>,
+        hint: Option<Ident>,
+        /// A function returning the values to complete, called when the
+        /// completions are rendered
+        hint_fn: Option<Path>,
+        category: Option<String>,
+        hide_possible_values: bool,
+        record: bool,
+        /// Whether the option ends the parse, ignoring the arguments after
+        /// it and the missing positional arguments
+        exclusive: bool,
+        /// The warning given when the option is used
+        deprecated: Option<String>,
+        /// How often the option can be given
+        max_occurrences: Option<usize>,
+        /// How the value is split into a list
+        delimiter: Option<Delimiter>,
     },
     Positional {
         num_args: RangeInclusive<usize>,
         last: bool,
+        command: bool,
+        count_only: bool,
+        /// The minimum number of values when the option of the variant is
+        /// given
+        min_if: Vec<(Ident, usize)>,
+        /// Whether the values are taken from the end of the operands
+        from_end: bool,
+        /// The value used when no values are given
+        default: Option<Box<Expr>>,
+        /// The name of the values instead of the name of the variant
+        value_name: Option<String>,
+        /// A function returning the values to complete, called when the
+        /// completions are rendered
+        hint_fn: Option<Path>,
+    },
+    /// A subcommand, which parses the arguments after its name with the
+    /// `Options` type in the field
+    Subcommand {
+        /// The name of the subcommand followed by its aliases
+        names: Vec<String>,
     },
+    /// The options of the `Arguments` enum in the field, which are parsed as
+    /// if they were options of this enum
+    Flatten,
+}
+
+impl Argument {
+    /// The name of the values of a positional argument in the help and
+    /// errors, like `FILE`
+    pub(crate) fn metavar(&self) -> String {
+        match &self.arg_type {
+            ArgType::Positional {
+                value_name: Some(name),
+                ..
+            } => name.clone(),
+            _ => self.name.to_uppercase(),
+        }
+    }
 }
 
-pub(crate) fn parse_arguments_attr(attrs: &[Attribute]) -> ArgumentsAttr {
+pub(crate) fn parse_arguments_attr(attrs: &[Attribute]) -> syn::Result<ArgumentsAttr> {
     for attr in attrs {
         if attr.path.is_ident("arguments") {
             return ArgumentsAttr::parse(attr);
         }
     }
-    ArgumentsAttr::default()
+    Ok(ArgumentsAttr::default())
 }
 
-pub(crate) fn parse_argument(v: Variant) -> Option<Argument> {
+pub(crate) fn parse_argument(v: Variant) -> syn::Result<Option<Argument>> {
     let ident = v.ident;
     let name = ident.to_string();
-    let attribute = get_arg_attribute(&v.attrs)?;
+    let Some(attribute) = get_arg_attribute(&v.attrs)? else {
+        return Ok(None);
+    };
     let help = collect_help(&v.attrs);
+    let error = |msg: &str| Err(syn::Error::new(ident.span(), msg));
 
     let field = match v.fields {
         Fields::Unit => None,
         Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
-            let v: Vec<_> = unnamed.iter().collect();
-            assert!(
-                v.len() == 1,
-                "Variants in an Arguments enum can have at most 1 field."
-            );
-            Some(v[0].ty.clone())
+            if unnamed.len() != 1 {
+                return Err(syn::Error::new_spanned(
+                    unnamed,
+                    "Variants in an Arguments enum can have at most 1 field.",
+                ));
+            }
+            Some(unnamed[0].ty.clone())
         }
-        Fields::Named(_) => {
-            panic!("Named fields are not supported in Arguments");
+        Fields::Named(fields) => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "Named fields are not supported in Arguments",
+            ));
         }
     };
 
     let arg_type = match attribute {
         ArgAttr::Option(opt) => {
-            let default_expr = match opt.default {
-                Some(expr) => quote!(#expr),
-                None => quote!(Default::default()),
-            };
+            if (opt.hint.is_some() || opt.hint_fn.is_some()) && field.is_none() {
+                return error(
+                    "A `hint` or `hint_fn` can only be given for an option that takes a value",
+                );
+            }
+            if let Some(field) = &field {
+                check_field_shape(&ident, field, opt.delimiter.is_some())?;
+            }
+            let is_list = field.as_ref().and_then(|f| inner_type(f, "Vec")).is_some();
+            if opt.delimiter.is_some() && !is_list {
+                return error("A `delimiter` needs a field like `Vec<T>` for the items");
+            }
+            if opt.delimiter.is_some() && !opt.flags.number.is_empty() {
+                return error("A number option like `-{NUM}` cannot have a `delimiter`");
+            }
+            if !opt.flags.number.is_empty() && field.is_none() {
+                return error("A number option like `-{NUM}` must have a field for the number");
+            }
+            if !opt.flags.keyword.is_empty() && field.is_none() {
+                return error("A keyword option like `bs=BYTES` must have a field for the value");
+            }
+            if (opt.flags.dash || opt.flags.double_dash) && field.is_some() {
+                return error("The flags `-` and `--` cannot take a value");
+            }
+            let takes_value = opt
+                .flags
+                .short
+                .iter()
+                .map(|f| &f.value)
+                .chain(opt.flags.long.iter().map(|f| &f.value))
+                .any(|v| !matches!(v, Value::No));
+            if takes_value && field.is_none() {
+                return error("Option cannot take a value if the variant doesn't have a field");
+            }
+            // Without a value, an optional value is the `default`, or `None`
+            // for a field like `Option<T>`
+            let optional = opt
+                .flags
+                .short
+                .iter()
+                .map(|f| &f.value)
+                .chain(opt.flags.long.iter().map(|f| &f.value))
+                .find_map(|v| match v {
+                    Value::Optional(name) => Some(name),
+                    _ => None,
+                });
+            if let (Some(name), Some(ty), None) = (optional, &field, &opt.default) {
+                if inner_type(ty, "Option").is_none() {
+                    return error(&format!(
+                        "The value {name} of `{ident}` is optional, so it needs a `default` \
+                        for when it is not given, or a field like `Option<T>`"
+                    ));
+                }
+            }
+            // The occurrences are counted by the name of the option
+            if opt.max_occurrences.is_some() && option_name_of(&opt.flags).is_none() {
+                return error("`max_occurrences` needs a short or long flag to count");
+            }
             ArgType::Option {
                 flags: opt.flags,
                 takes_value: field.is_some(),
-                default: default_expr,
-                hidden: opt.hidden,
+                default: opt.default.map(Box::new),
+                hidden_help: opt.hidden_help,
+                hidden_complete: opt.hidden_complete,
+                hint: opt.hint,
+                hint_fn: opt.hint_fn,
+                category: opt.category,
+                hide_possible_values: opt.hide_possible_values,
+                record: opt.record,
+                exclusive: opt.exclusive,
+                deprecated: opt.deprecated,
+                max_occurrences: opt.max_occurrences,
+                delimiter: opt.delimiter,
             }
         }
         ArgAttr::Positional(pos) => {
-            assert!(field.is_some(), "Positional arguments must have a field");
+            if field.is_none() {
+                return error("Positional arguments must have a field");
+            }
+            if pos.last && pos.command {
+                return error("A positional argument cannot be both `last` and `command`");
+            }
+            if pos.command && *pos.num_args.end() != 1 {
+                return error("A `command` positional argument must take at most one value");
+            }
+            if let Some((variant, _)) = pos.min_if.iter().find(|(_, min)| min > pos.num_args.end())
+            {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    "The minimum of `min_if` cannot be more than the maximum number of values",
+                ));
+            }
             ArgType::Positional {
                 num_args: pos.num_args,
                 last: pos.last,
+                command: pos.command,
+                count_only: pos.count_only,
+                min_if: pos.min_if,
+                from_end: pos.from_end,
+                default: pos.default.map(Box::new),
+                value_name: pos.value_name,
+                hint_fn: pos.hint_fn,
+            }
+        }
+        ArgAttr::Subcommand(sub) => {
+            if field.is_none() {
+                return error("A subcommand must have a field with the settings it parses");
+            }
+            let names = if sub.names.is_empty() {
+                vec![name.to_lowercase()]
+            } else {
+                sub.names
+            };
+            ArgType::Subcommand { names }
+        }
+        ArgAttr::Flatten => {
+            if field.is_none() {
+                return error("A flattened variant must have a field with the arguments it parses");
             }
+            ArgType::Flatten
         }
     };
 
-    Some(Argument {
+    Ok(Some(Argument {
         ident,
         name,
         arg_type,
         help,
-    })
+        field,
+    }))
+}
+
+/// The type inside `ty` if it is `wrapper<T>`, like `Option<T>`
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+/// The type that a value of the argument is parsed into, which is the type
+/// of the items for an option with a `delimiter`
+pub(crate) fn value_type(arg: &Argument) -> Option<&Type> {
+    let ty = arg.field.as_ref()?;
+    match &arg.arg_type {
+        ArgType::Option {
+            delimiter: Some(_), ..
+        } => inner_type(ty, "Vec"),
+        _ => Some(ty),
+    }
+}
+
+/// Reject the field types that `FromValue` cannot produce from a value.
+///
+/// Each value is parsed into the field separately, except for `last` and
+/// `command` positional arguments, which collect all their values into the
+/// field. `Option<T>` always parses to `Some`, so neither `Vec<T>` nor
+/// `Option<T>` can be nested in it.
+fn check_field_shape(ident: &Ident, ty: &Type, collects: bool) -> syn::Result<()> {
+    let unsupported = if collects {
+        inner_type(ty, "Option").is_some()
+    } else {
+        inner_type(ty, "Vec").is_some()
+            || inner_type(ty, "Option").is_some_and(|inner| {
+                inner_type(inner, "Vec")
+                    .or(inner_type(inner, "Option"))
+                    .is_some()
+            })
+    };
+    if unsupported {
+        let shown = quote!(#ty).to_string().replace(' ', "");
+        let expected = if collects {
+            "a collection of values"
+        } else {
+            "a single value"
+        };
+        return Err(syn::Error::new_spanned(
+            ty,
+            format!(
+                "The field of `{ident}` has type `{shown}`, but it takes {expected}. \
+                Wrap the inner type in a newtype with a custom `FromValue` impl."
+            ),
+        ));
+    }
+    Ok(())
 }
 
-fn collect_help(attrs: &[Attribute]) -> String {
+pub(crate) fn collect_help(attrs: &[Attribute]) -> String {
     let mut help = Vec::new();
     for attr in attrs {
-        let Ok(meta) = attr.parse_meta() else { continue; };
-        let Meta::NameValue(name_value) = meta else { continue; };
+        let Ok(meta) = attr.parse_meta() else {
+            continue;
+        };
+        let Meta::NameValue(name_value) = meta else {
+            continue;
+        };
         if !name_value.path.is_ident("doc") {
             continue;
         }
-        let Lit::Str(litstr) = name_value.lit else { continue; };
+        let Lit::Str(litstr) = name_value.lit else {
+            continue;
+        };
         help.push(litstr.value().trim().to_string())
     }
     help.join("\n")
 }
 
-fn get_arg_attribute(attrs: &[Attribute]) -> Option<ArgAttr> {
+fn get_arg_attribute(attrs: &[Attribute]) -> syn::Result<Option<ArgAttr>> {
     let attrs: Vec<_> = attrs
         .iter()
-        .filter(|a| a.path.is_ident("option") || a.path.is_ident("positional"))
+        .filter(|a| {
+            a.path.is_ident("option")
+                || a.path.is_ident("positional")
+                || a.path.is_ident("subcommand")
+                || a.path.is_ident("flatten")
+        })
         .collect();
     match attrs[..] {
-        [] => None,
-        [attr] => Some(parse_argument_attribute(attr)),
-        _ => panic!("Can only specify one #[option] or #[positional] per argument variant"),
+        [] => Ok(None),
+        [attr] => Ok(Some(parse_argument_attribute(attr)?)),
+        [_, extra, ..] => Err(syn::Error::new_spanned(
+            extra,
+            "Can only specify one #[option], #[positional], #[subcommand] or #[flatten] \
+            per argument variant",
+        )),
+    }
+}
+
+/// Check the combinations of arguments that the generated parser cannot
+/// tell apart or that contradict each other
+pub(crate) fn check_arguments(args: &[Argument]) -> syn::Result<()> {
+    let subcommand = args
+        .iter()
+        .find(|arg| matches!(arg.arg_type, ArgType::Subcommand { .. }));
+    let has_positional = args
+        .iter()
+        .any(|arg| matches!(arg.arg_type, ArgType::Positional { .. }));
+    if let (Some(arg), true) = (subcommand, has_positional) {
+        return Err(syn::Error::new(
+            arg.ident.span(),
+            "Subcommands cannot be combined with positional arguments, \
+            because the subcommand parses all arguments after it",
+        ));
+    }
+    let mut names: Vec<&str> = Vec::new();
+    for arg in args {
+        let ArgType::Subcommand { names: own } = &arg.arg_type else {
+            continue;
+        };
+        if let Some(name) = own.iter().find(|name| names.contains(&name.as_str())) {
+            return Err(syn::Error::new(
+                arg.ident.span(),
+                format!("The subcommand name `{name}` is used more than once"),
+            ));
+        }
+        names.extend(own.iter().map(String::as_str));
+    }
+
+    let mut keywords: Vec<&str> = Vec::new();
+    for arg in args {
+        let ArgType::Option { flags, .. } = &arg.arg_type else {
+            continue;
+        };
+        for keyword in &flags.keyword {
+            if keywords.contains(&keyword.flag.as_str()) {
+                return Err(syn::Error::new(
+                    arg.ident.span(),
+                    format!("The keyword `{}` is used more than once", keyword.flag),
+                ));
+            }
+            keywords.push(&keyword.flag);
+        }
+    }
+
+    for double in [false, true] {
+        let mut dashes = args.iter().filter(|arg| match &arg.arg_type {
+            ArgType::Option { flags, .. } if double => flags.double_dash,
+            ArgType::Option { flags, .. } => flags.dash,
+            ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => false,
+        });
+        if let (Some(_), Some(other)) = (dashes.next(), dashes.next()) {
+            let flag = if double { "--" } else { "-" };
+            return Err(syn::Error::new(
+                other.ident.span(),
+                format!("Only one option can be `{flag}`"),
+            ));
+        }
+    }
+
+    if let Some(ident) = dash_option(args, true) {
+        let raw_positional = args.iter().any(|arg| {
+            matches!(
+                arg.arg_type,
+                ArgType::Positional { last: true, .. } | ArgType::Positional { command: true, .. }
+            )
+        });
+        if raw_positional {
+            return Err(syn::Error::new(
+                ident.span(),
+                "`--` cannot be an option when a `last` or `command` positional argument \
+                takes the remaining arguments as is",
+            ));
+        }
+    }
+
+    for prefix in ['-', '+'] {
+        let mut numbers = args.iter().filter(|arg| match &arg.arg_type {
+            ArgType::Option { flags, .. } => flags.number(prefix).is_some(),
+            ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => false,
+        });
+        if let (Some(_), Some(other)) = (numbers.next(), numbers.next()) {
+            return Err(syn::Error::new(
+                other.ident.span(),
+                format!("Only one option can take a number prefixed with '{prefix}'"),
+            ));
+        }
+    }
+
+    if let Some((_, number)) = number_option(args, '-') {
+        for arg in args {
+            let ArgType::Option { flags, .. } = &arg.arg_type else {
+                continue;
+            };
+            if let Some(digit) = flags.short.iter().find(|f| f.flag.is_ascii_digit()) {
+                return Err(syn::Error::new(
+                    arg.ident.span(),
+                    format!(
+                        "The option -{} cannot be told apart from the number option {}",
+                        digit.flag,
+                        number_spelling(number)
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut follows_command = false;
+    let mut unbounded: Option<&Ident> = None;
+    // The operands of a `from_end` positional are split off from those of
+    // the unbounded positional right before it.
+    let mut follows_lead = false;
+    let mut trailing: Option<&Ident> = None;
+    for arg in args {
+        let ArgType::Positional {
+            num_args,
+            last,
+            command,
+            min_if,
+            from_end,
+            default,
+            ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+        if let (Some(default), true) = (default, *last || *command || *from_end || follows_command)
+        {
+            return Err(syn::Error::new_spanned(
+                default,
+                "A `default` is only supported for positional arguments that take \
+                their values one by one",
+            ));
+        }
+        if let Some(previous) = trailing {
+            return Err(syn::Error::new(
+                arg.ident.span(),
+                format!(
+                    "A positional argument cannot follow `{previous}`, \
+                    which takes the last values"
+                ),
+            ));
+        }
+        if *from_end {
+            if *last || *command {
+                return Err(syn::Error::new(
+                    arg.ident.span(),
+                    "A positional argument with `from_end` cannot be `last` or a `command`",
+                ));
+            }
+            if !follows_lead {
+                return Err(syn::Error::new(
+                    arg.ident.span(),
+                    "A positional argument with `from_end` must follow a positional \
+                    argument that takes any number of values",
+                ));
+            }
+            trailing = Some(&arg.ident);
+            if let Some(field) = &arg.field {
+                check_field_shape(&arg.ident, field, false)?;
+            }
+            continue;
+        }
+        follows_lead = *num_args.end() == usize::MAX && !last && !follows_command;
+        // The values after a `command` are not counted, so the positional
+        // after it can take any number of values.
+        if let (Some(previous), false) = (unbounded, follows_command) {
+            return Err(syn::Error::new(
+                arg.ident.span(),
+                format!(
+                    "A positional argument cannot follow `{previous}`, \
+                    which takes any number of values"
+                ),
+            ));
+        }
+        if *num_args.end() == usize::MAX && !follows_command {
+            unbounded = Some(&arg.ident);
+        }
+        if let Some(field) = &arg.field {
+            check_field_shape(&arg.ident, field, *last || follows_command)?;
+        }
+        for (variant, _) in min_if {
+            let is_option = args.iter().any(|a| {
+                a.ident == *variant
+                    && matches!(&a.arg_type, ArgType::Option { flags, .. } if option_name_of(flags).is_some())
+            });
+            if !is_option {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    format!("`min_if` must refer to an option, found `{variant}`"),
+                ));
+            }
+        }
+        follows_command = *command;
+    }
+    if let Some(arg) = args
+        .iter()
+        .rev()
+        .find(|arg| matches!(arg.arg_type, ArgType::Positional { .. }))
+    {
+        if matches!(arg.arg_type, ArgType::Positional { command: true, .. }) {
+            return Err(syn::Error::new(
+                arg.ident.span(),
+                "A `command` positional argument must be followed by another positional argument",
+            ));
+        }
     }
+
+    Ok(())
+}
+
+/// Write `spelling` to the `flag` of the generated `next_arg`
+///
+/// The buffer of `flag` is reused, so that parsing an option does not
+/// allocate.
+fn set_flag(spelling: impl ToTokens) -> TokenStream {
+    quote!(
+        flag.clear();
+        flag.push_str(#spelling);
+    )
+}
+
+/// The handling of an option that is not recognized
+///
+/// With `collect_unknown`, the option is returned as that variant, spelled
+/// like it was given, including its value if it was attached. The rest of
+/// a cluster of short options is taken as the value.
+fn unknown_option(collect_unknown: Option<&Ident>, long: bool) -> TokenStream {
+    let Some(ident) = collect_unknown else {
+        return quote!(return Err(arg.unexpected().into()));
+    };
+    let separator = if long {
+        quote!(raw.push("=");)
+    } else {
+        quote!()
+    };
+    let set_flag = if long {
+        quote!(
+            flag.clear();
+            flag.push_str("--");
+            flag.push_str(long);
+        )
+    } else {
+        quote!()
+    };
+    quote!({
+        #set_flag
+        let mut raw = std::ffi::OsString::from(flag.as_str());
+        if let Some(value) = parser.optional_value() {
+            #separator
+            raw.push(value);
+        }
+        return Ok(Some(Argument::Custom(Self::#ident(FromValue::from_value("", raw)?))));
+    })
 }
 
-pub(crate) fn short_handling(args: &[Argument]) -> TokenStream {
+/// The match arms of the short options, which evaluate to the variant
+fn short_arms(args: &[Argument]) -> Vec<TokenStream> {
     let mut match_arms = Vec::new();
 
     for arg in args {
-        let (flags, takes_value, default) = match arg.arg_type {
+        let (flags, takes_value, default, delimiter) = match arg.arg_type {
             ArgType::Option {
                 ref flags,
                 takes_value,
                 ref default,
-                hidden: _,
-            } => (flags, takes_value, default),
-            ArgType::Positional { .. } => continue,
+                hidden_help: _,
+                hidden_complete: _,
+                hint: _,
+                hint_fn: _,
+                category: _,
+                hide_possible_values: _,
+                record: _,
+                exclusive: _,
+                deprecated: _,
+                max_occurrences: _,
+                ref delimiter,
+            } => (flags, takes_value, default, delimiter),
+            ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => continue,
         };
 
-        if flags.short.is_empty() {
-            continue;
-        }
-
         for flag in &flags.short {
             let pat = flag.flag;
             let expr = match (&flag.value, takes_value) {
                 (Value::No, false) => no_value_expression(&arg.ident),
-                (_, false) => {
-                    panic!("Option cannot take a value if the variant doesn't have a field")
-                }
+                (_, false) => unreachable!("checked in `parse_argument`"),
                 (Value::No, true) => default_value_expression(&arg.ident, default),
-                (Value::Optional(_), true) => optional_value_expression(&arg.ident, default),
-                (Value::Required(_), true) => required_value_expression(&arg.ident),
+                (Value::Optional(_), true) => {
+                    optional_value_expression(&arg.ident, default, delimiter)
+                }
+                (Value::Required(_), true) => required_value_expression(&arg.ident, delimiter),
             };
             match_arms.push(quote!(#pat => { #expr }))
         }
     }
 
+    match_arms
+}
+
+/// The handling of the short options
+///
+/// With `unknown_positional`, the handling of the positional arguments, an
+/// unrecognized short option and the rest of its cluster are an operand,
+/// dash included.
+pub(crate) fn short_handling(
+    args: &[Argument],
+    collect_unknown: Option<&Ident>,
+    unknown_positional: Option<&TokenStream>,
+) -> TokenStream {
+    let mut match_arms = short_arms(args);
+
+    if let Some((ident, flag)) = number_option(args, '-') {
+        let set_spelling = set_flag(number_spelling(flag));
+        match_arms.push(quote!('0'..='9' => {
+            // The digits and everything after them in the cluster
+            let mut value = std::ffi::OsString::from(short.to_string());
+            if let Some(rest) = parser.optional_value() {
+                value.push(rest);
+            }
+            #set_spelling
+            Self::#ident(FromValue::from_value(flag.as_str(), value)?)
+        }));
+    }
+
+    for (ident, ty) in flattened(args) {
+        match_arms.push(quote!(
+            short if <#ty as uutils_args::Arguments>::SHORT_OPTIONS.contains(&short) => {
+                Self::#ident(<#ty as uutils_args::Arguments>::flattened_short(short, parser, flag)?)
+            }
+        ));
+    }
+
+    let unknown = match unknown_positional {
+        Some(positional) => quote!({
+            let mut value = std::ffi::OsString::from(flag.as_str());
+            if let Some(rest) = parser.optional_value() {
+                value.push(rest);
+            }
+            #positional
+        }),
+        None => unknown_option(collect_unknown, false),
+    };
+
     quote!(
-        let option = format!("-{}", short);
+        flag.clear();
+        flag.push('-');
+        flag.push(short);
         match short {
             #(#match_arms)*
-            _ => return Err(arg.unexpected().into()),
+            _ => #unknown,
+        }
+    )
+}
+
+/// The variant of the option that is `-` or `--`
+///
+/// There is at most one, see [`check_arguments`].
+fn dash_option(args: &[Argument], double: bool) -> Option<&Ident> {
+    args.iter()
+        .find(|arg| match &arg.arg_type {
+            ArgType::Option { flags, .. } if double => flags.double_dash,
+            ArgType::Option { flags, .. } => flags.dash,
+            ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => false,
+        })
+        .map(|arg| &arg.ident)
+}
+
+/// Whether `--` is claimed as an option, see [`dash_handling`]
+pub(crate) fn claims_double_dash(args: &[Argument]) -> bool {
+    dash_option(args, true).is_some()
+}
+
+/// Whether `--` is handled by the generated parser instead of lexopt
+///
+/// The `--` is then not seen by lexopt, because it is replaced by `--\0`,
+/// which lexopt gives as the long option `\0`, see [`long_handling`]. No
+/// argument from the command line can contain a nul byte. When `--` is not
+/// claimed, the parser still needs to know where the options end, so that a
+/// `-` after it is an operand, and an `@file` after it is not an argument
+/// file.
+pub(crate) fn handles_double_dash(args: &[Argument], argfile: bool) -> bool {
+    claims_double_dash(args) || dash_option(args, false).is_some() || argfile
+}
+
+/// Turn an escaped `--` back into `--` where it is a value
+pub(crate) fn unescape_double_dash(value: TokenStream) -> TokenStream {
+    quote!({
+        let value: std::ffi::OsString = #value;
+        if value == "--\0" {
+            std::ffi::OsString::from("--")
+        } else {
+            value
+        }
+    })
+}
+
+/// The handling of the lone `-` as an option, when it is one
+pub(crate) fn dash_handling(args: &[Argument]) -> TokenStream {
+    let Some(ident) = dash_option(args, false) else {
+        return quote!();
+    };
+    quote!(
+        if value == "-" && !*operands_only {
+            flag.clear();
+            flag.push('-');
+            return Ok(Some(Argument::Custom(Self::#ident)));
+        }
+    )
+}
+
+/// The variant and flag of the number option with the `prefix`
+///
+/// There is at most one, see [`check_arguments`].
+fn number_option(args: &[Argument], prefix: char) -> Option<(&Ident, &Flag<char>)> {
+    args.iter().find_map(|arg| match &arg.arg_type {
+        ArgType::Option { flags, .. } => Some((&arg.ident, flags.number(prefix)?)),
+        ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => None,
+    })
+}
+
+/// The handling of a value like `+5` for a number option prefixed with `+`
+///
+/// In the POSIX parse mode, such a value is an operand after the first
+/// operand, like all other arguments.
+pub(crate) fn plus_number_handling(args: &[Argument]) -> TokenStream {
+    let Some((ident, flag)) = number_option(args, '+') else {
+        return quote!();
+    };
+    let set_spelling = set_flag(number_spelling(flag));
+    quote!(
+        let is_number = value
+            .to_str()
+            .and_then(|v| v.strip_prefix('+'))
+            .is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit()));
+        if is_number && !*operands_only {
+            #set_spelling
+            return Ok(Some(Argument::Custom(Self::#ident(FromValue::from_value(flag.as_str(), value)?))));
+        }
+    )
+}
+
+pub(crate) fn short_takes_value(args: &[Argument]) -> TokenStream {
+    let mut shorts = Vec::new();
+
+    for arg in args {
+        let ArgType::Option {
+            flags,
+            takes_value: true,
+            ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+
+        for flag in &flags.short {
+            if !matches!(flag.value, Value::No) {
+                shorts.push(flag.flag);
+            }
+        }
+    }
+
+    // The digits of a number option take the rest of the cluster
+    if number_option(args, '-').is_some() {
+        shorts.extend('0'..='9');
+    }
+
+    let num_shorts = shorts.len();
+
+    let flattened = flattened(args).map(|(_, ty)| {
+        quote!(
+            || (<#ty as uutils_args::Arguments>::SHORT_OPTIONS.contains(&short)
+                && <#ty as uutils_args::Arguments>::short_takes_value(short))
+        )
+    });
+
+    quote!(
+        let shorts: [char; #num_shorts] = [#(#shorts),*];
+        shorts.contains(&short) #(#flattened)*
+    )
+}
+
+pub(crate) fn option_name(args: &[Argument]) -> TokenStream {
+    let mut match_arms = Vec::new();
+
+    for arg in args {
+        let ArgType::Option { flags, .. } = &arg.arg_type else {
+            continue;
+        };
+
+        let Some(name) = option_name_of(flags) else {
+            continue;
+        };
+
+        let ident = &arg.ident;
+        let pat = match arg.field {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        match_arms.push(quote!(#pat => Some(#name),));
+    }
+
+    for (ident, _) in flattened(args) {
+        match_arms.push(quote!(Self::#ident(arg) => arg.option_name(),));
+    }
+
+    quote!(
+        match self {
+            #(#match_arms)*
+            _ => None,
+        }
+    )
+}
+
+/// The name of the constant that the `Options` derive uses to check a
+/// `#[set(...)]` of `variant`, see [`set_checks`]
+pub(crate) fn set_check_name(variant: &Ident) -> Ident {
+    format_ident!("set_{}", variant, span = variant.span())
+}
+
+/// Constants for each variant that `#[set(...)]` in the `Options` derive
+/// refers to
+///
+/// The constant is deprecated for positional arguments that can be given
+/// more than once, because setting a single field to them silently keeps
+/// only the last value. This is how the derive warns about it, because proc
+/// macros cannot emit warnings on stable Rust.
+pub(crate) fn set_checks(args: &[Argument]) -> TokenStream {
+    let mut follows_command = false;
+    let consts = args.iter().map(|arg| {
+        let name = set_check_name(&arg.ident);
+        let repeated = match &arg.arg_type {
+            ArgType::Positional {
+                num_args,
+                last,
+                command,
+                ..
+            } => {
+                let repeated = *num_args.end() > 1 && !last && !follows_command;
+                follows_command = *command;
+                repeated
+            }
+            ArgType::Option { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => false,
+        };
+        let deprecated = if repeated {
+            let note = format!(
+                "the positional argument `{}` can be given more than once, but \
+                `set` keeps only the last value; use `collect`, `first` or `rest`",
+                arg.ident
+            );
+            quote!(#[deprecated(note = #note)])
+        } else {
+            quote!()
+        };
+        quote!(
+            #deprecated
+            #[allow(dead_code, non_upper_case_globals)]
+            pub const #name: () = ();
+        )
+    });
+    quote!(#(#consts)*)
+}
+
+pub(crate) fn is_recorded(args: &[Argument]) -> TokenStream {
+    let mut match_arms = Vec::new();
+
+    for arg in args {
+        let ArgType::Option { record: true, .. } = &arg.arg_type else {
+            continue;
+        };
+
+        let ident = &arg.ident;
+        let pat = match arg.field {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        match_arms.push(quote!(#pat => true,));
+    }
+
+    for (ident, _) in flattened(args) {
+        match_arms.push(quote!(Self::#ident(arg) => arg.is_recorded(),));
+    }
+
+    quote!(
+        match self {
+            #(#match_arms)*
+            _ => false,
         }
     )
 }
 
-pub(crate) fn long_handling(args: &[Argument], help_flags: &Flags) -> TokenStream {
+pub(crate) fn is_exclusive(args: &[Argument]) -> TokenStream {
     let mut match_arms = Vec::new();
+
+    for arg in args {
+        let ArgType::Option {
+            exclusive: true, ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+
+        let ident = &arg.ident;
+        let pat = match arg.field {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        match_arms.push(quote!(#pat => true,));
+    }
+
+    for (ident, _) in flattened(args) {
+        match_arms.push(quote!(Self::#ident(arg) => arg.is_exclusive(),));
+    }
+
+    quote!(
+        match self {
+            #(#match_arms)*
+            _ => false,
+        }
+    )
+}
+
+pub(crate) fn max_occurrences(args: &[Argument]) -> TokenStream {
+    let mut match_arms = Vec::new();
+
+    for arg in args {
+        let ArgType::Option {
+            max_occurrences: Some(max),
+            ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+
+        let ident = &arg.ident;
+        let pat = match arg.field {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        match_arms.push(quote!(#pat => Some(#max),));
+    }
+
+    for (ident, _) in flattened(args) {
+        match_arms.push(quote!(Self::#ident(arg) => arg.max_occurrences(),));
+    }
+
+    quote!(
+        match self {
+            #(#match_arms)*
+            _ => None,
+        }
+    )
+}
+
+pub(crate) fn deprecation(args: &[Argument]) -> TokenStream {
+    let mut match_arms = Vec::new();
+
+    for arg in args {
+        let ArgType::Option {
+            deprecated: Some(warning),
+            ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+
+        let ident = &arg.ident;
+        let pat = match arg.field {
+            Some(_) => quote!(Self::#ident(_)),
+            None => quote!(Self::#ident),
+        };
+        match_arms.push(quote!(#pat => Some(#warning),));
+    }
+
+    for (ident, _) in flattened(args) {
+        match_arms.push(quote!(Self::#ident(arg) => arg.deprecation(),));
+    }
+
+    quote!(
+        match self {
+            #(#match_arms)*
+            _ => None,
+        }
+    )
+}
+
+/// The name of an option, as returned by `Arguments::option_name`
+fn option_name_of(flags: &Flags) -> Option<String> {
+    // The first long flag is the most descriptive name for the option
+    match (flags.long.first(), flags.short.first()) {
+        (Some(long), _) => Some(format!("--{}", long.flag)),
+        (None, Some(short)) => Some(format!("-{}", short.flag)),
+        (None, None) if flags.double_dash => Some("--".into()),
+        (None, None) if flags.dash => Some("-".into()),
+        (None, None) => match flags.number.first() {
+            Some(number) => Some(number_spelling(number)),
+            None => flags.keyword.first().map(|k| k.flag.clone()),
+        },
+    }
+}
+
+pub(crate) fn long_handling(
+    args: &[Argument],
+    help_flags: &Flags,
+    short_help_flags: &Flags,
+    collect_unknown: Option<&Ident>,
+    argfile: bool,
+) -> TokenStream {
     let mut options = Vec::new();
+    let unknown = unknown_option(collect_unknown, true);
+
+    let double_dash = match dash_option(args, true) {
+        Some(ident) => quote!(if long == "\0" {
+            flag.clear();
+            flag.push_str("--");
+            return Ok(Some(Argument::Custom(Self::#ident)));
+        }),
+        None if handles_double_dash(args, argfile) => quote!(if long == "\0" {
+            *operands_only = true;
+            return Self::next_arg(
+                parser,
+                positional_idx,
+                flag,
+                operands_only,
+                bin_name,
+                config,
+            );
+        }),
+        None => quote!(),
+    };
 
     options.extend(help_flags.long.iter().map(|f| f.flag.clone()));
+    options.extend(short_help_flags.long.iter().map(|f| f.flag.clone()));
+
+    let (mut match_arms, long_options) = long_arms(args);
+    options.extend(long_options);
+
+    let flattened: Vec<_> = flattened(args).collect();
+    for (ident, ty) in &flattened {
+        match_arms.push(quote!(
+            long if <#ty as uutils_args::Arguments>::LONG_OPTIONS.contains(&long) => {
+                Self::#ident(<#ty as uutils_args::Arguments>::flattened_long(long, parser, flag)?)
+            }
+        ));
+    }
+
+    if options.is_empty() && flattened.is_empty() {
+        return quote!(#double_dash #unknown);
+    }
+
+    // TODO: Add version check
+    let mut help_check = Vec::new();
+    if !help_flags.long.is_empty() {
+        let long_help_flags = help_flags.long.iter().map(|f| &f.flag);
+        help_check.push(quote!(if let #(#long_help_flags)|* = long {
+            return Ok(Some(Argument::Help));
+        }));
+    }
+    if !short_help_flags.long.is_empty() {
+        let long_help_flags = short_help_flags.long.iter().map(|f| &f.flag);
+        help_check.push(quote!(if let #(#long_help_flags)|* = long {
+            return Ok(Some(Argument::ShortHelp));
+        }));
+    }
+
+    let num_opts = options.len();
+    // The long options of the flattened arguments are abbreviated together
+    // with the local ones.
+    let flattened_options = flattened
+        .iter()
+        .map(|(_, ty)| quote!(<#ty as uutils_args::Arguments>::LONG_OPTIONS));
+
+    quote!(
+        #double_dash
+        static LONG_OPTIONS: [&str; #num_opts] = [#(#options),*];
+        let groups: &[&[&str]] = &[&LONG_OPTIONS, #(#flattened_options),*];
+        let long = match uutils_args::resolve_long_among(long, groups) {
+            Ok(opt) => opt,
+            Err(Error::UnexpectedOption(_)) => #unknown,
+            Err(err) => {
+                // The value belongs to the ambiguous option, so it is not
+                // parsed as the next argument.
+                let _ = parser.optional_value();
+                return Err(err);
+            }
+        };
+
+        flag.clear();
+        flag.push_str("--");
+        flag.push_str(long);
+
+        #(#help_check)*
+
+        match long {
+            #(#match_arms)*
+            _ => unreachable!("resolved to one of the long options")
+        }
+    )
+}
+
+/// The match arms of the long options, which evaluate to the variant, and
+/// the long options without the leading `--`
+fn long_arms(args: &[Argument]) -> (Vec<TokenStream>, Vec<String>) {
+    let mut match_arms = Vec::new();
+    let mut options = Vec::new();
 
     for arg in args {
-        let (flags, takes_value, default) = match &arg.arg_type {
+        let (flags, takes_value, default, delimiter) = match &arg.arg_type {
             ArgType::Option {
                 flags,
                 takes_value,
                 ref default,
-                hidden: _,
-            } => (flags, takes_value, default),
-            ArgType::Positional { .. } => continue,
+                hidden_help: _,
+                hidden_complete: _,
+                hint: _,
+                hint_fn: _,
+                category: _,
+                hide_possible_values: _,
+                record: _,
+                exclusive: _,
+                deprecated: _,
+                max_occurrences: _,
+                delimiter,
+            } => (flags, takes_value, default, delimiter),
+            ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => continue,
         };
 
-        if flags.long.is_empty() {
-            continue;
-        }
-
         for flag in &flags.long {
             let pat = &flag.flag;
             let expr = match (&flag.value, takes_value) {
-                (Value::No, false) => no_value_expression(&arg.ident),
-                (_, false) => {
-                    panic!("Option cannot take a value if the variant doesn't have a field")
+                (Value::No, false) => no_attached_value(no_value_expression(&arg.ident)),
+                (_, false) => unreachable!("checked in `parse_argument`"),
+                (Value::No, true) => {
+                    no_attached_value(default_value_expression(&arg.ident, default))
                 }
-                (Value::No, true) => default_value_expression(&arg.ident, default),
-                (Value::Optional(_), true) => optional_value_expression(&arg.ident, default),
-                (Value::Required(_), true) => required_value_expression(&arg.ident),
+                (Value::Optional(_), true) => {
+                    optional_value_expression(&arg.ident, default, delimiter)
+                }
+                (Value::Required(_), true) => required_value_expression(&arg.ident, delimiter),
             };
             match_arms.push(quote!(#pat => { #expr }));
             options.push(flag.flag.clone());
         }
     }
 
-    if options.is_empty() {
-        return quote!(return Err(arg.unexpected().into()));
-    }
+    (match_arms, options)
+}
 
-    // TODO: Add version check
-    let help_check = if !help_flags.long.is_empty() {
-        let long_help_flags = help_flags.long.iter().map(|f| &f.flag);
-        quote!(if let #(#long_help_flags)|* = long {
-            return Ok(Some(Argument::Help));
+/// The variants with `#[flatten]` and the `Arguments` type in their field
+pub(crate) fn flattened(args: &[Argument]) -> impl Iterator<Item = (&Ident, &Type)> {
+    args.iter()
+        .filter_map(|arg| match (&arg.arg_type, &arg.field) {
+            (ArgType::Flatten, Some(ty)) => Some((&arg.ident, ty)),
+            _ => None,
         })
-    } else {
-        quote!()
-    };
+}
 
-    let num_opts = options.len();
+/// The items of `Arguments` with which an enum that flattens this one parses
+/// its short and long options
+///
+/// Only the options of the enum itself are flattened, not its help and
+/// version options, its number options or the options it flattens itself.
+pub(crate) fn flattened_handling(args: &[Argument]) -> TokenStream {
+    let short_arms = short_arms(args);
+    let (long_arms, long_options) = long_arms(args);
+    let short_options = args.iter().flat_map(|arg| match &arg.arg_type {
+        ArgType::Option { flags, .. } => flags.short.iter().map(|f| f.flag).collect(),
+        _ => Vec::new(),
+    });
 
     quote!(
-        let long_options: [&str; #num_opts] = [#(#options),*];
-        let mut candidates = Vec::new();
-        let mut exact_match = None;
-        for opt in long_options {
-            if opt == long {
-                exact_match = Some(opt);
-                break;
-            } else if opt.starts_with(long) {
-                candidates.push(opt);
-            }
-        }
-
-        let long = match (exact_match, &candidates[..]) {
-            (Some(opt), _) => opt,
-            (None, [opt]) => opt,
-            (None, []) => return Err(arg.unexpected().into()),
-            (None, opts) => return Err(Error::AmbiguousOption {
-                option: long.to_string(),
-                candidates: candidates.iter().map(|s| s.to_string()).collect(),
-            })
-        };
+        const LONG_OPTIONS: &'static [&'static str] = &[#(#long_options),*];
 
-        #help_check
+        const SHORT_OPTIONS: &'static [char] = &[#(#short_options),*];
 
-        let option = format!("--{}", long);
-        match long {
-            #(#match_arms)*
-            _ => unreachable!("Should be caught by (None, []) case above.")
+        #[allow(unreachable_code, unused_variables)]
+        fn flattened_short(
+            short: char,
+            parser: &mut uutils_args::lexopt::Parser,
+            flag: &mut String,
+        ) -> Result<Self, uutils_args::Error> {
+            use uutils_args::{FromValue, Error};
+            Ok(match short {
+                #(#short_arms)*
+                _ => unreachable!("only the short options are flattened"),
+            })
+        }
+
+        #[allow(unreachable_code, unused_variables)]
+        fn flattened_long(
+            long: &str,
+            parser: &mut uutils_args::lexopt::Parser,
+            flag: &mut String,
+        ) -> Result<Self, uutils_args::Error> {
+            use uutils_args::{FromValue, Error};
+            Ok(match long {
+                #(#long_arms)*
+                _ => unreachable!("only the long options are flattened"),
+            })
         }
     )
 }
 
-pub(crate) fn positional_handling(args: &[Argument]) -> (TokenStream, TokenStream) {
+/// Checks that the options of the flattened arguments are not also options
+/// of this enum or of another flattened enum
+///
+/// The options of the other enums are only known to the compiler, so they
+/// are checked in constants, which fail to compile at the variant that has
+/// the option.
+pub(crate) fn flatten_checks(
+    args: &[Argument],
+    help_flags: &Flags,
+    short_help_flags: &Flags,
+    version_flags: &Flags,
+) -> TokenStream {
+    let flattened: Vec<_> = flattened(args).collect();
+    let mut checks = Vec::new();
+
+    let local = args.iter().filter_map(|arg| match &arg.arg_type {
+        ArgType::Option { flags, .. } => Some((&arg.ident, flags)),
+        _ => None,
+    });
+    // The help and version options are reported at the flattened variant
+    let builtins = flattened.iter().flat_map(|(ident, _)| {
+        [help_flags, short_help_flags, version_flags]
+            .into_iter()
+            .map(move |flags| (*ident, flags))
+    });
+    for (ident, flags) in local.chain(builtins) {
+        for (other, ty) in &flattened {
+            for f in &flags.long {
+                let long = &f.flag;
+                let msg = format!("The option --{long} is also an option of `{other}`");
+                checks.push(quote_spanned!(ident.span() =>
+                    if uutils_args::contains_long(<#ty as uutils_args::Arguments>::LONG_OPTIONS, #long) {
+                        panic!(#msg);
+                    }
+                ));
+            }
+            for f in &flags.short {
+                let short = f.flag;
+                let msg = format!("The option -{short} is also an option of `{other}`");
+                checks.push(quote_spanned!(ident.span() =>
+                    if uutils_args::contains_short(<#ty as uutils_args::Arguments>::SHORT_OPTIONS, #short) {
+                        panic!(#msg);
+                    }
+                ));
+            }
+        }
+    }
+
+    for (i, (ident, ty)) in flattened.iter().enumerate() {
+        for (other, other_ty) in &flattened[..i] {
+            let msg = format!("`{ident}` and `{other}` have an option in common");
+            checks.push(quote_spanned!(ident.span() =>
+                if uutils_args::overlaps(
+                    <#ty as uutils_args::Arguments>::LONG_OPTIONS,
+                    <#other_ty as uutils_args::Arguments>::LONG_OPTIONS,
+                    <#ty as uutils_args::Arguments>::SHORT_OPTIONS,
+                    <#other_ty as uutils_args::Arguments>::SHORT_OPTIONS,
+                ) {
+                    panic!(#msg);
+                }
+            ));
+        }
+    }
+
+    if checks.is_empty() {
+        return quote!();
+    }
+
+    quote!(const _: () = { #(#checks)* };)
+}
+
+/// Returns the handling of values, the handling of raw arguments following a
+/// `command` positional and the checks for missing positional arguments.
+pub(crate) fn positional_handling(args: &[Argument]) -> (TokenStream, TokenStream, TokenStream) {
+    // Subcommands take the place of the positional arguments, see
+    // `check_arguments`.
+    if let Some(handling) = subcommand_handling(args) {
+        return handling;
+    }
+
     let mut match_arms = Vec::new();
     // The largest index of the previous argument, so the the argument after this should
-    // belong to the next argument.
-    let mut last_index = 0;
+    // belong to the next argument. It is `usize::MAX` after an unbounded
+    // argument, which is always the final one, see `check_arguments`.
+    let mut last_index: usize = 0;
 
     // The minimum number of arguments needed to not return a missing argument error.
     let mut minimum_needed = 0;
     let mut missing_argument_checks = vec![];
 
-    for arg @ Argument { name, arg_type, .. } in args {
-        let (num_args, last) = match arg_type {
-            ArgType::Positional { num_args, last } => (num_args, last),
-            ArgType::Option { .. } => continue,
+    // The checks for positional arguments that need more values when an
+    // option is given
+    let mut conditional_checks = vec![];
+
+    // The index of a preceding `command` positional, after which all
+    // arguments are given raw to the next positional argument.
+    let mut command_index = None;
+    let mut raw_handling = quote!();
+
+    // The operands of the unbounded positional argument before a `from_end`
+    // one are held back, because the last of them belong to the latter.
+    let has_trailing = args
+        .iter()
+        .any(|arg| matches!(arg.arg_type, ArgType::Positional { from_end: true, .. }));
+    // The number of operands needed before those of a `from_end` argument
+    let mut lead_minimum: usize = 0;
+
+    for arg @ Argument { arg_type, .. } in args {
+        let (num_args, last, command, count_only, min_if, from_end, default) = match arg_type {
+            ArgType::Positional {
+                num_args,
+                last,
+                command,
+                count_only,
+                min_if,
+                from_end,
+                default,
+                value_name: _,
+                hint_fn: _,
+            } => (
+                num_args, last, command, count_only, min_if, from_end, default,
+            ),
+            ArgType::Option { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => continue,
         };
+        let metavar = arg.metavar();
+
+        // The operands are assigned by `from_operand`, so only the missing
+        // values have to be checked.
+        if *from_end {
+            minimum_needed = lead_minimum.saturating_add(*num_args.end());
+            missing_argument_checks.push(if *count_only {
+                quote!(if positional_idx < #minimum_needed && missing.is_empty() {
+                    return Err(uutils_args::Error::MissingOperand);
+                })
+            } else {
+                quote!(if positional_idx < #minimum_needed {
+                    missing.push(#metavar);
+                })
+            });
+            continue;
+        }
+
+        let follows_command = if let Some(idx) = command_index.take() {
+            raw_handling = raw_positional_expression(&arg.ident, &metavar, idx);
+            true
+        } else {
+            false
+        };
+
+        // A positional argument with a default is never missing.
+        if *num_args.start() > 0 && default.is_none() {
+            minimum_needed = last_index.saturating_add(*num_args.start());
+            missing_argument_checks.push(if *count_only {
+                quote!(if positional_idx < #minimum_needed && missing.is_empty() {
+                    return Err(uutils_args::Error::MissingOperand);
+                })
+            } else {
+                quote!(if positional_idx < #minimum_needed {
+                    missing.push(#metavar);
+                })
+            });
+        }
 
-        if *num_args.start() > 0 {
-            minimum_needed = last_index + num_args.start();
-            missing_argument_checks.push(quote!(if positional_idx < #minimum_needed {
-                missing.push(#name);
-            }));
+        for (variant, min) in min_if {
+            if min <= num_args.start() {
+                continue;
+            }
+            let option = args
+                .iter()
+                .find_map(|a| match &a.arg_type {
+                    ArgType::Option { flags, .. } if a.ident == *variant => option_name_of(flags),
+                    _ => None,
+                })
+                .unwrap_or_else(|| unreachable!("checked in `check_arguments`"));
+            let needed = last_index.saturating_add(*min);
+            conditional_checks.push(quote!(
+                if positional_idx < #needed && seen.contains(&#option) {
+                    return Err(uutils_args::Error::MissingOperandWith {
+                        operand: #metavar.into(),
+                        option: #option.into(),
+                    });
+                }
+            ));
+        }
+
+        lead_minimum = last_index.saturating_add(*num_args.start());
+        last_index = last_index.saturating_add(*num_args.end());
+
+        if *command {
+            command_index = Some(last_index);
         }
 
-        last_index += num_args.end();
+        // All values for this argument are handled as raw arguments.
+        if follows_command {
+            continue;
+        }
 
         let expr = if *last {
             last_positional_expression(&arg.ident)
+        } else if has_trailing && last_index == usize::MAX {
+            quote!(return Ok(Some(Argument::Operand(value))))
         } else {
             positional_expression(&arg.ident)
         };
-        match_arms.push(quote!(0..=#last_index => { #expr }));
+        match_arms.push(quote!(0..=#last_index => {
+            flag.clear();
+            flag.push_str(#metavar);
+            #expr
+        }));
     }
 
+    // Values beyond the positional arguments, also if there are none, are
+    // extra operands. With keywords, they are operands that are not like
+    // the others, as for `dd`.
+    let unexpected = if has_keywords(args) {
+        quote!(return Err(Error::UnrecognizedOperand(value)))
+    } else {
+        quote!(return Err(Error::ExtraOperand(value)))
+    };
+
     let value_handling = quote!(
         *positional_idx += 1;
         match positional_idx {
             #(#match_arms)*
-            _ => return Err(lexopt::Arg::Value(value).unexpected().into()),
+            _ => #unexpected,
         }
     );
 
     let missing_argument_checks = quote!(
-        // We have the minimum number of required arguments overall.
-        // So we don't need to check the others.
-        if positional_idx >= #minimum_needed {
-            return Ok(());
-        }
-
-        let mut missing: Vec<&str> = vec![];
-        #(#missing_argument_checks)*
-        if !missing.is_empty() {
-            Err(uutils_args::Error::MissingPositionalArguments(
-                missing.iter().map(ToString::to_string).collect::<Vec<String>>()
-            ))
-        } else {
-            Ok(())
+        // With the minimum number of required arguments overall, we don't
+        // need to check the others.
+        if positional_idx < #minimum_needed {
+            let mut missing: Vec<&str> = vec![];
+            #(#missing_argument_checks)*
+            if !missing.is_empty() {
+                return Err(uutils_args::Error::MissingPositionalArguments(
+                    missing.iter().map(ToString::to_string).collect::<Vec<String>>()
+                ));
+            }
         }
+
+        #(#conditional_checks)*
+
+        Ok(())
     );
 
-    (value_handling, missing_argument_checks)
+    (value_handling, raw_handling, missing_argument_checks)
+}
+
+fn has_keywords(args: &[Argument]) -> bool {
+    args.iter().any(
+        |arg| matches!(&arg.arg_type, ArgType::Option { flags, .. } if !flags.keyword.is_empty()),
+    )
+}
+
+/// The handling of operands like `bs=1M` for keyword options
+///
+/// This comes before the positional arguments and also applies after `--`,
+/// because keywords are operands. An operand with an `=` after something
+/// that is not a keyword is rejected instead of being given to the
+/// positional arguments.
+pub(crate) fn keyword_handling(args: &[Argument]) -> TokenStream {
+    let mut match_arms = Vec::new();
+    for arg in args {
+        let ArgType::Option {
+            flags, delimiter, ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+        if flags.keyword.is_empty() {
+            continue;
+        }
+        let keywords = flags.keyword.iter().map(|k| &k.flag);
+        let ident = &arg.ident;
+        let parsed = parse_value(quote!(rest), delimiter);
+        match_arms.push(quote!(
+            keyword @ (#(#keywords)|*) => {
+                flag.clear();
+                flag.push_str(keyword);
+                return Ok(Some(Argument::Custom(Self::#ident(#parsed))));
+            }
+        ));
+    }
+    if match_arms.is_empty() {
+        return quote!();
+    }
+    quote!(
+        if let Some((keyword, rest)) = uutils_args::split_keyword(&value) {
+            match keyword {
+                #(#match_arms)*
+                _ => return Err(Error::UnrecognizedOperand(value)),
+            }
+        }
+    )
+}
+
+/// The handling of the first operand as the name of a subcommand, like
+/// [`positional_handling`]
+///
+/// The arguments after the name are parsed as is by the `Options` type in
+/// the field of the subcommand, with the name of the utility followed by the
+/// subcommand as its binary name. Its help and version are printed by that
+/// parse, like for `prog add --help`.
+fn subcommand_handling(args: &[Argument]) -> Option<(TokenStream, TokenStream, TokenStream)> {
+    let mut commands = Vec::new();
+    let mut match_arms = Vec::new();
+
+    for arg in args {
+        let ArgType::Subcommand { names } = &arg.arg_type else {
+            continue;
+        };
+        let ident = &arg.ident;
+        let ty = arg.field.as_ref().expect("checked in `parse_argument`");
+        let name = &names[0];
+        match_arms.push(quote!(#(#names)|* => {
+            // The subcommand gets the name and the configuration of this
+            // parse, but the help of its own arguments.
+            let parent = bin_name
+                .or(parser.bin_name())
+                .unwrap_or(config.help.name_or(Self::NAME));
+            let config = uutils_args::ParseConfig {
+                help: uutils_args::HelpConfig::default(),
+                ..*config
+            };
+            let iter = <<#ty as uutils_args::Options>::Arg as uutils_args::Arguments>::parse_with_name(
+                &format!("{parent} {}", #name),
+                raw_args,
+            )
+            .with_config(config);
+            Self::#ident(<#ty as uutils_args::Options>::try_parse_iter(iter)?)
+        }));
+        commands.extend(names);
+    }
+
+    if commands.is_empty() {
+        return None;
+    }
+
+    let num_commands = commands.len();
+    let unescaped = unescape_double_dash(quote!(v));
+
+    let value_handling = quote!(
+        static COMMANDS: [&str; #num_commands] = [#(#commands),*];
+        let name = uutils_args::resolve_subcommand(&value.to_string_lossy(), &COMMANDS)?;
+        *positional_idx += 1;
+        flag.clear();
+        flag.push_str(name);
+        let raw_args: Vec<std::ffi::OsString> = parser.raw_args()?.map(|v| #unescaped).collect();
+        match name {
+            #(#match_arms)*
+            _ => unreachable!("resolved to one of the subcommands"),
+        }
+    );
+
+    let missing_argument_checks = quote!(
+        if positional_idx == 0 {
+            return Err(uutils_args::Error::MissingPositionalArguments(vec![
+                "COMMAND".into(),
+            ]));
+        }
+        Ok(())
+    );
+
+    Some((value_handling, quote!(), missing_argument_checks))
+}
+
+/// The `default_operand` method of `Arguments`, which gives the defaults
+/// of the positional arguments that got no values
+///
+/// Each call considers the positional arguments from `next` onwards and
+/// returns the first default, so that `next` moves past every positional
+/// argument once.
+pub(crate) fn default_handling(args: &[Argument]) -> TokenStream {
+    let mut last_index: usize = 0;
+    let mut checks = Vec::new();
+
+    for arg in args {
+        let ArgType::Positional {
+            num_args, default, ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+        if let Some(default) = default {
+            let ident = &arg.ident;
+            let metavar = arg.metavar();
+            let n = checks.len();
+            let expr = quote!(Self::#ident(
+                FromValue::from_value("", std::ffi::OsString::from(#default))
+                    .map_err(|err| err.with_source(uutils_args::ValueSource::Default))?
+            ));
+            checks.push(quote!(
+                if *next <= #n {
+                    *next = #n + 1;
+                    if positional_idx <= #last_index {
+                        flag.clear();
+                        flag.push_str(#metavar);
+                        return Ok(Some(#expr));
+                    }
+                }
+            ));
+        }
+        last_index = last_index.saturating_add(*num_args.end());
+    }
+
+    if checks.is_empty() {
+        return quote!();
+    }
+
+    quote!(
+        fn default_operand(
+            positional_idx: usize,
+            next: &mut usize,
+            flag: &mut String,
+        ) -> Result<Option<Self>, uutils_args::Error> {
+            use uutils_args::FromValue;
+            #(#checks)*
+            Ok(None)
+        }
+    )
+}
+
+/// The items of `Arguments` that assign the operands held back for a
+/// positional argument with `from_end`
+///
+/// The trailing operands go to that argument and the others to the
+/// unbounded argument before it, see `check_arguments`.
+pub(crate) fn from_end_handling(args: &[Argument]) -> TokenStream {
+    let positionals: Vec<_> = args
+        .iter()
+        .filter(|arg| matches!(arg.arg_type, ArgType::Positional { .. }))
+        .collect();
+    let Some(idx) = positionals
+        .iter()
+        .position(|arg| matches!(arg.arg_type, ArgType::Positional { from_end: true, .. }))
+    else {
+        return quote!();
+    };
+    let (lead, trailing) = (positionals[idx - 1], positionals[idx]);
+    let ArgType::Positional { num_args, .. } = &trailing.arg_type else {
+        unreachable!("only positional arguments were selected");
+    };
+    let count = num_args.end();
+    let lead_metavar = lead.metavar();
+    let trailing_metavar = trailing.metavar();
+    let lead_expr = positional_expression(&lead.ident);
+    let trailing_expr = positional_expression(&trailing.ident);
+
+    quote!(
+        const TRAILING_OPERANDS: usize = #count;
+
+        fn from_operand(
+            value: std::ffi::OsString,
+            trailing: bool,
+            flag: &mut String,
+        ) -> Result<Self, uutils_args::Error> {
+            use uutils_args::FromValue;
+            Ok(if trailing {
+                flag.clear();
+                flag.push_str(#trailing_metavar);
+                #trailing_expr
+            } else {
+                flag.clear();
+                flag.push_str(#lead_metavar);
+                #lead_expr
+            })
+        }
+    )
+}
+
+/// Reject a value attached to a long option that does not take one, like
+/// `--recursive=1`
+///
+/// The error names the option as it is defined, even if it was abbreviated,
+/// and the value is consumed, so that the next argument is parsed as usual.
+fn no_attached_value(expr: TokenStream) -> TokenStream {
+    quote!(
+        if let Some(value) = parser.optional_value() {
+            return Err(Error::UnexpectedValue {
+                option: flag.clone(),
+                value,
+            });
+        }
+        #expr
+    )
 }
 
 fn no_value_expression(ident: &Ident) -> TokenStream {
     quote!(Self::#ident)
 }
 
-fn default_value_expression(ident: &Ident, default_expr: &TokenStream) -> TokenStream {
+fn default_expression(default: &Option<Box<Expr>>) -> TokenStream {
+    match default {
+        Some(expr) => quote!(#expr),
+        None => quote!(Default::default()),
+    }
+}
+
+fn default_value_expression(ident: &Ident, default: &Option<Box<Expr>>) -> TokenStream {
+    let default_expr = default_expression(default);
     quote!(Self::#ident(#default_expr))
 }
 
-fn optional_value_expression(ident: &Ident, default_expr: &TokenStream) -> TokenStream {
+/// The parse of the `value` of an option, split into a list if it has a
+/// `delimiter`
+fn parse_value(value: TokenStream, delimiter: &Option<Delimiter>) -> TokenStream {
+    match delimiter {
+        Some(Delimiter { byte, skip_empty }) => {
+            quote!(uutils_args::split_value(flag.as_str(), #value, #byte, #skip_empty)?)
+        }
+        None => quote!(FromValue::from_value(flag.as_str(), #value)?),
+    }
+}
+
+fn optional_value_expression(
+    ident: &Ident,
+    default: &Option<Box<Expr>>,
+    delimiter: &Option<Delimiter>,
+) -> TokenStream {
+    // Without a `default`, the field is like `Option<T>`, see
+    // `parse_argument`
+    let default_expr = match default {
+        Some(expr) => quote!(#expr),
+        None => quote!(None),
+    };
+    let parsed = parse_value(quote!(value), delimiter);
+    // An attached empty value, like `--suffix=`, is a value, so it is
+    // parsed instead of falling back to the default.
     quote!(match parser.optional_value() {
-        Some(value) => Self::#ident(FromValue::from_value(&option, value)?),
+        Some(value) => Self::#ident(#parsed),
         None => Self::#ident(#default_expr),
     })
 }
 
-fn required_value_expression(ident: &Ident) -> TokenStream {
-    quote!(Self::#ident(FromValue::from_value(&option, parser.value()?)?))
+fn required_value_expression(ident: &Ident, delimiter: &Option<Delimiter>) -> TokenStream {
+    // The error names the option as it was resolved, like `--lines` for
+    // `--lin`, instead of as lexopt saw it.
+    let value = unescape_double_dash(quote!(parser.value().map_err(|_| {
+        uutils_args::Error::MissingValue {
+            option: Some(flag.clone()),
+        }
+    })?));
+    let parsed = parse_value(value, delimiter);
+    quote!(Self::#ident(#parsed))
 }
 
 fn positional_expression(ident: &Ident) -> TokenStream {
@@ -332,13 +1824,36 @@ fn positional_expression(ident: &Ident) -> TokenStream {
 }
 
 fn last_positional_expression(ident: &Ident) -> TokenStream {
+    let unescaped = unescape_double_dash(quote!(v));
     // TODO: Add option name in this from_value call
     quote!({
         let raw_args = parser.raw_args()?;
         let collection = std::iter::once(value)
             .chain(raw_args)
-            .map(|v| FromValue::from_value("", v))
+            .map(|v| FromValue::from_value("", #unescaped))
             .collect::<Result<_,_>>()?;
         Self::#ident(collection)
     })
 }
+
+fn raw_positional_expression(ident: &Ident, metavar: &str, command_index: usize) -> TokenStream {
+    let unescaped = unescape_double_dash(quote!(v));
+    // TODO: Add option name in this from_value call
+    quote!(
+        // Everything after the command is passed on as is, including
+        // arguments that look like options.
+        if *positional_idx == #command_index {
+            let raw_args: Vec<std::ffi::OsString> = parser.raw_args()?.collect();
+            if !raw_args.is_empty() {
+                *positional_idx += raw_args.len();
+                flag.clear();
+                flag.push_str(#metavar);
+                let collection = raw_args
+                    .into_iter()
+                    .map(|v| FromValue::from_value("", #unescaped))
+                    .collect::<Result<_,_>>()?;
+                return Ok(Some(Argument::Custom(Self::#ident(collection))));
+            }
+        }
+    )
+}
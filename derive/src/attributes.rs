@@ -1,87 +1,310 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, path::PathBuf};
 
+use proc_macro2::Span;
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
-    Attribute, Expr, ExprLit, ExprRange, Ident, Lit, LitInt, LitStr, RangeLimits, Token,
+    Attribute, Expr, ExprLit, ExprRange, Ident, Lit, LitChar, LitInt, LitStr, Path, RangeLimits,
+    Token,
 };
 
-use crate::flags::Flags;
+use crate::{flags::Flags, help::read_file};
 
 pub(crate) enum ArgAttr {
     Option(OptionAttr),
     Positional(PositionalAttr),
+    Subcommand(SubcommandAttr),
+    Flatten,
 }
 
-pub(crate) fn parse_argument_attribute(attr: &Attribute) -> ArgAttr {
+pub(crate) fn parse_argument_attribute(attr: &Attribute) -> syn::Result<ArgAttr> {
     if attr.path.is_ident("option") {
-        ArgAttr::Option(OptionAttr::parse(attr))
+        Ok(ArgAttr::Option(OptionAttr::parse(attr)?))
     } else if attr.path.is_ident("positional") {
-        ArgAttr::Positional(PositionalAttr::parse(attr))
+        Ok(ArgAttr::Positional(PositionalAttr::parse(attr)?))
+    } else if attr.path.is_ident("subcommand") {
+        Ok(ArgAttr::Subcommand(SubcommandAttr::parse(attr)?))
+    } else if attr.path.is_ident("flatten") {
+        if let Some((span, _)) = AttributeArguments::parse_all(attr)?.first() {
+            return Err(unsupported(*span, attr));
+        }
+        Ok(ArgAttr::Flatten)
     } else {
-        panic!("Internal error: invalid argument attribute");
+        unreachable!("only option, positional, subcommand and flatten attributes are passed")
     }
 }
 
+/// The variants of `ValueHint` that can be given with `hint = ...`
+const VALUE_HINTS: &[&str] = &[
+    "Unknown",
+    "AnyPath",
+    "FilePath",
+    "ExecutablePath",
+    "DirPath",
+    "Username",
+    "Hostname",
+];
+
 enum AttributeArguments {
     String(String),
     Parser(Expr),
     Default(Expr),
+    DefaultByName(Expr),
     Value(Expr),
+    Hint(Ident),
+    HintFn(Path),
+    Category(String),
     NumArgs(RangeInclusive<usize>),
-    File(String),
+    File(LitStr),
+    CompletionExtra(LitStr),
     Env(String),
     ExitCode(i32),
-    Help(Vec<String>),
-    Version(Vec<String>),
+    Help(Vec<LitStr>),
+    ShortHelp(Vec<LitStr>),
+    HelpText(String),
+    Version(Vec<LitStr>),
+    VersionExtra(Expr),
     Last,
+    Command,
+    CountOnly,
+    MinIf(Ident, usize),
+    FromEnd(usize),
     Hidden,
+    HiddenHelp,
+    HiddenComplete,
+    HidePossibleValues,
+    Record,
+    ArgFile,
+    ParseMode(Ident),
+    CollectUnknown(Ident),
+    UnrecognizedShortAsPositional,
+    DenyMissingHelp,
+    Literal,
+    WarnMissingHelp,
+    ValueName(String),
+    Keyword(LitStr),
+    Delimiter(LitChar),
+    SkipEmpty,
+    MixedArity,
+    FromStr,
+    Display,
+    Exact,
+    Fallback,
+    IgnoreInvalidEnv,
+    Exclusive,
+    Deprecated(String),
+    MaxOccurrences(usize),
 }
 
 impl AttributeArguments {
-    fn parse_all(attr: &Attribute) -> Vec<Self> {
-        attr.parse_args_with(Punctuated::<AttributeArguments, Token![,]>::parse_terminated)
-            .map(|iter| iter.into_iter().collect::<Vec<_>>())
-            .unwrap_or_default()
+    /// Parse the arguments of `attr`, each with the span of its first token
+    /// to report errors at
+    fn parse_all(attr: &Attribute) -> syn::Result<Vec<(Span, Self)>> {
+        // An attribute without arguments, like `#[positional]`
+        if attr.tokens.is_empty() {
+            return Ok(Vec::new());
+        }
+        let args = attr.parse_args_with(|input: ParseStream| {
+            Punctuated::<_, Token![,]>::parse_terminated_with(input, |input| {
+                Ok((input.span(), input.parse()?))
+            })
+        })?;
+        Ok(args.into_iter().collect())
     }
 }
 
+/// The error for an argument that the attribute does not support
+fn unsupported(span: Span, attr: &Attribute) -> syn::Error {
+    let name = attr
+        .path
+        .get_ident()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    syn::Error::new(
+        span,
+        format!("This argument is not supported in `#[{name}]`"),
+    )
+}
+
+/// The shell and absolute path of a file with extra completions
+///
+/// The shell follows from the extension of the file, which is relative to the
+/// manifest of the crate like the `file` of the help.
+fn completion_extra(file: &LitStr) -> syn::Result<(String, String)> {
+    const SHELLS: &[(&str, &str)] = &[
+        ("fish", "fish"),
+        ("bash", "bash"),
+        ("zsh", "zsh"),
+        ("ps1", "powershell"),
+        ("nu", "nu"),
+    ];
+    let path =
+        PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(file.value());
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let Some((_, shell)) = SHELLS.iter().find(|(ext, _)| *ext == extension) else {
+        return Err(syn::Error::new(
+            file.span(),
+            format!(
+                "Cannot tell the shell of '{}', expected an extension of: {}",
+                file.value(),
+                SHELLS
+                    .iter()
+                    .map(|(ext, _)| *ext)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    };
+    if !path.is_file() {
+        return Err(syn::Error::new(
+            file.span(),
+            format!("File '{}' does not exist", path.display()),
+        ));
+    }
+    Ok((shell.to_string(), path.to_string_lossy().into_owned()))
+}
+
+/// Flags from string literals, with errors at the invalid literal
+fn parse_flags(flags: &[LitStr]) -> syn::Result<Flags> {
+    let mut parsed = Flags::default();
+    for flag in flags {
+        parsed
+            .add(&flag.value())
+            .map_err(|msg| syn::Error::new(flag.span(), msg))?;
+    }
+    Ok(parsed)
+}
+
 pub(crate) struct ArgumentsAttr {
     pub(crate) help_flags: Flags,
+    /// The flags that print the condensed help, none by default
+    pub(crate) short_help_flags: Flags,
     pub(crate) version_flags: Flags,
+    /// Build information that is added to the version
+    pub(crate) version_extra: Option<Expr>,
+    /// The contents of the help file
     pub(crate) file: Option<String>,
+    /// Snippets appended to the completion script of a shell, as the shell
+    /// and the absolute path of the file
+    pub(crate) completion_extra: Vec<(String, String)>,
     pub(crate) exit_code: i32,
+    /// Whether positional values starting with `@` are read from a file
+    pub(crate) argfile: bool,
+    /// Whether all arguments after the first operand are operands
+    pub(crate) posix: bool,
+    /// The variant that unrecognized options are returned as
+    pub(crate) collect_unknown: Option<Ident>,
+    /// Whether an unrecognized short option and the rest of its cluster are
+    /// an operand, like the mode in `chmod -w file`
+    pub(crate) unrecognized_short_as_positional: bool,
+    /// What to do with options without help text
+    pub(crate) missing_help: MissingHelp,
+}
+
+/// What to do with options that are shown in the help, but have no help text
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum MissingHelp {
+    #[default]
+    Allow,
+    Warn,
+    Deny,
 }
 
 impl Default for ArgumentsAttr {
     fn default() -> Self {
         Self {
             help_flags: Flags::new(["--help"]),
+            short_help_flags: Flags::default(),
             version_flags: Flags::new(["--version"]),
+            version_extra: None,
             file: None,
+            completion_extra: Vec::new(),
             exit_code: 1,
+            argfile: false,
+            posix: false,
+            collect_unknown: None,
+            unrecognized_short_as_positional: false,
+            missing_help: MissingHelp::Allow,
         }
     }
 }
 
 impl ArgumentsAttr {
-    pub(crate) fn parse(attr: &Attribute) -> Self {
+    pub(crate) fn parse(attr: &Attribute) -> syn::Result<Self> {
         let mut arguments_attr = Self::default();
-        for arg in AttributeArguments::parse_all(attr) {
+        for (span, arg) in AttributeArguments::parse_all(attr)? {
             match arg {
                 AttributeArguments::Help(flags) => {
-                    arguments_attr.help_flags = Flags::new(flags);
+                    arguments_attr.help_flags = parse_flags(&flags)?;
+                }
+                AttributeArguments::ShortHelp(flags) => {
+                    arguments_attr.short_help_flags = parse_flags(&flags)?;
                 }
                 AttributeArguments::Version(flags) => {
-                    arguments_attr.version_flags = Flags::new(flags);
+                    arguments_attr.version_flags = parse_flags(&flags)?;
+                }
+                AttributeArguments::VersionExtra(e) => arguments_attr.version_extra = Some(e),
+                AttributeArguments::File(s) => arguments_attr.file = Some(read_file(&s)?),
+                AttributeArguments::CompletionExtra(file) => {
+                    let extra = completion_extra(&file)?;
+                    if arguments_attr
+                        .completion_extra
+                        .iter()
+                        .any(|(shell, _)| *shell == extra.0)
+                    {
+                        return Err(syn::Error::new(
+                            file.span(),
+                            format!("Extra completions for {} are already given", extra.0),
+                        ));
+                    }
+                    arguments_attr.completion_extra.push(extra);
                 }
-                AttributeArguments::File(s) => arguments_attr.file = Some(s),
                 AttributeArguments::ExitCode(code) => arguments_attr.exit_code = code,
-                _ => panic!(),
+                AttributeArguments::ArgFile => arguments_attr.argfile = true,
+                AttributeArguments::ParseMode(mode) => {
+                    arguments_attr.posix = match mode.to_string().as_str() {
+                        "posix" => true,
+                        "permute" => false,
+                        _ => {
+                            return Err(syn::Error::new(
+                                mode.span(),
+                                format!(
+                                    "Invalid parse mode `{mode}`, expected `posix` or `permute`"
+                                ),
+                            ))
+                        }
+                    };
+                }
+                AttributeArguments::CollectUnknown(variant) => {
+                    arguments_attr.collect_unknown = Some(variant);
+                }
+                AttributeArguments::UnrecognizedShortAsPositional => {
+                    arguments_attr.unrecognized_short_as_positional = true;
+                }
+                AttributeArguments::DenyMissingHelp => {
+                    arguments_attr.missing_help = MissingHelp::Deny;
+                }
+                AttributeArguments::WarnMissingHelp => {
+                    arguments_attr.missing_help = MissingHelp::Warn;
+                }
+                _ => return Err(unsupported(span, attr)),
             }
         }
 
-        arguments_attr
+        if arguments_attr.collect_unknown.is_some()
+            && arguments_attr.unrecognized_short_as_positional
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`collect_unknown` and `unrecognized_short_as_positional` cannot both be given, \
+                because both take the unrecognized short options",
+            ));
+        }
+
+        Ok(arguments_attr)
     }
 }
 
@@ -90,51 +313,170 @@ pub(crate) struct OptionAttr {
     pub(crate) flags: Flags,
     pub(crate) parser: Option<Expr>,
     pub(crate) default: Option<Expr>,
-    pub(crate) hidden: bool,
+    /// Whether the option is left out of the help and the man page
+    pub(crate) hidden_help: bool,
+    /// Whether the option is left out of the shell completions
+    pub(crate) hidden_complete: bool,
+    /// The variant of `ValueHint` to use instead of the hint of the type
+    pub(crate) hint: Option<Ident>,
+    /// A function returning the values to complete
+    pub(crate) hint_fn: Option<Path>,
+    /// The heading the option is listed under in the help
+    pub(crate) category: Option<String>,
+    /// Whether the values of the type are left out of the help
+    pub(crate) hide_possible_values: bool,
+    /// Whether the tokens of the option are kept as typed
+    pub(crate) record: bool,
+    /// Whether the option ends the parse
+    pub(crate) exclusive: bool,
+    /// The warning given when the option is used
+    pub(crate) deprecated: Option<String>,
+    /// How often the option can be given
+    pub(crate) max_occurrences: Option<usize>,
+    /// How the value is split into a list
+    pub(crate) delimiter: Option<Delimiter>,
+}
+
+/// How the value of an option is split into a list, like `1,3-5` for
+/// `cut -f`
+#[derive(Clone, Copy)]
+pub(crate) struct Delimiter {
+    /// The ASCII character that separates the items
+    pub(crate) byte: u8,
+    /// Whether empty items are left out instead of being an error
+    pub(crate) skip_empty: bool,
 }
 
 impl OptionAttr {
-    pub(crate) fn parse(attr: &Attribute) -> Self {
+    pub(crate) fn parse(attr: &Attribute) -> syn::Result<Self> {
         let mut option_attr = OptionAttr::default();
+        let mut value_name = None;
+        let mut mixed_arity = false;
+        let mut literal = false;
+        let mut skip_empty = false;
 
-        for arg in AttributeArguments::parse_all(attr) {
+        for (span, arg) in AttributeArguments::parse_all(attr)? {
             match arg {
-                AttributeArguments::String(a) => option_attr.flags.add(&a),
+                AttributeArguments::String(a) => option_attr
+                    .flags
+                    .add(&a)
+                    .map_err(|msg| syn::Error::new(span, msg))?,
                 AttributeArguments::Parser(e) => option_attr.parser = Some(e),
                 AttributeArguments::Default(e) => option_attr.default = Some(e),
-                AttributeArguments::Hidden => option_attr.hidden = true,
-                _ => panic!("Invalid argument"),
+                AttributeArguments::Hidden => {
+                    option_attr.hidden_help = true;
+                    option_attr.hidden_complete = true;
+                }
+                AttributeArguments::HiddenHelp => option_attr.hidden_help = true,
+                AttributeArguments::HiddenComplete => option_attr.hidden_complete = true,
+                AttributeArguments::HidePossibleValues => option_attr.hide_possible_values = true,
+                AttributeArguments::Record => option_attr.record = true,
+                AttributeArguments::Exclusive => option_attr.exclusive = true,
+                AttributeArguments::Deprecated(d) => option_attr.deprecated = Some(d),
+                AttributeArguments::MaxOccurrences(n) => option_attr.max_occurrences = Some(n),
+                AttributeArguments::Hint(h) => option_attr.hint = Some(h),
+                AttributeArguments::HintFn(f) => option_attr.hint_fn = Some(f),
+                AttributeArguments::Category(c) => option_attr.category = Some(c),
+                AttributeArguments::ValueName(n) => value_name = Some(n),
+                AttributeArguments::Keyword(k) => option_attr
+                    .flags
+                    .add_keyword(&k.value())
+                    .map_err(|msg| syn::Error::new(k.span(), msg))?,
+                AttributeArguments::Delimiter(d) => {
+                    // Splitting at an ASCII byte keeps the items valid
+                    // `OsStr`s, see `uutils_args::split_value`
+                    let c = d.value();
+                    if !c.is_ascii() {
+                        return Err(syn::Error::new(d.span(), "The delimiter must be ASCII"));
+                    }
+                    option_attr.delimiter = Some(Delimiter {
+                        byte: c as u8,
+                        skip_empty: false,
+                    });
+                }
+                AttributeArguments::SkipEmpty => skip_empty = true,
+                AttributeArguments::MixedArity => mixed_arity = true,
+                AttributeArguments::Literal => literal = true,
+                _ => return Err(unsupported(span, attr)),
             };
         }
 
-        assert!(
-            !option_attr.flags.is_empty(),
-            "must give a flag in an option attribute"
-        );
+        let error = |msg: &str| Err(syn::Error::new_spanned(attr, msg));
+        if option_attr.flags.is_empty() {
+            return error("must give a flag in an option attribute");
+        }
+        if let Err(msg) = option_attr
+            .flags
+            .check_values(value_name.as_deref(), mixed_arity)
+        {
+            return error(&msg);
+        }
+        if option_attr.hint.is_some() && option_attr.hint_fn.is_some() {
+            return error("Only one of `hint` and `hint_fn` can be given");
+        }
+        if !literal && option_attr.flags.double_dash {
+            return error("`--` ends the options, add `literal` to use it as an option instead");
+        }
+        if literal && !option_attr.flags.double_dash {
+            return error("`literal` can only be given for the flag `--`");
+        }
+        if !option_attr.flags.keyword.is_empty()
+            && option_attr.flags.keyword.len() != option_attr.flags.len()
+        {
+            return error("A keyword option like `bs=BYTES` cannot have flags as well");
+        }
+        match &mut option_attr.delimiter {
+            Some(delimiter) => delimiter.skip_empty = skip_empty,
+            None if skip_empty => {
+                return error("`skip_empty` can only be given with a `delimiter`")
+            }
+            None => {}
+        }
 
-        option_attr
+        Ok(option_attr)
     }
 }
 
 #[derive(Default)]
 pub(crate) struct FieldAttr {
     pub(crate) default: Option<Expr>,
+    /// A function from the bin name to the default value
+    pub(crate) default_by_name: Option<Expr>,
     pub(crate) env: Option<String>,
+    /// Use the default instead of failing if the environment variable is
+    /// invalid
+    pub(crate) ignore_invalid_env: bool,
 }
 
 impl FieldAttr {
-    pub(crate) fn parse(attr: &Attribute) -> Self {
+    pub(crate) fn parse(attr: &Attribute) -> syn::Result<Self> {
         let mut field_attr = Self::default();
 
-        for arg in AttributeArguments::parse_all(attr) {
+        for (span, arg) in AttributeArguments::parse_all(attr)? {
             match arg {
                 AttributeArguments::Default(e) => field_attr.default = Some(e),
+                AttributeArguments::DefaultByName(e) => field_attr.default_by_name = Some(e),
                 AttributeArguments::Env(e) => field_attr.env = Some(e),
-                _ => panic!("Invalid argument"),
+                AttributeArguments::IgnoreInvalidEnv => field_attr.ignore_invalid_env = true,
+                _ => return Err(unsupported(span, attr)),
             };
         }
 
-        field_attr
+        if field_attr.ignore_invalid_env && field_attr.env.is_none() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`ignore_invalid_env` can only be given with `env`",
+            ));
+        }
+
+        if field_attr.default.is_some() && field_attr.default_by_name.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "Only one of `default` and `default_by_name` can be given",
+            ));
+        }
+
+        Ok(field_attr)
     }
 }
 
@@ -142,27 +484,93 @@ impl FieldAttr {
 pub(crate) struct ValueAttr {
     pub(crate) keys: Vec<String>,
     pub(crate) value: Option<Expr>,
+    /// Description of the value for completion
+    pub(crate) help: Option<String>,
+    /// Whether the variant holds any value that does not match a key
+    pub(crate) fallback: bool,
 }
 
 impl ValueAttr {
-    pub(crate) fn parse(attr: &Attribute) -> Self {
+    pub(crate) fn parse(attr: &Attribute) -> syn::Result<Self> {
         let mut value_attr = Self::default();
 
-        for arg in AttributeArguments::parse_all(attr) {
+        for (span, arg) in AttributeArguments::parse_all(attr)? {
             match arg {
                 AttributeArguments::String(k) => value_attr.keys.push(k),
                 AttributeArguments::Value(e) => value_attr.value = Some(e),
-                _ => panic!(),
+                AttributeArguments::HelpText(h) => value_attr.help = Some(h),
+                AttributeArguments::Fallback => value_attr.fallback = true,
+                _ => return Err(unsupported(span, attr)),
             };
         }
 
-        value_attr
+        if value_attr.fallback && (!value_attr.keys.is_empty() || value_attr.value.is_some()) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "A `fallback` value cannot have keys or a `value` expression",
+            ));
+        }
+
+        Ok(value_attr)
+    }
+}
+
+/// Additional impls to derive for a `FromValue` enum
+#[derive(Default)]
+pub(crate) struct ValueExtrasAttr {
+    pub(crate) from_str: bool,
+    pub(crate) display: bool,
+    /// Whether `FromStr` only accepts exact keys instead of also accepting
+    /// unambiguous abbreviations like `from_value` does.
+    pub(crate) exact: bool,
+}
+
+impl ValueExtrasAttr {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut extras_attr = Self::default();
+        let mut exact = None;
+
+        for attr in attrs {
+            if !attr.path.is_ident("value_extras") {
+                continue;
+            }
+            for (span, arg) in AttributeArguments::parse_all(attr)? {
+                match arg {
+                    AttributeArguments::FromStr => extras_attr.from_str = true,
+                    AttributeArguments::Display => extras_attr.display = true,
+                    AttributeArguments::Exact => {
+                        extras_attr.exact = true;
+                        exact = Some(span);
+                    }
+                    _ => return Err(unsupported(span, attr)),
+                };
+            }
+        }
+
+        if let (Some(span), false) = (exact, extras_attr.from_str) {
+            return Err(syn::Error::new(span, "`exact` only applies to `FromStr`"));
+        }
+
+        Ok(extras_attr)
     }
 }
 
 pub(crate) struct PositionalAttr {
     pub(crate) num_args: RangeInclusive<usize>,
     pub(crate) last: bool,
+    pub(crate) command: bool,
+    pub(crate) count_only: bool,
+    /// The minimum number of values when the option of the variant is given
+    pub(crate) min_if: Vec<(Ident, usize)>,
+    /// Whether the values are the last ones on the command line, after
+    /// those of the preceding positional argument
+    pub(crate) from_end: bool,
+    /// The value used when no values are given
+    pub(crate) default: Option<Expr>,
+    /// The name of the values, like `SET2`
+    pub(crate) value_name: Option<String>,
+    /// A function returning the values to complete
+    pub(crate) hint_fn: Option<Path>,
 }
 
 impl Default for PositionalAttr {
@@ -170,140 +578,322 @@ impl Default for PositionalAttr {
         Self {
             num_args: 1..=1,
             last: false,
+            command: false,
+            count_only: false,
+            min_if: Vec::new(),
+            from_end: false,
+            default: None,
+            value_name: None,
+            hint_fn: None,
         }
     }
 }
 
 impl PositionalAttr {
-    pub(crate) fn parse(attr: &Attribute) -> Self {
+    pub(crate) fn parse(attr: &Attribute) -> syn::Result<Self> {
         let mut positional_attr = Self::default();
+        let mut num_args_span = None;
 
-        for arg in AttributeArguments::parse_all(attr) {
+        for (span, arg) in AttributeArguments::parse_all(attr)? {
             match arg {
-                AttributeArguments::NumArgs(k) => positional_attr.num_args = k,
+                AttributeArguments::NumArgs(k) => {
+                    positional_attr.num_args = k;
+                    num_args_span = Some(span);
+                }
+                AttributeArguments::FromEnd(n) => {
+                    if n == 0 {
+                        return Err(syn::Error::new(span, "`from_end` must be at least 1"));
+                    }
+                    positional_attr.num_args = n..=n;
+                    positional_attr.from_end = true;
+                }
+                AttributeArguments::Default(e) => positional_attr.default = Some(e),
+                AttributeArguments::ValueName(n) => positional_attr.value_name = Some(n),
+                AttributeArguments::HintFn(f) => positional_attr.hint_fn = Some(f),
                 AttributeArguments::Last => positional_attr.last = true,
-                _ => panic!(),
+                AttributeArguments::Command => positional_attr.command = true,
+                AttributeArguments::CountOnly => positional_attr.count_only = true,
+                AttributeArguments::MinIf(variant, min) => {
+                    positional_attr.min_if.push((variant, min))
+                }
+                _ => return Err(unsupported(span, attr)),
+            };
+        }
+
+        if let (true, Some(span)) = (positional_attr.from_end, num_args_span) {
+            return Err(syn::Error::new(
+                span,
+                "A positional argument with `from_end` cannot have a range",
+            ));
+        }
+
+        Ok(positional_attr)
+    }
+}
+
+/// The names of a subcommand, like `#[subcommand("remove", "rm")]`
+///
+/// Without names, the subcommand is named after the variant.
+#[derive(Default)]
+pub(crate) struct SubcommandAttr {
+    pub(crate) names: Vec<String>,
+}
+
+impl SubcommandAttr {
+    pub(crate) fn parse(attr: &Attribute) -> syn::Result<Self> {
+        let mut subcommand_attr = Self::default();
+
+        for (span, arg) in AttributeArguments::parse_all(attr)? {
+            match arg {
+                AttributeArguments::String(name) => {
+                    if name.is_empty() || name.starts_with('-') {
+                        return Err(syn::Error::new(
+                            span,
+                            "A subcommand name cannot be empty or start with '-'",
+                        ));
+                    }
+                    subcommand_attr.names.push(name);
+                }
+                _ => return Err(unsupported(span, attr)),
             };
         }
 
-        positional_attr
+        Ok(subcommand_attr)
     }
 }
 
+/// The `usize` of a bound of a range
+fn range_bound(bound: Option<&Expr>) -> syn::Result<Option<usize>> {
+    match bound {
+        Some(Expr::Lit(ExprLit {
+            lit: Lit::Int(i), ..
+        })) => Ok(Some(i.base10_parse::<usize>()?)),
+        None => Ok(None),
+        Some(bound) => Err(syn::Error::new_spanned(
+            bound,
+            "Range must consist of usize",
+        )),
+    }
+}
+
+/// String literals in an array, like the flags in `help = ["-h", "--help"]`
+fn string_array(expr: Expr, name: &str) -> syn::Result<Vec<LitStr>> {
+    let syn::Expr::Array(arr) = expr else {
+        return Err(syn::Error::new_spanned(
+            expr,
+            format!("Argument to `{name}` must be an array"),
+        ));
+    };
+    arr.elems
+        .into_iter()
+        .map(|elem| match elem {
+            syn::Expr::Lit(syn::ExprLit {
+                attrs: _,
+                lit: syn::Lit::Str(litstr),
+            }) => Ok(litstr),
+            elem => Err(syn::Error::new_spanned(
+                elem,
+                format!("Argument to `{name}` must be an array of string literals"),
+            )),
+        })
+        .collect()
+}
+
 impl Parse for AttributeArguments {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         if input.peek(LitStr) {
-            return Ok(Self::String(input.parse::<LitStr>().unwrap().value()));
+            return Ok(Self::String(input.parse::<LitStr>()?.value()));
         }
 
         if (input.peek(LitInt) && input.peek2(Token![..])) || input.peek(Token![..]) {
             // We're dealing with a range
             let range = input.parse::<ExprRange>()?;
-            let from = match range.from.as_deref() {
-                Some(Expr::Lit(ExprLit {
-                    lit: Lit::Int(i), ..
-                })) => i.base10_parse::<usize>().unwrap(),
-                None => 0,
-                _ => panic!("Range must consist of usize"),
-            };
-
-            let inclusive = matches!(range.limits, RangeLimits::Closed(_));
-            let to = match range.to.as_deref() {
-                Some(Expr::Lit(ExprLit {
-                    lit: Lit::Int(i), ..
-                })) => {
-                    let n = i.base10_parse::<usize>().unwrap();
-                    if inclusive {
-                        Some(n)
-                    } else {
-                        Some(n - 1)
-                    }
-                }
-                None => None,
-                _ => panic!("Range must consist of usize"),
-            };
-
-            return Ok(Self::NumArgs(match to {
-                Some(to) => from..=to,
-                None => from..=usize::MAX,
-            }));
+            let from = range_bound(range.from.as_deref())?.unwrap_or(0);
+            let to =
+                match range_bound(range.to.as_deref())? {
+                    Some(n) if matches!(range.limits, RangeLimits::Closed(_)) => Some(n),
+                    Some(n) => Some(n.checked_sub(1).ok_or_else(|| {
+                        syn::Error::new_spanned(&range, "The range cannot be empty")
+                    })?),
+                    None => None,
+                };
+            let to = to.unwrap_or(usize::MAX);
+            if from > to {
+                return Err(syn::Error::new_spanned(
+                    &range,
+                    "The start of the range cannot be after its end",
+                ));
+            }
+            return Ok(Self::NumArgs(from..=to));
         } else if input.peek(LitInt) {
             // We're dealing with a single interger
             let int = input.parse::<LitInt>()?;
             let suffix = int.suffix();
-            assert!(
-                suffix.is_empty() || suffix == "usize",
-                "The position index must be usize"
-            );
-            let n = int.base10_parse::<usize>().unwrap();
+            if !(suffix.is_empty() || suffix == "usize") {
+                return Err(syn::Error::new(
+                    int.span(),
+                    "The position index must be usize",
+                ));
+            }
+            let n = int.base10_parse::<usize>()?;
             return Ok(Self::NumArgs(n..=n));
         }
 
         if input.peek(Ident) {
-            let name = input.parse::<Ident>()?.to_string();
+            let ident = input.parse::<Ident>()?;
+            let name = ident.to_string();
 
             // Arguments that do not take values
             match name.as_str() {
                 "last" => return Ok(Self::Last),
+                "command" => return Ok(Self::Command),
+                "count_only" => return Ok(Self::CountOnly),
                 "hidden" => return Ok(Self::Hidden),
+                "hidden_help" => return Ok(Self::HiddenHelp),
+                "hidden_complete" => return Ok(Self::HiddenComplete),
+                "hide_possible_values" => return Ok(Self::HidePossibleValues),
+                "record" => return Ok(Self::Record),
+                "argfile" => return Ok(Self::ArgFile),
+                "deny_missing_help" => return Ok(Self::DenyMissingHelp),
+                "unrecognized_short_as_positional" => {
+                    return Ok(Self::UnrecognizedShortAsPositional)
+                }
+                "literal" => return Ok(Self::Literal),
+                "warn_missing_help" => return Ok(Self::WarnMissingHelp),
+                "mixed_arity" => return Ok(Self::MixedArity),
+                "skip_empty" => return Ok(Self::SkipEmpty),
+                "FromStr" => return Ok(Self::FromStr),
+                "Display" => return Ok(Self::Display),
+                "exact" => return Ok(Self::Exact),
+                "fallback" => return Ok(Self::Fallback),
+                "ignore_invalid_env" => return Ok(Self::IgnoreInvalidEnv),
+                "exclusive" => return Ok(Self::Exclusive),
                 _ => {}
             };
 
+            // Arguments that take a list in parentheses
+            if name == "min_if" {
+                let content;
+                syn::parenthesized!(content in input);
+                let variant = content.parse::<syn::Path>()?;
+                let variant = variant.segments.last().unwrap().ident.clone();
+                content.parse::<Token![,]>()?;
+                let min = content.parse::<LitInt>()?.base10_parse::<usize>()?;
+                return Ok(Self::MinIf(variant, min));
+            }
+
+            const WITH_VALUE: &[&str] = &[
+                "parser",
+                "default",
+                "default_by_name",
+                "value",
+                "hint",
+                "hint_fn",
+                "category",
+                "value_name",
+                "file",
+                "completion_extra",
+                "env",
+                "exit_code",
+                "parse_mode",
+                "collect_unknown",
+                "help",
+                "short_help",
+                "version",
+                "version_extra",
+                "from_end",
+                "deprecated",
+                "max_occurrences",
+                "keyword",
+                "delimiter",
+            ];
+            if !WITH_VALUE.contains(&name.as_str()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("Unrecognized argument `{name}`"),
+                ));
+            }
+
             input.parse::<Token![=]>()?;
 
             // Arguments that do take values
-            match name.as_str() {
-                "parser" => return Ok(Self::Parser(input.parse::<Expr>()?)),
-                "default" => return Ok(Self::Default(input.parse::<Expr>()?)),
-                "value" => return Ok(Self::Value(input.parse::<Expr>()?)),
-                "file" => return Ok(Self::File(input.parse::<LitStr>()?.value())),
-                "env" => return Ok(Self::Env(input.parse::<LitStr>()?.value())),
-                "exit_code" => return Ok(Self::ExitCode(input.parse::<LitInt>()?.base10_parse()?)),
-                "help" => {
-                    let expr = input.parse::<Expr>()?;
-                    let arr = match expr {
-                        syn::Expr::Array(arr) => arr,
-                        _ => panic!("Argument to `help` must be an array"),
-                    };
-
-                    let mut strings = Vec::new();
-                    for elem in arr.elems {
-                        let val = match elem {
-                            syn::Expr::Lit(syn::ExprLit {
-                                attrs: _,
-                                lit: syn::Lit::Str(litstr),
-                            }) => litstr.value(),
-                            _ => panic!("Argument to `help` must be an array of string literals"),
-                        };
-                        strings.push(val);
+            return match name.as_str() {
+                "parser" => Ok(Self::Parser(input.parse::<Expr>()?)),
+                "default" => Ok(Self::Default(input.parse::<Expr>()?)),
+                "default_by_name" => Ok(Self::DefaultByName(input.parse::<Expr>()?)),
+                "value" => Ok(Self::Value(input.parse::<Expr>()?)),
+                "version_extra" => Ok(Self::VersionExtra(input.parse::<Expr>()?)),
+                "hint" => {
+                    let hint = input.parse::<Ident>()?;
+                    if !VALUE_HINTS.contains(&hint.to_string().as_str()) {
+                        return Err(syn::Error::new(
+                            hint.span(),
+                            format!(
+                                "Invalid hint `{hint}`, expected one of: {}",
+                                VALUE_HINTS.join(", ")
+                            ),
+                        ));
                     }
-                    return Ok(Self::Help(strings));
+                    Ok(Self::Hint(hint))
                 }
-                "version" => {
-                    let expr = input.parse::<Expr>()?;
-                    let arr = match expr {
-                        syn::Expr::Array(arr) => arr,
-                        _ => panic!("Argument to `version` must be an array"),
-                    };
-
-                    let mut strings = Vec::new();
-                    for elem in arr.elems {
-                        let val = match elem {
-                            syn::Expr::Lit(syn::ExprLit {
-                                attrs: _,
-                                lit: syn::Lit::Str(litstr),
-                            }) => litstr.value(),
-                            _ => {
-                                panic!("Argument to `version` must be an array of string literals")
-                            }
-                        };
-                        strings.push(val);
+                "hint_fn" => Ok(Self::HintFn(input.parse::<Path>()?)),
+                "category" => Ok(Self::Category(input.parse::<LitStr>()?.value())),
+                "value_name" => {
+                    let lit = input.parse::<LitStr>()?;
+                    let value_name = lit.value();
+                    if value_name.is_empty()
+                        || !value_name.chars().all(|c| c.is_alphanumeric() || c == '-')
+                    {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            format!("Invalid value name '{value_name}'"),
+                        ));
                     }
-                    return Ok(Self::Version(strings));
+                    Ok(Self::ValueName(value_name))
                 }
-                _ => panic!("Unrecognized argument {} for option attribute", name),
+                "file" => Ok(Self::File(input.parse::<LitStr>()?)),
+                "completion_extra" => Ok(Self::CompletionExtra(input.parse::<LitStr>()?)),
+                "env" => Ok(Self::Env(input.parse::<LitStr>()?.value())),
+                "exit_code" => Ok(Self::ExitCode(input.parse::<LitInt>()?.base10_parse()?)),
+                "from_end" => Ok(Self::FromEnd(input.parse::<LitInt>()?.base10_parse()?)),
+                "deprecated" => Ok(Self::Deprecated(input.parse::<LitStr>()?.value())),
+                "keyword" => Ok(Self::Keyword(input.parse::<LitStr>()?)),
+                "delimiter" => Ok(Self::Delimiter(input.parse::<LitChar>()?)),
+                "max_occurrences" => {
+                    let int = input.parse::<LitInt>()?;
+                    match int.base10_parse()? {
+                        0 => Err(syn::Error::new(
+                            int.span(),
+                            "`max_occurrences` must be at least 1",
+                        )),
+                        max => Ok(Self::MaxOccurrences(max)),
+                    }
+                }
+                "parse_mode" => Ok(Self::ParseMode(input.parse::<Ident>()?)),
+                "collect_unknown" => Ok(Self::CollectUnknown(input.parse::<Ident>()?)),
+                "help" => match input.parse::<Expr>()? {
+                    syn::Expr::Lit(syn::ExprLit {
+                        attrs: _,
+                        lit: syn::Lit::Str(litstr),
+                    }) => Ok(Self::HelpText(litstr.value())),
+                    expr @ syn::Expr::Array(_) => Ok(Self::Help(string_array(expr, "help")?)),
+                    expr => Err(syn::Error::new_spanned(
+                        expr,
+                        "Argument to `help` must be an array or a string literal",
+                    )),
+                },
+                "short_help" => Ok(Self::ShortHelp(string_array(
+                    input.parse::<Expr>()?,
+                    "short_help",
+                )?)),
+                "version" => Ok(Self::Version(string_array(
+                    input.parse::<Expr>()?,
+                    "version",
+                )?)),
+                _ => unreachable!("all arguments with a value are listed"),
             };
         }
-        panic!("Arguments to option attribute must be string literals");
+
+        Err(input.error("Arguments to option attribute must be string literals"))
     }
 }
@@ -0,0 +1,478 @@
+use crate::{
+    argument::{flattened, value_type, ArgType, Argument},
+    flags::{Flag, Flags, Value},
+    help::{package_version, synopsis},
+    markdown::{get_after_event_text, get_h2_text},
+};
+use std::ops::RangeInclusive;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, ExprLit, Lit, Path, Type};
+
+pub(crate) fn complete(
+    args: &[Argument],
+    help_flags: &Flags,
+    short_help_flags: &Flags,
+    version_flags: &Flags,
+    file: &Option<String>,
+    completion_extra: &[(String, String)],
+) -> TokenStream {
+    let mut arg_specs = option_specs(args);
+    let mut positional_specs = Vec::new();
+    let mut follows_command = false;
+    let mut commands = Vec::new();
+
+    let (summary, after_options) = if let Some(contents) = file {
+        (
+            get_h2_text("summary", contents),
+            get_after_event_text(pulldown_cmark::Event::Rule, contents),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+
+    for arg @ Argument {
+        arg_type,
+        help,
+        field,
+        ..
+    } in args
+    {
+        if let ArgType::Positional {
+            num_args,
+            last,
+            command,
+            count_only: _,
+            min_if: _,
+            from_end: _,
+            default: _,
+            value_name: _,
+            hint_fn,
+        } = arg_type
+        {
+            // Arguments following a command are taken as is, just like the
+            // last argument.
+            let last = *last || follows_command;
+            follows_command = *command;
+            positional_specs.push(positional_spec(
+                &arg.metavar(),
+                num_args,
+                help,
+                field,
+                hint_fn,
+                last,
+            ));
+            continue;
+        }
+
+        if let ArgType::Subcommand { names } = arg_type {
+            commands.extend(
+                names
+                    .iter()
+                    .map(|name| quote!((#name.into(), #help.into()))),
+            );
+        }
+    }
+
+    // The subcommands are the values of the only positional argument, see
+    // `check_arguments`.
+    if !commands.is_empty() {
+        positional_specs.push(quote!(PositionalArg {
+            name: "COMMAND",
+            num_args: 1..=1,
+            help: "",
+            value: Some(ValueHint::Described(vec![#(#commands),*])),
+            last: false,
+        }));
+    }
+
+    let num_options = arg_specs.len();
+
+    if !help_flags.is_empty() {
+        arg_specs.push(arg_spec(
+            help_flags,
+            "Display this help message",
+            quote!(None),
+            quote!(None),
+            quote!(None),
+            Visibility::default(),
+        ));
+    }
+
+    if !short_help_flags.is_empty() {
+        arg_specs.push(arg_spec(
+            short_help_flags,
+            "Display a short help message",
+            quote!(None),
+            quote!(None),
+            quote!(None),
+            Visibility::default(),
+        ));
+    }
+
+    if !version_flags.is_empty() {
+        arg_specs.push(arg_spec(
+            version_flags,
+            "Display version information",
+            quote!(None),
+            quote!(None),
+            quote!(None),
+            Visibility::default(),
+        ));
+    }
+
+    // The options of flattened enums come before the help and version
+    // options, like in the help
+    let flattened: Vec<_> = flattened(args).map(|(_, ty)| ty).collect();
+    let all_args = if flattened.is_empty() {
+        quote!(vec![#(#arg_specs),*])
+    } else {
+        let builtins = arg_specs.split_off(num_options);
+        quote!(
+            vec![#(#arg_specs),*]
+                .into_iter()
+                #(.chain(<#flattened as uutils_args::Arguments>::flattened_args()))*
+                .chain(vec![#(#builtins),*])
+                .collect()
+        )
+    };
+
+    let version = package_version();
+    let usage = synopsis(args);
+    // Included rather than read here, so that changes to the files rebuild
+    let extra = completion_extra
+        .iter()
+        .map(|(shell, path)| quote!((#shell, include_str!(#path))));
+
+    quote!(
+        use uutils_args::complete::{Command, Arg, Flag, PositionalArg, Value, ValueHint};
+        use uutils_args::FromValue;
+
+        Command {
+            name: config.name_or(Self::NAME),
+            summary: config.summary_text().unwrap_or(#summary),
+            version: #version,
+            after_options: config.after_options_text().unwrap_or(#after_options),
+            args: #all_args,
+            positional: vec![#(#positional_specs),*],
+            usage: #usage,
+            extra: vec![#(#extra),*],
+        }
+    )
+}
+
+/// The completions of the options, without the help and version options
+fn option_specs(args: &[Argument]) -> Vec<TokenStream> {
+    let mut arg_specs = Vec::new();
+
+    for arg in args {
+        let Argument { arg_type, help, .. } = arg;
+        let ArgType::Option {
+            flags,
+            hidden_help,
+            hidden_complete,
+            default,
+            hint,
+            hint_fn,
+            category,
+            deprecated,
+            ..
+        } = arg_type
+        else {
+            continue;
+        };
+
+        // The command is documented as well as completed, so the option is
+        // only left out if it is hidden from both.
+        if *hidden_help && *hidden_complete {
+            continue;
+        }
+
+        // Keywords like `bs=BYTES` are operands, which the shells cannot
+        // complete as options.
+        if !flags.keyword.is_empty() {
+            continue;
+        }
+
+        let takes_value = flags
+            .short
+            .iter()
+            .map(|f| &f.value)
+            .chain(flags.long.iter().map(|f| &f.value))
+            .any(|v| !matches!(v, Value::No));
+
+        // Only ask for a hint if the type is actually parsed with `FromValue`,
+        // otherwise the type might not implement it.
+        let hint = match (hint, hint_fn, value_type(arg)) {
+            (Some(hint), _, _) if takes_value => quote!(Some(ValueHint::#hint)),
+            (None, Some(f), _) if takes_value => quote!(Some(ValueHint::Strings(#f()))),
+            (None, None, Some(ty)) if takes_value => {
+                quote!(Some(<#ty as FromValue>::value_hint()))
+            }
+            _ => quote!(None),
+        };
+
+        // The default is only shown when it can be observed, i.e. when the
+        // value is optional.
+        let has_optional = flags
+            .short
+            .iter()
+            .map(|f| &f.value)
+            .chain(flags.long.iter().map(|f| &f.value))
+            .any(|v| matches!(v, Value::Optional(_)));
+        let default = match default {
+            Some(expr) if has_optional => {
+                let default = expr_to_string(expr);
+                quote!(Some(#default))
+            }
+            _ => quote!(None),
+        };
+
+        let category = match category {
+            Some(category) => quote!(Some(#category)),
+            None => quote!(None),
+        };
+
+        arg_specs.push(arg_spec(
+            flags,
+            help,
+            hint,
+            default,
+            category,
+            Visibility {
+                hidden_help: *hidden_help,
+                hidden_complete: *hidden_complete,
+                deprecated: deprecated.is_some(),
+            },
+        ));
+    }
+
+    arg_specs
+}
+
+/// The `flattened_args` method of `Arguments`
+pub(crate) fn flattened_args(args: &[Argument]) -> TokenStream {
+    let arg_specs = option_specs(args);
+    quote!(
+        fn flattened_args() -> Vec<uutils_args::complete::Arg<'static>> {
+            use uutils_args::complete::{Arg, Flag, Value, ValueHint};
+            use uutils_args::FromValue;
+
+            vec![#(#arg_specs),*]
+        }
+    )
+}
+
+/// The static list of the options and positional arguments for
+/// `Arguments::options`
+pub(crate) fn option_metas(
+    args: &[Argument],
+    help_flags: &Flags,
+    short_help_flags: &Flags,
+    version_flags: &Flags,
+) -> TokenStream {
+    let mut metas = Vec::new();
+
+    for arg @ Argument {
+        name,
+        arg_type,
+        help,
+        ..
+    } in args
+    {
+        let meta = match arg_type {
+            // Deprecated options are left out of the help too
+            ArgType::Option {
+                flags,
+                hidden_help,
+                deprecated,
+                ..
+            } => option_meta(name, flags, *hidden_help || deprecated.is_some(), help),
+            ArgType::Positional { num_args, .. } => {
+                let metavar = arg.metavar();
+                let start = num_args.start();
+                let end = match num_args.end() {
+                    &usize::MAX => quote!(usize::MAX),
+                    end => quote!(#end),
+                };
+                quote!(OptionMeta {
+                    name: #name,
+                    short: &[],
+                    long: &[],
+                    metavar: Some(#metavar),
+                    num_args: Some(#start..=#end),
+                    hidden: false,
+                    help: #help,
+                })
+            }
+            ArgType::Subcommand { .. } => quote!(OptionMeta {
+                name: #name,
+                short: &[],
+                long: &[],
+                metavar: Some("COMMAND"),
+                num_args: Some(1..=1),
+                hidden: false,
+                help: #help,
+            }),
+            // Listed by the `options` of the flattened enum
+            ArgType::Flatten => continue,
+        };
+        metas.push(meta);
+    }
+
+    for (name, flags, help) in [
+        ("Help", help_flags, "Display this help message"),
+        (
+            "ShortHelp",
+            short_help_flags,
+            "Display a short help message",
+        ),
+        ("Version", version_flags, "Display version information"),
+    ] {
+        if !flags.is_empty() {
+            metas.push(option_meta(name, flags, false, help));
+        }
+    }
+
+    quote!(
+        use uutils_args::complete::{Flag, Value};
+        use uutils_args::OptionMeta;
+
+        static OPTIONS: &[OptionMeta] = &[#(#metas),*];
+        OPTIONS
+    )
+}
+
+fn option_meta(name: &str, flags: &Flags, hidden: bool, help: &str) -> TokenStream {
+    let short: Vec<_> = flags
+        .short
+        .iter()
+        .map(|f| flag_spec(&f.flag.to_string(), f))
+        .collect();
+    let long: Vec<_> = flags.long.iter().map(|f| flag_spec(&f.flag, f)).collect();
+    let metavar = flags
+        .short
+        .iter()
+        .map(|f| &f.value)
+        .chain(flags.long.iter().map(|f| &f.value))
+        .chain(flags.keyword.iter().map(|f| &f.value))
+        .find_map(|v| match v {
+            Value::Required(name) | Value::Optional(name) => Some(name),
+            Value::No => None,
+        });
+    let metavar = match metavar {
+        Some(metavar) => quote!(Some(#metavar)),
+        None => quote!(None),
+    };
+    quote!(OptionMeta {
+        name: #name,
+        short: &[#(#short),*],
+        long: &[#(#long),*],
+        metavar: #metavar,
+        num_args: None,
+        hidden: #hidden,
+        help: #help,
+    })
+}
+
+/// Where an option is shown besides the help
+#[derive(Default)]
+struct Visibility {
+    hidden_help: bool,
+    hidden_complete: bool,
+    deprecated: bool,
+}
+
+fn arg_spec(
+    flags: &Flags,
+    help: &str,
+    hint: TokenStream,
+    default: TokenStream,
+    category: TokenStream,
+    visibility: Visibility,
+) -> TokenStream {
+    let Visibility {
+        hidden_help,
+        hidden_complete,
+        deprecated,
+    } = visibility;
+    let short: Vec<_> = flags
+        .short
+        .iter()
+        .map(|f| flag_spec(&f.flag.to_string(), f))
+        .collect();
+    let long: Vec<_> = flags.long.iter().map(|f| flag_spec(&f.flag, f)).collect();
+    quote!(Arg {
+        short: vec![#(#short),*],
+        long: vec![#(#long),*],
+        help: #help,
+        value: #hint,
+        default: #default,
+        category: #category,
+        deprecated: #deprecated,
+        hidden_help: #hidden_help,
+        hidden_complete: #hidden_complete,
+    })
+}
+
+fn positional_spec(
+    metavar: &str,
+    num_args: &RangeInclusive<usize>,
+    help: &str,
+    field: &Option<Type>,
+    hint_fn: &Option<Path>,
+    last: bool,
+) -> TokenStream {
+    let start = num_args.start();
+    let end = match num_args.end() {
+        &usize::MAX => quote!(usize::MAX),
+        end => quote!(#end),
+    };
+    // The field of the last argument is a collection of values, which does
+    // not implement `FromValue`.
+    let hint = match (hint_fn, field) {
+        (Some(f), _) => quote!(Some(ValueHint::Strings(#f()))),
+        (None, Some(ty)) if !last => quote!(Some(<#ty as FromValue>::value_hint())),
+        _ => quote!(None),
+    };
+    quote!(PositionalArg {
+        name: #metavar,
+        num_args: #start..=#end,
+        help: #help,
+        value: #hint,
+        last: #last,
+    })
+}
+
+/// Render an expression like it would be written in the source
+pub(crate) fn expr_to_string(expr: &Expr) -> String {
+    // Literals are shown as their value, e.g. without quotes.
+    if let Expr::Lit(ExprLit { lit, .. }) = expr {
+        match lit {
+            Lit::Str(s) => return s.value(),
+            Lit::Int(i) => return i.base10_digits().to_string(),
+            _ => {}
+        }
+    }
+    quote!(#expr)
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" (", "(")
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace(" ,", ",")
+}
+
+fn flag_spec<T>(flag: &str, f: &Flag<T>) -> TokenStream {
+    let value = match &f.value {
+        Value::No => quote!(Value::No),
+        Value::Optional(name) => quote!(Value::Optional(#name)),
+        Value::Required(name) => quote!(Value::Required(#name)),
+    };
+    quote!(Flag {
+        flag: #flag,
+        value: #value,
+    })
+}
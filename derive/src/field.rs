@@ -1,9 +1,10 @@
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{Attribute, Field, Ident};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{spanned::Spanned, Attribute, Field, Ident, Type};
 
 use crate::{
-    action::{parse_action_attr, ActionAttr, ActionType},
+    action::{parse_action_attr, ActionAttr, ActionType, Choice},
+    argument::set_check_name,
     attributes::FieldAttr,
 };
 
@@ -11,55 +12,204 @@ pub(crate) struct FieldData {
     pub(crate) ident: Ident,
     pub(crate) default_value: TokenStream,
     pub(crate) match_stmt: TokenStream,
+    /// Adds the name of the field to `fields` if `arg` is applied to it
+    pub(crate) mark_stmt: TokenStream,
+    /// Takes the field from `other` if it is in the `mask`
+    pub(crate) merge_stmt: TokenStream,
+    /// Declares the state that `match_stmt` keeps between arguments
+    pub(crate) state_stmt: TokenStream,
+    /// Refers to the checks of the arguments in `#[set(...)]`
+    pub(crate) check_stmt: TokenStream,
+    /// The environment variable the field is read from, if any
+    pub(crate) env_var: Option<String>,
+    /// The patterns of the arguments that are applied to the field
+    pub(crate) patterns: Vec<TokenStream>,
 }
 
-pub(crate) fn parse_field(field: &Field) -> FieldData {
-    let field_ident = field.ident.as_ref().unwrap().clone();
+pub(crate) fn parse_field(field: &Field) -> syn::Result<FieldData> {
+    let Some(field_ident) = field.ident.clone() else {
+        unreachable!("only named fields are passed");
+    };
 
-    let field_attr = parse_field_attr(&field.attrs);
+    let field_attr = parse_field_attr(&field.attrs)?;
 
-    let mut default_value = match field_attr.default {
-        Some(val) => val.to_token_stream(),
-        None => quote!(::core::default::Default::default()),
+    let mut default_value = match (field_attr.default, field_attr.default_by_name) {
+        (Some(val), _) => val.to_token_stream(),
+        (None, Some(f)) => quote!((#f)(bin_name)),
+        (None, None) => quote!(::core::default::Default::default()),
     };
 
-    if let Some(env_var) = field_attr.env {
+    if let Some(env_var) = &field_attr.env {
+        // Only a value that is used is recorded in `env`, an ignored invalid
+        // value falls back to the default.
+        let name = field_ident.to_string();
+        let from_env = if field_attr.ignore_invalid_env {
+            quote!(match ::uutils_args::FromValue::from_value("", x.clone()) {
+                Ok(value) => {
+                    env.push((#name, #env_var));
+                    value
+                }
+                Err(_) => {
+                    ::uutils_args::report(
+                        bin_name,
+                        config,
+                        ::uutils_args::Warning::InvalidEnv {
+                            var: #env_var.into(),
+                            value: x,
+                        },
+                    );
+                    #default_value
+                }
+            })
+        } else {
+            quote!({
+                let value = ::uutils_args::FromValue::from_value("", x).map_err(|err| {
+                    err.with_source(::uutils_args::ValueSource::Env { var: #env_var.into() })
+                })?;
+                env.push((#name, #env_var));
+                value
+            })
+        };
         default_value = quote!(
-            match ::std::env::var_os(#env_var) {
-                Some(x) => ::uutils_args::FromValue::from_value("", x)?,
+            match (config.env)(#env_var) {
+                Some(x) => #from_env,
                 None => #default_value
             }
         )
     }
 
-    let match_arms = field
-        .attrs
+    let mut action_attrs = Vec::new();
+    for attr in &field.attrs {
+        action_attrs.extend(parse_action_attr(attr)?);
+    }
+    let collect = action_attrs.iter().any(|attr| attr.collect);
+    let patterns: Vec<_> = action_attrs
+        .iter()
+        .flat_map(action_attr_to_patterns)
+        .collect();
+    let checks = action_attrs
         .iter()
-        .filter_map(parse_action_attr)
-        .flat_map(|attr| action_attr_to_match_arms(&field_ident, attr));
+        .filter(|attr| !attr.collect)
+        .flat_map(|attr| action_attr_to_checks(attr, &field.ty));
+    let check_stmt = quote!(#(#checks)*);
 
-    let match_stmt = quote!(match arg.clone() {
-        #(#match_arms)*,
+    // Whether an argument for `first` or `rest` has been applied already
+    let seen = format_ident!("{}_seen", field_ident);
+    let state_stmt = if action_attrs
+        .iter()
+        .any(|attr| matches!(attr.action_type, ActionType::First(_) | ActionType::Rest(_)))
+    {
+        quote!(let mut #seen = false;)
+    } else {
+        quote!()
+    };
+
+    let match_arms = action_attrs
+        .into_iter()
+        .flat_map(|attr| action_attr_to_match_arms(&field_ident, &seen, attr));
+
+    let match_stmt = quote!(match arg {
+        #(#match_arms)*
         _ => {}
     });
 
-    FieldData {
+    let name = field_ident.to_string();
+    let mark_stmt = quote!(
+        #[allow(unused_variables)]
+        match arg {
+            #(#patterns => fields.push(#name),)*
+            _ => {}
+        }
+    );
+
+    // Collected values are added to the values that are already there
+    let merge = if collect {
+        quote!(self.#field_ident.extend(other.#field_ident))
+    } else {
+        quote!(self.#field_ident = other.#field_ident)
+    };
+    let merge_stmt = quote!(if mask.contains(#name) {
+        #merge;
+    });
+
+    Ok(FieldData {
         ident: field_ident,
         default_value,
         match_stmt,
+        mark_stmt,
+        merge_stmt,
+        state_stmt,
+        check_stmt,
+        env_var: field_attr.env,
+        patterns,
+    })
+}
+
+/// The patterns of the arguments that are applied to the field
+fn action_attr_to_patterns(attr: &ActionAttr) -> Vec<TokenStream> {
+    match &attr.action_type {
+        ActionType::Map(arms) => arms.iter().map(|arm| arm.pat.to_token_stream()).collect(),
+        ActionType::Set(pats) | ActionType::First(pats) | ActionType::Rest(pats) => {
+            pats.iter().map(|p| quote!(#p(_))).collect()
+        }
+        ActionType::Choice(choice) => choice.pairs.iter().map(|(p, _)| quote!(#p)).collect(),
     }
 }
 
-pub(crate) fn parse_field_attr(attrs: &[Attribute]) -> FieldAttr {
+/// The uses of the check constants of the arguments in `#[set(...)]` and
+/// the check that an exhaustive `#[choice(...)]` lists every variant of
+/// `ty`
+///
+/// These are spanned to the path in the attribute, so that the warning for
+/// a deprecated check is reported there.
+fn action_attr_to_checks(attr: &ActionAttr, ty: &Type) -> Vec<TokenStream> {
+    let pats = match &attr.action_type {
+        ActionType::Set(pats) => pats,
+        ActionType::Choice(Choice {
+            exhaustive: true,
+            pairs,
+        }) => {
+            // The variants are matched on the type of the field, so the
+            // compiler reports the ones that are not covered.
+            let variants = pairs.iter().map(|(_, variant)| variant);
+            let span = pairs
+                .first()
+                .map_or_else(Span::call_site, |(_, v)| v.span());
+            return vec![quote_spanned!(span => const _: () = {
+                #[allow(dead_code)]
+                fn exhaustive(value: #ty) {
+                    match value {
+                        #(#variants => {})*
+                    }
+                }
+            };)];
+        }
+        _ => return Vec::new(),
+    };
+    pats.iter()
+        .filter_map(|p| {
+            let mut path = p.clone();
+            let last = path.segments.last_mut()?;
+            last.ident = set_check_name(&last.ident);
+            Some(quote_spanned!(last.ident.span() => const _: () = #path;))
+        })
+        .collect()
+}
+
+pub(crate) fn parse_field_attr(attrs: &[Attribute]) -> syn::Result<FieldAttr> {
     for attr in attrs {
         if attr.path.is_ident("field") {
             return FieldAttr::parse(attr);
         }
     }
-    FieldAttr::default()
+    Ok(FieldAttr::default())
 }
 
-fn action_attr_to_match_arms(field_ident: &Ident, attr: ActionAttr) -> Vec<TokenStream> {
+fn action_attr_to_match_arms(
+    field_ident: &Ident,
+    seen: &Ident,
+    attr: ActionAttr,
+) -> Vec<TokenStream> {
     let mut match_arms = Vec::new();
     match attr.action_type {
         ActionType::Map(arms) => {
@@ -82,6 +232,36 @@ fn action_attr_to_match_arms(field_ident: &Ident, attr: ActionAttr) -> Vec<Token
                 attr.collect,
             ));
         }
+
+        ActionType::First(pats) => {
+            match_arms.push(quote!(
+                #(#pats(x))|* => if !#seen {
+                    #seen = true;
+                    self.#field_ident = x;
+                }
+            ));
+        }
+
+        ActionType::Choice(choice) => {
+            for (arg, variant) in choice.pairs {
+                match_arms.push(field_expression(
+                    quote!(#arg),
+                    quote!(#variant),
+                    field_ident,
+                    attr.collect,
+                ));
+            }
+        }
+
+        ActionType::Rest(pats) => {
+            match_arms.push(quote!(
+                #(#pats(x))|* => if #seen {
+                    self.#field_ident.push(x);
+                } else {
+                    #seen = true;
+                }
+            ));
+        }
     };
     match_arms
 }
@@ -5,6 +5,16 @@ use quote::quote;
 pub(crate) struct Flags {
     pub short: Vec<Flag<char>>,
     pub long: Vec<Flag<String>>,
+    /// Numbers given as an option, like `-5` or `+5`, written as `-{NUM}`
+    /// and `+{NUM}`, with the prefix as the flag
+    pub number: Vec<Flag<char>>,
+    /// Whether the lone `-` is this option
+    pub dash: bool,
+    /// Whether `--` is this option instead of the end of the options
+    pub double_dash: bool,
+    /// Operands like `bs=1M` for `dd`, with the name before the `=` as the
+    /// flag
+    pub keyword: Vec<Flag<String>>,
 }
 
 #[derive(Clone)]
@@ -21,16 +31,54 @@ pub(crate) struct Flag<T> {
 }
 
 impl Flags {
+    /// Flags from strings that are known to be valid, like the defaults
     pub(crate) fn new<T: AsRef<str>>(flags: impl IntoIterator<Item = T>) -> Self {
         let mut self_ = Self::default();
         for flag in flags {
-            self_.add(flag.as_ref());
+            if let Err(msg) = self_.add(flag.as_ref()) {
+                unreachable!("{msg}");
+            }
         }
         self_
     }
 
-    pub(crate) fn add(&mut self, flag: &str) {
-        assert!(flag.starts_with('-'), "Flags must start with a '-'");
+    /// Add a flag like `-f`, `--file=FILE` or `-{NUM}`, or describe why it
+    /// is invalid
+    pub(crate) fn add(&mut self, flag: &str) -> Result<(), String> {
+        let is_name = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '-');
+
+        // A number as an option: -{NUM} or +{NUM}
+        let mut chars = flag.chars();
+        let prefix = chars.next();
+        if let Some(name) = chars.as_str().strip_prefix('{') {
+            let Some(name) = name.strip_suffix('}').filter(|n| is_name(n)) else {
+                return Err(format!(
+                    "Invalid number flag '{flag}', expected like '-{{NUM}}'"
+                ));
+            };
+            let Some(prefix @ ('-' | '+')) = prefix else {
+                return Err(format!(
+                    "Numbers can only be prefixed with '-' or '+', found '{flag}'"
+                ));
+            };
+            self.number.push(Flag {
+                flag: prefix,
+                value: Value::Required(name.into()),
+            });
+            return Ok(());
+        }
+
+        if !flag.starts_with('-') {
+            return Err(format!("Flags must start with a '-', found '{flag}'"));
+        }
+        if flag == "-" {
+            self.dash = true;
+            return Ok(());
+        }
+        if flag == "--" {
+            self.double_dash = true;
+            return Ok(());
+        }
         if let Some(s) = flag.strip_prefix("--") {
             // There are three possible patterns:
             //   --flag
@@ -49,28 +97,27 @@ impl Flags {
             let val: String = chars.collect();
 
             // Now check the cases:
-            let value = if val.is_empty() {
+            let value = if val.is_empty() && f == s {
                 Value::No
-            } else if sep == '=' {
-                assert!(val.chars().all(|c: char| c.is_alphanumeric() || c == '-'));
+            } else if sep == '=' && is_name(&val) {
                 Value::Required(val)
-            } else if sep == '[' {
-                let optional = val
-                    .strip_prefix('=')
-                    .and_then(|s| s.strip_suffix(']'))
-                    .unwrap();
-                assert!(optional
-                    .chars()
-                    .all(|c: char| c.is_alphanumeric() || c == '-'));
+            } else if let Some(optional) = val
+                .strip_prefix('=')
+                .and_then(|s| s.strip_suffix(']'))
+                .filter(|s| sep == '[' && is_name(s))
+            {
                 Value::Optional(optional.into())
             } else {
-                panic!("Invalid long flag '{flag}'");
+                return Err(format!(
+                    "Invalid long flag '{flag}', expected '--flag', '--flag=VALUE' or '--flag[=VALUE]'"
+                ));
             };
+            if f.is_empty() {
+                return Err(format!("Invalid long flag '{flag}', the name is missing"));
+            }
 
             self.long.push(Flag { flag: f, value });
         } else if let Some(s) = flag.strip_prefix('-') {
-            assert!(!s.is_empty());
-
             // There are three possible patterns:
             //   -f
             //   -f value
@@ -78,31 +125,137 @@ impl Flags {
 
             // First we trim up to the = or [
             let mut chars = s.chars();
-            let f = chars.next().unwrap();
+            let Some(f) = chars.next() else {
+                unreachable!("the lone '-' is handled above");
+            };
             let val: String = chars.collect();
 
             // Now check the cases:
             let value = if val.is_empty() {
                 Value::No
-            } else if let Some(optional) = val.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
-                assert!(optional
-                    .chars()
-                    .all(|c: char| c.is_alphanumeric() || c == '-'));
+            } else if let Some(optional) = val
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .filter(|s| is_name(s))
+            {
                 Value::Optional(optional.into())
-            } else if let Some(required) = val.strip_prefix(' ') {
-                assert!(required
-                    .chars()
-                    .all(|c: char| c.is_alphanumeric() || c == '-'));
+            } else if let Some(required) = val.strip_prefix(' ').filter(|s| is_name(s)) {
                 Value::Required(required.into())
             } else {
-                panic!("Invalid short flag '{flag}'")
+                return Err(format!(
+                    "Invalid short flag '{flag}', exactly one character must follow '-', \
+                    optionally followed by ' VALUE' or '[VALUE]'"
+                ));
             };
             self.short.push(Flag { flag: f, value });
         }
+        Ok(())
+    }
+
+    /// Add a keyword like `bs` or `bs=BYTES`, or describe why it is invalid
+    ///
+    /// A keyword always takes a value, which is named `VALUE` if it is not
+    /// given.
+    pub(crate) fn add_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        let is_name = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '-');
+        let (name, value) = keyword.split_once('=').unwrap_or((keyword, "VALUE"));
+        let is_keyword = !name.is_empty()
+            && !name.starts_with('-')
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !is_keyword || !is_name(value) {
+            return Err(format!(
+                "Invalid keyword '{keyword}', expected like 'bs' or 'bs=BYTES'"
+            ));
+        }
+        self.keyword.push(Flag {
+            flag: name.into(),
+            value: Value::Required(value.into()),
+        });
+        Ok(())
+    }
+
+    /// Check that all flags name their value the same and agree on whether
+    /// the value is required
+    ///
+    /// With a `value_name`, the names of all flags are replaced by it.
+    /// Mixing required and optional values is only allowed with
+    /// `mixed_arity`, like for `mktemp -p DIR, --tmpdir[=DIR]`.
+    pub(crate) fn check_values(
+        &mut self,
+        value_name: Option<&str>,
+        mixed_arity: bool,
+    ) -> Result<(), String> {
+        let flags = self.format().trim().to_string();
+        let values = self
+            .short
+            .iter_mut()
+            .map(|f| &mut f.value)
+            .chain(self.long.iter_mut().map(|f| &mut f.value))
+            .chain(self.number.iter_mut().map(|f| &mut f.value))
+            .chain(self.keyword.iter_mut().map(|f| &mut f.value));
+
+        let mut name: Option<String> = None;
+        let (mut required, mut optional) = (false, false);
+        for value in values {
+            let n = match value {
+                Value::No => continue,
+                Value::Optional(n) => {
+                    optional = true;
+                    n
+                }
+                Value::Required(n) => {
+                    required = true;
+                    n
+                }
+            };
+            if let Some(value_name) = value_name {
+                *n = value_name.to_string();
+            }
+            match &name {
+                Some(name) if name != n => {
+                    return Err(format!(
+                        "The flags {flags} use different value names ('{name}' and '{n}'), \
+                        use the same name or set one with `value_name = \"...\"`"
+                    ))
+                }
+                Some(_) => {}
+                None => name = Some(n.clone()),
+            }
+        }
+
+        if value_name.is_some() && name.is_none() {
+            return Err(format!(
+                "`value_name` is given for the flags {flags}, but they do not take a value"
+            ));
+        }
+        if !mixed_arity && required && optional {
+            return Err(format!(
+                "The flags {flags} mix required and optional values, \
+                add `mixed_arity` if that is intended"
+            ));
+        }
+        Ok(())
     }
 
     pub(crate) fn is_empty(&self) -> bool {
-        self.short.is_empty() && self.long.is_empty()
+        self.len() == 0
+    }
+
+    /// The number of spellings of the option
+    pub(crate) fn len(&self) -> usize {
+        self.short.len()
+            + self.long.len()
+            + self.number.len()
+            + usize::from(self.dash)
+            + usize::from(self.double_dash)
+            + self.keyword.len()
+    }
+
+    /// The number flag with the `prefix`, if any
+    pub(crate) fn number(&self, prefix: char) -> Option<&Flag<char>> {
+        self.number.iter().find(|f| f.flag == prefix)
     }
 
     pub(crate) fn pat(&self) -> TokenStream {
@@ -119,6 +272,19 @@ impl Flags {
     }
 
     pub(crate) fn format(&self) -> String {
+        // Keywords are not combined with flags, see `OptionAttr::parse`
+        if !self.keyword.is_empty() {
+            return self
+                .keyword
+                .iter()
+                .map(|f| match &f.value {
+                    Value::Required(v) => format!("{}={v}", f.flag),
+                    _ => unreachable!("keywords always have a value"),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+
         let short = self
             .short
             .iter()
@@ -130,6 +296,8 @@ impl Flags {
                     Value::Required(v) => format!("-{s} {v}"),
                 }
             })
+            .chain(self.number.iter().map(number_spelling))
+            .chain(self.dash.then(|| "-".to_string()))
             .collect::<Vec<_>>()
             .join(", ");
 
@@ -144,6 +312,7 @@ impl Flags {
                     Value::Required(v) => format!("--{l}={v}"),
                 }
             })
+            .chain(self.double_dash.then(|| "--".to_string()))
             .collect::<Vec<_>>()
             .join(", ");
 
@@ -156,3 +325,11 @@ impl Flags {
         }
     }
 }
+
+/// A number flag as it is shown to the user, like `-NUM`
+pub(crate) fn number_spelling(flag: &Flag<char>) -> String {
+    let Value::Required(name) = &flag.value else {
+        unreachable!("numbers always have a value");
+    };
+    format!("{}{name}", flag.flag)
+}
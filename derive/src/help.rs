@@ -1,165 +1,719 @@
-use std::{
-    io::Read,
-    path::{Path, PathBuf},
-};
+use std::path::PathBuf;
 
 use crate::{
-    argument::{ArgType, Argument},
-    flags::Flags,
-    markdown::{get_after_event, get_h2, str_to_renderer},
+    argument::{flattened, value_type, ArgType, Argument},
+    attributes::MissingHelp,
+    complete::expr_to_string,
+    flags::{Flags, Value},
+    markdown::{get_h2_text, render_after_event, render_h2, render_str},
 };
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use syn::{Expr, LitStr};
 
-pub(crate) fn help_handling(help_flags: &Flags) -> TokenStream {
-    if help_flags.is_empty() {
-        return quote!();
+/// Store the spelling of the short or long flag in `arg` as the flag of
+/// the help or version argument.
+fn set_flag() -> TokenStream {
+    quote!(
+        *flag = match &arg {
+            lexopt::Arg::Short(short) => format!("-{short}"),
+            lexopt::Arg::Long(long) => format!("--{long}"),
+            lexopt::Arg::Value(_) => unreachable!(),
+        };
+    )
+}
+
+pub(crate) fn help_handling(help_flags: &Flags, short_help_flags: &Flags) -> TokenStream {
+    let mut checks = Vec::new();
+    let set_flag = set_flag();
+
+    if !help_flags.is_empty() {
+        let pat = help_flags.pat();
+        checks.push(quote!(
+            if let #pat = arg {
+                #set_flag
+                return Ok(Some(Argument::Help));
+            }
+        ));
     }
 
-    let pat = help_flags.pat();
+    if !short_help_flags.is_empty() {
+        let pat = short_help_flags.pat();
+        checks.push(quote!(
+            if let #pat = arg {
+                #set_flag
+                return Ok(Some(Argument::ShortHelp));
+            }
+        ));
+    }
 
-    quote!(
-        if let #pat = arg {
-            return Ok(Some(Argument::Help));
+    quote!(#(#checks)*)
+}
+
+/// The options that are shown in the help, grouped by category
+///
+/// The categories are in order of first appearance. The options without a
+/// category come last, under the "Options" heading.
+fn option_groups(args: &[Argument]) -> Vec<(&str, Vec<&Argument>)> {
+    let mut groups: Vec<(&str, Vec<&Argument>)> = Vec::new();
+    let mut options = Vec::new();
+    for arg in args {
+        match &arg.arg_type {
+            ArgType::Option {
+                hidden_help: false,
+                deprecated: None,
+                category,
+                ..
+            } => match category {
+                Some(category) => match groups.iter_mut().find(|(c, _)| c == category) {
+                    Some((_, options)) => options.push(arg),
+                    None => groups.push((category, vec![arg])),
+                },
+                None => options.push(arg),
+            },
+            // Hidden and deprecated arguments should not show up in --help
+            ArgType::Option { .. } => {}
+            ArgType::Positional { .. } | ArgType::Subcommand { .. } | ArgType::Flatten => {}
         }
-    )
+    }
+    groups.push(("Options", options));
+    groups
+}
+
+/// The names of the subcommands, joined with their aliases, and their help
+fn subcommands(args: &[Argument]) -> Vec<(String, &str)> {
+    args.iter()
+        .filter_map(|arg| match &arg.arg_type {
+            ArgType::Subcommand { names } => Some((names.join(", "), arg.help.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The flags and help of the help and version options, which are listed
+/// with the options without a category
+fn builtin_options<'a>(
+    help_flags: &'a Flags,
+    short_help_flags: &'a Flags,
+    version_flags: &'a Flags,
+) -> impl Iterator<Item = (&'a Flags, &'static str)> {
+    [
+        (help_flags, "Display this help message"),
+        (short_help_flags, "Display a short help message"),
+        (version_flags, "Display version information"),
+    ]
+    .into_iter()
+    .filter(|(flags, _)| !flags.is_empty())
+}
+
+/// Code that pushes text to the help string `s`
+///
+/// Consecutive static text is joined into one literal, so most of the help
+/// is pushed at once.
+#[derive(Default)]
+struct HelpText {
+    pushes: Vec<TokenStream>,
+    text: String,
+}
+
+impl HelpText {
+    fn text(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn code(&mut self, code: TokenStream) {
+        self.flush();
+        self.pushes.push(code);
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let text = std::mem::take(&mut self.text);
+            self.pushes.push(quote!(s.push_str(#text);));
+        }
+    }
+
+    fn finish(mut self) -> TokenStream {
+        self.flush();
+        let pushes = self.pushes;
+        quote!(#(#pushes)*)
+    }
+}
+
+/// Push the usage lines
+///
+/// The usage given at parse time takes precedence over the usage section of
+/// the help file. Without either, the usage is generated from the positional
+/// arguments.
+fn usage_string(text: &mut HelpText, args: &[Argument], file: &Option<String>) {
+    let usage = match file {
+        Some(contents) => get_h2_text("usage", contents),
+        None => String::new(),
+    };
+    let usage = if usage.is_empty() {
+        format!("{{}} {}", synopsis(args))
+    } else {
+        usage
+    };
+    let usage: String = usage
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("  {line}\n"))
+        .collect();
+
+    text.text("Usage:\n");
+    text.code(quote!(
+        match config.usage() {
+            Some(usage) => {
+                for line in usage.lines().filter(|line| !line.is_empty()) {
+                    s.push_str("  ");
+                    s.push_str(&line.replace("{}", bin_name));
+                    s.push('\n');
+                }
+            }
+            None => s.push_str(&#usage.replace("{}", bin_name)),
+        }
+    ));
+}
+
+/// Push an option with its help, which is rendered by the derive
+///
+/// The lines with the default and the possible values are only known at
+/// runtime. If the option has no help, the first of them goes next to the
+/// flags.
+fn option_help(text: &mut HelpText, flags: &str, help: &str, extras: Option<TokenStream>) {
+    let width: usize = 16;
+    let indent = " ".repeat(2);
+    let help_indent = " ".repeat(width + indent.len() + 2);
+
+    let help = render_str(help);
+    let mut lines = help.lines();
+
+    text.text(&indent);
+    text.text(flags);
+    let first_indent = if flags.len() <= width {
+        let first_indent = " ".repeat(width - flags.len() + 2);
+        match lines.next() {
+            Some(line) => {
+                text.text(&first_indent);
+                text.text(line);
+                text.text("\n");
+                None
+            }
+            None => Some(first_indent),
+        }
+    } else {
+        text.text("\n");
+        None
+    };
+
+    for line in lines {
+        text.text(&help_indent);
+        text.text(line);
+        text.text("\n");
+    }
+
+    let Some(extras) = extras else {
+        if first_indent.is_some() {
+            text.text("\n");
+        }
+        return;
+    };
+
+    let first = match first_indent {
+        Some(first_indent) => quote!(
+            match extras.next() {
+                Some(line) => {
+                    s.push_str(#first_indent);
+                    s.push_str(line);
+                    s.push('\n');
+                }
+                None => s.push('\n'),
+            }
+        ),
+        None => quote!(),
+    };
+    text.code(quote!({
+        let extras: Vec<String> = #extras;
+        let mut extras = extras.iter();
+        #first
+        for line in extras {
+            s.push_str(#help_indent);
+            s.push_str(line);
+            s.push('\n');
+        }
+    }));
 }
 
 pub(crate) fn help_string(
     args: &[Argument],
     help_flags: &Flags,
+    short_help_flags: &Flags,
     version_flags: &Flags,
     file: &Option<String>,
 ) -> TokenStream {
-    let mut options = Vec::new();
+    let version = package_version();
+    let mut text = HelpText::default();
 
-    let width: usize = 16;
-    let indent: usize = 2;
+    text.code(quote!(
+        s.push_str(config.name_or(Self::NAME));
+        s.push(' ');
+        s.push_str(#version);
+        s.push('\n');
+    ));
 
-    for Argument { arg_type, help, .. } in args {
-        match arg_type {
-            ArgType::Option {
+    // The help file given at parse time takes precedence over the file from
+    // the attribute.
+    let after_options = match file {
+        Some(contents) => {
+            let summary = render_h2("summary", contents);
+            text.code(quote!(match config.summary() {
+                Some(summary) => s.push_str(&summary),
+                None => s.push_str(#summary),
+            }));
+            let after_options = render_after_event(pulldown_cmark::Event::Rule, contents);
+            quote!(
+                s.push('\n');
+                match config.after_options() {
+                    Some(after_options) => s.push_str(&after_options),
+                    None => s.push_str(#after_options),
+                }
+            )
+        }
+        None => {
+            text.code(quote!(if let Some(summary) = config.summary() {
+                s.push_str(&summary);
+            }));
+            quote!(if let Some(after_options) = config.after_options() {
+                s.push('\n');
+                s.push_str(&after_options);
+            })
+        }
+    };
+
+    text.text("\n");
+    usage_string(&mut text, args, file);
+
+    let commands = subcommands(args);
+    if !commands.is_empty() {
+        text.text("\nCommands:\n");
+        for (names, help) in &commands {
+            option_help(&mut text, names, help, None);
+        }
+    }
+
+    let groups = option_groups(args);
+    let builtins: Vec<_> = builtin_options(help_flags, short_help_flags, version_flags).collect();
+    let flattened: Vec<_> = flattened(args).map(|(_, ty)| ty).collect();
+    let last = groups.len() - 1;
+    for (i, (heading, args)) in groups.iter().enumerate() {
+        if args.is_empty() && (i != last || (builtins.is_empty() && flattened.is_empty())) {
+            continue;
+        }
+        text.text(&format!("\n{heading}:\n"));
+        for arg in args {
+            let ArgType::Option {
                 flags,
-                hidden: false,
+                default,
+                hide_possible_values,
                 ..
-            } => {
-                let flags = flags.format();
-                let renderer = str_to_renderer(help);
-                options.push(quote!((#flags, #renderer)));
+            } = &arg.arg_type
+            else {
+                unreachable!("only options are grouped")
+            };
+            let extras = help_extras(flags, arg, default, *hide_possible_values);
+            option_help(&mut text, &flags.format(), &arg.help, extras);
+        }
+        if i == last {
+            for ty in &flattened {
+                text.code(quote!(
+                    <#ty as uutils_args::Arguments>::flattened_help(&mut s, false);
+                ));
+            }
+            for (flags, help) in &builtins {
+                option_help(&mut text, &flags.format(), help, None);
             }
-            // Hidden arguments should not show up in --help
-            ArgType::Option { hidden: true, .. } => {}
-            ArgType::Positional { .. } => {}
         }
     }
 
-    let (summary, after_options) = if let Some(file) = &file {
-        let (summary, after_options) = read_help_file(file);
-        (
-            quote!(s.push_str(&#summary.render());),
-            quote!(
-                s.push('\n');
-                s.push_str(&#after_options.render());
-            ),
-        )
-    } else {
-        (quote!(), quote!())
-    };
+    text.code(after_options);
 
-    if !help_flags.is_empty() {
-        let flags = help_flags.format();
-        let renderer = str_to_renderer("Display this help message");
-        options.push(quote!((#flags, #renderer)));
-    }
+    let text = text.finish();
 
-    if !version_flags.is_empty() {
-        let flags = version_flags.format();
-        let renderer = str_to_renderer("Display version information");
-        options.push(quote!((#flags, #renderer)));
-    }
+    let show_default = show_default(args);
+
+    quote!(
+        #show_default
 
-    let options = if !options.is_empty() {
-        let options = quote!([#(#options),*]);
+        let mut s = String::new();
+        #text
+        s
+    )
+}
+
+/// The helpers that show the default of an option in the help
+fn show_default(args: &[Argument]) -> TokenStream {
+    // Show the default with `Display` if the type implements it and with the
+    // text of the expression otherwise. The `Display` impl is picked first,
+    // because it needs fewer auto-derefs.
+    if args.iter().any(|arg| {
+        matches!(
+            &arg.arg_type,
+            ArgType::Option {
+                default: Some(_),
+                ..
+            }
+        )
+    }) {
         quote!(
-            s.push_str("\nOptions:\n");
-            for (flags, renderer) in #options {
-                let indent = " ".repeat(#indent);
-
-                let help_string = renderer.render();
-                let mut help_lines = help_string.lines();
-                s.push_str(&indent);
-                s.push_str(&flags);
-
-                if flags.len() <= #width {
-                    let line = match help_lines.next() {
-                        Some(line) => line,
-                        None => return s,
-                    };
-                    let help_indent = " ".repeat(#width-flags.len()+2);
-                    s.push_str(&help_indent);
-                    s.push_str(line);
-                    s.push('\n');
-                } else {
-                    s.push('\n');
+            struct DefaultValue<T>(T);
+            trait ShowDisplay {
+                fn show(&self, text: &str) -> String;
+            }
+            impl<T: std::fmt::Display> ShowDisplay for &DefaultValue<T> {
+                fn show(&self, _text: &str) -> String {
+                    self.0.to_string()
                 }
-
-                let help_indent = " ".repeat(#width+#indent+2);
-                for line in help_lines {
-                    s.push_str(&help_indent);
-                    s.push_str(line);
-                    s.push('\n');
+            }
+            trait ShowText {
+                fn show(&self, text: &str) -> String;
+            }
+            impl<T> ShowText for DefaultValue<T> {
+                fn show(&self, text: &str) -> String {
+                    text.to_string()
                 }
             }
         )
     } else {
         quote!()
-    };
-
-    quote!(
-        let mut s = String::new();
+    }
+}
 
-        s.push_str(&format!("{} {}\n",
-            option_env!("CARGO_BIN_NAME").unwrap_or(env!("CARGO_PKG_NAME")),
-            env!("CARGO_PKG_VERSION"),
-        ));
+/// The condensed help, with the usage and the flags of the options but
+/// without their descriptions and the text after the options
+pub(crate) fn short_help_string(
+    args: &[Argument],
+    help_flags: &Flags,
+    short_help_flags: &Flags,
+    version_flags: &Flags,
+    file: &Option<String>,
+) -> TokenStream {
+    let mut text = HelpText::default();
+    usage_string(&mut text, args, file);
 
-        #summary
+    let commands = subcommands(args);
+    if !commands.is_empty() {
+        text.text("\nCommands:\n");
+        for (names, _) in &commands {
+            text.text("  ");
+            text.text(names);
+            text.text("\n");
+        }
+    }
 
-        s.push_str(&format!("\nUsage:\n  {} [OPTIONS] [ARGS]\n", bin_name));
+    let groups: Vec<(&str, Vec<String>)> = option_groups(args)
+        .into_iter()
+        .map(|(heading, args)| {
+            let flags = args
+                .into_iter()
+                .map(|arg| {
+                    let ArgType::Option { flags, .. } = &arg.arg_type else {
+                        unreachable!("only options are grouped")
+                    };
+                    flags.format()
+                })
+                .collect();
+            (heading, flags)
+        })
+        .collect();
+    let builtins: Vec<_> = builtin_options(help_flags, short_help_flags, version_flags)
+        .map(|(flags, _)| flags.format())
+        .collect();
+    let flattened: Vec<_> = flattened(args).map(|(_, ty)| ty).collect();
 
-        #options
+    let last = groups.len() - 1;
+    for (i, (heading, flags)) in groups.iter().enumerate() {
+        if flags.is_empty() && (i != last || (builtins.is_empty() && flattened.is_empty())) {
+            continue;
+        }
+        text.text(&format!("\n{heading}:\n"));
+        for flags in flags {
+            text.text("  ");
+            text.text(flags);
+            text.text("\n");
+        }
+        if i == last {
+            for ty in &flattened {
+                text.code(quote!(
+                    <#ty as uutils_args::Arguments>::flattened_help(&mut s, true);
+                ));
+            }
+            for flags in &builtins {
+                text.text("  ");
+                text.text(flags);
+                text.text("\n");
+            }
+        }
+    }
 
-        #after_options
+    if let Some(flag) = help_hint_flag(help_flags) {
+        text.text("\nTry '");
+        text.code(quote!(s.push_str(bin_name);));
+        text.text(&format!(" {flag}' for more information.\n"));
+    }
 
+    let text = text.finish();
+    quote!(
+        let mut s = String::new();
+        #text
         s
     )
 }
 
-fn read_help_file(file: &str) -> (TokenStream, TokenStream) {
-    let path = Path::new(file);
-    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
-    let mut location = PathBuf::from(manifest_dir);
-    location.push(path);
-    let mut contents = String::new();
-    let mut f = std::fs::File::open(location).unwrap();
-    f.read_to_string(&mut contents).unwrap();
+/// The `flattened_help` method of `Arguments`, which pushes the options as
+/// they are listed in the help, without their categories
+pub(crate) fn flattened_help_string(args: &[Argument]) -> TokenStream {
+    let mut full = HelpText::default();
+    let mut short = HelpText::default();
+    for (_, args) in option_groups(args) {
+        for arg in args {
+            let ArgType::Option {
+                flags,
+                default,
+                hide_possible_values,
+                ..
+            } = &arg.arg_type
+            else {
+                unreachable!("only options are grouped")
+            };
+            let extras = help_extras(flags, arg, default, *hide_possible_values);
+            option_help(&mut full, &flags.format(), &arg.help, extras);
+            short.text("  ");
+            short.text(&flags.format());
+            short.text("\n");
+        }
+    }
+    let full = full.finish();
+    let short = short.finish();
+    let show_default = show_default(args);
+
+    quote!(
+        fn flattened_help(s: &mut String, short: bool) {
+            #show_default
 
-    (
-        get_h2("summary", &contents),
-        get_after_event(pulldown_cmark::Event::Rule, &contents),
+            if short {
+                #short
+            } else {
+                #full
+            }
+        }
     )
 }
 
+/// The flag that errors and the short help point to for the full help,
+/// preferring the long flag
+pub(crate) fn help_hint_flag(help_flags: &Flags) -> Option<String> {
+    help_flags
+        .long
+        .first()
+        .map(|f| format!("--{}", f.flag))
+        .or_else(|| help_flags.short.first().map(|f| format!("-{}", f.flag)))
+}
+
+/// The lines shown after the help of an option, with the default and the
+/// possible values, or `None` if there are none
+fn help_extras(
+    flags: &Flags,
+    arg: &Argument,
+    default: &Option<Box<Expr>>,
+    hide_possible_values: bool,
+) -> Option<TokenStream> {
+    let ty = arg.field.as_ref()?;
+
+    let takes_value = flags
+        .short
+        .iter()
+        .map(|f| &f.value)
+        .chain(flags.long.iter().map(|f| &f.value))
+        .any(|v| !matches!(v, Value::No));
+
+    if default.is_none() && (!takes_value || hide_possible_values) {
+        return None;
+    }
+
+    let possible_values = if takes_value && !hide_possible_values {
+        let value_ty = value_type(arg);
+        quote!(
+            let hint = <#value_ty as uutils_args::FromValue>::value_hint();
+            if let Some(values) = hint.values() {
+                extras.push(format!("[possible values: {}]", values.join(", ")));
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let default = match default {
+        Some(expr) => {
+            let text = expr_to_string(expr);
+            quote!(
+                let default = (&&DefaultValue::<#ty>(#expr)).show(#text);
+                extras.push(format!("[default: {default}]"));
+            )
+        }
+        None => quote!(),
+    };
+
+    Some(quote!({
+        let mut extras = Vec::<String>::new();
+        #default
+        #possible_values
+        extras
+    }))
+}
+
+/// The version of the crate using the derive, or an empty string if it is
+/// not built by Cargo.
+pub(crate) fn package_version() -> TokenStream {
+    quote!(match option_env!("CARGO_PKG_VERSION") {
+        Some(version) => version,
+        None => "",
+    })
+}
+
+/// Read a file relative to the manifest directory of the crate using the derive.
+pub(crate) fn read_file(file: &LitStr) -> syn::Result<String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let location = PathBuf::from(manifest_dir).join(file.value());
+    std::fs::read_to_string(&location).map_err(|err| {
+        syn::Error::new(
+            file.span(),
+            format!("Cannot read the help file '{}': {err}", file.value()),
+        )
+    })
+}
+
+/// The arguments in the usage line, like `[OPTION]... SOURCE... DEST`
+///
+/// Optional positional arguments, including those with a default, are in
+/// brackets and arguments that take multiple values are followed by `...`.
+/// Subcommands are shown as `COMMAND [ARGS]...`.
+pub(crate) fn synopsis(args: &[Argument]) -> String {
+    let mut parts = vec![String::from("[OPTION]...")];
+    let mut follows_command = false;
+
+    for arg in args {
+        let ArgType::Positional {
+            num_args,
+            last,
+            command,
+            default,
+            ..
+        } = &arg.arg_type
+        else {
+            continue;
+        };
+
+        let mut part = arg.metavar();
+        if *num_args.start() == 0 || default.is_some() {
+            part = format!("[{part}]");
+        }
+        // Arguments after a command are all taken by the next argument.
+        if *num_args.end() > 1 || *last || follows_command {
+            part.push_str("...");
+        }
+        follows_command = *command;
+        parts.push(part);
+    }
+
+    // The subcommand takes all arguments after it
+    if args
+        .iter()
+        .any(|arg| matches!(arg.arg_type, ArgType::Subcommand { .. }))
+    {
+        parts.push(String::from("COMMAND [ARGS]..."));
+    }
+
+    parts.join(" ")
+}
+
 pub(crate) fn version_handling(version_flags: &Flags) -> TokenStream {
     if version_flags.is_empty() {
         return quote!();
     }
 
     let pat = version_flags.pat();
+    let set_flag = set_flag();
 
     quote!(
         if let #pat = arg {
+            #set_flag
             return Ok(Some(Argument::Version));
         }
     )
 }
+
+/// Report the options that are shown in the help without a help text
+///
+/// Denied options fail the derive, all at once. Warnings are emitted as uses
+/// of deprecated constants, because proc macros cannot emit warnings on
+/// stable Rust.
+pub(crate) fn missing_help_check(
+    args: &[Argument],
+    missing_help: MissingHelp,
+) -> syn::Result<TokenStream> {
+    if missing_help == MissingHelp::Allow {
+        return Ok(quote!());
+    }
+
+    let missing: Vec<&Argument> = args
+        .iter()
+        .filter(|arg| {
+            matches!(
+                arg.arg_type,
+                ArgType::Option {
+                    hidden_help: false,
+                    ..
+                }
+            )
+        })
+        .filter(|arg| arg.help.is_empty())
+        .collect();
+
+    if missing_help == MissingHelp::Deny {
+        let errors = missing.iter().map(|arg| {
+            syn::Error::new(
+                arg.ident.span(),
+                format!(
+                    "The option `{}` has no help text, add a doc comment or mark it `hidden`",
+                    arg.name
+                ),
+            )
+        });
+        // All options without help are reported at once
+        if let Some(error) = errors.reduce(|mut all, error| {
+            all.combine(error);
+            all
+        }) {
+            return Err(error);
+        }
+    }
+
+    let warnings = missing.iter().map(|arg| {
+        let note = format!(
+            "the option `{}` has no help text, add a doc comment or mark it `hidden`",
+            arg.name
+        );
+        // With the span of the variant, the warning is reported like for
+        // code that the user wrote instead of being hidden in the macro.
+        quote_spanned!(arg.ident.span() =>
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const missing_help: () = ();
+                missing_help
+            };
+        )
+    });
+    Ok(quote!(#(#warnings)*))
+}
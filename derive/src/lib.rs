@@ -1,17 +1,26 @@
 mod action;
 mod argument;
 mod attributes;
+mod complete;
 mod field;
 mod flags;
 mod help;
 mod markdown;
 
 use argument::{
-    long_handling, parse_argument, parse_arguments_attr, positional_handling, short_handling,
+    check_arguments, claims_double_dash, collect_help, dash_handling, default_handling,
+    deprecation, flatten_checks, flattened_handling, from_end_handling, handles_double_dash,
+    is_exclusive, is_recorded, keyword_handling, long_handling, max_occurrences, option_name,
+    parse_argument, parse_arguments_attr, plus_number_handling, positional_handling, set_checks,
+    short_handling, short_takes_value, unescape_double_dash,
 };
-use attributes::ValueAttr;
+use attributes::{ValueAttr, ValueExtrasAttr};
+use complete::{complete, flattened_args, option_metas};
 use field::{parse_field, FieldData};
-use help::{help_handling, help_string, version_handling};
+use help::{
+    flattened_help_string, help_handling, help_hint_flag, help_string, missing_help_check,
+    package_version, short_help_string, version_handling,
+};
 
 use proc_macro::TokenStream;
 use quote::quote;
@@ -22,126 +31,452 @@ use syn::{
     DeriveInput, Fields,
 };
 
-#[proc_macro_derive(Options, attributes(arg_type, map, set, field, collect))]
+#[proc_macro_derive(
+    Options,
+    attributes(
+        arg_type,
+        map,
+        set,
+        first,
+        rest,
+        choice,
+        field,
+        collect,
+        validate,
+        try_apply,
+        try_apply_named
+    )
+)]
 pub fn options(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    options_impl(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
+fn options_impl(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = input.ident;
     let arg_type = input
         .attrs
         .iter()
         .find(|a| a.path.is_ident("arg_type"))
-        .expect("An Options struct must have a `arg_type` attribute")
+        .ok_or_else(|| {
+            syn::Error::new(
+                name.span(),
+                "An Options struct must have a `arg_type` attribute",
+            )
+        })?
         .parse_args_with(syn::Ident::parse)
-        .expect("The `arg_type` attribute must contain a valid identifier.");
+        .map_err(|err| {
+            syn::Error::new(
+                err.span(),
+                "The `arg_type` attribute must contain a valid identifier.",
+            )
+        })?;
+    let validate = match input.attrs.iter().find(|a| a.path.is_ident("validate")) {
+        Some(attr) => {
+            let f = attr.parse_args_with(syn::Path::parse).map_err(|err| {
+                syn::Error::new(
+                    err.span(),
+                    "The `validate` attribute must contain a path to a function.",
+                )
+            })?;
+            quote!(
+                fn validate(&self) -> Result<(), uutils_args::Error> {
+                    #f(self)
+                }
+            )
+        }
+        None => quote!(),
+    };
+    let try_apply = match input.attrs.iter().find(|a| a.path.is_ident("try_apply")) {
+        Some(attr) => {
+            let f = attr.parse_args_with(syn::Path::parse).map_err(|err| {
+                syn::Error::new(
+                    err.span(),
+                    "The `try_apply` attribute must contain a path to a function.",
+                )
+            })?;
+            quote!(
+                fn try_apply(&mut self, arg: &Self::Arg) -> Result<(), uutils_args::Error> {
+                    #f(self, arg)
+                }
+            )
+        }
+        None => quote!(),
+    };
+    let try_apply_named = match input
+        .attrs
+        .iter()
+        .find(|a| a.path.is_ident("try_apply_named"))
+    {
+        Some(attr) => {
+            let f = attr.parse_args_with(syn::Path::parse).map_err(|err| {
+                syn::Error::new(
+                    err.span(),
+                    "The `try_apply_named` attribute must contain a path to a function.",
+                )
+            })?;
+            quote!(
+                fn try_apply_named(
+                    &mut self,
+                    arg: &Self::Arg,
+                    flag: &str
+                ) -> Result<(), uutils_args::Error> {
+                    #f(self, arg, flag)
+                }
+            )
+        }
+        None => quote!(),
+    };
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
     let Struct(data) = input.data else {
-        panic!("Input should be a struct!");
+        return Err(syn::Error::new(name.span(), "Input should be a struct!"));
     };
 
     let Fields::Named(fields) = data.fields else {
-        panic!("Fields must be named");
+        return Err(syn::Error::new_spanned(data.fields, "Fields must be named"));
     };
 
     // The key of this map is a literal pattern and the value
     // is whatever code needs to be run when that pattern is encountered.
     let mut stmts = Vec::new();
     let mut defaults = Vec::new();
+    let mut mark_stmts = Vec::new();
+    let mut merge_stmts = Vec::new();
+    let mut state_stmts = Vec::new();
+    let mut check_stmts = Vec::new();
+    let mut env_fields = Vec::new();
     for field in fields.named {
         let FieldData {
             ident,
             default_value,
             match_stmt,
-        } = parse_field(&field);
+            mark_stmt,
+            merge_stmt,
+            state_stmt,
+            check_stmt,
+            env_var,
+            patterns,
+        } = parse_field(&field)?;
+
+        if let Some(var) = env_var {
+            let name = ident.to_string();
+            env_fields.push(quote!((#name, #var)));
+        }
 
         defaults.push(quote!(#ident: #default_value));
-        stmts.push(match_stmt);
+        if !patterns.is_empty() {
+            stmts.push((match_stmt, patterns));
+        }
+        mark_stmts.push(mark_stmt);
+        merge_stmts.push(merge_stmt);
+        state_stmts.push(state_stmt);
+        check_stmts.push(check_stmt);
     }
 
+    // The argument is moved into the last field it is applied to and only
+    // copied for the fields before that.
+    let stmts: Vec<_> = stmts
+        .iter()
+        .enumerate()
+        .map(|(i, (match_stmt, patterns))| {
+            let later: Vec<_> = stmts[i + 1..].iter().flat_map(|(_, p)| p).collect();
+            let take = if later.is_empty() {
+                quote!(arg.take())
+            } else {
+                quote!(if #(matches!(&arg, Some(#later)))||* {
+                    arg.clone()
+                } else {
+                    arg.take()
+                })
+            };
+            quote!(
+                #[allow(unused_variables)]
+                if #(matches!(&arg, Some(#patterns)))||* {
+                    let arg = #take.unwrap();
+                    #match_stmt
+                }
+            )
+        })
+        .collect();
+    let apply_stmts = if stmts.is_empty() {
+        quote!()
+    } else {
+        quote!(
+            let mut arg = Some(arg);
+            #(#stmts)*
+        )
+    };
+
     let expanded = quote!(
         impl #impl_generics Options for #name #ty_generics #where_clause {
             type Arg = #arg_type;
 
-            fn initial() -> Result<Self, uutils_args::Error> {
+            const ENV_FIELDS: &'static [(&'static str, &'static str)] = &[#(#env_fields),*];
+
+            #[allow(unused_variables)]
+            fn initial_with_env(
+                bin_name: &str,
+                config: &uutils_args::ParseConfig,
+                env: &mut Vec<(&'static str, &'static str)>,
+            ) -> Result<Self, uutils_args::Error> {
                 Ok(Self {
                     #(#defaults),*
                 })
             }
 
-            fn apply_args<I>(&mut self, args: I) -> Result<(), uutils_args::Error>
-            where
-                I: IntoIterator + 'static,
-                I::Item: Into<std::ffi::OsString>,
-            {
+            fn fields_of(arg: &Self::Arg) -> Vec<&'static str> {
+                let mut fields = Vec::new();
+                #(#mark_stmts)*
+                fields
+            }
+
+            #[allow(unused_variables)]
+            fn merge_from(&mut self, other: Self, mask: &uutils_args::SetMask) {
+                #(#merge_stmts)*
+            }
+
+            fn apply_iter_with_optional_mask(
+                &mut self,
+                iter: uutils_args::ArgumentIter<Self::Arg>,
+                mut mask: Option<&mut uutils_args::SetMask>,
+            ) -> Result<(), uutils_args::Error> {
                 use uutils_args::{lexopt, FromValue, Argument};
-                let mut iter = <Self as Options>::Arg::parse(args);
-                while let Some(arg) = iter.next_arg()? {
-                    match arg {
+                #(#state_stmts)*
+                let mut args = iter.expanded();
+                while let Some(arg) = args.next() {
+                    let iter = args.inner();
+                    match arg? {
                         Argument::Help => {
-                            print!("{}", iter.help());
-                            std::process::exit(0);
+                            return Err(uutils_args::Error::DisplayHelp(iter.help()));
                         },
+                        Argument::ShortHelp => {
+                            return Err(uutils_args::Error::DisplayHelp(iter.short_help()));
+                        },
+                        Argument::ArgFile(_) => unreachable!("argument files are read by the iterator"),
+                        Argument::Operand(_) => unreachable!("operands are assigned by the iterator"),
                         Argument::Version => {
-                            println!("{}", iter.version());
+                            let text = format!("{}\n", iter.version());
+                            return Err(uutils_args::Error::DisplayVersion(text));
                         },
                         Argument::Custom(arg) => {
-                            #(#stmts)*
+                            if let Err(err) = self.try_apply_named(&arg, iter.flag()) {
+                                return Err(match arg.option_name() {
+                                    Some(_) => err.with_option(iter.flag()),
+                                    None => err,
+                                });
+                            }
+                            if let (Some(mask), false) = (mask.as_deref_mut(), iter.is_default()) {
+                                for field in Self::fields_of(&arg) {
+                                    mask.set_by(field, iter.flag());
+                                }
+                            }
+                            #apply_stmts
                         }
                     }
                 }
-                <Self as Options>::Arg::check_missing(iter.positional_idx)?;
                 Ok(())
             }
+
+            #validate
+
+            #try_apply
+
+            #try_apply_named
         }
+
+        #(#check_stmts)*
     );
 
-    TokenStream::from(expanded)
+    Ok(expanded)
 }
 
-#[proc_macro_derive(Arguments, attributes(flag, option, positional, arguments))]
+#[proc_macro_derive(
+    Arguments,
+    attributes(flag, option, positional, subcommand, flatten, arguments)
+)]
 pub fn arguments(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    arguments_impl(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
+fn arguments_impl(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
     let Enum(data) = input.data else {
-        panic!("Input should be an enum!");
+        return Err(syn::Error::new(name.span(), "Input should be an enum!"));
     };
 
-    let arguments_attr = parse_arguments_attr(&input.attrs);
-    let arguments: Vec<_> = data.variants.into_iter().flat_map(parse_argument).collect();
+    let arguments_attr = parse_arguments_attr(&input.attrs)?;
+    let mut arguments = Vec::new();
+    for variant in data.variants {
+        arguments.extend(parse_argument(variant)?);
+    }
+    check_arguments(&arguments)?;
 
     let exit_code = arguments_attr.exit_code;
-    let short = short_handling(&arguments);
-    let long = long_handling(&arguments, &arguments_attr.help_flags);
-    let (positional, missing_argument_checks) = positional_handling(&arguments);
+    let parse_mode = if arguments_attr.posix {
+        quote!(uutils_args::ParseMode::Posix)
+    } else {
+        quote!(uutils_args::ParseMode::Permute)
+    };
+    let (positional, raw, missing_argument_checks) = positional_handling(&arguments);
+    let collect_unknown = arguments_attr.collect_unknown.as_ref();
+    let has_positional = arguments
+        .iter()
+        .any(|arg| matches!(arg.arg_type, argument::ArgType::Positional { .. }));
+    if arguments_attr.unrecognized_short_as_positional && !has_positional {
+        return Err(syn::Error::new(
+            name.span(),
+            "`unrecognized_short_as_positional` needs a positional argument for the operands",
+        ));
+    }
+    let short = short_handling(
+        &arguments,
+        collect_unknown,
+        arguments_attr
+            .unrecognized_short_as_positional
+            .then_some(&positional),
+    );
+    let short_takes_value = short_takes_value(&arguments);
+    let option_name = option_name(&arguments);
+    let is_recorded = is_recorded(&arguments);
+    let is_exclusive = is_exclusive(&arguments);
+    let deprecation = deprecation(&arguments);
+    let max_occurrences = max_occurrences(&arguments);
+    let long = long_handling(
+        &arguments,
+        &arguments_attr.help_flags,
+        &arguments_attr.short_help_flags,
+        collect_unknown,
+        arguments_attr.argfile,
+    );
+    let from_end = from_end_handling(&arguments);
+    let default_operand = default_handling(&arguments);
+    let plus_number = plus_number_handling(&arguments);
+    let dash = dash_handling(&arguments);
+    let keyword = keyword_handling(&arguments);
+    let claims_double_dash = claims_double_dash(&arguments);
+    let handles_double_dash = handles_double_dash(&arguments, arguments_attr.argfile);
+    let unescaped = unescape_double_dash(quote!(value));
     let help_string = help_string(
         &arguments,
         &arguments_attr.help_flags,
+        &arguments_attr.short_help_flags,
+        &arguments_attr.version_flags,
+        &arguments_attr.file,
+    );
+    let short_help_string = short_help_string(
+        &arguments,
+        &arguments_attr.help_flags,
+        &arguments_attr.short_help_flags,
         &arguments_attr.version_flags,
         &arguments_attr.file,
     );
-    let help = help_handling(&arguments_attr.help_flags);
+    let complete = complete(
+        &arguments,
+        &arguments_attr.help_flags,
+        &arguments_attr.short_help_flags,
+        &arguments_attr.version_flags,
+        &arguments_attr.file,
+        &arguments_attr.completion_extra,
+    );
+    let option_metas = option_metas(
+        &arguments,
+        &arguments_attr.help_flags,
+        &arguments_attr.short_help_flags,
+        &arguments_attr.version_flags,
+    );
+    let help = help_handling(&arguments_attr.help_flags, &arguments_attr.short_help_flags);
+    let argfile = if arguments_attr.argfile {
+        // The options, and with them the argument files, end at `--`
+        quote!(
+            if !*operands_only && value.to_str().is_some_and(|v| v.starts_with('@')) {
+                return Ok(Some(Argument::ArgFile(value)));
+            }
+        )
+    } else {
+        quote!()
+    };
     let version = version_handling(&arguments_attr.version_flags);
-    let version_string = quote!(format!(
-        "{} {}",
-        option_env!("CARGO_BIN_NAME").unwrap_or(env!("CARGO_PKG_NAME")),
-        env!("CARGO_PKG_VERSION"),
-    ));
+    let missing_help = missing_help_check(&arguments, arguments_attr.missing_help)?;
+    let set_checks = set_checks(&arguments);
+    let flattened = flattened_handling(&arguments);
+    let flattened_help = flattened_help_string(&arguments);
+    let flattened_args = flattened_args(&arguments);
+    let flatten_checks = flatten_checks(
+        &arguments,
+        &arguments_attr.help_flags,
+        &arguments_attr.short_help_flags,
+        &arguments_attr.version_flags,
+    );
+    let package_version = package_version();
+    let version_extra = match &arguments_attr.version_extra {
+        Some(extra) => quote!(Some((#extra).to_string())),
+        None => quote!(None),
+    };
+    let version_info = quote!(uutils_args::Version {
+        name: config.name_or(Self::NAME),
+        version: #package_version,
+        extra: #version_extra,
+    });
+
+    let help_flag = match help_hint_flag(&arguments_attr.help_flags) {
+        Some(flag) => quote!(Some(#flag)),
+        None => quote!(None),
+    };
 
     let expanded = quote!(
         impl #impl_generics Arguments for #name #ty_generics #where_clause {
             const EXIT_CODE: i32 = #exit_code;
 
+            const PARSE_MODE: uutils_args::ParseMode = #parse_mode;
+
+            const CLAIMS_DOUBLE_DASH: bool = #claims_double_dash;
+
+            const HANDLES_DOUBLE_DASH: bool = #handles_double_dash;
+
+            const HELP_FLAG: Option<&'static str> = #help_flag;
+
+            #from_end
+
+            #default_operand
+
+            const NAME: &'static str = match option_env!("CARGO_BIN_NAME") {
+                Some(name) => name,
+                None => match option_env!("CARGO_PKG_NAME") {
+                    Some(name) => name,
+                    None => "",
+                },
+            };
+
             #[allow(unreachable_code)]
             fn next_arg(
-                parser: &mut uutils_args::lexopt::Parser, positional_idx: &mut usize
+                parser: &mut uutils_args::lexopt::Parser,
+                positional_idx: &mut usize,
+                flag: &mut String,
+                operands_only: &mut bool,
+                bin_name: Option<&str>,
+                config: &uutils_args::ParseConfig,
             ) -> Result<Option<uutils_args::Argument<Self>>, uutils_args::Error> {
                 use uutils_args::{FromValue, lexopt, Error, Argument};
 
-                let Some(arg) = parser.next()? else { return Ok(None); };
+                #raw
+
+                let arg = if *operands_only {
+                    match parser.raw_args()?.next() {
+                        Some(value) => lexopt::Arg::Value(#unescaped),
+                        None => return Ok(None),
+                    }
+                } else {
+                    let Some(arg) = parser.next()? else { return Ok(None); };
+                    arg
+                };
 
                 #help
 
@@ -150,51 +485,155 @@ pub fn arguments(input: TokenStream) -> TokenStream {
                 let parsed = match arg {
                     lexopt::Arg::Short(short) => { #short }
                     lexopt::Arg::Long(long) => { #long }
-                    lexopt::Arg::Value(value) => { #positional }
+                    lexopt::Arg::Value(value) => {
+                        #dash
+                        #plus_number
+                        #argfile
+                        #keyword
+                        #positional
+                    }
                 };
                 Ok(Some(Argument::Custom(parsed)))
             }
 
-            fn check_missing(positional_idx: usize) -> Result<(), uutils_args::Error> {
+            #[allow(unused_variables)]
+            fn check_missing(
+                positional_idx: usize,
+                seen: &[&'static str],
+            ) -> Result<(), uutils_args::Error> {
                 #missing_argument_checks
             }
 
-            fn help(bin_name: &str) -> String {
+            fn short_takes_value(short: char) -> bool {
+                #short_takes_value
+            }
+
+            fn option_name(&self) -> Option<&'static str> {
+                #option_name
+            }
+
+            fn is_recorded(&self) -> bool {
+                #is_recorded
+            }
+
+            fn is_exclusive(&self) -> bool {
+                #is_exclusive
+            }
+
+            fn deprecation(&self) -> Option<&'static str> {
+                #deprecation
+            }
+
+            fn max_occurrences(&self) -> Option<usize> {
+                #max_occurrences
+            }
+
+            fn help_with_config(bin_name: &str, config: &uutils_args::HelpConfig) -> String {
                 #help_string
             }
 
-            fn version() -> String {
-                #version_string
+            fn short_help_with_config(bin_name: &str, config: &uutils_args::HelpConfig) -> String {
+                #short_help_string
+            }
+
+            fn version_info_with_config(config: &uutils_args::HelpConfig) -> uutils_args::Version {
+                #version_info
+            }
+
+            fn complete_with_config(
+                config: &uutils_args::HelpConfig
+            ) -> uutils_args::complete::Command<'static> {
+                #complete
+            }
+
+            fn options() -> &'static [uutils_args::OptionMeta] {
+                #option_metas
             }
+
+            #flattened
+
+            #flattened_help
+
+            #flattened_args
+        }
+
+        #missing_help
+
+        #flatten_checks
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #set_checks
         }
     );
 
-    TokenStream::from(expanded)
+    Ok(expanded)
 }
 
-#[proc_macro_derive(FromValue, attributes(value))]
+#[proc_macro_derive(FromValue, attributes(value, value_extras))]
 pub fn from_value(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    from_value_impl(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
+fn from_value_impl(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let extras = ValueExtrasAttr::parse(&input.attrs)?;
 
     let Enum(data) = input.data else {
-        panic!("Input should be an enum!");
+        return Err(syn::Error::new(name.span(), "Input should be an enum!"));
     };
 
     let mut options = Vec::new();
+    let mut all_keys = Vec::new();
+    let mut descriptions = Vec::new();
+    let mut display_arms = Vec::new();
 
     let mut match_arms = vec![];
+    // The variant for values that match no key, and whether it holds an
+    // `OsString`
+    let mut fallback: Option<(syn::Ident, bool)> = None;
     for variant in data.variants {
         let variant_name = variant.ident.to_string();
+        let mut display_key = None;
+        let doc = collect_help(&variant.attrs);
+        let doc = doc.lines().next().unwrap_or_default().to_string();
         let attrs = variant.attrs.clone();
         for attr in attrs {
             if !attr.path.is_ident("value") {
                 continue;
             }
 
-            let ValueAttr { keys, value } = ValueAttr::parse(&attr);
+            let ValueAttr {
+                keys,
+                value,
+                help,
+                fallback: is_fallback,
+            } = ValueAttr::parse(&attr)?;
+
+            if is_fallback {
+                if fallback.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "Only one variant can be the `fallback`",
+                    ));
+                }
+                let field = match &variant.fields {
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+                    _ => return Err(syn::Error::new(
+                        variant.ident.span(),
+                        "The `fallback` variant must have a single `String` or `OsString` field",
+                    )),
+                };
+                let os_string = matches!(
+                    &field.ty,
+                    syn::Type::Path(ty) if ty.path.segments.last().is_some_and(|s| s.ident == "OsString")
+                );
+                fallback = Some((variant.ident.clone(), os_string));
+                continue;
+            }
 
             let keys = if keys.is_empty() {
                 vec![variant_name.to_lowercase()]
@@ -203,8 +642,17 @@ pub fn from_value(input: TokenStream) -> TokenStream {
             };
 
             options.push(quote!(&[#(#keys),*]));
+            all_keys.extend(keys.iter().cloned());
+            let help = help.unwrap_or_else(|| doc.clone());
+            descriptions.extend(keys.iter().map(|_| help.clone()));
 
             let stmt = if let Some(v) = value {
+                if extras.display {
+                    return Err(syn::Error::new_spanned(
+                        v,
+                        "Display cannot be derived for a value with a `value` expression",
+                    ));
+                }
                 quote!(#(| #keys)* => #v)
             } else {
                 let mut v = variant.clone();
@@ -212,51 +660,191 @@ pub fn from_value(input: TokenStream) -> TokenStream {
                 quote!(#(| #keys)* => Self::#v)
             };
             match_arms.push(stmt);
+
+            display_key.get_or_insert_with(|| keys[0].clone());
+        }
+
+        if extras.display {
+            let ident = &variant.ident;
+            match &fallback {
+                Some((f, os_string)) if f == ident => {
+                    let display = if *os_string {
+                        quote!(&v.to_string_lossy())
+                    } else {
+                        quote!(v)
+                    };
+                    display_arms.push(quote!(Self::#ident(v) => return f.write_str(#display)));
+                    continue;
+                }
+                _ => {}
+            }
+            let Some(key) = display_key else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("Display cannot be derived if variant {variant_name} has no value"),
+                ));
+            };
+            display_arms.push(quote!(Self::#ident { .. } => #key));
         }
     }
 
-    let expanded = quote!(
-        impl #impl_generics FromValue for #name #ty_generics #where_clause {
-            fn from_value(option: &str, value: std::ffi::OsString) -> Result<Self, uutils_args::Error> {
-                let value = String::from_value(option, value)?;
-                let options: &[&[&str]] = &[#(#options),*];
-                let mut candidates: Vec<&str> = Vec::new();
-                let mut exact_match: Option<&str> = None;
-
-                'outer: for &opt in options {
-                    'inner: for &o in opt {
-                        if value == o {
-                            exact_match = Some(o);
-                            break 'outer;
-                        } else if o.starts_with(&value) {
-                            candidates.push(o);
-                            break 'inner;
-                        }
-                    }
+    let fallback_ident = fallback.as_ref().map(|(ident, _)| ident);
+    let matching = value_matching(&options, &match_arms, true, fallback_ident);
+
+    let from_str = if extras.from_str {
+        let matching = value_matching(&options, &match_arms, !extras.exact, fallback_ident);
+        quote!(
+            impl #impl_generics std::str::FromStr for #name #ty_generics #where_clause {
+                type Err = uutils_args::Error;
+
+                #[allow(unused_mut, unused_labels)]
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    let option = "";
+                    let value = value.to_string();
+                    #matching
                 }
+            }
+        )
+    } else {
+        quote!()
+    };
 
-                let opt = match (exact_match, &candidates[..]) {
-                    (Some(opt), _) => opt,
-                    (None, [opt]) => opt,
-                    (None, []) => return Err(uutils_args::Error::ParsingFailed {
-                        option: option.to_string(),
-                        value,
-                        error: "Invalid value".into(),
-                    }),
-                    (None, opts) => return Err(uutils_args::Error::AmbiguousValue {
-                        option: option.to_string(),
-                        value,
-                        candidates: candidates.iter().map(|s| s.to_string()).collect(),
+    let display = if extras.display {
+        quote!(
+            impl #impl_generics std::fmt::Display for #name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(match self {
+                        #(#display_arms),*
                     })
-                };
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
 
-                Ok(match opt {
-                    #(#match_arms),*,
-                    _ => unreachable!("Should be caught by (None, []) case above.")
-                })
+    // Only describe the values if there is a description for any of them.
+    let value_hint = if descriptions.iter().any(|d| !d.is_empty()) {
+        quote!(uutils_args::complete::ValueHint::Described(
+            [#((#all_keys, #descriptions)),*]
+                .into_iter()
+                .map(|(k, d)| (k.to_string(), d.to_string()))
+                .collect()
+        ))
+    } else {
+        quote!(uutils_args::complete::ValueHint::Strings(
+            [#(#all_keys),*].into_iter().map(ToString::to_string).collect()
+        ))
+    };
+
+    // A value that is not valid UTF-8 cannot match any key, so it goes
+    // straight to an `OsString` fallback.
+    let to_string = match &fallback {
+        Some((ident, true)) => quote!(
+            let value = match value.into_string() {
+                Ok(value) => value,
+                Err(value) => return Ok(Self::#ident(value)),
+            };
+        ),
+        _ => quote!(let value = String::from_value(option, value)?;),
+    };
+
+    let expanded = quote!(
+        impl #impl_generics FromValue for #name #ty_generics #where_clause {
+            fn from_value(option: &str, value: std::ffi::OsString) -> Result<Self, uutils_args::Error> {
+                #to_string
+                #matching
+            }
+
+            fn value_hint() -> uutils_args::complete::ValueHint {
+                #value_hint
             }
         }
+
+        #from_str
+
+        #display
     );
 
-    TokenStream::from(expanded)
+    Ok(expanded)
+}
+
+/// Match `value` against the keys of a `FromValue` enum
+///
+/// If `abbreviate` is true, unambiguous prefixes of the keys are accepted
+/// too. A value that matches no key is given to the `fallback` variant if
+/// there is one. The `option` and `value` variables must be in scope.
+fn value_matching(
+    options: &[proc_macro2::TokenStream],
+    match_arms: &[proc_macro2::TokenStream],
+    abbreviate: bool,
+    fallback: Option<&syn::Ident>,
+) -> proc_macro2::TokenStream {
+    // An empty value is a prefix of every value, but it is not an
+    // abbreviation of any of them.
+    let abbreviation = if abbreviate {
+        quote!(else if !value.is_empty() && o.starts_with(&value) {
+            candidates.push(o);
+            break 'inner;
+        })
+    } else {
+        quote!()
+    };
+
+    let no_match = if let Some(ident) = fallback {
+        quote!(return Ok(Self::#ident(value.into())))
+    } else {
+        quote!(
+            return Err(uutils_args::Error::InvalidValue {
+                option: option.to_string(),
+                value,
+                expected: options
+                    .iter()
+                    .flat_map(|o| o.iter())
+                    .map(|s| s.to_string())
+                    .collect(),
+            })
+        )
+    };
+
+    // With a fallback, a value that is not exactly one of the options or
+    // an abbreviation of only one is free-form, even if it is ambiguous.
+    let ambiguous = if fallback.is_some() {
+        no_match.clone()
+    } else {
+        quote!(
+            return Err(uutils_args::Error::AmbiguousValue {
+                option: option.to_string(),
+                value,
+                candidates: candidates.iter().map(|s| s.to_string()).collect(),
+            })
+        )
+    };
+
+    quote!(
+        let options: &[&[&str]] = &[#(#options),*];
+        let mut candidates: Vec<&str> = Vec::new();
+        let mut exact_match: Option<&str> = None;
+
+        'outer: for &opt in options {
+            'inner: for &o in opt {
+                if value == o {
+                    exact_match = Some(o);
+                    break 'outer;
+                } #abbreviation
+            }
+        }
+
+        let opt = match (exact_match, &candidates[..]) {
+            (Some(opt), _) => opt,
+            (None, [opt]) => opt,
+            (None, []) => #no_match,
+            (None, _) => #ambiguous,
+        };
+
+        Ok(match opt {
+            #(#match_arms,)*
+            _ => unreachable!("Should be caught by (None, []) case above.")
+        })
+    )
 }
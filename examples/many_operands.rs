@@ -0,0 +1,59 @@
+//! Parse many operands, like `rm` does when it is run by `xargs`, and report
+//! how long that takes. Run it in release mode:
+//!
+//! ```sh
+//! cargo run --release --example many_operands [N]
+//! ```
+//!
+//! That the operands are not copied on the way is checked by the
+//! allocation tests in `tests/allocations.rs`.
+
+use std::{ffi::OsString, path::PathBuf, time::Instant};
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-f", "--force")]
+    Force,
+
+    #[option("-r", "-R", "--recursive")]
+    Recursive,
+
+    #[positional(1..)]
+    Files(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Force => true)]
+    force: bool,
+    #[map(Arg::Recursive => true)]
+    recursive: bool,
+    #[collect(set(Arg::Files))]
+    files: Vec<PathBuf>,
+}
+
+fn main() -> Result<(), uutils_args::Error> {
+    let n: usize = std::env::args()
+        .nth(1)
+        .map_or(100_000, |n| n.parse().expect("N must be a number"));
+    let args: Vec<OsString> = ["rm", "-rf"]
+        .into_iter()
+        .map(OsString::from)
+        .chain((0..n).map(|i| OsString::from(format!("dir/file-{i}.txt"))))
+        .collect();
+
+    let start = Instant::now();
+    let settings = Settings::try_parse(args)?;
+    let elapsed = start.elapsed();
+
+    assert!(settings.force && settings.recursive);
+    assert_eq!(settings.files.len(), n);
+    println!(
+        "{n} operands in {elapsed:?} ({:?} per operand)",
+        elapsed / n as u32
+    );
+    Ok(())
+}
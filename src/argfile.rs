@@ -0,0 +1,72 @@
+//! Reading arguments from `@file` arguments, enabled with
+//! `#[arguments(argfile)]`.
+//!
+//! Each line of the file is one argument, taken literally, so spaces do not
+//! need to be quoted. Empty lines are skipped. A line that starts and ends
+//! with a `"` is the text between the quotes, which is how an empty argument
+//! or an argument with leading or trailing spaces is written. The arguments
+//! are parsed as if they were given in place of the `@file` argument.
+//! Argument files cannot refer to other argument files.
+
+use std::{cell::RefCell, collections::VecDeque, ffi::OsString, rc::Rc};
+
+use crate::Error;
+
+/// The arguments from argument files that have not been parsed yet
+#[derive(Default)]
+pub(crate) struct ArgFiles {
+    queue: VecDeque<OsString>,
+    /// The file that the last argument was read from
+    current: Option<String>,
+}
+
+/// Take the arguments from the argument files before the remaining `args`.
+///
+/// A file is read when its argument is parsed, so this relies on lexopt
+/// pulling the arguments one at a time, see [`record_tokens`](crate::metadata::record_tokens).
+pub(crate) fn splice<I>(
+    args: I,
+    files: Rc<RefCell<ArgFiles>>,
+) -> impl Iterator<Item = OsString> + 'static
+where
+    I: IntoIterator + 'static,
+    I::Item: Into<OsString>,
+{
+    let mut args = args.into_iter().map(Into::into);
+    std::iter::from_fn(move || {
+        let mut files = files.borrow_mut();
+        if let Some(arg) = files.queue.pop_front() {
+            return Some(arg);
+        }
+        files.current = None;
+        args.next()
+    })
+}
+
+impl ArgFiles {
+    /// Read the arguments from the file named by `arg`, which starts with `@`.
+    pub(crate) fn read(&mut self, arg: &OsString) -> Result<(), Error> {
+        let arg = arg.to_string_lossy();
+        let path = &arg[1..];
+
+        if let Some(current) = &self.current {
+            return Err(Error::Custom(
+                format!("argument files cannot be nested: '{arg}' found in '{current}'").into(),
+            ));
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            Error::Custom(format!("cannot read arguments from '{path}': {err}").into())
+        })?;
+
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let arg = line
+                .strip_prefix('"')
+                .and_then(|line| line.strip_suffix('"'))
+                .unwrap_or(line);
+            self.queue.push_back(arg.into());
+        }
+        self.current = Some(path.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use std::ffi::OsString;
+
+use crate::{warning, HelpConfig, Warning};
+
+/// How options and operands can be mixed
+///
+/// Set the default for a utility with `#[arguments(parse_mode = posix)]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Options can come after operands, like GNU `getopt` allows by default.
+    #[default]
+    Permute,
+    /// Every argument after the first operand is an operand, even if it
+    /// looks like an option.
+    Posix,
+}
+
+/// Configuration for a single parse
+///
+/// Everything that influences parsing besides the arguments is given here
+/// instead of in process-global state, so that multiple utilities can be
+/// parsed concurrently in one process, like in a multicall binary or in
+/// tests.
+#[derive(Clone, Copy, Debug)]
+pub struct ParseConfig {
+    /// The name and help file for the help and version output
+    pub help: HelpConfig,
+    /// Looks up environment variables for `#[field(env = ...)]` defaults
+    pub env: fn(&str) -> Option<OsString>,
+    /// Reports a warning, like the use of a deprecated option, with the bin
+    /// name and the warning
+    pub warn: fn(&str, &str),
+    /// The maximum number of candidates listed in the message of an
+    /// ambiguous option or an invalid value, see
+    /// [`Error::display_with`](crate::Error::display_with)
+    ///
+    /// The remaining candidates are summarized as "and N more". The error
+    /// itself always contains all candidates. Defaults to 8.
+    pub candidate_limit: usize,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            help: HelpConfig::default(),
+            env: process_env,
+            warn: print_warning,
+            candidate_limit: 8,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// The parse mode for a utility that uses `default`
+    ///
+    /// Setting the `POSIXLY_CORRECT` environment variable, looked up with
+    /// [`ParseConfig::env`], selects [`ParseMode::Posix`] for every utility.
+    pub fn parse_mode(&self, default: ParseMode) -> ParseMode {
+        if (self.env)("POSIXLY_CORRECT").is_some() {
+            ParseMode::Posix
+        } else {
+            default
+        }
+    }
+
+    /// The narrowest width that the help is wrapped at
+    pub const MIN_HELP_WIDTH: usize = 20;
+
+    /// The width to wrap the help at
+    ///
+    /// The first width that is known is used:
+    /// 1. `detected`, the width of the terminal if the utility could
+    ///    determine it,
+    /// 2. the `COLUMNS` environment variable, looked up with
+    ///    [`ParseConfig::env`],
+    /// 3. 80 columns.
+    ///
+    /// A width of 0 or over 65535 counts as unknown. An invalid `COLUMNS` is
+    /// reported as a [`Warning::InvalidEnv`] of `bin_name`, like `ls` does,
+    /// and otherwise ignored. The width is never less than
+    /// [`ParseConfig::MIN_HELP_WIDTH`].
+    ///
+    /// The help is not wrapped at this width yet: it is a building block for
+    /// utilities that lay out their own output, like `ls`.
+    pub fn help_width(&self, bin_name: &str, detected: Option<usize>) -> usize {
+        let valid = |width: usize| (1..=u16::MAX as usize).contains(&width);
+
+        let from_env = || {
+            let columns = (self.env)("COLUMNS")?;
+            match columns.to_str().and_then(|s| s.parse().ok()) {
+                Some(width) if valid(width) => Some(width),
+                _ => {
+                    let warning = Warning::InvalidEnv {
+                        var: "COLUMNS".into(),
+                        value: columns,
+                    };
+                    warning::report(bin_name, self, warning);
+                    None
+                }
+            }
+        };
+
+        let width = detected
+            .filter(|&width| valid(width))
+            .or_else(from_env)
+            .unwrap_or(80);
+        width.max(Self::MIN_HELP_WIDTH)
+    }
+}
+
+/// Read the variable from the environment of the process
+fn process_env(name: &str) -> Option<OsString> {
+    std::env::var_os(name)
+}
+
+/// Print the warning to stderr, like `tail: warning: ...`
+fn print_warning(bin_name: &str, warning: &str) {
+    eprintln!("{bin_name}: warning: {warning}");
+}
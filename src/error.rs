@@ -1,14 +1,113 @@
 use std::{
     error::Error as StdError,
-    ffi::OsString,
+    ffi::{OsStr, OsString},
     fmt::{Debug, Display},
 };
 
+use crate::ParseConfig;
+
+fn write_candidates(
+    f: &mut std::fmt::Formatter<'_>,
+    prefix: &str,
+    candidates: &[String],
+    limit: usize,
+) -> std::fmt::Result {
+    for candidate in candidates.iter().take(limit) {
+        write!(f, "\n  - {prefix}{candidate}")?;
+    }
+    if candidates.len() > limit {
+        write!(f, "\n  and {} more", candidates.len() - limit)?;
+    }
+    Ok(())
+}
+
+/// A value from the command line in an error message, with its control
+/// characters escaped
+///
+/// Values are shown as they were given, but a newline would break up the
+/// message and an escape sequence would be interpreted by the terminal.
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\t' => f.write_str("\\t")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                c if c.is_control() => write!(f, "\\x{:02x}", c as u32)?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An operand in an error message, escaped like [`Escaped`]
+///
+/// Bytes that are not valid UTF-8 are shown as `\xHH`, so that different
+/// operands are not shown the same.
+struct EscapedOs<'a>(&'a OsStr);
+
+impl Display for EscapedOs<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.0.as_encoded_bytes().utf8_chunks() {
+            write!(f, "{}", Escaped(chunk.valid()))?;
+            for byte in chunk.invalid() {
+                write!(f, "\\x{byte:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a value that could not be converted came from, see
+/// [`Error::with_source`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueSource {
+    /// An option or operand on the command line, like `--width`
+    Cli { flag: String },
+    /// An environment variable, like for `#[field(env = "COLUMNS")]`
+    Env { var: String },
+    /// A layer of settings that the utility reads itself, like a config file
+    Config { layer: String },
+    /// The default of a positional argument or a field
+    Default,
+}
+
+impl Display for ValueSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueSource::Cli { flag } => write!(f, "'{}'", Escaped(flag)),
+            ValueSource::Env { var } => write!(f, "environment variable {var}"),
+            ValueSource::Config { layer } => write!(f, "{layer} configuration"),
+            ValueSource::Default => write!(f, "the default"),
+        }
+    }
+}
+
 pub enum Error {
+    /// An option that requires a value was given without one, with the
+    /// spelling of the option, like `-n` or `--lines`
     MissingValue {
         option: Option<String>,
     },
+    /// Positional arguments that did not get enough values, by their metavar
     MissingPositionalArguments(Vec<String>),
+    /// A `count_only` positional argument did not get enough values
+    MissingOperand,
+    /// A positional argument did not get enough values for an option that
+    /// requires more of them, see `min_if` of the `positional` attribute
+    MissingOperandWith {
+        operand: String,
+        option: String,
+    },
+    /// A value was given after the positional arguments got all their
+    /// values, or for a utility without positional arguments
+    ExtraOperand(OsString),
+    /// An operand that is not like `NAME=VALUE` for one of the keyword
+    /// options, like `bs=1M` for `dd`
+    UnrecognizedOperand(OsString),
     UnexpectedOption(String),
     UnexpectedArgument(OsString),
     UnexpectedValue {
@@ -17,7 +116,8 @@ pub enum Error {
     },
     ParsingFailed {
         option: String,
-        value: String,
+        /// The value as it was given, which might not be valid unicode
+        value: OsString,
         error: Box<dyn StdError + Send + Sync + 'static>,
     },
     AmbiguousOption {
@@ -29,11 +129,72 @@ pub enum Error {
         value: String,
         candidates: Vec<String>,
     },
+    /// A value did not match any of the values that the option accepts
+    InvalidValue {
+        option: String,
+        value: String,
+        expected: Vec<String>,
+    },
     NonUnicodeValue(OsString),
+    /// An item of a list value could not be parsed, like the `x` in
+    /// `--fields=1,x`, see [`split_value`](crate::split_value)
+    InvalidItem {
+        option: String,
+        /// The index of the item in the list, starting at 0
+        index: usize,
+        error: Box<Error>,
+    },
+    /// The first operand is not one of the subcommands
+    UnknownSubcommand {
+        name: String,
+        expected: Vec<String>,
+    },
+    /// The first operand is a prefix of more than one subcommand
+    AmbiguousSubcommand {
+        name: String,
+        candidates: Vec<String>,
+    },
+    /// An option was given more often than its `max_occurrences`
+    TooManyOccurrences {
+        option: String,
+        max: usize,
+    },
+    /// The settings are invalid after all arguments are applied
+    Validation(String),
+    /// An option was rejected when it was applied to the settings
+    Rejected {
+        option: String,
+        error: Box<dyn StdError + Send + Sync + 'static>,
+    },
     Custom(Box<dyn StdError + Send + Sync + 'static>),
+    /// A value from outside the command line could not be converted, see
+    /// [`Error::with_source`]
+    FromSource {
+        source: ValueSource,
+        error: Box<Error>,
+    },
+    /// The help was requested, with the text to print
+    ///
+    /// This is not a failure: [`Options::parse`](crate::Options::parse)
+    /// prints the text to stdout and exits with code 0, while
+    /// [`Options::try_parse`](crate::Options::try_parse) returns it, so that
+    /// tests and embedders do not exit.
+    DisplayHelp(String),
+    /// The version was requested, with the text to print, see
+    /// [`Error::DisplayHelp`]
+    DisplayVersion(String),
 }
 
-impl StdError for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::ParsingFailed { error, .. } | Error::Rejected { error, .. } => Some(&**error),
+            Error::Custom(error) => Some(&**error),
+            Error::FromSource { error, .. } | Error::InvalidItem { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
 
 impl Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -41,76 +202,282 @@ impl Debug for Error {
     }
 }
 
+/// The messages are phrased like those of the GNU utilities, without the
+/// name of the utility in front of them. Control characters in the values
+/// from the command line are escaped, like `\t` and `\x1b`.
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "error: ")?;
+        self.fmt_with_limit(f, ParseConfig::default().candidate_limit)
+    }
+}
+
+/// An error displayed with the settings of a [`ParseConfig`], see
+/// [`Error::display_with`]
+pub struct DisplayWith<'a> {
+    error: &'a Error,
+    config: &'a ParseConfig,
+}
+
+impl Display for DisplayWith<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error.fmt_with_limit(f, self.config.candidate_limit)
+    }
+}
+
+impl Error {
+    /// Display the error with the settings of `config`, like its
+    /// [`ParseConfig::candidate_limit`].
+    ///
+    /// The [`Display`] implementation uses the default configuration.
+    pub fn display_with<'a>(&'a self, config: &'a ParseConfig) -> DisplayWith<'a> {
+        DisplayWith {
+            error: self,
+            config,
+        }
+    }
+
+    /// The message of the error, listing at most `limit` candidates
+    fn fmt_with_limit(&self, f: &mut std::fmt::Formatter<'_>, limit: usize) -> std::fmt::Result {
         match self {
             Error::MissingValue { option } => match option {
-                Some(option) => write!(f, "Missing value for '{option}'."),
-                None => write!(f, "Missing value"),
+                Some(option) => match option.strip_prefix('-') {
+                    Some(short) if !short.starts_with('-') => {
+                        write!(f, "option requires an argument -- '{}'", Escaped(short))
+                    }
+                    _ => write!(f, "option '{}' requires an argument", Escaped(option)),
+                },
+                None => write!(f, "option requires an argument"),
             },
-            Error::MissingPositionalArguments(args) => {
-                write!(f, "Missing values for the following positional arguments:")?;
-                for arg in args {
-                    write!(f, "  - {arg}")?;
-                }
-                Ok(())
+            Error::MissingPositionalArguments(args) => match &args[..] {
+                [arg] => write!(f, "missing {arg} operand"),
+                _ => write!(f, "missing operands: {}", args.join(", ")),
+            },
+            Error::MissingOperand => write!(f, "missing operand"),
+            Error::MissingOperandWith { operand, option } => {
+                write!(f, "missing {operand} operand (required with {option})")
             }
-            Error::UnexpectedOption(opt) => {
-                write!(f, "Found an invalid option '{opt}'.")
+            Error::ExtraOperand(operand) | Error::UnexpectedArgument(operand) => {
+                write!(f, "extra operand '{}'", EscapedOs(operand))
             }
-            Error::UnexpectedArgument(arg) => {
-                write!(f, "Found an invalid argument '{}'.", arg.to_string_lossy())
+            Error::UnrecognizedOperand(operand) => {
+                write!(f, "unrecognized operand '{}'", EscapedOs(operand))
             }
-            Error::UnexpectedValue { option, value } => {
+            Error::UnexpectedOption(opt) => match opt.strip_prefix('-') {
+                Some(short) if !short.starts_with('-') => {
+                    write!(f, "invalid option -- '{}'", Escaped(short))
+                }
+                _ => write!(f, "unrecognized option '{}'", Escaped(opt)),
+            },
+            Error::UnexpectedValue { option, .. } => {
+                write!(f, "option '{}' doesn't allow an argument", Escaped(option))
+            }
+            Error::ParsingFailed { option, .. }
+            | Error::AmbiguousValue { option, .. }
+            | Error::InvalidValue { option, .. } => {
+                self.fmt_value(f, "argument", &Origin::Option(option), limit)
+            }
+            Error::AmbiguousOption { option, candidates } => {
                 write!(
                     f,
-                    "Got an unexpected value '{}' for option '{option}'.",
-                    value.to_string_lossy(),
-                )
+                    "option '--{}' is ambiguous; possibilities:",
+                    Escaped(option)
+                )?;
+                write_candidates(f, "--", candidates, limit)
             }
-            Error::ParsingFailed {
+            Error::NonUnicodeValue(_) => self.fmt_value(f, "argument", &Origin::Option(""), limit),
+            Error::InvalidItem {
                 option,
-                value,
+                index,
                 error,
             } => {
-                if option.is_empty() {
-                    write!(f, "Could not parse value '{value}': {error}")
-                } else {
-                    write!(
-                        f,
-                        "Could not parse value '{value}' for option '{option}': {error}"
-                    )
-                }
+                let noun = format!("item {}", index + 1);
+                error.fmt_value(f, &noun, &Origin::Option(option), limit)
             }
-            Error::AmbiguousOption { option, candidates } => {
+            Error::UnknownSubcommand { name, expected } => {
+                write!(f, "unrecognized command '{}'", Escaped(name))?;
+                write!(f, "\nValid commands are:")?;
+                write_candidates(f, "", expected, limit)
+            }
+            Error::AmbiguousSubcommand { name, candidates } => {
                 write!(
                     f,
-                    "Option '{option}' is ambiguous. The following candidates match:"
+                    "command '{}' is ambiguous; possibilities:",
+                    Escaped(name)
                 )?;
-                for candidate in candidates {
-                    write!(f, "  - {candidate}")?;
-                }
-                Ok(())
+                write_candidates(f, "", candidates, limit)
+            }
+            Error::TooManyOccurrences { option, max: 1 } => {
+                write!(f, "option '{option}' can only be given once")
+            }
+            Error::TooManyOccurrences { option, max } => {
+                write!(f, "option '{option}' can be given at most {max} times")
+            }
+            Error::Validation(msg) => write!(f, "{msg}"),
+            Error::Rejected { option, error } => {
+                write!(f, "invalid use of option '{option}': {error}")
+            }
+            Error::Custom(err) => std::fmt::Display::fmt(err, f),
+            Error::FromSource { source, error } => {
+                let (noun, origin) = match source {
+                    ValueSource::Default => ("default value", Origin::Option("")),
+                    source => ("value", Origin::Source(source)),
+                };
+                error.fmt_value(f, noun, &origin, limit)
+            }
+            Error::DisplayHelp(text) | Error::DisplayVersion(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// What a value was given for, shown after the value in an error message
+enum Origin<'a> {
+    /// An option on the command line, or nothing if it is empty
+    Option(&'a str),
+    Source(&'a ValueSource),
+}
+
+impl Display for Origin<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Option("") => Ok(()),
+            Origin::Option(option) => write!(f, " for '{option}'"),
+            Origin::Source(source) => write!(f, " for {source}"),
+        }
+    }
+}
+
+impl Error {
+    /// The message of an error about a value, like "invalid argument '1x0'
+    /// for '--width'"
+    ///
+    /// Other errors are shown after the origin of the value.
+    fn fmt_value(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        noun: &str,
+        origin: &Origin,
+        limit: usize,
+    ) -> std::fmt::Result {
+        match self {
+            Error::ParsingFailed { value, error, .. } => {
+                let value = value.to_string_lossy();
+                let value = Escaped(&value);
+                // The error of a parser might repeat the value.
+                let error = error.to_string();
+                let error = Escaped(&error);
+                write!(f, "invalid {noun} '{value}'{origin}: {error}")
             }
             Error::AmbiguousValue {
-                option,
-                value,
-                candidates,
+                value, candidates, ..
             } => {
                 write!(
                     f,
-                    "Value '{value}' for option '{option}' is ambiguous. The following candidates match:",
+                    "ambiguous {noun} '{}'{origin}; possibilities:",
+                    Escaped(value)
                 )?;
-                for candidate in candidates {
-                    write!(f, "  - {candidate}")?;
-                }
-                Ok(())
+                write_candidates(f, "", candidates, limit)
+            }
+            Error::InvalidValue {
+                value, expected, ..
+            } => {
+                write!(f, "invalid {noun} '{}'{origin}", Escaped(value))?;
+                write!(f, "\nValid arguments are:")?;
+                write_candidates(f, "", expected, limit)
             }
             Error::NonUnicodeValue(x) => {
-                write!(f, "Invalid unicode value found: {}", x.to_string_lossy())
+                write!(
+                    f,
+                    "invalid UTF-8 in {noun} '{}'{origin}",
+                    Escaped(&x.to_string_lossy())
+                )
             }
-            Error::Custom(err) => std::fmt::Display::fmt(err, f),
+            other => {
+                write!(f, "invalid {noun}{origin}: ")?;
+                other.fmt_with_limit(f, limit)
+            }
+        }
+    }
+
+    /// The exit code for this error if the utility does not set one
+    ///
+    /// All parse errors are usage errors, for which most utilities exit with
+    /// code 1. Utilities that use another code, like `ls` with 2 or `timeout`
+    /// with 125, set it with `#[arguments(exit_code = ...)]`, which is used
+    /// by [`Options::parse`](crate::Options::parse) instead. Printing the
+    /// help or version is not an error and exits with code 0.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::DisplayHelp(_) | Error::DisplayVersion(_) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Attach the name of the option that caused this error.
+    ///
+    /// Only [`Error::Validation`] and [`Error::Custom`] are turned into
+    /// [`Error::Rejected`], the other errors already describe their cause.
+    pub fn with_option(self, option: &str) -> Self {
+        let error = match self {
+            Error::Validation(msg) => msg.into(),
+            Error::Custom(err) => err,
+            other => return other,
+        };
+        Error::Rejected {
+            option: option.to_string(),
+            error,
+        }
+    }
+
+    /// Attach where the value that caused this error came from.
+    ///
+    /// For [`ValueSource::Cli`], only the option of a value error without
+    /// one is set, so the message stays like that of any other option.
+    /// Errors for other sources are turned into [`Error::FromSource`], which
+    /// names the source instead of an option, like "invalid value '1x0' for
+    /// environment variable COLUMNS".
+    pub fn with_source(self, source: ValueSource) -> Self {
+        match (source, self) {
+            (
+                ValueSource::Cli { flag },
+                Error::ParsingFailed {
+                    option,
+                    value,
+                    error,
+                },
+            ) if option.is_empty() => Error::ParsingFailed {
+                option: flag,
+                value,
+                error,
+            },
+            (
+                ValueSource::Cli { flag },
+                Error::AmbiguousValue {
+                    option,
+                    value,
+                    candidates,
+                },
+            ) if option.is_empty() => Error::AmbiguousValue {
+                option: flag,
+                value,
+                candidates,
+            },
+            (
+                ValueSource::Cli { flag },
+                Error::InvalidValue {
+                    option,
+                    value,
+                    expected,
+                },
+            ) if option.is_empty() => Error::InvalidValue {
+                option: flag,
+                value,
+                expected,
+            },
+            (ValueSource::Cli { .. }, error) | (_, error @ Error::FromSource { .. }) => error,
+            (source, error) => Error::FromSource {
+                source,
+                error: Box::new(error),
+            },
         }
     }
 }
@@ -124,7 +491,12 @@ impl From<lexopt::Error> for Error {
             lexopt::Error::UnexpectedValue { option, value } => {
                 Self::UnexpectedValue { option, value }
             }
-            lexopt::Error::ParsingFailed { .. } => panic!("Conversion not supported"),
+            // lexopt does not know which option the value belongs to
+            lexopt::Error::ParsingFailed { value, error } => Self::ParsingFailed {
+                option: String::new(),
+                value: value.into(),
+                error,
+            },
             lexopt::Error::NonUnicodeValue(s) => Self::NonUnicodeValue(s),
             lexopt::Error::Custom(e) => Self::Custom(e),
         }
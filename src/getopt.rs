@@ -0,0 +1,275 @@
+//! Normalization of arguments for options that are only known at runtime,
+//! like those given to the `getopt` utility.
+//!
+//! The options are described like `getopt` does, with a string of short
+//! options and a list of long options. [`normalize`] then splits the
+//! arguments into the options and operands that they contain, resolving
+//! clusters of short options, attached values and abbreviated long options.
+//!
+//! ```
+//! use uutils_args::getopt::{normalize, GetoptSpec, NormalizedToken};
+//!
+//! let spec = GetoptSpec::new("ab:").long("verbose");
+//! let normalized = normalize(&spec, ["-ab5", "file", "--verb"], true);
+//! assert_eq!(
+//!     normalized.tokens,
+//!     [
+//!         NormalizedToken::flag("-a", None),
+//!         NormalizedToken::flag("-b", Some("5")),
+//!         NormalizedToken::flag("--verbose", None),
+//!         NormalizedToken::Operand("file".into()),
+//!     ]
+//! );
+//! ```
+
+use std::ffi::OsString;
+
+use crate::{Error, ParseMode};
+
+/// Whether an option takes a value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HasArg {
+    /// No value, like `a` in the short options
+    No,
+    /// A value that is attached or is the next argument, like `b:`
+    Required,
+    /// A value that must be attached, like `c::`
+    Optional,
+}
+
+/// The options to normalize the arguments for
+#[derive(Clone, Debug)]
+pub struct GetoptSpec {
+    name: String,
+    shorts: Vec<(char, HasArg)>,
+    longs: Vec<(String, HasArg)>,
+    mode: ParseMode,
+}
+
+impl GetoptSpec {
+    /// The short options in the syntax of `getopt`, like `ab:c::`
+    ///
+    /// A `:` after an option means that it takes a value and `::` that the
+    /// value is optional. A leading `+` selects [`ParseMode::Posix`].
+    pub fn new(shorts: &str) -> Self {
+        let (mode, shorts) = match shorts.strip_prefix('+') {
+            Some(shorts) => (ParseMode::Posix, shorts),
+            None => (ParseMode::Permute, shorts),
+        };
+        let mut parsed = Vec::new();
+        let mut chars = shorts.chars().peekable();
+        while let Some(c) = chars.next() {
+            let mut colons = 0;
+            while colons < 2 && chars.next_if_eq(&':').is_some() {
+                colons += 1;
+            }
+            parsed.push((c, has_arg(colons)));
+        }
+        Self {
+            name: "getopt".into(),
+            shorts: parsed,
+            longs: Vec::new(),
+            mode,
+        }
+    }
+
+    /// Add long options in the syntax of `getopt`, like `verbose,file:`
+    ///
+    /// The names are separated by commas or whitespace and followed by `:`
+    /// or `::` like the short options.
+    pub fn long(mut self, longs: &str) -> Self {
+        for long in longs.split(|c: char| c == ',' || c.is_whitespace()) {
+            let name = long.trim_end_matches(':');
+            if name.is_empty() {
+                continue;
+            }
+            let colons = (long.len() - name.len()).min(2);
+            self.longs.push((name.into(), has_arg(colons)));
+        }
+        self
+    }
+
+    /// The name in front of the errors, `getopt` by default
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Override the parse mode, for example with
+    /// [`ParseConfig::parse_mode`](crate::ParseConfig::parse_mode) to
+    /// follow `POSIXLY_CORRECT`.
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The parse mode from the short options or [`GetoptSpec::parse_mode`]
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    fn short(&self, short: char) -> Option<HasArg> {
+        self.shorts
+            .iter()
+            .find(|(c, _)| *c == short)
+            .map(|(_, has_arg)| *has_arg)
+    }
+
+    /// The long option that `name` is an abbreviation of
+    fn resolve_long(&self, name: &str) -> Result<(&str, HasArg), Error> {
+        if let Some((long, has_arg)) = self.longs.iter().find(|(long, _)| long == name) {
+            return Ok((long, *has_arg));
+        }
+        let candidates: Vec<_> = self
+            .longs
+            .iter()
+            .filter(|(long, _)| long.starts_with(name))
+            .collect();
+        match candidates[..] {
+            [] => Err(Error::UnexpectedOption(format!("--{name}"))),
+            [(long, has_arg)] => Ok((long, *has_arg)),
+            _ => Err(Error::AmbiguousOption {
+                option: name.into(),
+                candidates: candidates.iter().map(|(long, _)| long.clone()).collect(),
+            }),
+        }
+    }
+}
+
+fn has_arg(colons: usize) -> HasArg {
+    match colons {
+        0 => HasArg::No,
+        1 => HasArg::Required,
+        _ => HasArg::Optional,
+    }
+}
+
+/// An option or operand in the normalized arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NormalizedToken {
+    /// An option, with `canonical` the short option like `-a` or the full
+    /// name of the long option like `--verbose`, even if it was abbreviated
+    Flag {
+        canonical: String,
+        value: Option<OsString>,
+    },
+    Operand(OsString),
+}
+
+impl NormalizedToken {
+    /// A [`NormalizedToken::Flag`]
+    pub fn flag(canonical: &str, value: Option<&str>) -> Self {
+        Self::Flag {
+            canonical: canonical.into(),
+            value: value.map(Into::into),
+        }
+    }
+}
+
+/// The result of [`normalize`]
+#[derive(Debug, Default)]
+pub struct Normalized {
+    /// The options in the order they were given, followed by the operands
+    ///
+    /// The caller places the `--` between the options and the operands.
+    pub tokens: Vec<NormalizedToken>,
+    /// The invalid options, which are left out of the tokens
+    pub errors: Vec<Error>,
+}
+
+/// Split `args`, without the name of the utility, into options and
+/// operands according to `spec`.
+///
+/// Like `getopt`, invalid options do not stop the normalization: they are
+/// reported to stderr, unless `quiet` is set, and collected in
+/// [`Normalized::errors`].
+pub fn normalize<I>(spec: &GetoptSpec, args: I, quiet: bool) -> Normalized
+where
+    I: IntoIterator,
+    I::Item: Into<OsString>,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let mut parser = lexopt::Parser::from_args(args);
+    let mut normalized = Normalized::default();
+    let mut operands = Vec::new();
+
+    loop {
+        let arg = match parser.next() {
+            Ok(Some(arg)) => arg,
+            Ok(None) => break,
+            Err(err) => {
+                report(spec, quiet, &mut normalized, err.into());
+                break;
+            }
+        };
+        let token = match arg {
+            lexopt::Arg::Short(short) => match spec.short(short) {
+                Some(has_arg) => option_token(&mut parser, format!("-{short}"), has_arg, false),
+                None => Err(Error::UnexpectedOption(format!("-{short}"))),
+            },
+            lexopt::Arg::Long(name) => match spec.resolve_long(name) {
+                Ok((long, has_arg)) => {
+                    option_token(&mut parser, format!("--{long}"), has_arg, true)
+                }
+                Err(err) => {
+                    // The value of an invalid option is not an operand.
+                    let _ = parser.optional_value();
+                    Err(err)
+                }
+            },
+            lexopt::Arg::Value(value) => {
+                operands.push(value);
+                if spec.mode == ParseMode::Posix {
+                    // Everything after the first operand is an operand.
+                    if let Ok(rest) = parser.raw_args() {
+                        operands.extend(rest);
+                    }
+                }
+                continue;
+            }
+        };
+        match token {
+            Ok(token) => normalized.tokens.push(token),
+            Err(err) => report(spec, quiet, &mut normalized, err),
+        }
+    }
+
+    normalized
+        .tokens
+        .extend(operands.into_iter().map(NormalizedToken::Operand));
+    normalized
+}
+
+fn report(spec: &GetoptSpec, quiet: bool, normalized: &mut Normalized, err: Error) {
+    if !quiet {
+        eprintln!("{}: {err}", spec.name);
+    }
+    normalized.errors.push(err);
+}
+
+/// The option with its value, if it takes one
+///
+/// Only long options can have a value attached that they do not take, the
+/// rest of a cluster of short options are other options.
+fn option_token(
+    parser: &mut lexopt::Parser,
+    canonical: String,
+    has_arg: HasArg,
+    long: bool,
+) -> Result<NormalizedToken, Error> {
+    let value = match has_arg {
+        HasArg::No if !long => None,
+        HasArg::No => {
+            if let Some(value) = parser.optional_value() {
+                return Err(Error::UnexpectedValue {
+                    option: canonical,
+                    value,
+                });
+            }
+            None
+        }
+        HasArg::Required => Some(parser.value()?),
+        HasArg::Optional => parser.optional_value(),
+    };
+    Ok(NormalizedToken::Flag { canonical, value })
+}
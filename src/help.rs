@@ -0,0 +1,120 @@
+use std::sync::{Mutex, PoisonError};
+
+use term_md::{after_rule, all_events, events_to_text, h2_events, Event, Renderer};
+
+/// Help information given at parse time instead of in the `arguments`
+/// attribute
+///
+/// This allows multiple utilities, like `ls`, `dir` and `vdir`, to share one
+/// `Arguments` enum while each having their own name and help file. Empty
+/// fields fall back to the information from the enum.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HelpConfig {
+    /// The name of the utility
+    pub name: &'static str,
+    /// The contents of the help file, e.g. from `include_str!`
+    pub file: &'static str,
+    /// Markdown shown after the options, instead of the text after the rule
+    /// in the help file
+    pub after_help: &'static str,
+}
+
+impl HelpConfig {
+    /// The name of the utility, or `default` if no name is given
+    pub fn name_or(&self, default: &'static str) -> &'static str {
+        if self.name.is_empty() {
+            default
+        } else {
+            self.name
+        }
+    }
+
+    /// The rendered summary of the help file, if a file is given
+    pub fn summary(&self) -> Option<String> {
+        if self.file.is_empty() {
+            return None;
+        }
+        Some(render(h2_events("summary", self.file)))
+    }
+
+    /// The usage section of the help file, if a file with such a section is
+    /// given
+    ///
+    /// Every non-empty line is a usage line, in which `{}` stands for the bin
+    /// name.
+    pub fn usage(&self) -> Option<String> {
+        let usage = events_to_text(h2_events("usage", self.file));
+        if usage.is_empty() {
+            None
+        } else {
+            Some(usage)
+        }
+    }
+
+    /// The rendered text after the options, if given
+    pub fn after_options(&self) -> Option<String> {
+        if !self.after_help.is_empty() {
+            Some(render(all_events(self.after_help)))
+        } else if !self.file.is_empty() {
+            Some(render(after_rule(self.file)))
+        } else {
+            None
+        }
+    }
+
+    /// The summary of the help file as plain text, if a file is given
+    ///
+    /// The completion metadata borrows its text for `'static`, so the text
+    /// is leaked, but only once for each help file.
+    pub fn summary_text(&self) -> Option<&'static str> {
+        if self.file.is_empty() {
+            return None;
+        }
+        Some(cached_text(TextKind::Summary, self.file))
+    }
+
+    /// The text after the options as plain text, if given
+    ///
+    /// The text is leaked once like in [`HelpConfig::summary_text`].
+    pub fn after_options_text(&self) -> Option<&'static str> {
+        if !self.after_help.is_empty() {
+            Some(cached_text(TextKind::All, self.after_help))
+        } else if !self.file.is_empty() {
+            Some(cached_text(TextKind::AfterRule, self.file))
+        } else {
+            None
+        }
+    }
+}
+
+/// The part of a markdown source that is turned into plain text
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TextKind {
+    Summary,
+    AfterRule,
+    All,
+}
+
+/// The plain text of a part of `source`, which is generated and leaked only
+/// the first time it is requested.
+fn cached_text(kind: TextKind, source: &'static str) -> &'static str {
+    type Cache = Vec<(TextKind, &'static str, &'static str)>;
+    static CACHE: Mutex<Cache> = Mutex::new(Vec::new());
+
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&(_, _, text)) = cache.iter().find(|(k, s, _)| *k == kind && *s == source) {
+        return text;
+    }
+    let text = match kind {
+        TextKind::Summary => events_to_text(h2_events("summary", source)),
+        TextKind::AfterRule => events_to_text(after_rule(source)),
+        TextKind::All => events_to_text(all_events(source)),
+    };
+    let text: &'static str = Box::leak(text.into_boxed_str());
+    cache.push((kind, source, text));
+    text
+}
+
+fn render<E: Into<Event>>(events: Vec<E>) -> String {
+    Renderer::new(80, events.into_iter().map(Into::into)).render()
+}
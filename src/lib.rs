@@ -1,23 +1,414 @@
+mod argfile;
+mod config;
 mod error;
+pub mod getopt;
+mod help;
+mod metadata;
+mod output;
+pub mod value_types;
+mod version;
+mod warning;
+pub use complete;
 pub use derive::*;
 pub use lexopt;
 pub use term_md;
 
-pub use error::Error;
+use argfile::{splice, ArgFiles};
+use complete::ValueHint;
+pub use config::{ParseConfig, ParseMode};
+pub use error::{DisplayWith, Error, ValueSource};
+pub use help::HelpConfig;
+use metadata::{record_tokens, Tokens, Tracker};
+pub use metadata::{ArgKind, OptionMeta, ParseMetadata, Provenance, SetMask};
+pub use output::write_info;
+use std::collections::VecDeque;
 use std::num::ParseIntError;
 use std::path::PathBuf;
-use std::{ffi::OsString, marker::PhantomData};
+use std::{
+    cell::RefCell,
+    ffi::{OsStr, OsString},
+    marker::PhantomData,
+    rc::Rc,
+};
+pub use version::Version;
+pub use warning::{report, warn, Warning};
 
 #[derive(Clone)]
 pub enum Argument<T: Arguments> {
     Help,
+    /// The condensed help, see [`Arguments::short_help`]
+    ShortHelp,
     Version,
+    /// A positional value starting with `@` for utilities with
+    /// `#[arguments(argfile)]`
+    ///
+    /// The arguments in the file are read by [`ArgumentIter::next_arg`], so
+    /// this is never returned from it.
+    ArgFile(OsString),
+    /// An operand of a positional argument that is followed by one with
+    /// `from_end`
+    ///
+    /// Which of the two it belongs to is only known once all arguments have
+    /// been read, so [`ArgumentIter::next_arg`] holds it back until then and
+    /// this is never returned from it.
+    Operand(OsString),
     Custom(T),
 }
 
+/// The arguments of a utility, usually derived with `#[derive(Arguments)]`
+///
+/// All spellings of an option must name their value the same, so that the
+/// help, errors and completions agree on it:
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-w COLS", "--width=COLS")]
+///     Width(u16),
+///     // `value_name` renames the values of all spellings
+///     #[option("-s NUM", "--size=SIZE", value_name = "SIZE")]
+///     Size(u64),
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-s NUM", "--size=SIZE")]
+///     Size(u64),
+/// }
+/// ```
+///
+/// A value that is required for one spelling and optional for another is
+/// likely a mistake, so it has to be marked with `mixed_arity`:
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-p DIR", "--tmpdir[=DIR]", mixed_arity, default = "/tmp".into())]
+///     TmpDir(String),
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-p DIR", "--tmpdir[=DIR]", default = "/tmp".into())]
+///     TmpDir(String),
+/// }
+/// ```
+///
+/// The field of a variant is parsed from each value with [`FromValue`], so
+/// it is a single value like `T` or `Option<T>`. An `Option<T>` is always
+/// `Some` when a value is given; an option with an optional value is `None`
+/// when the value is left out, unless it has another `default`. With any
+/// other field, an optional value needs a `default`. Only `last`
+/// positional arguments and the positional argument after a `command`
+/// collect all their values, into a collection like `Vec<T>`. Other shapes,
+/// like `Option<Vec<T>>`, are rejected, because no value could produce them:
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("--output-delimiter=STRING")]
+///     OutputDelimiter(Option<Vec<String>>),
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[positional(..)]
+///     Files(Vec<std::path::PathBuf>),
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[positional(.., last)]
+///     Files(Option<Vec<String>>),
+/// }
+/// ```
+///
+/// A required value is always the next argument, taken literally, so
+/// `-o -`, `-o-` and `--output=-` all give the value `-`, and `-d --` gives
+/// `--`. An optional value must be attached, so in `--color -` the `-` is an
+/// operand, while `--color=-` and `-c-` give the value `-`. An attached
+/// empty value, like `--suffix=`, is parsed as the empty value, not replaced
+/// by the default. A lone `-` is always an operand otherwise, usually meaning
+/// stdin or stdout.
+///
+/// A value attached to a long option that takes none, like `--rec=1` for
+/// `--recursive`, is an error that names the full option. The value is never
+/// parsed as an operand, also when the option is ambiguous.
+///
+/// A few utilities give `-` a meaning of its own, like `su -`. It can be
+/// declared as a flag with `#[option("-")]`, which matches a lone `-` before
+/// the end of the options. The `--` that ends the options can be claimed as
+/// an option as well, but only with `literal`, because it then no longer
+/// ends the options:
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-", "-l", "--login")]
+///     Login,
+///     #[option("--", literal)]
+///     End,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("--")]
+///     End,
+/// }
+/// ```
+///
+/// Operands in fixed slots, like the `SET1` and `SET2` of `tr`, are
+/// separate positional arguments that each take one value, so the variant
+/// tells which slot a value landed in. The metavar in the help and in the
+/// "missing operand" error is the name of the variant in uppercase, or the
+/// `value_name`. Operands beyond the last slot are an extra operand.
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[positional(1, value_name = "SET1")]
+///     First(String),
+///     #[positional(0..=1, value_name = "SET2")]
+///     Second(String),
+/// }
+/// ```
+///
+/// Utilities that pass options on to a command, like `timeout`, can collect
+/// the options they do not recognize into a variant without an attribute
+/// instead of failing on them. The option is spelled like it was given,
+/// including an attached value, and the rest of a cluster of short options
+/// is taken as its value:
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// # use std::ffi::OsString;
+/// #[derive(Clone, Arguments)]
+/// #[arguments(collect_unknown = Unknown)]
+/// enum Arg {
+///     #[option("-v", "--verbose")]
+///     Verbose,
+///     // Receives `--signal=KILL` and `-s9` as they are
+///     Unknown(OsString),
+/// }
+/// ```
+///
+/// Utilities with operands that look like short options, like the mode in
+/// `chmod -w file`, can instead take an unrecognized short option as an
+/// operand with `#[arguments(unrecognized_short_as_positional)]`. The
+/// operand is the dash, the option and the rest of its cluster, like `-rw`.
+/// The short options that are recognized are still options and long options
+/// are never operands.
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// #[arguments(unrecognized_short_as_positional)]
+/// enum Arg {
+///     #[option("-R", "--recursive")]
+///     Recursive,
+///     #[positional]
+///     Mode(String),
+///     #[positional(1..)]
+///     File(String),
+/// }
+/// ```
+///
+/// Completions that cannot be derived from the options, like the units of a
+/// size, can be written by hand with
+/// `#[arguments(completion_extra = "completions/ls.fish")]`. The file is
+/// relative to the manifest of the crate and its extension (`fish`, `bash`,
+/// `zsh`, `ps1` or `nu`) is the shell whose script it is appended to. It can
+/// be given once for every shell.
+///
+/// The values to complete are given by the type of the field, see
+/// [`FromValue::value_hint`], or by a variant of [`ValueHint`] with
+/// `hint = Username`. Values only known at runtime, like the signals of
+/// `kill -s`, are listed by a function with `hint_fn = path::to::function`,
+/// which returns a `Vec<String>`. The function is called when the
+/// completions are rendered and its values are embedded in the script, not
+/// computed again when the shell completes.
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// fn signals() -> Vec<String> {
+///     vec!["HUP".into(), "INT".into(), "KILL".into()]
+/// }
+///
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-s SIGNAL", "--signal=SIGNAL", hint_fn = signals)]
+///     Signal(String),
+/// }
+/// ```
+///
+/// An option with `hidden` is left out of the help, the man page and the
+/// completions. With `hidden_help`, it is only left out of the help and the
+/// man page and with `hidden_complete` only out of the completions.
+///
+/// A variant with `#[subcommand]` is a subcommand, named after the variant
+/// or by the names given like `#[subcommand("remove", "rm")]`. The first
+/// operand selects the subcommand, abbreviated like a long option, and the
+/// arguments after it are parsed by the [`Options`] type in its field, so
+/// `prog add --help` shows the help of that type. Subcommands cannot be
+/// combined with positional arguments.
+///
+/// ```
+/// # use uutils_args::{Arguments, Options};
+/// #[derive(Clone, Arguments)]
+/// enum AddArg {
+///     #[positional(1..)]
+///     File(String),
+/// }
+///
+/// #[derive(Clone, Default, Options)]
+/// #[arg_type(AddArg)]
+/// struct AddSettings {
+///     #[collect(set(AddArg::File))]
+///     files: Vec<String>,
+/// }
+///
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-v", "--verbose")]
+///     Verbose,
+///     /// Add files to the index
+///     #[subcommand]
+///     Add(AddSettings),
+/// }
+/// ```
+///
+/// Options shared by several utilities can be declared once in an enum of
+/// their own and merged in with `#[flatten]` on a variant with that enum as
+/// its field. Its short and long options are parsed as options of the outer
+/// enum, wrapped in the variant, and long options are abbreviated among all
+/// of them. An option that both enums have is an error at compile time. The
+/// help and version options of the flattened enum are not merged.
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Common {
+///     /// Print debugging information
+///     #[option("--debug")]
+///     Debug,
+/// }
+///
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-a", "--all")]
+///     All,
+///     #[flatten]
+///     Common(Common),
+/// }
+/// ```
+///
+/// Operands like `bs=1M` for `dd` are declared with `keyword`, like
+/// `#[option(keyword = "bs=BYTES")]`, which can be given more than once for
+/// other names of the same option. The value after the first `=` is parsed
+/// with [`FromValue`], also after `--`. Other operands with an `=` are
+/// rejected as unrecognized, as are all operands beyond the positional
+/// arguments. A keyword option cannot have flags.
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// # use std::path::PathBuf;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     /// Read from FILE instead of stdin
+///     #[option(keyword = "if=FILE")]
+///     Input(PathBuf),
+///     #[option(keyword = "skip=N", keyword = "iseek=N")]
+///     Skip(u64),
+/// }
+/// ```
+///
+/// The value of an option with a `delimiter` is a list, like `1,3` for
+/// `cut -f`. It is split at the delimiter, which must be ASCII, and each
+/// item is parsed into the `Vec` of the variant with [`FromValue`]. Empty
+/// items are an error, unless they are skipped with `skip_empty`.
+///
+/// ```
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// enum Arg {
+///     #[option("-f LIST", "--fields=LIST", delimiter = ',')]
+///     Fields(Vec<u32>),
+///     #[option("--hide=PATTERN", delimiter = ',', skip_empty)]
+///     Hide(Vec<String>),
+/// }
+/// ```
+///
+/// With `#[arguments(deny_missing_help)]`, options without a doc comment are
+/// an error, unless they are `hidden` or `hidden_help`. All of them are
+/// listed at once. With `#[arguments(warn_missing_help)]`, they are warnings
+/// instead.
+///
+/// ```compile_fail
+/// # use uutils_args::Arguments;
+/// #[derive(Clone, Arguments)]
+/// #[arguments(deny_missing_help)]
+/// enum Arg {
+///     /// Do not ignore entries starting with .
+///     #[option("-a", "--all")]
+///     All,
+///     #[option("-l")]
+///     Long,
+/// }
+/// ```
 pub trait Arguments: Sized + Clone {
     const EXIT_CODE: i32;
 
+    /// Name of the utility, used when the arguments do not contain a bin name.
+    const NAME: &'static str;
+
+    /// Whether options can come after operands, unless overridden by the
+    /// environment, see [`ParseConfig::parse_mode`].
+    const PARSE_MODE: ParseMode;
+
+    /// Whether `--` is an option, declared with `#[option("--", literal)]`,
+    /// instead of the end of the options
+    const CLAIMS_DOUBLE_DASH: bool;
+
+    /// Whether [`Arguments::next_arg`] handles `--` itself, instead of
+    /// leaving it to lexopt. This is the case if `--` or `-` is an option.
+    const HANDLES_DOUBLE_DASH: bool;
+
+    /// The number of operands taken from the end by a positional argument
+    /// with `from_end`, or 0 if there is none
+    const TRAILING_OPERANDS: usize = 0;
+
+    /// The flag that shows the help, preferring a long flag, which some
+    /// errors point to, like "Try 'tty --help' for more information."
+    const HELP_FLAG: Option<&'static str> = None;
+
+    /// The long options, without the leading `--`, that an enum which
+    /// flattens this one parses with [`Arguments::flattened_long`]
+    const LONG_OPTIONS: &'static [&'static str] = &[];
+
+    /// The short options that an enum which flattens this one parses with
+    /// [`Arguments::flattened_short`]
+    const SHORT_OPTIONS: &'static [char] = &[];
+
     fn parse<I>(args: I) -> ArgumentIter<Self>
     where
         I: IntoIterator + 'static,
@@ -26,87 +417,1325 @@ pub trait Arguments: Sized + Clone {
         ArgumentIter::<Self>::from_args(args)
     }
 
+    /// Parse arguments that do not start with the bin name.
+    ///
+    /// This is useful if the bin name has already been stripped, for example
+    /// in a multicall binary that delegates to a utility.
+    fn parse_without_binname<I>(args: I) -> ArgumentIter<Self>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        ArgumentIter::<Self>::from_args_without_binname(args)
+    }
+
+    /// Parse arguments that do not start with the bin name, for the utility
+    /// called `name`.
+    ///
+    /// This is for multicall binaries like `coreutils ls -l`, where the
+    /// first argument is the name of the multicall binary instead of the
+    /// utility. The name is used in the help and in the error messages.
+    fn parse_with_name<I>(name: &str, args: I) -> ArgumentIter<Self>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        ArgumentIter::<Self>::from_args_without_binname(args).with_bin_name(name)
+    }
+
+    /// Parse the next argument.
+    ///
+    /// The spelling of the flag that produced a custom argument, like `-c` or
+    /// `--bytes`, is written to `flag`. Positional arguments write their
+    /// metavar instead.
+    ///
+    /// With `operands_only`, the next argument is an operand, even if it looks
+    /// like an option. It is set when the parser ends the options itself.
+    ///
+    /// A subcommand is parsed with the `bin_name` given with
+    /// [`ArgumentIter::with_bin_name`], if any, and the `config` of this
+    /// parse.
     fn next_arg(
         parser: &mut lexopt::Parser,
         positional_idx: &mut usize,
+        flag: &mut String,
+        operands_only: &mut bool,
+        bin_name: Option<&str>,
+        config: &ParseConfig,
     ) -> Result<Option<Argument<Self>>, Error>;
 
-    fn check_missing(positional_idx: usize) -> Result<(), Error>;
+    /// Turn an operand that [`Arguments::next_arg`] held back as
+    /// [`Argument::Operand`] into the argument it belongs to.
+    ///
+    /// With `trailing`, it is one of the [last
+    /// operands](Arguments::TRAILING_OPERANDS), otherwise it belongs to the
+    /// positional argument before those. The metavar of that argument is
+    /// written to `flag`.
+    fn from_operand(_value: OsString, _trailing: bool, _flag: &mut String) -> Result<Self, Error> {
+        unreachable!("only arguments with a `from_end` positional hold back operands")
+    }
+
+    /// The default of a positional argument that got no values, declared
+    /// with `default` in its `positional` attribute.
+    ///
+    /// This is called after the last argument until it returns `None`. Only
+    /// the positional arguments from the `next` one that has a default are
+    /// considered and `next` is advanced past the one that is returned. The
+    /// metavar of that argument is written to `flag`.
+    fn default_operand(
+        _positional_idx: usize,
+        _next: &mut usize,
+        _flag: &mut String,
+    ) -> Result<Option<Self>, Error> {
+        Ok(None)
+    }
+
+    /// Check that all required positional arguments were given.
+    ///
+    /// `seen` contains the [names](Arguments::option_name) of the options that
+    /// were given, which can raise the number of required values.
+    fn check_missing(positional_idx: usize, seen: &[&'static str]) -> Result<(), Error>;
+
+    /// Whether the short option takes a value, which consumes the rest of a
+    /// cluster of short options.
+    fn short_takes_value(short: char) -> bool;
+
+    /// The name of the option that produced this argument, like `--pid`.
+    ///
+    /// This is the first long flag of the option, regardless of how it was
+    /// spelled, see [`ArgumentIter::flag`] for that. Positional arguments have
+    /// no name.
+    fn option_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Whether the tokens of this argument are kept as typed, see
+    /// [`ParseMetadata::recorded_args`].
+    ///
+    /// This is the case for options with the `record` attribute.
+    fn is_recorded(&self) -> bool {
+        false
+    }
+
+    /// Whether this argument ends the parse, set with the `exclusive`
+    /// attribute.
+    ///
+    /// The arguments after it are ignored and missing positional arguments
+    /// are not an error, like for `--help`. The settings are still applied
+    /// and [validated](Options::validate), so that the utility can act on
+    /// the option, like `kill --list` listing the signals.
+    fn is_exclusive(&self) -> bool {
+        false
+    }
+
+    /// The warning for a deprecated option, given with the `deprecated`
+    /// attribute.
+    ///
+    /// The warning is reported to [`ParseConfig::warn`] when the option is
+    /// used.
+    fn deprecation(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// How often the option of this argument can be given, set with the
+    /// `max_occurrences` attribute.
+    ///
+    /// All spellings of an option count together. Giving it more often is
+    /// an [`Error::TooManyOccurrences`].
+    fn max_occurrences(&self) -> Option<usize> {
+        None
+    }
+
+    fn help(bin_name: &str) -> String {
+        Self::help_with_config(bin_name, &HelpConfig::default())
+    }
 
-    fn help(bin_name: &str) -> String;
+    /// Same as [`Arguments::help`], with the name and help file from `config`.
+    fn help_with_config(bin_name: &str, config: &HelpConfig) -> String;
 
-    fn version() -> String;
+    /// The condensed help, printed for the `short_help` flags of the
+    /// `arguments` attribute.
+    ///
+    /// It only contains the usage and the flags of the options, without their
+    /// descriptions and the text after the options.
+    fn short_help(bin_name: &str) -> String {
+        Self::short_help_with_config(bin_name, &HelpConfig::default())
+    }
+
+    /// Same as [`Arguments::short_help`], with the name and help file from
+    /// `config`.
+    fn short_help_with_config(bin_name: &str, config: &HelpConfig) -> String;
+
+    /// The text shown by `--version`, see [`Version`] for its format.
+    fn version() -> String {
+        Self::version_with_config(&HelpConfig::default())
+    }
+
+    /// Same as [`Arguments::version`], with the name from `config`.
+    fn version_with_config(config: &HelpConfig) -> String {
+        Self::version_info_with_config(config).to_string()
+    }
+
+    /// The information shown by `--version`, to render it in another way,
+    /// like with [`Version::to_json`].
+    fn version_info() -> Version {
+        Self::version_info_with_config(&HelpConfig::default())
+    }
+
+    /// Same as [`Arguments::version_info`], with the name from `config`.
+    fn version_info_with_config(config: &HelpConfig) -> Version;
+
+    /// Description of the arguments used to generate completions.
+    fn complete() -> complete::Command<'static> {
+        Self::complete_with_config(&HelpConfig::default())
+    }
+
+    /// Same as [`Arguments::complete`], with the name and help file from
+    /// `config`.
+    fn complete_with_config(config: &HelpConfig) -> complete::Command<'static>;
+
+    /// The options and positional arguments, in the order of the variants,
+    /// followed by the help and version options
+    ///
+    /// The options of flattened enums are not included, they are listed by
+    /// the `options` of those enums.
+    fn options() -> &'static [OptionMeta];
+
+    /// Parse the short option `short`, one of [`Arguments::SHORT_OPTIONS`],
+    /// for an enum that flattens this one. The flag has been written to
+    /// `flag` already.
+    fn flattened_short(
+        _short: char,
+        _parser: &mut lexopt::Parser,
+        _flag: &mut String,
+    ) -> Result<Self, Error> {
+        unreachable!("only the short options are flattened")
+    }
+
+    /// Parse the long option `long`, one of [`Arguments::LONG_OPTIONS`],
+    /// for an enum that flattens this one. The flag has been written to
+    /// `flag` already.
+    fn flattened_long(
+        _long: &str,
+        _parser: &mut lexopt::Parser,
+        _flag: &mut String,
+    ) -> Result<Self, Error> {
+        unreachable!("only the long options are flattened")
+    }
+
+    /// Push the options to the help of an enum that flattens this one, with
+    /// only their flags if `short`
+    fn flattened_help(_s: &mut String, _short: bool) {}
+
+    /// The completions of the options for an enum that flattens this one
+    fn flattened_args() -> Vec<complete::Arg<'static>> {
+        Vec::new()
+    }
 }
 
 pub struct ArgumentIter<T: Arguments> {
     parser: lexopt::Parser,
     pub positional_idx: usize,
+    flag: String,
+    seen: Vec<&'static str>,
+    /// How often each option with a maximum was given
+    occurrences: Vec<(&'static str, usize)>,
+    tokens: Rc<RefCell<Tokens>>,
+    tracker: Option<Tracker>,
+    argfiles: Rc<RefCell<ArgFiles>>,
+    /// Whether an operand was given in [`ParseMode::Posix`]
+    operands_only: bool,
+    /// The parse mode from the config, looked up at the first operand
+    parse_mode: Option<ParseMode>,
+    /// The operands held back until it is known whether they are among the
+    /// [last ones](Arguments::TRAILING_OPERANDS)
+    operands: VecDeque<OsString>,
+    /// The next positional argument with a default to consider, see
+    /// [`Arguments::default_operand`]
+    defaults: usize,
+    /// The name of the utility given instead of the first argument, see
+    /// [`ArgumentIter::with_bin_name`]
+    bin_name: Option<String>,
+    /// The layers this parse is one of, see [`Options::apply_layer`]
+    layers: Option<Layers>,
+    /// Whether the last argument is the default of a positional argument
+    from_default: bool,
+    /// Whether an exclusive option was given, see
+    /// [`Arguments::is_exclusive`]
+    exclusive: bool,
+    config: ParseConfig,
     t: PhantomData<T>,
 }
 
+/// Replace `--` by `--\0` if `escape` is set, so that lexopt gives it as
+/// the long option `\0` instead of ending the options. Arguments from the
+/// command line cannot contain a nul byte, so this does not conflict with
+/// other arguments.
+fn escape_double_dash(
+    args: impl Iterator<Item = OsString> + 'static,
+    escape: bool,
+) -> impl Iterator<Item = OsString> + 'static {
+    args.map(move |arg| {
+        if escape && arg == "--" {
+            "--\0".into()
+        } else {
+            arg
+        }
+    })
+}
+
 impl<T: Arguments> ArgumentIter<T> {
     fn from_args<I>(args: I) -> Self
     where
         I: IntoIterator + 'static,
         I::Item: Into<OsString>,
     {
+        Self::new(args, true)
+    }
+
+    fn from_args_without_binname<I>(args: I) -> Self
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::new(args, false)
+    }
+
+    /// Parse `args`, which start with the bin name if `with_binname` is set
+    fn new<I>(args: I, with_binname: bool) -> Self
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        let argfiles = Rc::default();
+        let args = splice(args, Rc::clone(&argfiles));
+        // A claimed `--` is recorded as an option, otherwise as the end of
+        // the options, even if the parser handles it.
+        let args = escape_double_dash(args, T::CLAIMS_DOUBLE_DASH);
+        let (args, tokens) = record_tokens(args);
+        let args = escape_double_dash(args, T::HANDLES_DOUBLE_DASH && !T::CLAIMS_DOUBLE_DASH);
+        let parser = if with_binname {
+            lexopt::Parser::from_iter(args)
+        } else {
+            lexopt::Parser::from_args(args)
+        };
         Self {
-            parser: lexopt::Parser::from_iter(args),
+            parser,
             positional_idx: 0,
+            flag: String::new(),
+            seen: Vec::new(),
+            occurrences: Vec::new(),
+            tokens,
+            tracker: None,
+            argfiles,
+            operands_only: false,
+            parse_mode: None,
+            operands: VecDeque::new(),
+            defaults: 0,
+            bin_name: None,
+            layers: None,
+            from_default: false,
+            exclusive: false,
+            config: ParseConfig::default(),
             t: PhantomData,
         }
     }
 
+    /// Start counting positional arguments from `idx` instead of 0.
+    ///
+    /// Use this when some of the positional arguments have already been
+    /// consumed before the rest of the arguments are handed to this parser.
+    pub fn with_initial_positional_idx(mut self, idx: usize) -> Self {
+        self.positional_idx = idx;
+        self
+    }
+
+    /// Use `name` as the name of the utility instead of the first argument,
+    /// in the help and in the error messages.
+    pub fn with_bin_name(mut self, name: &str) -> Self {
+        self.bin_name = Some(name.to_string());
+        self
+    }
+
+    /// Parse the arguments as the next of `layers`, continuing with the
+    /// positional arguments and options of the layers before it.
+    fn in_layers(mut self, layers: &Layers) -> Self {
+        let state = layers.0.borrow();
+        self.positional_idx = state.positional_idx;
+        self.seen.clone_from(&state.seen);
+        self.exclusive = state.exclusive;
+        drop(state);
+        self.layers = Some(layers.clone());
+        self
+    }
+
+    /// Record which tokens are consumed by each argument.
+    ///
+    /// The recorded information is available from [`ArgumentIter::metadata`].
+    pub fn with_metadata(mut self) -> Self {
+        self.tokens.borrow_mut().keep();
+        self.tracker = Some(Tracker::default());
+        self
+    }
+
+    /// Use the name and help file from `config` for the help and version
+    /// output.
+    pub fn with_help_config(mut self, config: HelpConfig) -> Self {
+        self.config.help = config;
+        self
+    }
+
+    /// Use `config` for everything that is not determined by the arguments.
+    pub fn with_config(mut self, config: ParseConfig) -> Self {
+        self.config = config;
+        self.parse_mode = None;
+        self
+    }
+
+    /// The configuration of this parse
+    pub fn config(&self) -> &ParseConfig {
+        &self.config
+    }
+
+    /// The metadata recorded so far, if enabled with
+    /// [`ArgumentIter::with_metadata`].
+    pub fn metadata(&self) -> Option<&ParseMetadata> {
+        self.tracker.as_ref().map(|t| &t.metadata)
+    }
+
     pub fn next_arg(&mut self) -> Result<Option<Argument<T>>, Error> {
-        T::next_arg(&mut self.parser, &mut self.positional_idx)
+        self.from_default = false;
+        warning::report_pending(self.bin_name(), &self.config);
+        // Nothing is parsed after an exclusive option, not even the defaults,
+        // but the operands before it are, as the last ones
+        if self.exclusive {
+            if let Some(value) = self.operands.pop_front() {
+                let arg = T::from_operand(value, true, &mut self.flag)?;
+                return Ok(Some(Argument::Custom(arg)));
+            }
+            if let Some(layers) = &self.layers {
+                layers.0.borrow_mut().exclusive = true;
+            }
+            return Ok(None);
+        }
+        // The last operands are only known at the end, so the operands are
+        // held back until more than those have been read.
+        loop {
+            match self.read_arg()? {
+                Some(Argument::Operand(value)) => {
+                    self.operands.push_back(value);
+                    if self.operands.len() > T::TRAILING_OPERANDS {
+                        let value = self.operands.pop_front().unwrap();
+                        let arg = T::from_operand(value, false, &mut self.flag)?;
+                        return Ok(Some(Argument::Custom(arg)));
+                    }
+                }
+                None => {
+                    if let Some(value) = self.operands.pop_front() {
+                        let arg = T::from_operand(value, true, &mut self.flag)?;
+                        return Ok(Some(Argument::Custom(arg)));
+                    }
+                    // The defaults are only applied after the last layer
+                    if let Some(layers) = &self.layers {
+                        let mut state = layers.0.borrow_mut();
+                        state.positional_idx = self.positional_idx;
+                        state.seen.clone_from(&self.seen);
+                        return Ok(None);
+                    }
+                    let arg = T::default_operand(
+                        self.positional_idx,
+                        &mut self.defaults,
+                        &mut self.flag,
+                    )?;
+                    self.from_default = arg.is_some();
+                    return Ok(arg.map(Argument::Custom));
+                }
+                arg => return Ok(arg),
+            }
+        }
+    }
+
+    /// Read the next argument from the parser, reading argument files and
+    /// recording the metadata.
+    fn read_arg(&mut self) -> Result<Option<Argument<T>>, Error> {
+        let mut before = self.tokens.borrow().len();
+        let positional_idx = self.positional_idx;
+        let mut arg = T::next_arg(
+            &mut self.parser,
+            &mut self.positional_idx,
+            &mut self.flag,
+            &mut self.operands_only,
+            self.bin_name.as_deref(),
+            &self.config,
+        )?;
+        while let Some(Argument::ArgFile(file)) = &arg {
+            self.argfiles.borrow_mut().read(file)?;
+            before = self.tokens.borrow().len();
+            arg = T::next_arg(
+                &mut self.parser,
+                &mut self.positional_idx,
+                &mut self.flag,
+                &mut self.operands_only,
+                self.bin_name.as_deref(),
+                &self.config,
+            )?;
+        }
+        if self.positional_idx > positional_idx && !self.operands_only {
+            let config = &self.config;
+            let parse_mode = *self
+                .parse_mode
+                .get_or_insert_with(|| config.parse_mode(T::PARSE_MODE));
+            self.operands_only = parse_mode == ParseMode::Posix;
+        }
+        if let (Some(tracker), Some(arg)) = (&mut self.tracker, &arg) {
+            let recorded = matches!(arg, Argument::Custom(arg) if arg.is_recorded());
+            tracker.record(
+                self.tokens.borrow().kept(),
+                before,
+                T::short_takes_value,
+                recorded,
+            );
+        }
+        if let Some(Argument::Custom(arg)) = &arg {
+            if let Some(name) = arg.option_name() {
+                if !self.seen.contains(&name) {
+                    self.seen.push(name);
+                }
+                if let Some(max) = arg.max_occurrences() {
+                    self.count_occurrence(name, max)?;
+                }
+            }
+            self.exclusive |= arg.is_exclusive();
+            if let Some(message) = arg.deprecation() {
+                let warning = Warning::Deprecated {
+                    option: arg.option_name().unwrap_or_default().into(),
+                    message: message.into(),
+                };
+                warning::report(self.bin_name(), &self.config, warning);
+            }
+        }
+        Ok(arg)
+    }
+
+    /// Count an occurrence of the option `name`, which can be given at most
+    /// `max` times.
+    fn count_occurrence(&mut self, name: &'static str, max: usize) -> Result<(), Error> {
+        let count = match self.occurrences.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => count,
+            None => {
+                self.occurrences.push((name, 0));
+                &mut self.occurrences.last_mut().unwrap().1
+            }
+        };
+        *count += 1;
+        if *count > max {
+            return Err(Error::TooManyOccurrences {
+                option: name.into(),
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Turn the parser into an iterator over the arguments as they are
+    /// applied to the settings.
+    ///
+    /// This is the canonical stream of arguments, the same one that the
+    /// derived [`Options::apply_iter`] consumes: every spelling of an option
+    /// is resolved to its variant and, after the last argument, missing
+    /// positional arguments are reported as an error. The iterator ends
+    /// after the first error.
+    pub fn expanded(self) -> Expanded<T> {
+        Expanded {
+            iter: self,
+            done: false,
+        }
+    }
+
+    /// The arguments that have not been parsed yet, as they were given.
+    ///
+    /// This lets a utility parse its own options and pass the rest on as is,
+    /// like `env` does with the command. Operands that were read but not
+    /// returned yet, see [`Argument::Operand`], come first. A `--` that has
+    /// not been read is included, but one that ended the options already is
+    /// not.
+    ///
+    /// An argument that caused an error has been read, so it is not part of
+    /// the remaining arguments. This fails in the middle of a cluster of
+    /// short options, like after the `-i` of `-iu`, because the rest of the
+    /// cluster is not an argument by itself.
+    pub fn remaining(mut self) -> Result<Vec<OsString>, Error> {
+        let raw_args = self.parser.raw_args()?.map(|arg| {
+            // Undo the escaping of `--` for the parser.
+            if arg == "--\0" {
+                "--".into()
+            } else {
+                arg
+            }
+        });
+        Ok(self.operands.drain(..).chain(raw_args).collect())
+    }
+
+    /// The [names](Arguments::option_name) of the options given so far, in
+    /// order of their first occurrence.
+    pub fn seen_options(&self) -> &[&'static str] {
+        &self.seen
+    }
+
+    /// The spelling of the flag that produced the last argument, like `-c`
+    /// or `--bytes`, or the metavar of a positional argument.
+    pub fn flag(&self) -> &str {
+        &self.flag
+    }
+
+    /// Whether the last argument is the default of a positional argument
+    /// that got no values, instead of an argument that was given.
+    pub fn is_default(&self) -> bool {
+        self.from_default
+    }
+
+    /// The name of the utility, from [`ArgumentIter::with_bin_name`], the
+    /// arguments or the help config, falling back to [`Arguments::NAME`].
+    ///
+    /// Empty arguments are parsed as if they only contained this name.
+    pub fn bin_name(&self) -> &str {
+        self.bin_name
+            .as_deref()
+            .or(self.parser.bin_name())
+            .unwrap_or(self.config.help.name_or(T::NAME))
     }
 
     pub fn help(&self) -> String {
-        T::help(self.parser.bin_name().unwrap())
+        T::help_with_config(self.bin_name(), &self.config.help)
+    }
+
+    pub fn short_help(&self) -> String {
+        T::short_help_with_config(self.bin_name(), &self.config.help)
     }
 
     pub fn version(&self) -> String {
-        T::version()
+        T::version_with_config(&self.config.help)
+    }
+
+    /// The information shown by `--version`, see [`Arguments::version_info`]
+    pub fn version_info(&self) -> Version {
+        T::version_info_with_config(&self.config.help)
+    }
+}
+
+/// The arguments of a parse as applied to the settings, see
+/// [`ArgumentIter::expanded`]
+pub struct Expanded<T: Arguments> {
+    iter: ArgumentIter<T>,
+    done: bool,
+}
+
+impl<T: Arguments> Expanded<T> {
+    /// The parser, for example for the spelling of the last flag or the
+    /// help text
+    pub fn inner(&self) -> &ArgumentIter<T> {
+        &self.iter
+    }
+}
+
+impl<T: Arguments> Iterator for Expanded<T> {
+    type Item = Result<Argument<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.iter.next_arg() {
+            Ok(Some(arg)) => return Some(Ok(arg)),
+            // Missing arguments might still be given by a later layer
+            Ok(None) if self.iter.layers.is_some() => Ok(()),
+            Ok(None) if self.iter.exclusive => Ok(()),
+            Ok(None) => T::check_missing(self.iter.positional_idx, self.iter.seen_options()),
+            Err(err) => Err(err),
+        };
+        self.done = true;
+        result.err().map(Err)
+    }
+}
+
+/// The positional arguments and options given by the layers of arguments
+/// applied so far, see [`Options::apply_layer`]
+#[derive(Clone, Default)]
+pub struct Layers(Rc<RefCell<LayerState>>);
+
+#[derive(Default)]
+struct LayerState {
+    positional_idx: usize,
+    seen: Vec<&'static str>,
+    /// Whether a layer gave an exclusive option, which ends the parse
+    exclusive: bool,
+}
+
+/// The arguments in the order they are parsed, see
+/// [`ArgumentIter::next_arg`]
+///
+/// Unlike [`ArgumentIter::expanded`], this does not check for missing
+/// positional arguments and does not stop at an error: the argument that
+/// caused it has been read, so parsing continues after it.
+impl<T: Arguments> Iterator for ArgumentIter<T> {
+    type Item = Result<Argument<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_arg().transpose()
+    }
+}
+
+/// The long option that `input` is an abbreviation of, without the leading
+/// `--`
+///
+/// An exact match always wins, otherwise `input` must be a prefix of exactly
+/// one of the `options`. The candidates of an ambiguous abbreviation are listed
+/// in the order of `options`. The derived [`Arguments`] resolve every long
+/// option like this, with [`resolve_long_among`].
+///
+/// ```
+/// use uutils_args::{resolve_long, Error};
+///
+/// let options = ["reverse", "recursive", "re"];
+/// assert_eq!(resolve_long("rev", &options).unwrap(), "reverse");
+/// assert_eq!(resolve_long("re", &options).unwrap(), "re");
+/// assert_eq!(resolve_long("rec", &options).unwrap(), "recursive");
+/// assert!(matches!(resolve_long("x", &options), Err(Error::UnexpectedOption(_))));
+/// ```
+pub fn resolve_long<'a>(input: &str, options: &[&'a str]) -> Result<&'a str, Error> {
+    resolve_long_among(input, &[options])
+}
+
+/// The long option that `input` is an abbreviation of, among the options
+/// of all `groups`
+///
+/// This is [`resolve_long`] for the options of an enum together with those
+/// of the enums it flattens, without collecting them first.
+///
+/// ```
+/// use uutils_args::resolve_long_among;
+///
+/// let groups: &[&[&str]] = &[&["verbose"], &["version", "debug"]];
+/// assert_eq!(resolve_long_among("verb", groups).unwrap(), "verbose");
+/// assert_eq!(resolve_long_among("d", groups).unwrap(), "debug");
+/// assert!(resolve_long_among("ver", groups).is_err());
+/// ```
+pub fn resolve_long_among<'a>(input: &str, groups: &[&[&'a str]]) -> Result<&'a str, Error> {
+    let options = || groups.iter().flat_map(|group| group.iter().copied());
+    if let Some(exact) = options().find(|opt| *opt == input) {
+        return Ok(exact);
     }
+    let candidates = || options().filter(|opt| opt.starts_with(input));
+    let mut matches = candidates();
+    match (matches.next(), matches.next()) {
+        (Some(opt), None) => Ok(opt),
+        (None, _) => Err(Error::UnexpectedOption(format!("--{input}"))),
+        (Some(_), Some(_)) => Err(Error::AmbiguousOption {
+            option: input.into(),
+            candidates: candidates().map(|opt| opt.to_string()).collect(),
+        }),
+    }
+}
+
+/// Whether `options` contains the long option `long`, for the checks of
+/// flattened enums at compile time
+#[doc(hidden)]
+pub const fn contains_long(options: &[&str], long: &str) -> bool {
+    let mut i = 0;
+    while i < options.len() {
+        let (a, b) = (options[i].as_bytes(), long.as_bytes());
+        if a.len() == b.len() {
+            let mut j = 0;
+            while j < a.len() && a[j] == b[j] {
+                j += 1;
+            }
+            if j == a.len() {
+                return true;
+            }
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether `options` contains the short option `short`, for the checks of
+/// flattened enums at compile time
+#[doc(hidden)]
+pub const fn contains_short(options: &[char], short: char) -> bool {
+    let mut i = 0;
+    while i < options.len() {
+        if options[i] == short {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Whether two flattened enums have a long or short option in common
+#[doc(hidden)]
+pub const fn overlaps(
+    long: &[&str],
+    other_long: &[&str],
+    short: &[char],
+    other_short: &[char],
+) -> bool {
+    let mut i = 0;
+    while i < long.len() {
+        if contains_long(other_long, long[i]) {
+            return true;
+        }
+        i += 1;
+    }
+    let mut i = 0;
+    while i < short.len() {
+        if contains_short(other_short, short[i]) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// The subcommand that `input` is an abbreviation of
+///
+/// Subcommands are abbreviated like long options, see [`resolve_long`]. The
+/// derived [`Arguments`] call this for the first operand if the enum has
+/// `#[subcommand]` variants.
+///
+/// ```
+/// use uutils_args::{resolve_subcommand, Error};
+///
+/// let commands = ["add", "remove", "rename"];
+/// assert_eq!(resolve_subcommand("a", &commands).unwrap(), "add");
+/// assert_eq!(resolve_subcommand("rem", &commands).unwrap(), "remove");
+/// assert!(matches!(
+///     resolve_subcommand("re", &commands),
+///     Err(Error::AmbiguousSubcommand { .. })
+/// ));
+/// assert!(matches!(
+///     resolve_subcommand("x", &commands),
+///     Err(Error::UnknownSubcommand { .. })
+/// ));
+/// ```
+pub fn resolve_subcommand<'a>(input: &str, commands: &[&'a str]) -> Result<&'a str, Error> {
+    resolve_long(input, commands).map_err(|err| match err {
+        Error::AmbiguousOption { candidates, .. } => Error::AmbiguousSubcommand {
+            name: input.into(),
+            candidates,
+        },
+        _ => Error::UnknownSubcommand {
+            name: input.into(),
+            expected: commands.iter().map(|c| c.to_string()).collect(),
+        },
+    })
+}
+
+/// Split an operand like `bs=1M` into the keyword and the value
+///
+/// The keyword is everything before the first `=`, so the value can contain
+/// more of them. Only the value can be invalid unicode.
+///
+/// ```
+/// # use std::ffi::{OsStr, OsString};
+/// # use uutils_args::split_keyword;
+/// assert_eq!(
+///     split_keyword(OsStr::new("of=a=b")),
+///     Some(("of", OsString::from("a=b")))
+/// );
+/// assert_eq!(split_keyword(OsStr::new("input")), None);
+/// ```
+pub fn split_keyword(operand: &OsStr) -> Option<(&str, OsString)> {
+    let bytes = operand.as_encoded_bytes();
+    let idx = bytes.iter().position(|b| *b == b'=')?;
+    let keyword = std::str::from_utf8(&bytes[..idx]).ok()?;
+    // SAFETY: The bytes are split right after an ASCII character.
+    let value = unsafe { OsStr::from_encoded_bytes_unchecked(&bytes[idx + 1..]) };
+    Some((keyword, value.to_os_string()))
+}
+
+/// Split the value of an option at the ASCII `delimiter` and parse each
+/// item, as for `#[option("--fields=LIST", delimiter = ',')]`
+///
+/// Empty items, like the one after a trailing delimiter, are an error unless
+/// `skip_empty` is set. The first item that cannot be parsed is returned as
+/// [`Error::InvalidItem`].
+///
+/// ```
+/// # use uutils_args::split_value;
+/// let fields: Vec<u32> = split_value("--fields", "1,3".into(), b',', false).unwrap();
+/// assert_eq!(fields, [1, 3]);
+///
+/// let err = split_value::<u32>("--fields", "1,x".into(), b',', false).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid item 2 'x' for '--fields': invalid digit found in string"
+/// );
+/// ```
+pub fn split_value<T: FromValue>(
+    option: &str,
+    value: OsString,
+    delimiter: u8,
+    skip_empty: bool,
+) -> Result<Vec<T>, Error> {
+    debug_assert!(delimiter.is_ascii());
+    let bytes = value.as_encoded_bytes();
+    let mut items = Vec::new();
+    for (index, item) in bytes.split(|b| *b == delimiter).enumerate() {
+        let invalid = |error| Error::InvalidItem {
+            option: option.into(),
+            index,
+            error: Box::new(error),
+        };
+        if item.is_empty() {
+            if skip_empty {
+                continue;
+            }
+            return Err(invalid(Error::ParsingFailed {
+                option: option.into(),
+                value: OsString::new(),
+                error: "the item is empty".into(),
+            }));
+        }
+        // SAFETY: The bytes are split at an ASCII character.
+        let item = unsafe { OsStr::from_encoded_bytes_unchecked(item) };
+        items.push(T::from_value(option, item.to_os_string()).map_err(invalid)?);
+    }
+    Ok(items)
 }
 
 pub trait Options: Sized + Default {
     type Arg: Arguments;
 
+    /// The fields with `#[field(env = ...)]` and their environment variables
+    const ENV_FIELDS: &'static [(&'static str, &'static str)] = &[];
+
+    /// Parse the arguments, exiting if they are invalid.
+    ///
+    /// If help or version information is requested, it is printed and the
+    /// process exits with code 0. Printing does not panic if stdout is closed
+    /// early, see [`write_info`]. Errors are printed like described for
+    /// [`Options::parse_iter`].
     fn parse<I>(args: I) -> Self
     where
         I: IntoIterator + 'static,
         I::Item: Into<OsString>,
     {
-        match Self::try_parse(args) {
+        Self::parse_iter(<Self as Options>::Arg::parse(args))
+    }
+
+    /// Parse the arguments, returning an error if they are invalid.
+    ///
+    /// This never exits the process, which makes it the function for tests
+    /// and for embedding a utility. A request for help or version
+    /// information is returned as [`Error::DisplayHelp`] or
+    /// [`Error::DisplayVersion`] with the text to print.
+    fn try_parse<I>(args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::try_parse_iter(<Self as Options>::Arg::parse(args))
+    }
+
+    /// Same as [`Options::parse`], but with the name and help file from
+    /// `config` instead of from the `arguments` attribute.
+    fn parse_with_help<I>(args: I, config: HelpConfig) -> Self
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::parse_iter(<Self as Options>::Arg::parse(args).with_help_config(config))
+    }
+
+    /// Same as [`Options::try_parse`], but with the name and help file from
+    /// `config` instead of from the `arguments` attribute.
+    fn try_parse_with_help<I>(args: I, config: HelpConfig) -> Result<Self, Error>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::try_parse_iter(<Self as Options>::Arg::parse(args).with_help_config(config))
+    }
+
+    /// Same as [`Options::parse`], but with everything that is not
+    /// determined by the arguments taken from `config`.
+    ///
+    /// Unlike process-global state, the configuration only applies to this
+    /// parse, so utilities can be parsed concurrently with different
+    /// configurations.
+    fn parse_with_config<I>(args: I, config: ParseConfig) -> Self
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::parse_iter(<Self as Options>::Arg::parse(args).with_config(config))
+    }
+
+    /// Same as [`Options::try_parse`], but with everything that is not
+    /// determined by the arguments taken from `config`.
+    fn try_parse_with_config<I>(args: I, config: ParseConfig) -> Result<Self, Error>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::try_parse_iter(<Self as Options>::Arg::parse(args).with_config(config))
+    }
+
+    /// Same as [`Options::parse`], but the arguments do not start with the bin name.
+    fn parse_without_binname<I>(args: I) -> Self
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::parse_iter(<Self as Options>::Arg::parse_without_binname(args))
+    }
+
+    /// Same as [`Options::try_parse`], but the arguments do not start with the bin name.
+    fn try_parse_without_binname<I>(args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::try_parse_iter(<Self as Options>::Arg::parse_without_binname(args))
+    }
+
+    /// Same as [`Options::parse`], but the arguments do not start with the
+    /// bin name and the utility is called `name`, see
+    /// [`Arguments::parse_with_name`].
+    fn parse_with_name<I>(name: &str, args: I) -> Self
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::parse_iter(<Self as Options>::Arg::parse_with_name(name, args))
+    }
+
+    /// Same as [`Options::try_parse`], but the arguments do not start with
+    /// the bin name and the utility is called `name`, see
+    /// [`Arguments::parse_with_name`].
+    fn try_parse_with_name<I>(name: &str, args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::try_parse_iter(<Self as Options>::Arg::parse_with_name(name, args))
+    }
+
+    /// Same as [`Options::parse`], but from an existing [`ArgumentIter`].
+    ///
+    /// An invalid argument is printed to stderr prefixed with the bin name,
+    /// like `tail: invalid option -- 'x'`, followed by a hint to the help
+    /// after an extra operand. The process then exits with the `exit_code`
+    /// of the `arguments` attribute.
+    fn parse_iter(iter: ArgumentIter<Self::Arg>) -> Self {
+        let bin_name = iter.bin_name().to_string();
+        let config = *iter.config();
+        match Self::try_parse_iter(iter) {
             Ok(v) => v,
+            Err(Error::DisplayHelp(text) | Error::DisplayVersion(text)) => {
+                std::process::exit(write_info(&mut std::io::stdout(), &text));
+            }
             Err(err) => {
-                eprintln!("{err}");
+                eprintln!("{bin_name}: {}", err.display_with(&config));
+                // Like the GNU utilities, point to the help after an extra
+                // operand.
+                if let (
+                    Error::ExtraOperand(_)
+                    | Error::UnexpectedArgument(_)
+                    | Error::UnrecognizedOperand(_),
+                    Some(flag),
+                ) = (&err, <Self as Options>::Arg::HELP_FLAG)
+                {
+                    eprintln!("Try '{bin_name} {flag}' for more information.");
+                }
                 std::process::exit(<Self as Options>::Arg::EXIT_CODE);
             }
         }
     }
 
-    fn try_parse<I>(args: I) -> Result<Self, Error>
+    /// Parse the arguments from an existing [`ArgumentIter`].
+    ///
+    /// The warnings of the parse are reported to [`ParseConfig::warn`] in
+    /// the order they occurred, before the result is returned.
+    fn try_parse_iter(iter: ArgumentIter<Self::Arg>) -> Result<Self, Error> {
+        let bin_name = iter.bin_name().to_string();
+        let config = *iter.config();
+        let (result, warnings) = Self::try_parse_iter_with_warnings(iter);
+        for warning in warnings {
+            report(&bin_name, &config, warning);
+        }
+        result
+    }
+
+    /// Same as [`Options::try_parse`], but return the warnings of the parse
+    /// instead of reporting them to [`ParseConfig::warn`].
+    ///
+    /// The warnings are in the order they occurred and are also returned
+    /// if the arguments are invalid.
+    fn try_parse_with_warnings<I>(args: I) -> (Result<Self, Error>, Vec<Warning>)
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        Self::try_parse_iter_with_warnings(<Self as Options>::Arg::parse(args))
+    }
+
+    /// Same as [`Options::try_parse_with_warnings`], but from an existing
+    /// [`ArgumentIter`].
+    fn try_parse_iter_with_warnings(
+        iter: ArgumentIter<Self::Arg>,
+    ) -> (Result<Self, Error>, Vec<Warning>) {
+        warning::collect(|| {
+            let mut _self = Self::initial_with_config(iter.bin_name(), iter.config())?;
+            _self.apply_iter(iter)?;
+            _self.validate()?;
+            Ok(_self)
+        })
+    }
+
+    /// Same as [`Options::try_parse`], but also return which fields were set
+    /// by the arguments, for [`Options::merge_from`].
+    fn try_parse_with_mask<I>(args: I) -> Result<(Self, SetMask), Error>
+    where
+        I: IntoIterator + 'static,
+        I::Item: Into<OsString>,
+    {
+        let iter = <Self as Options>::Arg::parse(args);
+        let bin_name = iter.bin_name().to_string();
+        let config = *iter.config();
+        let (result, warnings) = warning::collect(|| {
+            let mut mask = SetMask::default();
+            let mut _self = Self::initial_with_config(iter.bin_name(), iter.config())?;
+            _self.apply_iter_with_mask(iter, &mut mask)?;
+            _self.validate()?;
+            Ok((_self, mask))
+        });
+        for warning in warnings {
+            report(&bin_name, &config, warning);
+        }
+        result
+    }
+
+    /// Same as [`Options::try_parse`], but also return where each field got
+    /// its value: from the arguments, from an environment variable or the
+    /// default.
+    fn try_parse_with_provenance<I>(args: I) -> Result<(Self, Provenance), Error>
     where
         I: IntoIterator + 'static,
         I::Item: Into<OsString>,
     {
-        let mut _self = Self::initial()?;
-        _self.apply_args(args)?;
-        Ok(_self)
+        Self::try_parse_iter_with_provenance(<Self as Options>::Arg::parse(args))
+    }
+
+    /// Same as [`Options::try_parse_with_provenance`], but from an existing
+    /// [`ArgumentIter`].
+    fn try_parse_iter_with_provenance(
+        iter: ArgumentIter<Self::Arg>,
+    ) -> Result<(Self, Provenance), Error> {
+        let bin_name = iter.bin_name().to_string();
+        let config = *iter.config();
+        let (result, warnings) = warning::collect(|| {
+            let mut env = Vec::new();
+            let mut mask = SetMask::default();
+            let mut _self = Self::initial_with_env(iter.bin_name(), iter.config(), &mut env)?;
+            _self.apply_iter_with_mask(iter, &mut mask)?;
+            _self.validate()?;
+            Ok((_self, Provenance::new(mask, env)))
+        });
+        for warning in warnings {
+            report(&bin_name, &config, warning);
+        }
+        result
+    }
+
+    /// The names of the fields that `arg` is applied to
+    fn fields_of(arg: &Self::Arg) -> Vec<&'static str>;
+
+    /// Take the fields in `mask` from `other`, keeping the other fields.
+    ///
+    /// The values of `collect` fields are appended to the values in `self`,
+    /// the other fields are replaced. With the mask from
+    /// [`Options::try_parse_with_mask`], this lets the arguments override
+    /// settings from another source, like a configuration file, only where
+    /// they were given.
+    fn merge_from(&mut self, other: Self, mask: &SetMask);
+
+    /// Check the settings after all arguments have been applied.
+    ///
+    /// This is for checks that involve multiple arguments. The derive
+    /// implements it with the function given in `#[validate(...)]`.
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Check a single argument before it is applied to the fields.
+    ///
+    /// This is for arguments that can be rejected depending on the current
+    /// settings, like an option that may only be given once. An error is
+    /// reported with the name of the option attached, see
+    /// [`Error::with_option`]. The derive implements it with the function
+    /// given in `#[try_apply(...)]`.
+    fn try_apply(&mut self, _arg: &Self::Arg) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Same as [`Options::try_apply`], but with the spelling of the flag
+    /// that produced the argument, like `-c` or `--bytes`.
+    ///
+    /// Positional arguments are given with their metavar. The derive
+    /// implements it with the function given in `#[try_apply_named(...)]`
+    /// and falls back to [`Options::try_apply`] otherwise.
+    fn try_apply_named(&mut self, arg: &Self::Arg, _flag: &str) -> Result<(), Error> {
+        self.try_apply(arg)
+    }
+
+    fn initial() -> Result<Self, Error> {
+        Self::initial_with_name(<Self as Options>::Arg::NAME)
+    }
+
+    /// The settings before any arguments are applied, for the utility called
+    /// `bin_name`.
+    ///
+    /// Fields with `#[field(default_by_name = ...)]` get their default from
+    /// the bin name.
+    fn initial_with_name(bin_name: &str) -> Result<Self, Error> {
+        Self::initial_with_config(bin_name, &ParseConfig::default())
+    }
+
+    /// Same as [`Options::initial_with_name`], but fields with
+    /// `#[field(env = ...)]` read the environment with [`ParseConfig::env`].
+    ///
+    /// An invalid value in the environment is an error, unless the field
+    /// also has `ignore_invalid_env`. Then the default is used and a
+    /// [`Warning::InvalidEnv`] is reported.
+    fn initial_with_config(bin_name: &str, config: &ParseConfig) -> Result<Self, Error> {
+        Self::initial_with_env(bin_name, config, &mut Vec::new())
     }
 
-    fn initial() -> Result<Self, Error>;
+    /// Same as [`Options::initial_with_config`], but also record the fields
+    /// that got their value from the environment in `env`, with the variable.
+    ///
+    /// A field with an ignored invalid value has its default, so it is not
+    /// recorded.
+    fn initial_with_env(
+        bin_name: &str,
+        config: &ParseConfig,
+        env: &mut Vec<(&'static str, &'static str)>,
+    ) -> Result<Self, Error>;
 
+    /// Apply the arguments to the settings, including the defaults of the
+    /// positional arguments and the check for missing ones.
+    ///
+    /// To apply arguments from several sources, see
+    /// [`Options::apply_layer`].
     fn apply_args<I>(&mut self, args: I) -> Result<(), Error>
     where
         I: IntoIterator + 'static,
-        I::Item: Into<OsString>;
+        I::Item: Into<OsString>,
+    {
+        self.apply_iter(<Self as Options>::Arg::parse(args))
+    }
+
+    /// Apply the arguments to the settings as the next of `layers`, like
+    /// the arguments from a configuration file followed by those from the
+    /// command line.
+    ///
+    /// A later layer overrides the options of the layers before it and its
+    /// operands continue with the positional arguments that those did not
+    /// fill. The defaults of the positional arguments and the check for
+    /// missing ones are left to [`Options::finish_layers`], which is called
+    /// once after the last layer. Operands taken from the end, see
+    /// `from_end` of the `positional` attribute, are assigned per layer.
+    ///
+    /// ```
+    /// # use uutils_args::{Arguments, Layers, Options};
+    /// # #[derive(Clone, Arguments)]
+    /// # enum Arg {
+    /// #     #[option("-w N")]
+    /// #     Width(u16),
+    /// #     #[positional]
+    /// #     File(String),
+    /// # }
+    /// # #[derive(Default, Options)]
+    /// # #[arg_type(Arg)]
+    /// # struct Settings {
+    /// #     #[set(Arg::Width)]
+    /// #     width: u16,
+    /// #     #[set(Arg::File)]
+    /// #     file: String,
+    /// # }
+    /// let layers = Layers::default();
+    /// let mut settings = Settings::initial()?;
+    /// settings.apply_layer(Arg::parse_without_binname(["-w", "80"]), &layers)?;
+    /// settings.apply_layer(Arg::parse(["prog", "-w", "40", "file"]), &layers)?;
+    /// settings.finish_layers(layers)?;
+    /// assert_eq!(settings.width, 40);
+    /// # Ok::<(), uutils_args::Error>(())
+    /// ```
+    fn apply_layer(&mut self, iter: ArgumentIter<Self::Arg>, layers: &Layers) -> Result<(), Error> {
+        self.apply_iter(iter.in_layers(layers))
+    }
+
+    /// Apply the defaults of the positional arguments that no layer gave,
+    /// check that none are missing and [validate](Options::validate) the
+    /// settings, after the last call to [`Options::apply_layer`].
+    fn finish_layers(&mut self, layers: Layers) -> Result<(), Error> {
+        let LayerState {
+            positional_idx,
+            seen,
+            exclusive,
+        } = layers.0.take();
+        if exclusive {
+            return self.validate();
+        }
+        let mut iter = <Self as Options>::Arg::parse_without_binname(Vec::<OsString>::new())
+            .with_initial_positional_idx(positional_idx);
+        iter.seen = seen;
+        self.apply_iter(iter)?;
+        self.validate()
+    }
+
+    fn apply_iter(&mut self, iter: ArgumentIter<Self::Arg>) -> Result<(), Error> {
+        self.apply_iter_with_optional_mask(iter, None)
+    }
+
+    /// Same as [`Options::apply_iter`], but also add the fields that are set
+    /// to `mask`, with the flag that set them.
+    ///
+    /// The defaults of positional arguments are applied without adding
+    /// their fields, because they were not given.
+    fn apply_iter_with_mask(
+        &mut self,
+        iter: ArgumentIter<Self::Arg>,
+        mask: &mut SetMask,
+    ) -> Result<(), Error> {
+        self.apply_iter_with_optional_mask(iter, Some(mask))
+    }
+
+    /// Apply the arguments, adding the fields that are set to `mask` only
+    /// if there is one, so that a parse without a mask does not build it.
+    #[doc(hidden)]
+    fn apply_iter_with_optional_mask(
+        &mut self,
+        iter: ArgumentIter<Self::Arg>,
+        mask: Option<&mut SetMask>,
+    ) -> Result<(), Error>;
 }
 
+/// Parse a value from the [`OsString`] that lexopt returns.
+///
+/// The value is owned, so the implementations for [`OsString`] and
+/// [`PathBuf`] take it as is, without copying or checking that it is valid
+/// UTF-8. Prefer those types for operands that are passed on to the system,
+/// like the files of `rm`, of which there can be very many.
 pub trait FromValue: Sized {
     fn from_value(option: &str, value: OsString) -> Result<Self, Error>;
+
+    /// The kind of value this type expects, used for completions.
+    fn value_hint() -> ValueHint {
+        ValueHint::Unknown
+    }
 }
 
 impl FromValue for OsString {
@@ -119,6 +1748,10 @@ impl FromValue for PathBuf {
     fn from_value(_option: &str, value: OsString) -> Result<Self, Error> {
         Ok(PathBuf::from(value))
     }
+
+    fn value_hint() -> ValueHint {
+        ValueHint::AnyPath
+    }
 }
 
 impl FromValue for String {
@@ -130,6 +1763,31 @@ impl FromValue for String {
     }
 }
 
+/// A single character, like the delimiter of `cut -d`, or one of the
+/// escape sequences `\t`, `\n`, `\\` and `\0`
+impl FromValue for char {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let Some(s) = value.to_str() else {
+            return Err(Error::NonUnicodeValue(value));
+        };
+        let c = match s {
+            "\\t" => Some('\t'),
+            "\\n" => Some('\n'),
+            "\\\\" => Some('\\'),
+            "\\0" => Some('\0'),
+            _ => {
+                let mut chars = s.chars();
+                chars.next().filter(|_| chars.next().is_none())
+            }
+        };
+        c.ok_or_else(|| Error::ParsingFailed {
+            option: option.to_string(),
+            value,
+            error: "the delimiter must be a single character".into(),
+        })
+    }
+}
+
 impl<T> FromValue for Option<T>
 where
     T: FromValue,
@@ -137,20 +1795,24 @@ where
     fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
         Ok(Some(T::from_value(option, value)?))
     }
+
+    fn value_hint() -> ValueHint {
+        T::value_hint()
+    }
 }
 
 macro_rules! from_value_int {
     ($t: ty) => {
         impl FromValue for $t {
             fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
-                let value = String::from_value(option, value)?;
-                value
-                    .parse()
-                    .map_err(|e: ParseIntError| Error::ParsingFailed {
-                        value,
-                        option: option.to_string(),
-                        error: e.into(),
-                    })
+                // Invalid unicode is replaced by a character that is not a
+                // digit, so it is rejected by the parse
+                let result = value.to_string_lossy().parse();
+                result.map_err(|e: ParseIntError| Error::ParsingFailed {
+                    value,
+                    option: option.to_string(),
+                    error: e.into(),
+                })
             }
         }
     };
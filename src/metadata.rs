@@ -0,0 +1,389 @@
+use std::{
+    cell::RefCell,
+    ffi::OsString,
+    ops::{Range, RangeInclusive},
+    rc::Rc,
+};
+
+use complete::Flag;
+
+use crate::ValueSource;
+
+/// How an argument was given on the command line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A short option, possibly in a cluster of short options.
+    ///
+    /// The position is the index of the option character within the token,
+    /// so the options in `-lah` have the positions 1, 2 and 3 and all share
+    /// the same token range.
+    Short { position: usize },
+    /// A long option
+    Long,
+    /// A positional argument, including arguments passed on as is
+    Positional,
+}
+
+/// An option or positional argument of an [`Arguments`](crate::Arguments)
+/// enum, see [`Arguments::options`](crate::Arguments::options)
+///
+/// Unlike [`Arguments::complete`](crate::Arguments::complete), this includes
+/// the hidden options and is known without the help file or the types of the
+/// values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OptionMeta {
+    /// The name of the variant, like `Width`, or `Help`, `ShortHelp` and
+    /// `Version` for the builtin options
+    pub name: &'static str,
+    /// The short flags, without the `-`, which take a value if it is
+    /// required or optional
+    pub short: &'static [Flag<'static>],
+    /// The long flags, without the `--`
+    pub long: &'static [Flag<'static>],
+    /// The name of the value, like `COLS` for `--width=COLS`, or the metavar
+    /// of a positional argument, like `FILE`
+    pub metavar: Option<&'static str>,
+    /// The number of values of a positional argument, where an end of
+    /// `usize::MAX` means unbounded, or `None` for an option
+    pub num_args: Option<RangeInclusive<usize>>,
+    /// Whether the option is left out of the help
+    pub hidden: bool,
+    pub help: &'static str,
+}
+
+/// Information about the arguments that were parsed
+///
+/// This is only recorded if it is requested with
+/// [`ArgumentIter::with_metadata`](crate::ArgumentIter::with_metadata).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseMetadata {
+    spans: Vec<(ArgKind, Range<usize>)>,
+    recorded: Vec<OsString>,
+}
+
+impl ParseMetadata {
+    /// The kind of every produced argument together with the half-open range
+    /// of indices of the tokens it consumed, in the order the arguments were
+    /// produced.
+    ///
+    /// The indices include the bin name, if it is part of the arguments. An
+    /// option with a separate value, like `--width 80`, spans two tokens.
+    pub fn spans(&self) -> &[(ArgKind, Range<usize>)] {
+        &self.spans
+    }
+
+    /// The number of operands, i.e. the values of all positional arguments.
+    ///
+    /// This allows checking the number of operands without collecting them,
+    /// for example with a `count_only` positional argument.
+    pub fn operand_count(&self) -> usize {
+        self.spans
+            .iter()
+            .filter(|(kind, _)| *kind == ArgKind::Positional)
+            .map(|(_, range)| range.len())
+            .sum()
+    }
+
+    /// The tokens of the options marked with `record`, as they were typed.
+    ///
+    /// An option with a separate value, like `--signal KILL`, adds both
+    /// tokens and an attached value, like `--signal=KILL`, adds one token.
+    /// An option from a cluster of short options is taken out of the
+    /// cluster, so `-vk 5` adds `-k` and `5` for a recorded `-k`.
+    ///
+    /// Unlike the parsed arguments, this preserves the spelling of the user,
+    /// which is useful for passing the options on to another process.
+    pub fn recorded_args(&self) -> &[OsString] {
+        &self.recorded
+    }
+}
+
+/// The fields of an [`Options`](crate::Options) struct that were set by the
+/// arguments
+///
+/// This is returned by [`Options::try_parse_with_mask`](crate::Options::try_parse_with_mask)
+/// and used by [`Options::merge_from`](crate::Options::merge_from) to let the
+/// arguments override only what they set, for example over settings from a
+/// configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SetMask {
+    fields: Vec<&'static str>,
+    /// The flag that last set each of the fields, empty if unknown
+    flags: Vec<String>,
+}
+
+impl SetMask {
+    /// Whether the field with this name was set
+    pub fn contains(&self, field: &str) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// The names of the fields that were set, in the order they were first
+    /// set
+    pub fn fields(&self) -> &[&'static str] {
+        &self.fields
+    }
+
+    /// Mark the field with this name as set.
+    pub fn insert(&mut self, field: &'static str) {
+        if !self.contains(field) {
+            self.fields.push(field);
+            self.flags.push(String::new());
+        }
+    }
+
+    /// Mark the field with this name as set by `flag`, like `--width` or
+    /// the metavar of a positional argument.
+    pub fn set_by(&mut self, field: &'static str, flag: &str) {
+        self.insert(field);
+        let idx = self.fields.iter().position(|f| *f == field).unwrap();
+        self.flags[idx].clear();
+        self.flags[idx].push_str(flag);
+    }
+
+    /// The flag that last set the field, if it is known
+    pub fn flag(&self, field: &str) -> Option<&str> {
+        let idx = self.fields.iter().position(|f| *f == field)?;
+        Some(self.flags[idx].as_str()).filter(|flag| !flag.is_empty())
+    }
+}
+
+impl Extend<&'static str> for SetMask {
+    fn extend<I: IntoIterator<Item = &'static str>>(&mut self, fields: I) {
+        for field in fields {
+            self.insert(field);
+        }
+    }
+}
+
+/// Where the fields of an [`Options`](crate::Options) struct got their
+/// values, see
+/// [`Options::try_parse_with_provenance`](crate::Options::try_parse_with_provenance)
+///
+/// Utilities use this when an explicit option changes the meaning of
+/// another, like `--color=auto` depending on whether `ls` got a `--format`.
+#[derive(Clone, Debug, Default)]
+pub struct Provenance {
+    mask: SetMask,
+    /// The fields that were read from an environment variable, with the
+    /// variable
+    env: Vec<(&'static str, &'static str)>,
+}
+
+impl Provenance {
+    pub(crate) fn new(mask: SetMask, env: Vec<(&'static str, &'static str)>) -> Self {
+        Self { mask, env }
+    }
+
+    /// Whether the field was set by an argument on the command line
+    pub fn was_provided(&self, field: &str) -> bool {
+        self.mask.contains(field)
+    }
+
+    /// Where the value of the field came from
+    ///
+    /// An argument on the command line wins over an environment variable,
+    /// so the value of a field set by both came from the command line.
+    /// Fields that were set by neither have their default value.
+    pub fn source(&self, field: &str) -> ValueSource {
+        if self.mask.contains(field) {
+            let flag = self.mask.flag(field).unwrap_or_default();
+            return ValueSource::Cli { flag: flag.into() };
+        }
+        match self.env.iter().find(|(f, _)| *f == field) {
+            Some((_, var)) => ValueSource::Env {
+                var: var.to_string(),
+            },
+            None => ValueSource::Default,
+        }
+    }
+
+    /// The fields that were set by the arguments
+    pub fn mask(&self) -> &SetMask {
+        &self.mask
+    }
+}
+
+/// A token read by lexopt
+pub(crate) struct Token {
+    shape: TokenShape,
+    raw: OsString,
+}
+
+/// The part of a token that is needed to follow what lexopt does with it
+enum TokenShape {
+    DoubleDash,
+    Long,
+    Shorts(Vec<char>),
+    Other,
+}
+
+impl TokenShape {
+    fn of(token: &OsString) -> Self {
+        let token = token.to_string_lossy();
+        if token == "--" {
+            Self::DoubleDash
+        } else if token.starts_with("--") {
+            Self::Long
+        } else if token.starts_with('-') && token.len() > 1 {
+            Self::Shorts(token.chars().skip(1).collect())
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// The tokens read by lexopt, which are only kept once the metadata is
+/// requested
+#[derive(Default)]
+pub(crate) struct Tokens {
+    read: usize,
+    kept: Option<Vec<Token>>,
+}
+
+impl Tokens {
+    /// The number of tokens read so far
+    pub(crate) fn len(&self) -> usize {
+        self.read
+    }
+
+    /// The tokens read so far, empty if they are not kept
+    pub(crate) fn kept(&self) -> &[Token] {
+        self.kept.as_deref().unwrap_or_default()
+    }
+
+    /// Keep the tokens that are read from now on.
+    ///
+    /// The tokens read before, which is only the bin name if this is called
+    /// before parsing, are kept as tokens without a shape.
+    pub(crate) fn keep(&mut self) {
+        let read = self.read;
+        self.kept.get_or_insert_with(|| {
+            (0..read)
+                .map(|_| Token {
+                    shape: TokenShape::Other,
+                    raw: OsString::new(),
+                })
+                .collect()
+        });
+    }
+
+    fn push(&mut self, arg: &OsString) {
+        self.read += 1;
+        if let Some(kept) = &mut self.kept {
+            kept.push(Token {
+                shape: TokenShape::of(arg),
+                raw: arg.clone(),
+            });
+        }
+    }
+}
+
+/// Wrap the arguments to count every token that is read and, if the
+/// [`Tokens`] are kept, record its shape.
+///
+/// This relies on lexopt reading a token only when it needs it: the
+/// [`Tracker`] compares the tokens read before and after each argument. A
+/// lexopt that collects all arguments up front, like 0.3 does, breaks the
+/// attribution and the splicing of argument files.
+pub(crate) fn record_tokens<I>(
+    args: I,
+) -> (
+    impl Iterator<Item = OsString> + 'static,
+    Rc<RefCell<Tokens>>,
+)
+where
+    I: IntoIterator + 'static,
+    I::Item: Into<OsString>,
+{
+    let tokens = Rc::new(RefCell::new(Tokens::default()));
+    let recorded = Rc::clone(&tokens);
+    let args = args.into_iter().map(move |arg| {
+        let arg = arg.into();
+        recorded.borrow_mut().push(&arg);
+        arg
+    });
+    (args, tokens)
+}
+
+/// Follows the state of the lexopt parser to attribute arguments to tokens
+#[derive(Default)]
+pub(crate) struct Tracker {
+    pub(crate) metadata: ParseMetadata,
+    /// The token index and the next position in a cluster of short options
+    cluster: Option<(usize, usize)>,
+    /// Whether `--` has been found
+    finished: bool,
+}
+
+impl Tracker {
+    /// Record the argument produced while the tokens from `before` were read.
+    ///
+    /// Within a cluster, lexopt does not read a new token, so the position in
+    /// the cluster is tracked here. A short option that takes a value
+    /// consumes the rest of the cluster.
+    ///
+    /// If `recorded` is set, the tokens of the argument are added to the
+    /// [recorded arguments](ParseMetadata::recorded_args).
+    pub(crate) fn record(
+        &mut self,
+        tokens: &[Token],
+        before: usize,
+        short_takes_value: impl Fn(char) -> bool,
+        recorded: bool,
+    ) {
+        let after = tokens.len();
+
+        let (start, position) = match self.cluster.take() {
+            Some(cluster) => cluster,
+            None => {
+                let mut start = before;
+                if !self.finished
+                    && start + 1 < after
+                    && matches!(tokens[start].shape, TokenShape::DoubleDash)
+                {
+                    self.finished = true;
+                    start += 1;
+                }
+                (start, 1)
+            }
+        };
+
+        let end = after.max(start + 1);
+        let mut raw: Vec<OsString> = tokens[start.min(after)..after]
+            .iter()
+            .map(|t| t.raw.clone())
+            .collect();
+
+        let kind = match tokens.get(start).map(|t| &t.shape) {
+            Some(TokenShape::Long) if !self.finished => ArgKind::Long,
+            Some(TokenShape::Shorts(chars)) if !self.finished => {
+                let consumed_rest = match chars.get(position - 1) {
+                    Some(&c) => short_takes_value(c),
+                    None => true,
+                };
+                let in_cluster = !consumed_rest && position < chars.len();
+                if in_cluster {
+                    self.cluster = Some((start, position + 1));
+                }
+                // Take the option out of the cluster, unless it is the
+                // whole token.
+                if recorded && (position > 1 || in_cluster) {
+                    let rest = if consumed_rest {
+                        &chars[position - 1..]
+                    } else {
+                        &chars[position - 1..position]
+                    };
+                    raw[0] = format!("-{}", rest.iter().collect::<String>()).into();
+                }
+                ArgKind::Short { position }
+            }
+            _ => ArgKind::Positional,
+        };
+
+        if recorded {
+            self.metadata.recorded.extend(raw);
+        }
+        self.metadata.spans.push((kind, start..end));
+    }
+}
@@ -0,0 +1,19 @@
+use std::io::{ErrorKind, Write};
+
+/// Write the help or version text and return the exit code for the utility
+///
+/// The text is written with [`Write`] instead of `print!`, which panics when
+/// writing fails. A closed pipe, like in `ls --help | head -1`, is not an
+/// error, because the reader simply did not need the rest of the output, so
+/// the exit code is 0 just like for GNU utilities. Any other error is
+/// reported on stderr and results in exit code 1.
+pub fn write_info(w: &mut impl Write, text: &str) -> i32 {
+    match w.write_all(text.as_bytes()).and_then(|()| w.flush()) {
+        Ok(()) => 0,
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => 0,
+        Err(err) => {
+            eprintln!("write error: {err}");
+            1
+        }
+    }
+}
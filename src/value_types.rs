@@ -0,0 +1,716 @@
+//! Value types that are shared by multiple utilities.
+//!
+//! These types only recognize the structure of a value, so that errors are
+//! reported consistently and the values can be completed. Interpreting the
+//! value, like formatting a timestamp, is left to the utility.
+
+use std::{ffi::OsString, fmt::Display};
+
+use crate::{complete::ValueHint, Error, FromValue};
+
+/// The style of timestamps, as given to `ls --time-style`
+///
+/// The keywords can be abbreviated as long as they are unambiguous. Any value
+/// starting with `+` is a custom format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeStyleValue {
+    /// `full-iso`
+    FullIso,
+    /// `long-iso`
+    LongIso,
+    /// `iso`
+    Iso,
+    /// `locale`
+    Locale,
+    /// `+FORMAT`, with the format without the `+`
+    Format(String),
+}
+
+impl TimeStyleValue {
+    const KEYWORDS: [(&'static str, Self); 4] = [
+        ("full-iso", Self::FullIso),
+        ("long-iso", Self::LongIso),
+        ("iso", Self::Iso),
+        ("locale", Self::Locale),
+    ];
+}
+
+impl FromValue for TimeStyleValue {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+
+        if let Some(format) = value.strip_prefix('+') {
+            return Ok(Self::Format(format.to_string()));
+        }
+
+        if let Some((_, style)) = Self::KEYWORDS.iter().find(|(k, _)| *k == value) {
+            return Ok(style.clone());
+        }
+
+        let candidates: Vec<_> = Self::KEYWORDS
+            .iter()
+            .filter(|(k, _)| k.starts_with(&value))
+            .collect();
+
+        match &candidates[..] {
+            [(_, style)] => Ok(style.clone()),
+            [] => Err(Error::ParsingFailed {
+                option: option.to_string(),
+                value: value.into(),
+                error: "Invalid time style".into(),
+            }),
+            _ => Err(Error::AmbiguousValue {
+                option: option.to_string(),
+                value,
+                candidates: candidates.iter().map(|(k, _)| k.to_string()).collect(),
+            }),
+        }
+    }
+
+    fn value_hint() -> ValueHint {
+        ValueHint::Strings(Self::KEYWORDS.iter().map(|(k, _)| k.to_string()).collect())
+    }
+}
+
+/// A date as given to `date -d` or `touch -d`
+///
+/// Only a few unambiguous syntaxes are recognized. Anything else is passed
+/// through as is, so that the utility can apply its own parser.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RelativeOrAbsoluteDate {
+    /// `@EPOCH`, the number of seconds since the Unix epoch
+    Epoch(i64),
+    /// `YYYY-MM-DD[ HH:MM[:SS]]`
+    ///
+    /// The time is given as hours, minutes and seconds. If the seconds are
+    /// omitted, they are 0.
+    Absolute {
+        year: u16,
+        month: u8,
+        day: u8,
+        time: Option<(u8, u8, u8)>,
+    },
+    /// Any other value, which is not interpreted
+    Other(String),
+}
+
+impl FromValue for RelativeOrAbsoluteDate {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+
+        let invalid = |value: String, error: &str| Error::ParsingFailed {
+            option: option.to_string(),
+            value: value.into(),
+            error: error.into(),
+        };
+
+        if let Some(epoch) = value.strip_prefix('@') {
+            return match epoch.parse() {
+                Ok(epoch) => Ok(Self::Epoch(epoch)),
+                Err(_) => Err(invalid(value, "Invalid number of seconds")),
+            };
+        }
+
+        let Some(([year, month, day], time)) = split_date_time(&value) else {
+            return Ok(Self::Other(value));
+        };
+
+        // The parts have at most 2 digits except for the year, so they fit.
+        let (year, month, day) = (year, month as u8, day as u8);
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(invalid(value, "Invalid date"));
+        }
+
+        let time = time.map(|[hour, minute, second]| (hour as u8, minute as u8, second as u8));
+        if let Some((hour, minute, second)) = time {
+            if hour > 23 || minute > 59 || second > 59 {
+                return Err(invalid(value, "Invalid time"));
+            }
+        }
+
+        Ok(Self::Absolute {
+            year,
+            month,
+            day,
+            time,
+        })
+    }
+}
+
+/// Split `YYYY-MM-DD[ HH:MM[:SS]]` into its numbers, without validating them
+fn split_date_time(s: &str) -> Option<([u16; 3], Option<[u16; 3]>)> {
+    let (date, time) = match s.split_once(' ') {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+
+    let date = split_numbers(date, '-', [4, 2, 2])?;
+    let time = match time {
+        Some(time) => Some(
+            split_numbers(time, ':', [2, 2, 2])
+                .or_else(|| split_numbers(time, ':', [2, 2]).map(|[h, m]| [h, m, 0]))?,
+        ),
+        None => None,
+    };
+
+    Some((date, time))
+}
+
+/// Split `s` on `sep` into numbers of exactly `lengths` digits
+fn split_numbers<const N: usize>(s: &str, sep: char, lengths: [usize; N]) -> Option<[u16; N]> {
+    let mut parts = s.split(sep);
+    let mut out = [0; N];
+    for (out, len) in out.iter_mut().zip(lengths) {
+        let part = parts.next()?;
+        if part.len() != len || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *out = part.parse().ok()?;
+    }
+    match parts.next() {
+        Some(_) => None,
+        None => Some(out),
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A percentage like `50%`, as given to `df` or `shuf`
+///
+/// The value is stored as a fraction, so `50%` is 0.5. With `ALLOW_BARE`, the
+/// `%` can be left out. Negative percentages are always rejected and
+/// percentages over 100 are rejected unless `ALLOW_OVER_100` is set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Percentage<const ALLOW_BARE: bool = true, const ALLOW_OVER_100: bool = false> {
+    fraction: f64,
+}
+
+impl<const ALLOW_BARE: bool, const ALLOW_OVER_100: bool> Percentage<ALLOW_BARE, ALLOW_OVER_100> {
+    /// The percentage as a fraction, i.e. 0.5 for `50%`
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    /// The percentage as given, i.e. 50 for `50%`
+    pub fn percent(&self) -> f64 {
+        self.fraction * 100.0
+    }
+}
+
+impl<const ALLOW_BARE: bool, const ALLOW_OVER_100: bool> FromValue
+    for Percentage<ALLOW_BARE, ALLOW_OVER_100>
+{
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+
+        let invalid = |value: String, error: &str| Error::ParsingFailed {
+            option: option.to_string(),
+            value: value.into(),
+            error: error.into(),
+        };
+
+        let number = match value.strip_suffix('%') {
+            Some(number) => number,
+            None if ALLOW_BARE => &value,
+            None => return Err(invalid(value, "Expected a percentage ending in '%'")),
+        };
+
+        let (negative, digits) = match number.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, number),
+        };
+
+        // Only plain decimal numbers are accepted, not `inf` or `1e2`.
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+            return Err(invalid(value, "Invalid percentage"));
+        }
+        let Ok(percent) = digits.parse::<f64>() else {
+            return Err(invalid(value, "Invalid percentage"));
+        };
+
+        if negative && percent != 0.0 {
+            return Err(invalid(value, "Percentage cannot be negative"));
+        }
+        if !ALLOW_OVER_100 && percent > 100.0 {
+            return Err(invalid(value, "Percentage cannot be more than 100%"));
+        }
+
+        Ok(Self {
+            fraction: percent / 100.0,
+        })
+    }
+}
+
+impl<const ALLOW_BARE: bool, const ALLOW_OVER_100: bool> Display
+    for Percentage<ALLOW_BARE, ALLOW_OVER_100>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.percent())
+    }
+}
+
+/// A ratio `A/B` of two integers, with a denominator that is not zero
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ratio {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl Ratio {
+    /// The value of the ratio
+    pub fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+impl FromValue for Ratio {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+
+        let invalid = |value: String, error: &str| Error::ParsingFailed {
+            option: option.to_string(),
+            value: value.into(),
+            error: error.into(),
+        };
+
+        let parse = |s: &str| -> Option<u64> {
+            if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            s.parse().ok()
+        };
+
+        let Some((numerator, denominator)) = value.split_once('/') else {
+            return Err(invalid(value, "Expected a ratio of the form A/B"));
+        };
+        let (Some(numerator), Some(denominator)) = (parse(numerator), parse(denominator)) else {
+            return Err(invalid(value, "Expected a ratio of the form A/B"));
+        };
+        if denominator == 0 {
+            return Err(invalid(value, "Denominator cannot be zero"));
+        }
+
+        Ok(Self {
+            numerator,
+            denominator,
+        })
+    }
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// The sign of a [`SigNum`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
+/// A count with a sign, like `tail -n +5` or `head -c -100`
+///
+/// The sign is `+`, `-` or left out, in which case it is `-` if
+/// `BARE_IS_MINUS` is set, like for `tail`, and `+` otherwise, like for
+/// `head`. The sign is kept for zero, so `+0` and `-0` are different. The
+/// count can have a multiplier suffix: `b` for 512, `K`, `M`, `G`, `T`, `P`
+/// and `E` for powers of 1024, which can also be written as `KiB`, `MiB`,
+/// etc., and `KB`, `MB`, etc. for powers of 1000.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigNum<const BARE_IS_MINUS: bool = false> {
+    sign: Sign,
+    magnitude: u64,
+}
+
+impl<const BARE_IS_MINUS: bool> SigNum<BARE_IS_MINUS> {
+    pub fn new(sign: Sign, magnitude: u64) -> Self {
+        Self { sign, magnitude }
+    }
+
+    pub fn sign(&self) -> Sign {
+        self.sign
+    }
+
+    /// The count without the sign, with the suffix applied
+    pub fn magnitude(&self) -> u64 {
+        self.magnitude
+    }
+}
+
+impl<const BARE_IS_MINUS: bool> Default for SigNum<BARE_IS_MINUS> {
+    /// Zero, with the sign of a count without a sign
+    fn default() -> Self {
+        let sign = if BARE_IS_MINUS {
+            Sign::Minus
+        } else {
+            Sign::Plus
+        };
+        Self::new(sign, 0)
+    }
+}
+
+/// The multiplier of a count suffix, or `None` if the suffix is not valid
+fn suffix_multiplier(suffix: &str) -> Option<u128> {
+    if suffix.is_empty() {
+        return Some(1);
+    }
+    if suffix == "b" {
+        return Some(512);
+    }
+    let mut chars = suffix.chars();
+    let exponent = match chars.next()? {
+        'k' | 'K' => 1,
+        'M' => 2,
+        'G' => 3,
+        'T' => 4,
+        'P' => 5,
+        'E' => 6,
+        _ => return None,
+    };
+    let base: u128 = match chars.as_str() {
+        "" | "iB" => 1024,
+        "B" => 1000,
+        _ => return None,
+    };
+    Some(base.pow(exponent))
+}
+
+impl<const BARE_IS_MINUS: bool> FromValue for SigNum<BARE_IS_MINUS> {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+
+        let invalid = |value: String, error: &str| Error::ParsingFailed {
+            option: option.to_string(),
+            value: value.into(),
+            error: error.into(),
+        };
+
+        let (sign, rest) = if let Some(rest) = value.strip_prefix('+') {
+            (Sign::Plus, rest)
+        } else if let Some(rest) = value.strip_prefix('-') {
+            (Sign::Minus, rest)
+        } else if BARE_IS_MINUS {
+            (Sign::Minus, &value[..])
+        } else {
+            (Sign::Plus, &value[..])
+        };
+
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (digits, suffix) = rest.split_at(digits_end);
+        if digits.is_empty() {
+            return Err(invalid(value, "Invalid count"));
+        }
+        let Some(multiplier) = suffix_multiplier(suffix) else {
+            return Err(invalid(value, "Invalid suffix"));
+        };
+
+        let magnitude = digits
+            .parse::<u128>()
+            .ok()
+            .and_then(|n| n.checked_mul(multiplier))
+            .and_then(|n| u64::try_from(n).ok());
+        let Some(magnitude) = magnitude else {
+            return Err(invalid(value, "Value too large"));
+        };
+
+        Ok(Self { sign, magnitude })
+    }
+}
+
+impl<const BARE_IS_MINUS: bool> Display for SigNum<BARE_IS_MINUS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.sign {
+            Sign::Plus => '+',
+            Sign::Minus => '-',
+        };
+        write!(f, "{sign}{}", self.magnitude)
+    }
+}
+
+/// A duration like `5`, `0.5m` or `1e3`, as given to `sleep`, `timeout` or
+/// `tail --sleep-interval`
+///
+/// The number is in seconds unless it is followed by `s`, `m` for minutes,
+/// `h` for hours or `d` for days. Negative durations and `nan` are
+/// rejected. Durations that are too long for [`std::time::Duration`],
+/// including `inf`, are capped at [`std::time::Duration::MAX`], which is
+/// more than 500 billion years.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration(std::time::Duration);
+
+impl Duration {
+    /// The duration as a [`std::time::Duration`]
+    pub fn get(&self) -> std::time::Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        duration.0
+    }
+}
+
+impl FromValue for Duration {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+
+        let invalid = |value: String, error: &str| Error::ParsingFailed {
+            option: option.to_string(),
+            value: value.into(),
+            error: error.into(),
+        };
+
+        let (number, multiplier) = match value.as_bytes().last() {
+            Some(b's') => (&value[..value.len() - 1], 1.0),
+            Some(b'm') => (&value[..value.len() - 1], 60.0),
+            Some(b'h') => (&value[..value.len() - 1], 60.0 * 60.0),
+            Some(b'd') => (&value[..value.len() - 1], 24.0 * 60.0 * 60.0),
+            _ => (&value[..], 1.0),
+        };
+
+        let seconds = match number.parse::<f64>() {
+            Ok(seconds) if !seconds.is_nan() => seconds,
+            _ => return Err(invalid(value, "Invalid duration")),
+        };
+        // `-0` is zero, so it is not negative
+        if seconds < 0.0 {
+            return Err(invalid(value, "Duration cannot be negative"));
+        }
+
+        let duration = std::time::Duration::try_from_secs_f64(seconds * multiplier)
+            .unwrap_or(std::time::Duration::MAX);
+        Ok(Self(duration))
+    }
+}
+
+impl Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}s", self.0.as_secs_f64())
+    }
+}
+
+/// A shell pattern, as given to `ls --ignore` or `du --exclude`
+///
+/// The pattern is checked when the argument is parsed, so that a malformed
+/// pattern is reported with the option it was given to instead of when it is
+/// first used. The syntax is that of `fnmatch`: `*`, `?`, bracket
+/// expressions like `[a-z]`, `[!0-9]` and `[[:alpha:]]`, and `\` to escape
+/// the next character. Slashes and leading dots have no special meaning.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    source: String,
+    tokens: Vec<PatternToken>,
+}
+
+#[derive(Clone, Debug)]
+enum PatternToken {
+    Char(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    AnySequence,
+    /// `[...]`
+    Bracket {
+        negated: bool,
+        items: Vec<BracketItem>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum BracketItem {
+    Char(char),
+    Range(char, char),
+    Class(fn(char) -> bool),
+}
+
+impl Pattern {
+    /// The pattern as it was given
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the whole of `s` matches the pattern
+    pub fn matches(&self, s: &str) -> bool {
+        let chars: Vec<char> = s.chars().collect();
+        let (mut t, mut c) = (0, 0);
+        // The position after the last `*` and the char it was retried at
+        let mut backtrack = None;
+        while c < chars.len() {
+            match self.tokens.get(t) {
+                Some(PatternToken::AnySequence) => {
+                    t += 1;
+                    backtrack = Some((t, c));
+                    continue;
+                }
+                Some(token) if token.matches(chars[c]) => {
+                    t += 1;
+                    c += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            let Some((bt, bc)) = backtrack else {
+                return false;
+            };
+            backtrack = Some((bt, bc + 1));
+            t = bt;
+            c = bc + 1;
+        }
+        self.tokens[t..]
+            .iter()
+            .all(|token| matches!(token, PatternToken::AnySequence))
+    }
+
+    fn parse(source: &str) -> Result<Vec<PatternToken>, String> {
+        let mut tokens = Vec::new();
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '*' => PatternToken::AnySequence,
+                '?' => PatternToken::AnyChar,
+                '\\' => match chars.next() {
+                    Some(c) => PatternToken::Char(c),
+                    None => return Err("Pattern cannot end with a backslash".into()),
+                },
+                '[' => Self::parse_bracket(&mut chars)?,
+                c => PatternToken::Char(c),
+            };
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    /// Parse a bracket expression after its opening `[`
+    fn parse_bracket(
+        chars: &mut std::iter::Peekable<std::str::Chars>,
+    ) -> Result<PatternToken, String> {
+        let unclosed = || "Unclosed '[' in pattern".to_string();
+
+        let negated = chars.next_if(|&c| c == '!' || c == '^').is_some();
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = chars.next().ok_or_else(unclosed)?;
+            let start = match c {
+                // A `]` right after the `[` is part of the set
+                ']' if !first => break,
+                '[' if chars.peek() == Some(&':') => {
+                    chars.next();
+                    let mut name = String::new();
+                    loop {
+                        match chars.next().ok_or_else(unclosed)? {
+                            ':' if chars.next_if_eq(&']').is_some() => break,
+                            c => name.push(c),
+                        }
+                    }
+                    items.push(BracketItem::Class(character_class(&name)?));
+                    first = false;
+                    continue;
+                }
+                '\\' => chars.next().ok_or_else(unclosed)?,
+                c => c,
+            };
+            first = false;
+
+            // A `-` before the closing `]` is not a range
+            let mut lookahead = chars.clone();
+            if lookahead.next() == Some('-') && !matches!(lookahead.peek(), Some(']') | None) {
+                chars.next();
+                let end = match chars.next().ok_or_else(unclosed)? {
+                    '\\' => chars.next().ok_or_else(unclosed)?,
+                    c => c,
+                };
+                if start > end {
+                    return Err(format!("Invalid range '{start}-{end}' in pattern"));
+                }
+                items.push(BracketItem::Range(start, end));
+            } else {
+                items.push(BracketItem::Char(start));
+            }
+        }
+        Ok(PatternToken::Bracket { negated, items })
+    }
+}
+
+/// The predicate for a `[:name:]` class in a bracket expression
+fn character_class(name: &str) -> Result<fn(char) -> bool, String> {
+    Ok(match name {
+        "alnum" => |c| c.is_alphanumeric(),
+        "alpha" => |c| c.is_alphabetic(),
+        "blank" => |c| c == ' ' || c == '\t',
+        "cntrl" => |c| c.is_control(),
+        "digit" => |c| c.is_ascii_digit(),
+        "graph" => |c| c.is_ascii_graphic(),
+        "lower" => |c| c.is_lowercase(),
+        "print" => |c| !c.is_control(),
+        "punct" => |c| c.is_ascii_punctuation(),
+        "space" => |c| c.is_whitespace(),
+        "upper" => |c| c.is_uppercase(),
+        "xdigit" => |c| c.is_ascii_hexdigit(),
+        _ => return Err(format!("Invalid character class '{name}' in pattern")),
+    })
+}
+
+impl PatternToken {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Char(p) => *p == c,
+            Self::AnyChar => true,
+            Self::AnySequence => false,
+            Self::Bracket { negated, items } => {
+                let found = items.iter().any(|item| match item {
+                    BracketItem::Char(p) => *p == c,
+                    BracketItem::Range(start, end) => (*start..=*end).contains(&c),
+                    BracketItem::Class(f) => f(c),
+                });
+                found != *negated
+            }
+        }
+    }
+}
+
+// The tokens follow from the source, so comparing the source is enough
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for Pattern {}
+
+impl FromValue for Pattern {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+        match Self::parse(&value) {
+            Ok(tokens) => Ok(Self {
+                source: value,
+                tokens,
+            }),
+            Err(error) => Err(Error::ParsingFailed {
+                option: option.to_string(),
+                value: value.into(),
+                error: error.into(),
+            }),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
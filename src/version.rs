@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// The information shown by `--version`
+///
+/// The first line is always `{name} {version}`, so that it can be parsed
+/// reliably. The `extra` information, from `#[arguments(version_extra =
+/// ...)]`, follows on the next lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    /// The name of the utility
+    pub name: &'static str,
+    /// The version of the package
+    pub version: &'static str,
+    /// Build information, like a git hash or the enabled features
+    pub extra: Option<String>,
+}
+
+impl Version {
+    /// The version as a JSON object with the keys `name`, `version` and
+    /// `extra`, which is `null` if there is no extra information
+    pub fn to_json(&self) -> String {
+        let extra = match &self.extra {
+            Some(extra) => json_string(extra),
+            None => "null".into(),
+        };
+        format!(
+            "{{\"name\":{},\"version\":{},\"extra\":{}}}",
+            json_string(self.name),
+            json_string(self.version),
+            extra
+        )
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.version)?;
+        if let Some(extra) = &self.extra {
+            write!(f, "\n{extra}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A string literal in JSON, with the characters escaped that must be
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,99 @@
+use std::{cell::RefCell, ffi::OsString, fmt::Display};
+
+use crate::ParseConfig;
+
+thread_local! {
+    /// The warnings of the parse that is running on this thread, if any
+    static COLLECTED: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+
+    /// The warnings reported with [`warn`] outside of a parse, which are
+    /// reported when the next argument is read
+    static PENDING: RefCell<Vec<Warning>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A problem with the arguments that does not stop the parse
+///
+/// Like GNU `ls` with an invalid `COLUMNS`, the utility reports the problem
+/// and continues. The warnings of a parse are reported to
+/// [`ParseConfig::warn`] in the order they occurred, or returned by
+/// [`Options::try_parse_with_warnings`](crate::Options::try_parse_with_warnings).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// A deprecated option was used
+    Deprecated {
+        /// The name of the option, like `--retry`
+        option: String,
+        /// The warning given with the `deprecated` attribute
+        message: String,
+    },
+
+    /// The value of an environment variable was invalid and the default was
+    /// used instead, see the `ignore_invalid_env` attribute
+    InvalidEnv { var: String, value: OsString },
+
+    /// Any other warning, reported with [`warn`]
+    Custom(String),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deprecated { message, .. } => f.write_str(message),
+            Self::InvalidEnv { var, value } => write!(
+                f,
+                "ignoring invalid value in environment variable {var}: '{}'",
+                value.to_string_lossy()
+            ),
+            Self::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Report a warning from a [`FromValue`](crate::FromValue) implementation or
+/// a default value.
+///
+/// The warning is part of the parse that is running on this thread. Outside
+/// of a parse, it is reported when the next argument is read.
+pub fn warn(warning: Warning) {
+    if let Err(warning) = push_collected(warning) {
+        PENDING.with(|p| p.borrow_mut().push(warning));
+    }
+}
+
+/// Report a warning of a parse with the given bin name and configuration
+#[doc(hidden)]
+pub fn report(bin_name: &str, config: &ParseConfig, warning: Warning) {
+    if let Err(warning) = push_collected(warning) {
+        (config.warn)(bin_name, &warning.to_string());
+    }
+}
+
+/// Report the warnings from [`warn`] that were given outside of a parse
+pub(crate) fn report_pending(bin_name: &str, config: &ParseConfig) {
+    for warning in PENDING.with(|p| p.take()) {
+        report(bin_name, config, warning);
+    }
+}
+
+/// Add the warning to the collected warnings, or give it back if no
+/// warnings are collected
+fn push_collected(warning: Warning) -> Result<(), Warning> {
+    COLLECTED.with(|c| match &mut *c.borrow_mut() {
+        Some(warnings) => {
+            warnings.push(warning);
+            Ok(())
+        }
+        None => Err(warning),
+    })
+}
+
+/// Run `f`, collecting the warnings it reports instead of reporting them.
+///
+/// The warnings of an enclosing collection, like that of the parent of a
+/// subcommand, are kept separate.
+pub(crate) fn collect<T>(f: impl FnOnce() -> T) -> (T, Vec<Warning>) {
+    let outer = COLLECTED.with(|c| c.replace(Some(Vec::new())));
+    let value = f();
+    let warnings = COLLECTED.with(|c| c.replace(outer));
+    (value, warnings.unwrap_or_default())
+}
@@ -1,8 +1,10 @@
 mod event;
+mod sections;
 pub use event::*;
+pub use sections::*;
 
 use nu_ansi_term::{Color, Style};
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 pub struct Renderer<T: Iterator<Item = Event>> {
     // The output string, which will be returned by `render`
@@ -21,11 +23,15 @@ pub struct Renderer<T: Iterator<Item = Event>> {
 }
 
 impl<T: Iterator<Item = Event>> Renderer<T> {
+    /// Create a renderer that wraps at `width` columns
+    ///
+    /// A width of 0 is treated as 1. Words that are wider than the width are
+    /// broken up.
     pub fn new(width: usize, events: T) -> Self {
         Self {
             output: String::new(),
             current_column: 0,
-            width,
+            width: width.max(1),
             events,
         }
     }
@@ -127,47 +133,50 @@ impl<T: Iterator<Item = Event>> Renderer<T> {
     }
 
     fn wrap_words(&mut self, s: &str) {
-        let mut words = s.split_whitespace();
-        let first = words.next();
-
-        // The first word needs special treatment, because we only want to
-        // print a space in front of it if the string actually starts with a
-        // space.
-        let word = match first {
-            Some(word) => word,
-            None => return,
-        };
+        // Only print a space in front of the first word if the string
+        // actually starts with a space.
+        let mut space = s.starts_with(' ');
+        for word in s.split_whitespace() {
+            self.push_word(word, space);
+            space = true;
+        }
 
+        // The next word goes on a new line if the line is full, so the space
+        // is not needed then.
+        if s.ends_with(' ') && self.current_column < self.width {
+            self.output.push(' ');
+            self.current_column += 1;
+        }
+    }
+
+    /// Push a word to the output, on a new line if it does not fit
+    fn push_word(&mut self, word: &str, space: bool) {
         let width = word.width();
 
-        if self.current_column + width >= self.width {
+        // The +1 comes from the space we need in front of this word. A word
+        // that does not fit on an empty line is not preceded by a newline.
+        if self.current_column > 0 && self.current_column + width + usize::from(space) >= self.width
+        {
             self.newline();
-        } else if s.starts_with(' ') {
+        } else if space && self.current_column > 0 {
             self.output.push(' ');
             self.current_column += 1;
         }
 
-        self.current_column += width;
-        self.output.push_str(word);
-
-        for word in words {
-            let width = word.width();
-
-            // The +1 comes from the additional space we need in front of this
-            // word.
-            if self.current_column + width + 1 >= self.width {
-                self.newline();
-            } else {
-                self.output.push(' ');
-                self.current_column += 1;
-            }
-
+        if width <= self.width {
             self.current_column += width;
             self.output.push_str(word);
+            return;
         }
 
-        if s.ends_with(' ') {
-            self.output.push(' ')
+        // The word is wider than a line, so it is broken up at the width.
+        for c in word.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if self.current_column > 0 && self.current_column + char_width > self.width {
+                self.newline();
+            }
+            self.current_column += char_width;
+            self.output.push(c);
         }
     }
 
@@ -199,6 +208,7 @@ impl<T: Iterator<Item = Event>> Renderer<T> {
 mod tests {
     use super::Renderer;
     use pulldown_cmark::{Options, Parser};
+    use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
     #[test]
     fn it_works() {
@@ -225,7 +235,7 @@ mod tests {
         println!("{}", output);
         assert_eq!(
             output,
-            "We have \u{1b}[3memphasis\u{1b}[0m, \u{1b}[1mbold\u{1b}[0m, and \u{1b}[9mstrikethrough\u{1b}[0m.\n"
+            "We have \u{1b}[3memphasis\u{1b}[0m, \u{1b}[1mbold\u{1b}[0m, and \u{1b}[9m\nstrikethrough\u{1b}[0m.\n"
         );
     }
 
@@ -268,9 +278,8 @@ mod tests {
         let output = Renderer::new(10, events).render();
         println!("{}", output);
 
-        // The lone `!` at the end is technically a bug, because words across
-        // styles need be preserved, but that's a can of worms I don't want to
-        // open right now. You'd need to keep track of the last word and its
+        // Words across styles are not preserved, so the `!` could end up on
+        // its own line. You'd need to keep track of the last word and its
         // width and render it either at the end of the block or at the start
         // of the next inline Text or Code event. It could also happen that
         // there are more than 2 styles per word.
@@ -286,8 +295,8 @@ mod tests {
             wrapped,\n\
             so we\n\
             better do\n\
-            that \u{1b}[1mright\u{1b}[0m\n\
-            !\n"
+            that \u{1b}[1m\n\
+            right\u{1b}[0m!\n"
         )
     }
 
@@ -324,4 +333,82 @@ mod tests {
             "This text has\n────────────────────────────────────────\na rule!.\n"
         );
     }
+
+    /// A small xorshift generator, so that the random tests are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Random markdown and the widest word in it
+    fn random_help(rng: &mut Rng) -> (String, usize) {
+        // Lists are not supported, so there is no `-` that could start one
+        const CHARS: &[char] = &['a', 'b', 'x', ',', '.', 'é', '字', '🦀'];
+        const SEPARATORS: &[&str] = &[" ", " ", " ", "  ", "\n", "\n\n", "\n\n---\n\n"];
+        const STYLES: &[(&str, &str)] = &[("", ""), ("", ""), ("*", "*"), ("**", "**"), ("`", "`")];
+
+        let mut text = String::new();
+        let mut longest = 0;
+        for _ in 0..rng.below(60) {
+            let word: String = (0..1 + rng.below(30))
+                .map(|_| CHARS[rng.below(CHARS.len())])
+                .collect();
+            longest = longest.max(word.width());
+            let (open, close) = STYLES[rng.below(STYLES.len())];
+            text.push_str(open);
+            text.push_str(&word);
+            text.push_str(close);
+            text.push_str(SEPARATORS[rng.below(SEPARATORS.len())]);
+        }
+        (text, longest)
+    }
+
+    /// The display width of a line without the ANSI escape codes
+    fn line_width(line: &str) -> usize {
+        let mut width = 0;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\u{1b}' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                width += c.width().unwrap_or(0);
+            }
+        }
+        width
+    }
+
+    #[test]
+    fn random_wrapping() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..50 {
+            let (text, longest) = random_help(&mut rng);
+            for width in 1..=200 {
+                let events = Parser::new_ext(&text, Options::ENABLE_STRIKETHROUGH).map(Into::into);
+                let output = Renderer::new(width, events).render();
+                for line in output.lines() {
+                    assert!(
+                        line_width(line) <= width.max(longest),
+                        "line {line:?} is too wide for width {width} in {text:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_words() {
+        let events = Parser::new("ab abcdefgh").map(Into::into);
+        let output = Renderer::new(3, events).render();
+        assert_eq!(output, "ab\nabc\ndef\ngh\n");
+
+        let events = Parser::new("abcdefgh").map(Into::into);
+        let output = Renderer::new(0, events).render();
+        assert_eq!(output, "a\nb\nc\nd\ne\nf\ng\nh\n");
+    }
 }
@@ -0,0 +1,68 @@
+//! Selection of the parts of a help file.
+//!
+//! A help file has a `## Summary` section and the text after the first rule
+//! is shown after the options.
+
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+
+/// All events of the markdown
+pub fn all_events(s: &str) -> Vec<Event<'_>> {
+    Parser::new(s).collect()
+}
+
+/// The events of the section under the level 2 heading with the given name
+///
+/// The name is compared case-insensitively. The section ends at the next
+/// level 2 heading or rule.
+pub fn h2_events<'a>(heading_name: &str, s: &'a str) -> Vec<Event<'a>> {
+    let mut events = Parser::new(s);
+    let mut selected_events = Vec::new();
+    while let Some(event) = events.next() {
+        if let Event::Start(Tag::Heading(HeadingLevel::H2, _, _)) = event {
+            if let Some(Event::Text(s)) = events.next() {
+                if s.to_lowercase() == heading_name.to_lowercase() {
+                    selected_events.extend(
+                        (&mut events)
+                            .skip_while(|e| {
+                                !matches!(e, Event::End(Tag::Heading(HeadingLevel::H2, _, _)))
+                            })
+                            .skip(1)
+                            .take_while(|e| {
+                                !matches!(
+                                    e,
+                                    Event::Start(Tag::Heading(HeadingLevel::H2, _, _))
+                                        | Event::Rule
+                                )
+                            }),
+                    )
+                }
+            }
+        }
+    }
+    selected_events
+}
+
+/// The events after the first occurrence of `event`
+pub fn after_event<'a>(event: &Event, s: &'a str) -> Vec<Event<'a>> {
+    Parser::new(s).skip_while(|e| e != event).skip(1).collect()
+}
+
+/// The events after the first rule
+pub fn after_rule(s: &str) -> Vec<Event<'_>> {
+    after_event(&Event::Rule, s)
+}
+
+/// The text of the events, without any styling
+pub fn events_to_text<'a>(events: impl IntoIterator<Item = Event<'a>>) -> String {
+    let mut text = String::new();
+    for event in events {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::End(Tag::Paragraph | Tag::Heading(..)) => text.push_str("\n\n"),
+            _ => {}
+        }
+    }
+    text.trim_end().to_string()
+}
@@ -0,0 +1,267 @@
+//! Parsing options must not allocate more than lexopt does, because it is
+//! on the startup path of every utility. The allocations are counted per
+//! thread, so that the other tests running at the same time are not
+//! counted.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    ffi::OsString,
+    path::PathBuf,
+};
+
+use uutils_args::{lexopt, Argument, Arguments, Options, ParseConfig};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[derive(Arguments, Clone)]
+enum Arg {
+    #[option("-l")]
+    Long,
+
+    #[option("-a", "--all")]
+    All,
+
+    #[option("-A", "--almost-all")]
+    AlmostAll,
+
+    #[option("-w COLS", "--width=COLS")]
+    Width(u16),
+}
+
+fn parser(args: &[&str]) -> lexopt::Parser {
+    lexopt::Parser::from_args(parser_args(args))
+}
+
+/// The arguments converted beforehand, like the arguments of the process are
+fn parser_args(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+/// The number of allocations made while parsing `args` into `n` arguments
+fn count(args: &[&str], n: usize) -> usize {
+    let mut parser = parser(args);
+    let mut positional_idx = 0;
+    let mut flag = String::with_capacity(32);
+    let mut operands_only = false;
+    let config = ParseConfig::default();
+
+    let before = allocations();
+    for _ in 0..n {
+        let arg = Arg::next_arg(
+            &mut parser,
+            &mut positional_idx,
+            &mut flag,
+            &mut operands_only,
+            None,
+            &config,
+        );
+        match arg {
+            Ok(Some(Argument::Custom(Arg::Width(width)))) => assert_eq!(width, 80),
+            Ok(Some(_)) => {}
+            _ => panic!("expected an argument"),
+        }
+    }
+    allocations() - before
+}
+
+/// The number of allocations that lexopt makes by itself for `args`, when
+/// it is driven by `f`
+///
+/// Some versions of lexopt allocate, for example to split `--width=80`,
+/// which the generated code cannot avoid.
+fn lexopt_count(args: &[&str], f: impl FnOnce(&mut lexopt::Parser)) -> usize {
+    let mut parser = parser(args);
+    let before = allocations();
+    f(&mut parser);
+    allocations() - before
+}
+
+#[test]
+fn short_options() {
+    let args = ["-la"];
+    let lexopt = lexopt_count(&args, |p| {
+        p.next().unwrap();
+        p.next().unwrap();
+    });
+    assert_eq!(count(&args, 2), lexopt);
+
+    let args = ["-l", "-a", "-A"];
+    let lexopt = lexopt_count(&args, |p| {
+        for _ in 0..3 {
+            p.next().unwrap();
+        }
+    });
+    assert_eq!(count(&args, 3), lexopt);
+}
+
+#[test]
+fn long_options() {
+    let args = ["--all", "--almost-all"];
+    let lexopt = lexopt_count(&args, |p| {
+        p.next().unwrap();
+        p.next().unwrap();
+    });
+    assert_eq!(count(&args, 2), lexopt);
+}
+
+#[test]
+fn options_with_values() {
+    let args = ["-w80", "-w", "80", "--width", "80", "--width=80"];
+    let lexopt = lexopt_count(&args, |p| {
+        for _ in 0..4 {
+            p.next().unwrap();
+            p.value().unwrap();
+        }
+    });
+    assert_eq!(count(&args, 4), lexopt);
+}
+
+#[derive(Arguments, Clone)]
+enum Operands {
+    #[option("-f")]
+    Force,
+
+    #[positional(1)]
+    Name(OsString),
+
+    #[positional(..)]
+    Files(std::path::PathBuf),
+}
+
+#[test]
+fn operands_are_moved() {
+    // The operand that lexopt returns becomes the value of the variant as is.
+    let args = ["name", "a", "-f", "b", "--", "c", "-f"];
+    let lexopt = lexopt_count(&args, |p| while p.next().unwrap().is_some() {});
+
+    let mut parser = parser(&args);
+    let mut positional_idx = 0;
+    let mut flag = String::with_capacity(32);
+    let mut operands_only = false;
+    let config = ParseConfig::default();
+
+    let mut files = Vec::with_capacity(8);
+    let mut force = 0;
+    let before = allocations();
+    while let Some(arg) = Operands::next_arg(
+        &mut parser,
+        &mut positional_idx,
+        &mut flag,
+        &mut operands_only,
+        None,
+        &config,
+    )
+    .unwrap()
+    {
+        match arg {
+            Argument::Custom(Operands::Name(name)) => assert_eq!(name, "name"),
+            Argument::Custom(Operands::Files(file)) => files.push(file),
+            Argument::Custom(Operands::Force) => force += 1,
+            _ => panic!("expected an argument"),
+        }
+    }
+    assert_eq!(allocations() - before, lexopt);
+    assert_eq!(force, 1);
+    assert_eq!(files, ["a", "b", "c", "-f"].map(std::path::PathBuf::from));
+}
+
+#[test]
+fn operands_are_not_copied() {
+    // The tokens are only kept for the metadata and the parse mode is looked
+    // up once, so the operands cost no more than they do in lexopt, apart
+    // from a constant number of allocations.
+    let extra = |n: usize| {
+        let operands: Vec<String> = (0..n).map(|i| format!("file-{i}")).collect();
+        let args: Vec<&str> = operands.iter().map(String::as_str).collect();
+        let lexopt = lexopt_count(&args, |p| while p.next().unwrap().is_some() {});
+
+        let mut iter = Operands::parse_without_binname(parser_args(&args));
+        let before = allocations();
+        for _ in &args {
+            assert!(matches!(iter.next_arg(), Ok(Some(Argument::Custom(_)))));
+        }
+        allocations() - before - lexopt
+    };
+    assert_eq!(extra(10), extra(1000));
+}
+
+#[derive(Arguments, Clone)]
+enum RmArg {
+    #[option("-f", "--force")]
+    Force,
+
+    #[option("-r", "-R", "--recursive")]
+    Recursive,
+
+    #[positional(1..)]
+    File(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(RmArg)]
+struct RmSettings {
+    #[map(RmArg::Force => true)]
+    force: bool,
+    #[map(RmArg::Recursive => true)]
+    recursive: bool,
+    #[collect(set(RmArg::File))]
+    files: Vec<PathBuf>,
+}
+
+#[test]
+fn operands_are_not_copied_into_settings() {
+    // Applying the arguments to the settings moves the operands into the
+    // field, so twice the operands only cost the growth of that field.
+    let extra = |n: usize| {
+        let operands: Vec<String> = (0..n).map(|i| format!("file-{i}")).collect();
+        let args: Vec<&str> = ["rm", "-rf"]
+            .into_iter()
+            .chain(operands.iter().map(String::as_str))
+            .collect();
+        let lexopt = lexopt_count(&args, |p| while p.next().unwrap().is_some() {});
+
+        let files: Vec<PathBuf> = operands.iter().map(PathBuf::from).collect();
+        let before = allocations();
+        let mut field = Vec::new();
+        for file in files {
+            field.push(file);
+        }
+        let growth = allocations() - before;
+
+        // Some versions of lexopt read all arguments when the parser is
+        // created, so that is not counted.
+        let iter = RmArg::parse(parser_args(&args));
+        let before = allocations();
+        let settings = RmSettings::try_parse_iter(iter).unwrap();
+        let count = allocations() - before;
+        assert!(settings.force && settings.recursive);
+        assert_eq!(settings.files.len(), n);
+        (count - lexopt, growth)
+    };
+    let (count, growth) = extra(1000);
+    let (twice_count, twice_growth) = extra(2000);
+    assert!(twice_count - count <= twice_growth - growth);
+}
@@ -0,0 +1,135 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use uutils_args::{Arguments, Error, Options, ParseConfig};
+
+#[derive(Clone, Arguments)]
+#[arguments(argfile)]
+enum Arg {
+    #[option("-a", "--all")]
+    All,
+
+    #[option("-w N", "--width=N")]
+    Width(u16),
+
+    #[option("-o FILE")]
+    Output(OsString),
+
+    #[positional(..)]
+    File(OsString),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+
+    #[map(Arg::Width(w) => w)]
+    width: u16,
+
+    #[map(Arg::Output(o) => Some(o))]
+    output: Option<OsString>,
+
+    #[collect(set(Arg::File))]
+    files: Vec<OsString>,
+}
+
+/// Write `contents` to a file that is unique to this test.
+fn argfile(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("argfile-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn at(path: &std::path::Path) -> String {
+    format!("@{}", path.display())
+}
+
+#[test]
+fn options_and_positionals() {
+    let path = argfile("mixed", "--width=3\nfoo bar\n\n\"\"\n\" spaced \"\n-a\n");
+    let s = Settings::try_parse(["test".into(), "first".into(), at(&path), "last".into()]).unwrap();
+    assert!(s.all);
+    assert_eq!(s.width, 3);
+    assert_eq!(s.files, ["first", "foo bar", "", " spaced ", "last"]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn option_values_are_not_read() {
+    let s = Settings::try_parse(["test", "-o", "@out"]).unwrap();
+    assert_eq!(s.output.unwrap(), "@out");
+    assert!(s.files.is_empty());
+}
+
+#[test]
+fn without_attribute() {
+    #[derive(Clone, Arguments)]
+    enum Arg {
+        #[positional(..)]
+        File(OsString),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[collect(set(Arg::File))]
+        files: Vec<OsString>,
+    }
+
+    let s = Settings::try_parse(["test", "@missing"]).unwrap();
+    assert_eq!(s.files, ["@missing"]);
+}
+
+#[test]
+fn nested() {
+    let inner = argfile("inner", "x\n");
+    let outer = argfile("outer", &format!("-a\n{}\n", at(&inner)));
+    let Err(Error::Custom(err)) = Settings::try_parse(["test".into(), at(&outer)]) else {
+        panic!("expected an error for a nested argument file");
+    };
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "argument files cannot be nested: '{}' found in '{}'",
+            at(&inner),
+            outer.display()
+        )
+    );
+
+    std::fs::remove_file(inner).unwrap();
+    std::fs::remove_file(outer).unwrap();
+}
+
+#[test]
+fn unreadable() {
+    let path = std::env::temp_dir().join("argfile-test-does-not-exist");
+    let Err(Error::Custom(err)) = Settings::try_parse(["test".into(), at(&path)]) else {
+        panic!("expected an error for a missing argument file");
+    };
+    assert!(err.to_string().starts_with(&format!(
+        "cannot read arguments from '{}': ",
+        path.display()
+    )));
+}
+
+#[test]
+fn not_after_end_of_options() {
+    let s = Settings::try_parse(["test", "-a", "--", "@no-such-file", "-w"]).unwrap();
+    assert!(s.all);
+    assert_eq!(s.files, ["@no-such-file", "-w"]);
+
+    // Nor after the first operand when options cannot follow operands
+    fn posixly_correct(name: &str) -> Option<OsString> {
+        (name == "POSIXLY_CORRECT").then(OsString::new)
+    }
+    let config = ParseConfig {
+        env: posixly_correct,
+        ..ParseConfig::default()
+    };
+    let s = Settings::try_parse_with_config(["test", "first", "@no-such-file"], config).unwrap();
+    assert_eq!(s.files, ["first", "@no-such-file"]);
+}
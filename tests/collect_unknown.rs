@@ -0,0 +1,79 @@
+use std::ffi::OsString;
+
+use uutils_args::{Argument, Arguments, Options};
+
+#[derive(Clone, Debug, PartialEq, Eq, Arguments)]
+#[arguments(collect_unknown = Unknown)]
+enum Arg {
+    #[option("-v", "--verbose")]
+    Verbose,
+
+    #[option("-k DURATION", "--kill-after=DURATION")]
+    KillAfter(String),
+
+    #[positional(1, command)]
+    Command(OsString),
+
+    #[positional(..)]
+    Args(Vec<OsString>),
+
+    Unknown(OsString),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Verbose => true)]
+    verbose: bool,
+
+    #[map(Arg::KillAfter(k) => Some(k))]
+    kill_after: Option<String>,
+
+    #[map(Arg::Command(c) => c)]
+    command: OsString,
+
+    #[map(Arg::Args(a) => a)]
+    args: Vec<OsString>,
+
+    #[collect(set(Arg::Unknown))]
+    unknown: Vec<OsString>,
+}
+
+#[test]
+fn help_is_not_collected() {
+    let mut iter = Arg::parse(["timeout", "--help"]);
+    assert!(matches!(iter.next_arg(), Ok(Some(Argument::Help))));
+
+    let mut iter = Arg::parse(["timeout", "--hel"]);
+    assert!(matches!(iter.next_arg(), Ok(Some(Argument::Help))));
+}
+
+#[test]
+fn long_with_value() {
+    let mut iter = Arg::parse(["timeout", "--definitely-not-ours=3"]);
+    assert!(matches!(
+        iter.next_arg(),
+        Ok(Some(Argument::Custom(Arg::Unknown(u)))) if u == "--definitely-not-ours=3"
+    ));
+    assert_eq!(iter.flag(), "--definitely-not-ours");
+}
+
+#[test]
+fn spelling_is_preserved() {
+    let s = Settings::try_parse([
+        "timeout", "--foo", "--bar=", "-xyz", "-vq1", "--verb", "-k", "5", "10", "sleep", "--foo",
+        "1",
+    ])
+    .unwrap();
+    assert!(s.verbose);
+    assert_eq!(s.kill_after.unwrap(), "5");
+    assert_eq!(s.unknown, ["--foo", "--bar=", "-xyz", "-q1"]);
+    assert_eq!(s.command, "10");
+    assert_eq!(s.args, ["sleep", "--foo", "1"]);
+}
+
+#[test]
+fn known_options_still_fail() {
+    assert!(Settings::try_parse(["timeout", "--verbose=1", "10"]).is_err());
+    assert!(Settings::try_parse(["timeout", "-k"]).is_err());
+}
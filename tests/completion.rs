@@ -0,0 +1,356 @@
+use std::path::PathBuf;
+
+use uutils_args::{complete, Arguments, FromValue};
+
+#[derive(Clone, FromValue)]
+enum When {
+    #[value]
+    Always,
+    #[value]
+    Never,
+}
+
+#[derive(Clone, FromValue)]
+enum Format {
+    /// Print everything
+    #[value("long")]
+    Long,
+    #[value("short", help = "Print only the names")]
+    Short,
+    #[value("raw")]
+    Raw,
+}
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Arg {
+    /// Show all
+    #[option("-a", "--all")]
+    All,
+
+    /// Use a directory
+    #[option("-d DIR", "--dir=DIR")]
+    Dir(PathBuf),
+
+    /// Colorize
+    #[option("--color[=WHEN]")]
+    Color(Option<When>),
+
+    /// Not shown
+    #[option("--secret", hidden)]
+    Secret,
+}
+
+#[test]
+fn fish() {
+    assert_eq!(
+        complete::render(&Arg::complete(), "fish"),
+        format!(
+            "\
+complete -c {name} -s a -l all -d 'Show all'
+complete -c {name} -s d -l dir -d 'Use a directory' -r -F
+complete -c {name} -l color -d 'Colorize' -f -a 'always never'
+complete -c {name} -l help -d 'Display this help message'
+complete -c {name} -l version -d 'Display version information'
+",
+            name = Arg::NAME
+        )
+    );
+}
+
+#[test]
+fn fish_value_descriptions() {
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Output format
+        #[option("--format=FORMAT")]
+        Format(Format),
+    }
+
+    let out = complete::render(&Arg::complete(), "fish");
+    assert!(out.contains(&format!(
+        "complete -c {} -l format -d 'Output format' -r -f -a 'long\\t\"Print everything\" short\\t\"Print only the names\" raw'\n",
+        Arg::NAME
+    )));
+}
+
+#[test]
+fn fish_positional() {
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Output format
+        #[positional]
+        Format(Format),
+
+        #[positional(..)]
+        Files(PathBuf),
+    }
+
+    let c = Arg::complete();
+    assert_eq!(c.positional.len(), 2);
+    assert_eq!(c.positional[0].name, "FORMAT");
+    assert_eq!(c.positional[0].num_args, 1..=1);
+    assert_eq!(c.positional[1].name, "FILES");
+    assert_eq!(c.positional[1].num_args, 0..=usize::MAX);
+    assert!(!c.positional[1].last);
+
+    let out = complete::render(&c, "fish");
+    assert!(out.contains(&format!(
+        "complete -c {} -d 'Output format' -f -a 'long\\t",
+        Arg::NAME
+    )));
+    assert!(out.ends_with(&format!("complete -c {} -F\n", Arg::NAME)));
+}
+
+#[test]
+fn fish_hint_override() {
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Set the owner
+        #[option("--owner=OWNER", hint = Username)]
+        Owner(String),
+
+        /// Connect to a host
+        #[option("--host=HOST", hint = Hostname)]
+        Host(String),
+
+        /// Run a program
+        #[option("--program=PROGRAM", hint = ExecutablePath)]
+        Program(String),
+
+        /// Read a file
+        #[option("--file=FILE", hint = FilePath)]
+        File(String),
+
+        /// Change to a directory
+        #[option("--dir=DIR", hint = DirPath)]
+        Dir(PathBuf),
+
+        /// Not overridden
+        #[option("--name=NAME")]
+        Name(String),
+    }
+
+    assert_eq!(
+        complete::render(&Arg::complete(), "fish"),
+        format!(
+            "\
+complete -c {name} -l owner -d 'Set the owner' -r -f -a '(__fish_complete_users)'
+complete -c {name} -l host -d 'Connect to a host' -r -f -a '(__fish_print_hostnames)'
+complete -c {name} -l program -d 'Run a program' -r -F
+complete -c {name} -l file -d 'Read a file' -r -F
+complete -c {name} -l dir -d 'Change to a directory' -r -f -a '(__fish_complete_directories)'
+complete -c {name} -l name -d 'Not overridden' -r -f
+complete -c {name} -l help -d 'Display this help message'
+complete -c {name} -l version -d 'Display version information'
+",
+            name = Arg::NAME
+        )
+    );
+}
+
+fn signals() -> Vec<String> {
+    ["HUP", "INT", "KILL"].map(String::from).to_vec()
+}
+
+#[test]
+fn fish_hint_fn() {
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Send SIGNAL
+        #[option("-s SIGNAL", "--signal=SIGNAL", hint_fn = signals)]
+        Signal(String),
+
+        #[positional(.., hint_fn = self::signals)]
+        Pids(String),
+    }
+
+    let out = complete::render(&Arg::complete(), "fish");
+    assert!(out.contains(&format!(
+        "complete -c {} -s s -l signal -d 'Send SIGNAL' -r -f -a 'HUP INT KILL'\n",
+        Arg::NAME
+    )));
+    assert!(out.ends_with(&format!("complete -c {} -f -a 'HUP INT KILL'\n", Arg::NAME)));
+}
+
+#[test]
+fn fish_extra() {
+    #[derive(Clone, Arguments)]
+    #[arguments(completion_extra = "tests/fixtures/completion/extra.fish")]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Use a directory
+        #[option("--dir=DIR")]
+        Dir(PathBuf),
+    }
+
+    let extra = include_str!("fixtures/completion/extra.fish");
+    let c = Arg::complete();
+    assert_eq!(c.extra, [("fish", extra)]);
+
+    let out = complete::render(&c, "fish");
+    assert!(out.starts_with(&format!("complete -c {} -l dir", Arg::NAME)));
+    assert!(out.ends_with(&format!(
+        "complete -c {} -l version -d 'Display version information'\n{extra}",
+        Arg::NAME
+    )));
+
+    // The snippet belongs to fish only
+    assert!(!complete::render(&c, "bash").contains(extra));
+    assert!(!complete::render(&c, "zsh").contains(extra));
+}
+
+#[test]
+fn bash() {
+    let out = complete::render(&Arg::complete(), "bash");
+    assert!(out.contains("        -d|--dir)\n            COMPREPLY=($(compgen -f"));
+    assert!(out.contains("compgen -W \"always never\""));
+    assert!(out.contains("\"-a --all -d --dir --color --help --version\""));
+    assert!(!out.contains("--secret"));
+}
+
+#[test]
+fn zsh() {
+    let out = complete::render(&Arg::complete(), "zsh");
+    assert!(out.starts_with(&format!("#compdef {}\n", Arg::NAME)));
+    assert!(out.contains("'(-a --all)'{-a,--all}'[Show all]'"));
+    assert!(out.contains("'(-d --dir)'{-d+,--dir=}'[Use a directory]:DIR:_files'"));
+    assert!(out.contains("'--color=-[Colorize]::WHEN:(always never)'"));
+}
+
+#[test]
+fn powershell() {
+    let out = complete::render(&Arg::complete(), "powershell");
+    assert_eq!(out, complete::render(&Arg::complete(), "pwsh"));
+    assert!(out.contains(&format!("-CommandName '{}'", Arg::NAME)));
+    assert!(out.contains("'--color=' { 'always', 'never'; break }"));
+    assert!(out.contains("'-d' { return }"));
+    assert!(out.contains("::new('--all', '--all', 'ParameterName', 'Show all')"));
+    assert!(!out.contains("--secret"));
+}
+
+#[test]
+fn nushell() {
+    assert_eq!(
+        complete::render(&Arg::complete(), "nu"),
+        format!(
+            "\
+export extern \"{name}\" [
+    --all(-a) # Show all
+    --dir(-d): path # Use a directory
+    --color: string # Colorize
+    --version # Display version information
+    ...args
+]
+",
+            name = Arg::NAME
+        )
+    );
+    assert_eq!(
+        complete::render(&Arg::complete(), "nu"),
+        complete::render(&Arg::complete(), "nushell")
+    );
+}
+
+#[test]
+fn man() {
+    let out = complete::render(&Arg::complete(), "man");
+    assert!(out.starts_with(&format!(
+        ".TH {} 1",
+        Arg::NAME.to_uppercase().replace('-', "\\-")
+    )));
+    assert!(out.contains(".TP\n\\fB\\-a\\fR, \\fB\\-\\-all\\fR\nShow all\n"));
+    assert!(out.contains(
+        ".TP\n\\fB\\-d\\fR \\fIDIR\\fR, \\fB\\-\\-dir\\fR=\\fIDIR\\fR\nUse a directory\n"
+    ));
+    assert!(out.contains(".TP\n\\fB\\-\\-color\\fR[=\\fIWHEN\\fR]\nColorize\n"));
+    assert!(!out.contains("secret"));
+}
+
+#[test]
+fn hidden_help_and_complete() {
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Show all
+        #[option("-a", "--all")]
+        All,
+
+        /// Completed but undocumented
+        #[option("--internal", hidden_help)]
+        Internal,
+
+        /// Documented but not completed
+        #[option("--rare", hidden_complete)]
+        Rare,
+
+        /// Hidden everywhere
+        #[option("---presume-input-pipe", hidden)]
+        PresumeInputPipe,
+    }
+
+    let help = Arg::help("test");
+    let c = Arg::complete();
+    let fish = complete::render(&c, "fish");
+    let man = complete::render(&c, "man");
+
+    assert!(help.contains("--all") && fish.contains("-l all") && man.contains("\\-\\-all"));
+
+    assert!(!help.contains("--internal"));
+    assert!(fish.contains("-l internal -d 'Completed but undocumented'"));
+    assert!(!man.contains("internal"));
+
+    assert!(help.contains("--rare"));
+    assert!(!fish.contains("rare"));
+    assert!(man.contains(".TP\n\\fB\\-\\-rare\\fR\nDocumented but not completed\n"));
+
+    assert!(!help.contains("presume"));
+    assert!(!fish.contains("presume"));
+    assert!(!man.contains("presume"));
+    assert!(c
+        .args
+        .iter()
+        .all(|arg| arg.long.iter().all(|f| f.flag != "-presume-input-pipe")));
+}
+
+mod md {
+    use super::When;
+    use uutils_args::{complete, Arguments};
+
+    #[derive(Clone, Arguments)]
+    #[arguments(file = "tests/fixtures/md/help.md")]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Reverse the result
+        #[option("-r", "--reverse")]
+        Reverse,
+
+        /// Use N columns per tab
+        #[option("--tabsize[=N]", default = 8)]
+        Tabsize(u64),
+
+        /// Use color
+        #[option("--color[=WHEN]", default = When::Always)]
+        Color(When),
+
+        /// Write to FILE
+        #[option("-o FILE", "--output=FILE")]
+        Output(std::path::PathBuf),
+
+        /// Not shown
+        #[option("--secret", hidden)]
+        Secret,
+    }
+
+    #[test]
+    fn golden() {
+        let expected = include_str!("fixtures/md/golden.md")
+            .replace("{name}", Arg::NAME)
+            .replace("{version}", env!("CARGO_PKG_VERSION"));
+        assert_eq!(complete::render(&Arg::complete(), "md"), expected);
+    }
+}
@@ -0,0 +1,135 @@
+//! Parses on different threads must not influence each other.
+
+use std::{ffi::OsString, sync::Barrier, thread};
+
+use uutils_args::{Arguments, Error, HelpConfig, Options, ParseConfig};
+
+#[derive(Clone, Arguments)]
+enum LsArg {
+    #[option("-w COLS", "--width=COLS")]
+    Width(u16),
+
+    #[option("--sort=WORD")]
+    Sort(String),
+
+    #[option("--size")]
+    Size,
+}
+
+#[derive(Default, Options)]
+#[arg_type(LsArg)]
+struct LsSettings {
+    #[set(LsArg::Width)]
+    #[field(env = "COLUMNS")]
+    width: u16,
+
+    #[set(LsArg::Sort)]
+    sort: String,
+
+    #[map(LsArg::Size => true)]
+    size: bool,
+}
+
+#[derive(Clone, Arguments)]
+enum DateArg {
+    #[option("-u", "--utc", "--universal")]
+    Utc,
+}
+
+#[derive(Default, Options)]
+#[arg_type(DateArg)]
+struct DateSettings {
+    #[map(DateArg::Utc => "UTC".into())]
+    #[field(env = "TZ")]
+    tz: String,
+}
+
+fn ls_env(name: &str) -> Option<OsString> {
+    (name == "COLUMNS").then(|| "120".into())
+}
+
+fn date_env(name: &str) -> Option<OsString> {
+    (name == "TZ").then(|| "Europe/Amsterdam".into())
+}
+
+fn ls_config() -> ParseConfig {
+    ParseConfig {
+        help: HelpConfig {
+            name: "dir",
+            ..HelpConfig::default()
+        },
+        env: ls_env,
+        candidate_limit: 1,
+        ..ParseConfig::default()
+    }
+}
+
+fn date_config() -> ParseConfig {
+    ParseConfig {
+        env: date_env,
+        ..ParseConfig::default()
+    }
+}
+
+#[test]
+fn config_per_parse() {
+    let ls = LsSettings::try_parse_with_config(["ls"], ls_config()).unwrap();
+    assert_eq!(ls.width, 120);
+
+    let ls = LsSettings::try_parse_with_config(["ls", "-w", "80"], date_config()).unwrap();
+    assert_eq!(ls.width, 80);
+
+    let date = DateSettings::try_parse_with_config(["date"], date_config()).unwrap();
+    assert_eq!(date.tz, "Europe/Amsterdam");
+
+    let date = DateSettings::try_parse_with_config(["date", "-u"], date_config()).unwrap();
+    assert_eq!(date.tz, "UTC");
+
+    let date = DateSettings::try_parse_with_config(["date"], ls_config()).unwrap();
+    assert_eq!(date.tz, "");
+}
+
+#[test]
+fn concurrent_parses() {
+    const ROUNDS: usize = 200;
+    let barrier = Barrier::new(2);
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            barrier.wait();
+            for _ in 0..ROUNDS {
+                let ls = LsSettings::try_parse_with_config(["ls"], ls_config()).unwrap();
+                assert_eq!(ls.width, 120);
+
+                let iter = LsArg::parse(Vec::<String>::new()).with_config(ls_config());
+                assert_eq!(iter.bin_name(), "dir");
+                assert!(iter.help().contains("Usage:\n  dir "));
+
+                let Err(err) = LsSettings::try_parse_with_config(["ls", "--s"], ls_config()) else {
+                    panic!("expected an ambiguous option");
+                };
+                assert!(matches!(err, Error::AmbiguousOption { .. }));
+                let message = err.display_with(&ls_config()).to_string();
+                assert!(message.ends_with("\n  - --sort\n  and 1 more"));
+            }
+        });
+
+        s.spawn(|| {
+            barrier.wait();
+            for _ in 0..ROUNDS {
+                let date = DateSettings::try_parse_with_config(["date"], date_config()).unwrap();
+                assert_eq!(date.tz, "Europe/Amsterdam");
+
+                let iter = DateArg::parse(Vec::<String>::new()).with_config(date_config());
+                assert_eq!(iter.bin_name(), DateArg::NAME);
+
+                let Err(err) = LsSettings::try_parse_with_config(["ls", "--s"], date_config())
+                else {
+                    panic!("expected an ambiguous option");
+                };
+                let message = err.display_with(&date_config()).to_string();
+                assert!(message.ends_with("\n  - --sort\n  - --size"));
+            }
+        });
+    });
+}
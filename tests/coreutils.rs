@@ -1,20 +1,13 @@
-#[path = "coreutils/arch.rs"]
-mod arch;
-
-#[path = "coreutils/b2sum.rs"]
-mod b2sum;
-
-#[path = "coreutils/base32.rs"]
-mod base32;
-
-#[path = "coreutils/basename.rs"]
-mod basename;
-
-#[path = "coreutils/cat.rs"]
-mod cat;
-
-#[path = "coreutils/mktemp.rs"]
-mod mktemp;
-
-#[path = "coreutils/ls.rs"]
-mod ls;
+//! Tests of the argument handling of the GNU coreutils
+//!
+//! The fixtures are parsed from `&str` arguments here. The same fixtures are
+//! parsed from `String`, `OsString` and `Cow<OsStr>` arguments by the
+//! `coreutils_*` test crates, because the result must not depend on the item
+//! type of the arguments.
+
+/// The arguments as the item type that the fixtures are parsed with
+fn items(args: impl IntoIterator<Item = &'static str>) -> Vec<&'static str> {
+    args.into_iter().collect()
+}
+
+include!("coreutils/fixtures.rs");
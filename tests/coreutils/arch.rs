@@ -1,4 +1,4 @@
-use uutils_args::{Arguments, Options};
+use uutils_args::{Arguments, Error, Options};
 
 #[derive(Clone, Arguments)]
 enum Arg {}
@@ -9,12 +9,63 @@ struct Settings {}
 
 #[test]
 fn no_args() {
-    assert!(Settings::try_parse(["arch"]).is_ok());
+    assert!(Settings::try_parse(crate::items(["arch"])).is_ok());
 }
 
 #[test]
 fn one_arg_fails() {
-    assert!(Settings::try_parse(["arch", "-f"]).is_err());
-    assert!(Settings::try_parse(["arch", "--foo"]).is_err());
-    assert!(Settings::try_parse(["arch", "foo"]).is_err());
+    assert!(Settings::try_parse(crate::items(["arch", "-f"])).is_err());
+    assert!(Settings::try_parse(crate::items(["arch", "--foo"])).is_err());
+    assert!(Settings::try_parse(crate::items(["arch", "foo"])).is_err());
+}
+
+#[test]
+fn extra_operand() {
+    let Err(err) = Settings::try_parse(crate::items(["arch", "x86"])) else {
+        panic!("expected an error");
+    };
+    assert!(matches!(&err, Error::ExtraOperand(operand) if operand == "x86"));
+    assert_eq!(err.to_string(), "extra operand 'x86'");
+
+    let Err(err) = Settings::try_parse(crate::items(["arch", "--", "-x"])) else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.to_string(), "extra operand '-x'");
+}
+
+#[cfg(unix)]
+#[test]
+fn extra_operand_non_unicode() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    let operand = OsString::from_vec(vec![b'a', 0xff, b'\n']);
+    let Err(err) = Settings::try_parse([OsString::from("arch"), operand.clone()]) else {
+        panic!("expected an error");
+    };
+    assert!(matches!(&err, Error::ExtraOperand(o) if *o == operand));
+    assert_eq!(err.to_string(), "extra operand 'a\\xff\\n'");
+}
+
+#[test]
+fn extra_operand_points_to_help() {
+    // The exit path is checked in a copy of this test binary.
+    if std::env::var_os("ARCH_EXTRA_OPERAND").is_some() {
+        Settings::parse(crate::items(["arch", "x86"]));
+        return;
+    }
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "arch::extra_operand_points_to_help",
+            "--nocapture",
+        ])
+        .env("ARCH_EXTRA_OPERAND", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "arch: extra operand 'x86'\nTry 'arch --help' for more information.\n"
+    );
 }
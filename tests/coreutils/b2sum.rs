@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use uutils_args::{Arguments, Options};
+use uutils_args::{Arguments, Error, Options};
 
 #[derive(Clone, Arguments)]
 enum Arg {
@@ -28,7 +28,7 @@ enum Arg {
     #[option("-w", "--warn")]
     Warn,
 
-    #[positional(..)]
+    #[positional(.., min_if(Arg::Check, 1))]
     File(PathBuf),
 }
 
@@ -55,7 +55,8 @@ struct Settings {
     #[map(Arg::Tag => true)]
     tag: bool,
 
-    #[map(
+    #[choice(
+        exhaustive,
         Arg::Warn => CheckOutput::Warn,
         Arg::Quiet => CheckOutput::Quiet,
         Arg::Status => CheckOutput::Status,
@@ -71,48 +72,48 @@ struct Settings {
 
 #[test]
 fn binary() {
-    assert!(!Settings::parse(["b2sum"]).binary);
-    assert!(!Settings::parse(["b2sum", "--text"]).binary);
-    assert!(!Settings::parse(["b2sum", "-t"]).binary);
-    assert!(!Settings::parse(["b2sum", "--binary", "--text"]).binary);
-    assert!(!Settings::parse(["b2sum", "-b", "-t"]).binary);
-
-    assert!(Settings::parse(["b2sum", "--binary"]).binary);
-    assert!(Settings::parse(["b2sum", "-b"]).binary);
-    assert!(Settings::parse(["b2sum", "--text", "--binary"]).binary);
-    assert!(Settings::parse(["b2sum", "-t", "-b"]).binary);
+    assert!(!Settings::parse(crate::items(["b2sum"])).binary);
+    assert!(!Settings::parse(crate::items(["b2sum", "--text"])).binary);
+    assert!(!Settings::parse(crate::items(["b2sum", "-t"])).binary);
+    assert!(!Settings::parse(crate::items(["b2sum", "--binary", "--text"])).binary);
+    assert!(!Settings::parse(crate::items(["b2sum", "-b", "-t"])).binary);
+
+    assert!(Settings::parse(crate::items(["b2sum", "--binary"])).binary);
+    assert!(Settings::parse(crate::items(["b2sum", "-b"])).binary);
+    assert!(Settings::parse(crate::items(["b2sum", "--text", "--binary"])).binary);
+    assert!(Settings::parse(crate::items(["b2sum", "-t", "-b"])).binary);
 }
 
 #[test]
 fn check_output() {
     assert_eq!(
-        Settings::parse(["b2sum", "--warn"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--warn"])).check_output,
         CheckOutput::Warn
     );
     assert_eq!(
-        Settings::parse(["b2sum", "--quiet"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--quiet"])).check_output,
         CheckOutput::Quiet
     );
     assert_eq!(
-        Settings::parse(["b2sum", "--status"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--status"])).check_output,
         CheckOutput::Status
     );
     assert_eq!(
-        Settings::parse(["b2sum", "--status", "--warn"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--status", "--warn"])).check_output,
         CheckOutput::Warn
     );
     assert_eq!(
-        Settings::parse(["b2sum", "--status", "--warn"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--status", "--warn"])).check_output,
         CheckOutput::Warn
     );
 
     assert_eq!(
-        Settings::parse(["b2sum", "--warn", "--quiet"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--warn", "--quiet"])).check_output,
         CheckOutput::Quiet
     );
 
     assert_eq!(
-        Settings::parse(["b2sum", "--quiet", "--status"]).check_output,
+        Settings::parse(crate::items(["b2sum", "--quiet", "--status"])).check_output,
         CheckOutput::Status
     );
 }
@@ -120,7 +121,30 @@ fn check_output() {
 #[test]
 fn files() {
     assert_eq!(
-        Settings::parse(["b2sum", "foo", "bar"]).files,
+        Settings::parse(crate::items(["b2sum", "foo", "bar"])).files,
         vec![Path::new("foo"), Path::new("bar")]
     );
 }
+
+#[test]
+fn check_requires_file() {
+    assert!(Settings::parse(crate::items(["b2sum"])).files.is_empty());
+
+    let s = Settings::parse(crate::items(["b2sum", "--check", "sums.txt"]));
+    assert!(s.check);
+    assert_eq!(s.files, vec![Path::new("sums.txt")]);
+
+    for args in [&["b2sum", "--check"][..], &["b2sum", "-c", "--quiet"]] {
+        let Err(err) = Settings::try_parse(crate::items(args.iter().copied())) else {
+            panic!("expected a missing operand for {args:?}");
+        };
+        assert!(matches!(
+            &err,
+            Error::MissingOperandWith { operand, option } if operand == "FILE" && option == "--check"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "missing FILE operand (required with --check)"
+        );
+    }
+}
@@ -39,8 +39,14 @@ struct Settings {
 
 #[test]
 fn wrap() {
-    assert_eq!(Settings::parse(["base32"]).wrap, Some(76));
-    assert_eq!(Settings::parse(["base32", "-w0"]).wrap, None);
-    assert_eq!(Settings::parse(["base32", "-w100"]).wrap, Some(100));
-    assert_eq!(Settings::parse(["base32", "--wrap=100"]).wrap, Some(100));
+    assert_eq!(Settings::parse(crate::items(["base32"])).wrap, Some(76));
+    assert_eq!(Settings::parse(crate::items(["base32", "-w0"])).wrap, None);
+    assert_eq!(
+        Settings::parse(crate::items(["base32", "-w100"])).wrap,
+        Some(100)
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["base32", "--wrap=100"])).wrap,
+        Some(100)
+    );
 }
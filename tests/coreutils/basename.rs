@@ -32,7 +32,7 @@ struct Settings {
 }
 
 fn parse(args: &'static [&'static str]) -> Settings {
-    let mut settings = Settings::parse(args);
+    let mut settings = Settings::parse(crate::items(args.iter().copied()));
     if !settings.multiple {
         assert_eq!(settings.names.len(), 2);
         settings.suffix = settings.names.pop().unwrap();
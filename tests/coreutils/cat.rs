@@ -39,7 +39,7 @@ enum Arg {
     #[option("-v", "--show-nonprinting")]
     ShowNonPrinting,
 
-    #[positional(..)]
+    #[positional(.., default = "-")]
     File(PathBuf),
 }
 
@@ -76,27 +76,49 @@ struct Settings {
 
 #[test]
 fn show() {
-    let s = Settings::parse(["cat", "-v"]);
+    let s = Settings::parse(crate::items(["cat", "-v"]));
     assert!(!s.show_ends && !s.show_tabs && s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-E"]);
+    let s = Settings::parse(crate::items(["cat", "-E"]));
     assert!(s.show_ends && !s.show_tabs && !s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-T"]);
+    let s = Settings::parse(crate::items(["cat", "-T"]));
     assert!(!s.show_ends && s.show_tabs && !s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-e"]);
+    let s = Settings::parse(crate::items(["cat", "-e"]));
     assert!(s.show_ends && !s.show_tabs && s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-t"]);
+    let s = Settings::parse(crate::items(["cat", "-t"]));
     assert!(!s.show_ends && s.show_tabs && s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-A"]);
+    let s = Settings::parse(crate::items(["cat", "-A"]));
     assert!(s.show_ends && s.show_tabs && s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-te"]);
+    let s = Settings::parse(crate::items(["cat", "-te"]));
     assert!(s.show_ends && s.show_tabs && s.show_nonprinting);
 
-    let s = Settings::parse(["cat", "-vET"]);
+    let s = Settings::parse(crate::items(["cat", "-vET"]));
     assert!(s.show_ends && s.show_tabs && s.show_nonprinting);
 }
+
+#[test]
+fn default_file() {
+    let s = Settings::parse(crate::items(["cat"]));
+    assert_eq!(s.files, vec![PathBuf::from("-")]);
+
+    let s = Settings::parse(crate::items(["cat", "-n"]));
+    assert_eq!(s.files, vec![PathBuf::from("-")]);
+    assert!(matches!(s.number, NumberingMode::All));
+}
+
+#[test]
+fn given_files() {
+    let s = Settings::parse(crate::items(["cat", "foo"]));
+    assert_eq!(s.files, vec![PathBuf::from("foo")]);
+
+    let s = Settings::parse(crate::items(["cat", "foo", "-n", "bar"]));
+    assert_eq!(s.files, vec![PathBuf::from("foo"), PathBuf::from("bar")]);
+
+    let s = Settings::parse(crate::items(["cat", "--", "-"]));
+    assert_eq!(s.files, vec![PathBuf::from("-")]);
+}
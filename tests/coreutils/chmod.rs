@@ -0,0 +1,120 @@
+//! `chmod` takes modes like `-w` that look like short options, so its
+//! unrecognized short options are operands.
+
+use std::path::PathBuf;
+
+use uutils_args::{Arguments, Error, Options};
+
+#[derive(Clone, Arguments)]
+#[arguments(unrecognized_short_as_positional)]
+enum Arg {
+    /// Like verbose but report only when a change is made
+    #[option("-c", "--changes")]
+    Changes,
+
+    /// Suppress most error messages
+    #[option("-f", "--silent", "--quiet")]
+    Quiet,
+
+    /// Output a diagnostic for every file processed
+    #[option("-v", "--verbose")]
+    Verbose,
+
+    /// Use RFILE's mode instead of specifying MODE values
+    #[option("--reference=RFILE")]
+    Reference(PathBuf),
+
+    /// Change files and directories recursively
+    #[option("-R", "--recursive")]
+    Recursive,
+
+    #[positional]
+    Mode(String),
+
+    #[positional(1..)]
+    File(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Changes => true)]
+    changes: bool,
+
+    #[map(Arg::Quiet => true)]
+    quiet: bool,
+
+    #[map(Arg::Verbose => true)]
+    verbose: bool,
+
+    #[map(Arg::Reference(f) => Some(f))]
+    reference: Option<PathBuf>,
+
+    #[map(Arg::Recursive => true)]
+    recursive: bool,
+
+    #[set(Arg::Mode)]
+    mode: String,
+
+    #[collect(set(Arg::File))]
+    files: Vec<PathBuf>,
+}
+
+#[test]
+fn mode_like_an_option() {
+    let s = Settings::parse(crate::items(["chmod", "-w", "-R", "file"]));
+    assert!(s.recursive);
+    assert_eq!(s.mode, "-w");
+    assert_eq!(s.files, [PathBuf::from("file")]);
+
+    let s = Settings::parse(crate::items(["chmod", "-R", "-w", "file"]));
+    assert!(s.recursive);
+    assert_eq!(s.mode, "-w");
+}
+
+#[test]
+fn rest_of_cluster() {
+    // The rest of the cluster belongs to the mode
+    let s = Settings::parse(crate::items(["chmod", "-rwx", "file"]));
+    assert_eq!(s.mode, "-rwx");
+    assert!(!s.recursive);
+
+    // Options before the mode in the cluster are still options
+    let s = Settings::parse(crate::items(["chmod", "-vw", "file"]));
+    assert!(s.verbose);
+    assert_eq!(s.mode, "-w");
+}
+
+#[test]
+fn other_modes() {
+    let s = Settings::parse(crate::items(["chmod", "-c", "u+x,g-w", "a", "b"]));
+    assert!(s.changes);
+    assert_eq!(s.mode, "u+x,g-w");
+    assert_eq!(s.files, [PathBuf::from("a"), PathBuf::from("b")]);
+
+    let s = Settings::parse(crate::items(["chmod", "--quiet", "644", "--", "-file"]));
+    assert!(s.quiet);
+    assert_eq!(s.mode, "644");
+    assert_eq!(s.files, [PathBuf::from("-file")]);
+
+    let s = Settings::parse(crate::items(["chmod", "--reference=ref", "-x", "file"]));
+    assert_eq!(s.reference, Some("ref".into()));
+    assert_eq!(s.mode, "-x");
+}
+
+#[test]
+fn errors() {
+    // Long options are not operands
+    assert!(matches!(
+        Settings::try_parse(crate::items(["chmod", "--write", "file"])),
+        Err(Error::UnexpectedOption(_))
+    ));
+
+    assert_eq!(
+        Settings::try_parse(crate::items(["chmod", "-w"]))
+            .err()
+            .unwrap()
+            .to_string(),
+        "missing FILE operand"
+    );
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-f", "--force")]
+    Force,
+
+    #[option("-v", "--verbose")]
+    Verbose,
+
+    #[positional(1..)]
+    Source(PathBuf),
+
+    #[positional(from_end = 1)]
+    Dest(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Force => true)]
+    force: bool,
+
+    #[map(Arg::Verbose => true)]
+    verbose: bool,
+
+    #[collect(set(Arg::Source))]
+    sources: Vec<PathBuf>,
+
+    #[set(Arg::Dest)]
+    dest: PathBuf,
+}
+
+fn error(args: &'static [&'static str]) -> String {
+    match Settings::try_parse(crate::items(args.iter().copied())) {
+        Ok(_) => panic!("expected an error for {args:?}"),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn no_operands() {
+    assert_eq!(error(&["cp"]), "missing operands: SOURCE, DEST");
+}
+
+#[test]
+fn one_operand() {
+    assert_eq!(error(&["cp", "a"]), "missing DEST operand");
+    assert_eq!(error(&["cp", "-f", "a"]), "missing DEST operand");
+}
+
+#[test]
+fn two_operands() {
+    let settings = Settings::parse(crate::items(["cp", "a", "b"]));
+    assert_eq!(settings.sources, vec![PathBuf::from("a")]);
+    assert_eq!(settings.dest, PathBuf::from("b"));
+}
+
+#[test]
+fn three_operands() {
+    let settings = Settings::parse(crate::items(["cp", "a", "b", "c"]));
+    assert_eq!(
+        settings.sources,
+        vec![PathBuf::from("a"), PathBuf::from("b")]
+    );
+    assert_eq!(settings.dest, PathBuf::from("c"));
+}
+
+#[test]
+fn options_between_operands() {
+    let settings = Settings::parse(crate::items(["cp", "a", "-f", "b", "c", "-v"]));
+    assert!(settings.force);
+    assert!(settings.verbose);
+    assert_eq!(
+        settings.sources,
+        vec![PathBuf::from("a"), PathBuf::from("b")]
+    );
+    assert_eq!(settings.dest, PathBuf::from("c"));
+}
+
+#[test]
+fn option_like_operands() {
+    let settings = Settings::parse(crate::items(["cp", "-f", "--", "-a", "-v"]));
+    assert!(settings.force);
+    assert!(!settings.verbose);
+    assert_eq!(settings.sources, vec![PathBuf::from("-a")]);
+    assert_eq!(settings.dest, PathBuf::from("-v"));
+}
+
+#[test]
+fn usage() {
+    assert!(Arg::help("cp").contains("cp [OPTION]... SOURCE... DEST"));
+}
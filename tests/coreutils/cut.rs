@@ -0,0 +1,202 @@
+//! `cut` selects parts of lines by a list of ranges and splits fields at a
+//! single-character delimiter.
+
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Error, FromValue, Options};
+
+/// A range of a list like `1,3-5,7-`, counted from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Range {
+    start: usize,
+    end: usize,
+}
+
+impl FromValue for Range {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let invalid = |value: OsString| Error::ParsingFailed {
+            option: option.into(),
+            value,
+            error: "invalid field range".into(),
+        };
+        let Some(s) = value.to_str() else {
+            return Err(invalid(value));
+        };
+        let number = |n: &str, default: usize| match n {
+            "" => Some(default),
+            n => n.parse().ok().filter(|n| *n > 0),
+        };
+        let range = match s.split_once('-') {
+            Some(("", "")) => None,
+            Some((start, end)) => number(start, 1).zip(number(end, usize::MAX)),
+            None => number(s, 0).filter(|n| *n > 0).map(|n| (n, n)),
+        };
+        match range {
+            Some((start, end)) if start <= end => Ok(Self { start, end }),
+            _ => Err(invalid(value)),
+        }
+    }
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Select only these bytes
+    #[option("-b LIST", "--bytes=LIST", delimiter = ',')]
+    Bytes(Vec<Range>),
+
+    /// Select only these characters
+    #[option("-c LIST", "--characters=LIST", delimiter = ',')]
+    Characters(Vec<Range>),
+
+    /// Use DELIM instead of TAB for field delimiter
+    #[option("-d DELIM", "--delimiter=DELIM")]
+    Delimiter(char),
+
+    /// Select only these fields
+    #[option("-f LIST", "--fields=LIST", delimiter = ',')]
+    Fields(Vec<Range>),
+
+    /// Complement the set of selected bytes, characters or fields
+    #[option("--complement")]
+    Complement,
+
+    /// Do not print lines not containing delimiters
+    #[option("-s", "--only-delimited")]
+    OnlyDelimited,
+
+    /// Use STRING as the output delimiter
+    #[option("--output-delimiter=STRING")]
+    OutputDelimiter(String),
+
+    /// Line delimiter is NUL, not newline
+    #[option("-z", "--zero-terminated")]
+    ZeroTerminated,
+
+    #[positional(..)]
+    File(OsString),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    None,
+    Bytes(Vec<Range>),
+    Characters(Vec<Range>),
+    Fields(Vec<Range>),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(
+        Arg::Bytes(r) => Mode::Bytes(r),
+        Arg::Characters(r) => Mode::Characters(r),
+        Arg::Fields(r) => Mode::Fields(r),
+    )]
+    mode: Mode,
+
+    #[set(Arg::Delimiter)]
+    #[field(default = '\t')]
+    delimiter: char,
+
+    #[map(Arg::Complement => true)]
+    complement: bool,
+
+    #[map(Arg::OnlyDelimited => true)]
+    only_delimited: bool,
+
+    #[map(Arg::OutputDelimiter(s) => Some(s))]
+    output_delimiter: Option<String>,
+
+    #[map(Arg::ZeroTerminated => b'\0')]
+    #[field(default = b'\n')]
+    line_end: u8,
+
+    #[collect(set(Arg::File))]
+    files: Vec<OsString>,
+}
+
+fn range(start: usize, end: usize) -> Range {
+    Range { start, end }
+}
+
+fn delimiter(value: &'static str) -> Result<char, Error> {
+    Settings::try_parse(crate::items(["cut", "-f1", "-d", value])).map(|s| s.delimiter)
+}
+
+#[test]
+fn fields() {
+    let s = Settings::parse(crate::items([
+        "cut",
+        "-d:",
+        "-f",
+        "1,3-5,7-",
+        "/etc/passwd",
+    ]));
+    assert_eq!(s.delimiter, ':');
+    assert_eq!(
+        s.mode,
+        Mode::Fields(vec![range(1, 1), range(3, 5), range(7, usize::MAX)])
+    );
+    assert_eq!(s.files, ["/etc/passwd"]);
+
+    let s = Settings::parse(crate::items(["cut", "-c-3", "--complement"]));
+    assert_eq!(s.mode, Mode::Characters(vec![range(1, 3)]));
+    assert!(s.complement);
+    assert_eq!(s.delimiter, '\t');
+}
+
+#[test]
+fn invalid_ranges() {
+    for list in ["0", "3-1", "-", "a", "1,,2"] {
+        assert!(
+            Settings::try_parse(crate::items(["cut", "-b", list])).is_err(),
+            "{list} must be rejected"
+        );
+    }
+}
+
+#[test]
+fn single_character_delimiter() {
+    assert_eq!(delimiter(",").unwrap(), ',');
+    assert_eq!(delimiter(" ").unwrap(), ' ');
+    // A single character of more than one byte
+    assert_eq!(delimiter("é").unwrap(), 'é');
+    assert_eq!(delimiter("→").unwrap(), '→');
+}
+
+#[test]
+fn escaped_delimiter() {
+    assert_eq!(delimiter("\\t").unwrap(), '\t');
+    assert_eq!(delimiter("\\n").unwrap(), '\n');
+    assert_eq!(delimiter("\\\\").unwrap(), '\\');
+    assert_eq!(delimiter("\\0").unwrap(), '\0');
+    // A single backslash is just a backslash
+    assert_eq!(delimiter("\\").unwrap(), '\\');
+}
+
+#[test]
+fn invalid_delimiter() {
+    assert_eq!(
+        delimiter("ab").unwrap_err().to_string(),
+        "invalid argument 'ab' for '-d': the delimiter must be a single character"
+    );
+    for value in ["", "\\x", "::", "éé"] {
+        assert!(delimiter(value).is_err(), "{value:?} must be rejected");
+    }
+}
+
+#[test]
+fn other_options() {
+    let s = Settings::parse(crate::items([
+        "cut",
+        "-sz",
+        "--output-delimiter= | ",
+        "--delimiter=;",
+        "-f2",
+    ]));
+    assert!(s.only_delimited);
+    assert_eq!(s.line_end, b'\0');
+    assert_eq!(s.output_delimiter.as_deref(), Some(" | "));
+    assert_eq!(s.delimiter, ';');
+}
@@ -0,0 +1,467 @@
+//! `dd` takes its settings as operands of the form `NAME=VALUE`, which are
+//! declared as keyword options. The examples are from the GNU documentation
+//! of `dd`.
+
+use std::{ffi::OsString, path::PathBuf};
+
+use uutils_args::{Arguments, Error, FromValue, Options};
+
+const CONVERSIONS: &[&str] = &[
+    "ascii",
+    "ebcdic",
+    "ibm",
+    "block",
+    "unblock",
+    "lcase",
+    "ucase",
+    "sparse",
+    "swab",
+    "sync",
+    "excl",
+    "nocreat",
+    "notrunc",
+    "noerror",
+    "fdatasync",
+    "fsync",
+];
+
+const FLAGS: &[&str] = &[
+    "append",
+    "cio",
+    "direct",
+    "directory",
+    "dsync",
+    "sync",
+    "fullblock",
+    "nonblock",
+    "noatime",
+    "nocache",
+    "noctty",
+    "nofollow",
+    "count_bytes",
+    "skip_bytes",
+    "seek_bytes",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromValue)]
+enum Status {
+    #[value("none")]
+    None,
+    #[value("noxfer")]
+    NoXfer,
+    #[value("progress")]
+    Progress,
+}
+
+fn invalid(operand: &str, value: &str) -> Error {
+    Error::ParsingFailed {
+        option: operand.into(),
+        value: value.into(),
+        error: "invalid number".into(),
+    }
+}
+
+/// A number of bytes, with the suffixes of `dd` and `x` for a product, like
+/// `2x512`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Bytes(u64);
+
+impl FromValue for Bytes {
+    fn from_value(operand: &str, value: OsString) -> Result<Self, Error> {
+        let value = value.into_string().map_err(Error::NonUnicodeValue)?;
+        value
+            .split('x')
+            .try_fold(1u64, |product, factor| {
+                let digits_end = factor
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(factor.len());
+                let (digits, suffix) = factor.split_at(digits_end);
+                let multiplier: u64 = match suffix {
+                    "" | "c" => 1,
+                    "w" => 2,
+                    "b" => 512,
+                    "kB" => 1000,
+                    "K" | "KiB" => 1024,
+                    "MB" => 1000 * 1000,
+                    "M" | "MiB" => 1024 * 1024,
+                    "GB" => 1000 * 1000 * 1000,
+                    "G" | "GiB" => 1024 * 1024 * 1024,
+                    _ => return Err(invalid(operand, &value)),
+                };
+                digits
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|n| n.checked_mul(multiplier))
+                    .and_then(|n| n.checked_mul(product))
+                    .ok_or_else(|| invalid(operand, &value))
+            })
+            .map(Self)
+    }
+}
+
+/// A comma-separated list of the `allowed` symbols
+fn symbols(operand: &str, value: OsString, allowed: &[&str]) -> Result<Vec<String>, Error> {
+    let value = value.into_string().map_err(Error::NonUnicodeValue)?;
+    value
+        .split(',')
+        .map(|symbol| match allowed.contains(&symbol) {
+            true => Ok(symbol.to_string()),
+            false => Err(Error::InvalidValue {
+                option: operand.into(),
+                value: symbol.into(),
+                expected: allowed.iter().map(|s| s.to_string()).collect(),
+            }),
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Conversions(Vec<String>);
+
+impl FromValue for Conversions {
+    fn from_value(operand: &str, value: OsString) -> Result<Self, Error> {
+        symbols(operand, value, CONVERSIONS).map(Self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Flags(Vec<String>);
+
+impl FromValue for Flags {
+    fn from_value(operand: &str, value: OsString) -> Result<Self, Error> {
+        symbols(operand, value, FLAGS).map(Self)
+    }
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Read from FILE instead of stdin
+    #[option(keyword = "if=FILE")]
+    Input(PathBuf),
+
+    /// Write to FILE instead of stdout
+    #[option(keyword = "of=FILE")]
+    Output(PathBuf),
+
+    /// Read and write up to BYTES bytes at a time
+    #[option(keyword = "bs=BYTES")]
+    Bs(Bytes),
+
+    /// Read up to BYTES bytes at a time
+    #[option(keyword = "ibs=BYTES")]
+    Ibs(Bytes),
+
+    /// Write BYTES bytes at a time
+    #[option(keyword = "obs=BYTES")]
+    Obs(Bytes),
+
+    /// Convert BYTES bytes at a time
+    #[option(keyword = "cbs=BYTES")]
+    Cbs(Bytes),
+
+    /// Copy only N input blocks
+    #[option(keyword = "count=N")]
+    Count(Bytes),
+
+    /// Skip N ibs-sized input blocks
+    #[option(keyword = "skip=N", keyword = "iseek=N")]
+    Skip(Bytes),
+
+    /// Skip N obs-sized output blocks
+    #[option(keyword = "seek=N", keyword = "oseek=N")]
+    Seek(Bytes),
+
+    /// Convert the file as per the comma separated symbol list
+    #[option(keyword = "conv=CONVS")]
+    Conv(Conversions),
+
+    /// Read as per the comma separated symbol list
+    #[option(keyword = "iflag=FLAGS")]
+    Iflag(Flags),
+
+    /// Write as per the comma separated symbol list
+    #[option(keyword = "oflag=FLAGS")]
+    Oflag(Flags),
+
+    /// The level of information to print to stderr
+    #[option(keyword = "status=LEVEL")]
+    Status(Status),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Input(f) => Some(f))]
+    input: Option<PathBuf>,
+
+    #[map(Arg::Output(f) => Some(f))]
+    output: Option<PathBuf>,
+
+    #[map(Arg::Bs(Bytes(n)) => Some(n))]
+    bs: Option<u64>,
+
+    #[map(Arg::Ibs(Bytes(n)) => Some(n))]
+    ibs: Option<u64>,
+
+    #[map(Arg::Obs(Bytes(n)) => Some(n))]
+    obs: Option<u64>,
+
+    #[map(Arg::Cbs(Bytes(n)) => Some(n))]
+    cbs: Option<u64>,
+
+    #[map(Arg::Count(Bytes(n)) => Some(n))]
+    count: Option<u64>,
+
+    #[map(Arg::Skip(Bytes(n)) => n)]
+    skip: u64,
+
+    #[map(Arg::Seek(Bytes(n)) => n)]
+    seek: u64,
+
+    #[collect(map(Arg::Conv(Conversions(c)) => c))]
+    conv: Vec<Vec<String>>,
+
+    #[collect(map(Arg::Iflag(Flags(f)) => f))]
+    iflag: Vec<Vec<String>>,
+
+    #[collect(map(Arg::Oflag(Flags(f)) => f))]
+    oflag: Vec<Vec<String>>,
+
+    #[map(Arg::Status(s) => Some(s))]
+    status: Option<Status>,
+}
+
+impl Settings {
+    /// `bs` overrides `ibs`, wherever it is given
+    fn input_block_size(&self) -> u64 {
+        self.bs.or(self.ibs).unwrap_or(512)
+    }
+
+    fn output_block_size(&self) -> u64 {
+        self.bs.or(self.obs).unwrap_or(512)
+    }
+
+    fn conversions(&self) -> Vec<&str> {
+        self.conv.iter().flatten().map(String::as_str).collect()
+    }
+
+    fn input_flags(&self) -> Vec<&str> {
+        self.iflag.iter().flatten().map(String::as_str).collect()
+    }
+}
+
+#[test]
+fn defaults() {
+    let s = Settings::parse(crate::items(["dd"]));
+    assert_eq!(s.input, None);
+    assert_eq!(s.output, None);
+    assert_eq!(s.input_block_size(), 512);
+    assert_eq!(s.output_block_size(), 512);
+    assert_eq!(s.count, None);
+    assert_eq!(s.skip, 0);
+    assert_eq!(s.seek, 0);
+    assert!(s.conversions().is_empty());
+    assert_eq!(s.status, None);
+}
+
+#[test]
+fn copy_a_disk() {
+    // dd if=/dev/sda of=disk.img bs=4M
+    let s = Settings::parse(crate::items(["dd", "if=/dev/sda", "of=disk.img", "bs=4M"]));
+    assert_eq!(s.input, Some("/dev/sda".into()));
+    assert_eq!(s.output, Some("disk.img".into()));
+    assert_eq!(s.input_block_size(), 4 * 1024 * 1024);
+    assert_eq!(s.output_block_size(), 4 * 1024 * 1024);
+}
+
+#[test]
+fn block_size_suffixes() {
+    let bs = |value: &str| {
+        let operand = format!("bs={value}");
+        let s = Settings::try_parse(["dd".to_string(), operand]).unwrap();
+        s.bs.unwrap()
+    };
+    assert_eq!(bs("17"), 17);
+    assert_eq!(bs("17c"), 17);
+    assert_eq!(bs("3w"), 6);
+    assert_eq!(bs("2b"), 1024);
+    assert_eq!(bs("1kB"), 1000);
+    assert_eq!(bs("1K"), 1024);
+    assert_eq!(bs("1KiB"), 1024);
+    assert_eq!(bs("1MB"), 1_000_000);
+    assert_eq!(bs("1M"), 1024 * 1024);
+    assert_eq!(bs("1G"), 1024 * 1024 * 1024);
+    // A product of sizes, like `2x80` for two cards of 80 bytes
+    assert_eq!(bs("2x80"), 160);
+    assert_eq!(bs("2x1K"), 2048);
+    assert_eq!(bs("2x3x4"), 24);
+}
+
+#[test]
+fn invalid_numbers() {
+    for operand in [
+        "bs=",
+        "bs=x",
+        "bs=1Q",
+        "count=-1",
+        "skip=1.5",
+        "bs=99999999999G",
+    ] {
+        assert!(
+            Settings::try_parse(crate::items(["dd", operand])).is_err(),
+            "{operand} must be rejected"
+        );
+    }
+}
+
+#[test]
+fn bs_overrides_ibs_and_obs() {
+    let s = Settings::parse(crate::items(["dd", "ibs=1K", "obs=2K"]));
+    assert_eq!(s.input_block_size(), 1024);
+    assert_eq!(s.output_block_size(), 2048);
+
+    // Also when `bs` comes first
+    let s = Settings::parse(crate::items(["dd", "bs=4K", "ibs=1K", "obs=2K"]));
+    assert_eq!(s.input_block_size(), 4096);
+    assert_eq!(s.output_block_size(), 4096);
+}
+
+#[test]
+fn skip_and_seek() {
+    // Copy a part of a file: skip the first 2 blocks of input and write it
+    // after the first block of output.
+    let s = Settings::parse(crate::items([
+        "dd", "if=a", "of=b", "skip=2", "seek=1", "count=3",
+    ]));
+    assert_eq!(s.skip, 2);
+    assert_eq!(s.seek, 1);
+    assert_eq!(s.count, Some(3));
+
+    // `iseek` and `oseek` are the same as `skip` and `seek`
+    let s = Settings::parse(crate::items(["dd", "iseek=5", "oseek=6"]));
+    assert_eq!(s.skip, 5);
+    assert_eq!(s.seek, 6);
+}
+
+#[test]
+fn conversions_accumulate() {
+    // dd conv=ucase,notrunc conv=sync
+    let s = Settings::parse(crate::items([
+        "dd",
+        "conv=ucase,notrunc",
+        "conv=sync",
+        "cbs=80",
+    ]));
+    assert_eq!(s.conversions(), ["ucase", "notrunc", "sync"]);
+    assert_eq!(s.cbs, Some(80));
+
+    let Err(err) = Settings::try_parse(crate::items(["dd", "conv=ucase,upcase"])) else {
+        panic!("an unknown conversion must be rejected");
+    };
+    assert!(err.to_string().contains("'upcase'"), "{err}");
+}
+
+#[test]
+fn flags() {
+    // Count in bytes instead of blocks: dd iflag=skip_bytes,count_bytes
+    let s = Settings::parse(crate::items([
+        "dd",
+        "iflag=skip_bytes,count_bytes",
+        "oflag=append",
+        "skip=3",
+    ]));
+    assert_eq!(s.input_flags(), ["skip_bytes", "count_bytes"]);
+    assert_eq!(s.oflag, [vec!["append".to_string()]]);
+    assert!(Settings::try_parse(crate::items(["dd", "iflag=fast"])).is_err());
+}
+
+#[test]
+fn status() {
+    assert_eq!(
+        Settings::parse(crate::items(["dd", "status=progress"])).status,
+        Some(Status::Progress)
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["dd", "status=none"])).status,
+        Some(Status::None)
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["dd", "status=noxfer"])).status,
+        Some(Status::NoXfer)
+    );
+    assert!(Settings::try_parse(crate::items(["dd", "status=loud"])).is_err());
+}
+
+#[test]
+fn last_operand_wins() {
+    let s = Settings::parse(crate::items(["dd", "if=a", "count=1", "if=b", "count=2"]));
+    assert_eq!(s.input, Some("b".into()));
+    assert_eq!(s.count, Some(2));
+}
+
+#[test]
+fn file_names_with_equals_and_dashes() {
+    // Only the first `=` separates the name of the operand.
+    let s = Settings::parse(crate::items(["dd", "of=a=b", "if=-"]));
+    assert_eq!(s.output, Some("a=b".into()));
+    assert_eq!(s.input, Some("-".into()));
+}
+
+#[test]
+fn unrecognized_operands() {
+    let Err(err) = Settings::try_parse(crate::items(["dd", "foo=bar"])) else {
+        panic!("an unknown operand must be rejected");
+    };
+    assert_eq!(err.to_string(), "unrecognized operand 'foo=bar'");
+
+    let Err(err) = Settings::try_parse(crate::items(["dd", "input"])) else {
+        panic!("an operand without a value must be rejected");
+    };
+    assert_eq!(err.to_string(), "unrecognized operand 'input'");
+}
+
+#[test]
+fn options_are_not_operands() {
+    // `dd` has no options apart from `--help` and `--version`.
+    assert!(Settings::try_parse(crate::items(["dd", "-v"])).is_err());
+    assert!(Settings::try_parse(crate::items(["dd", "--bs=1"])).is_err());
+
+    // After `--`, everything is an operand.
+    let s = Settings::parse(crate::items(["dd", "--", "if=x"]));
+    assert_eq!(s.input, Some("x".into()));
+}
+
+#[cfg(unix)]
+#[test]
+fn non_unicode_file_names() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let operand = OsString::from_vec(b"of=out\xff".to_vec());
+    let s = Settings::try_parse([OsString::from("dd"), operand]).unwrap();
+    assert_eq!(
+        s.output.unwrap().into_os_string().into_vec(),
+        b"out\xff".to_vec()
+    );
+
+    let operand = OsString::from_vec(b"bs=1\xff".to_vec());
+    assert!(Settings::try_parse([OsString::from("dd"), operand]).is_err());
+}
+
+#[test]
+fn invalid_value_names_the_operand() {
+    let Err(err) = Settings::try_parse(crate::items(["dd", "count=x"])) else {
+        panic!("an invalid count must be rejected");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid argument 'x' for 'count': invalid number"
+    );
+}
+
+#[test]
+fn help() {
+    let help = Arg::help("dd");
+    assert!(help.contains("  bs=BYTES"));
+    assert!(help.contains("  skip=N, iseek=N"));
+    assert!(help.contains("Read from FILE instead of stdin"));
+}
@@ -0,0 +1,116 @@
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-i", "--ignore-environment")]
+    IgnoreEnvironment,
+
+    #[option("-0", "--null")]
+    Null,
+
+    #[option("-u NAME", "--unset=NAME")]
+    Unset(String),
+
+    #[option("-C DIR", "--chdir=DIR")]
+    Chdir(OsString),
+
+    /// The assignments followed by the command and its arguments
+    #[positional(.., last)]
+    Operands(Vec<OsString>),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::IgnoreEnvironment => true)]
+    ignore_environment: bool,
+
+    #[map(Arg::Null => true)]
+    null: bool,
+
+    #[collect(set(Arg::Unset))]
+    unset: Vec<String>,
+
+    #[set(Arg::Chdir)]
+    chdir: OsString,
+
+    #[set(Arg::Operands)]
+    operands: Vec<OsString>,
+}
+
+impl Settings {
+    /// Split the operands into the assignments and the command line
+    fn split(&self) -> (&[OsString], &[OsString]) {
+        let n = self
+            .operands
+            .iter()
+            .take_while(|s| s.to_string_lossy().contains('='))
+            .count();
+        self.operands.split_at(n)
+    }
+}
+
+#[test]
+fn no_operands() {
+    let s = Settings::parse(crate::items(["env", "-i"]));
+    assert!(s.ignore_environment);
+    assert!(s.operands.is_empty());
+}
+
+#[test]
+fn command_flags_are_verbatim() {
+    let s = Settings::parse(crate::items(["env", "-i", "FOO=bar", "cmd", "--flag"]));
+    assert!(s.ignore_environment);
+    let (assignments, command) = s.split();
+    assert_eq!(assignments, ["FOO=bar"]);
+    assert_eq!(command, ["cmd", "--flag"]);
+}
+
+#[test]
+fn utility_options_after_command() {
+    let s = Settings::parse(crate::items(["env", "cmd", "-i", "-u", "FOO", "--null"]));
+    assert!(!s.ignore_environment);
+    assert!(!s.null);
+    assert!(s.unset.is_empty());
+    assert_eq!(s.operands, ["cmd", "-i", "-u", "FOO", "--null"]);
+}
+
+#[test]
+fn options_before_operands() {
+    let s = Settings::parse(crate::items([
+        "env",
+        "-0",
+        "-u",
+        "FOO",
+        "--unset=BAR",
+        "-C",
+        "/tmp",
+        "cmd",
+    ]));
+    assert!(s.null);
+    assert_eq!(s.unset, ["FOO", "BAR"]);
+    assert_eq!(s.chdir, "/tmp");
+    assert_eq!(s.operands, ["cmd"]);
+}
+
+#[test]
+fn double_dash() {
+    let s = Settings::parse(crate::items(["env", "-i", "--", "-u", "FOO", "--", "x"]));
+    assert!(s.ignore_environment);
+    assert!(s.unset.is_empty());
+    assert_eq!(s.operands, ["-u", "FOO", "--", "x"]);
+
+    let s = Settings::parse(crate::items(["env", "--", "A=1", "cmd", "--flag"]));
+    let (assignments, command) = s.split();
+    assert_eq!(assignments, ["A=1"]);
+    assert_eq!(command, ["cmd", "--flag"]);
+}
+
+#[test]
+fn double_dash_without_operands() {
+    let s = Settings::parse(crate::items(["env", "-i", "--"]));
+    assert!(s.ignore_environment);
+    assert!(s.operands.is_empty());
+}
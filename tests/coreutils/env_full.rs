@@ -0,0 +1,329 @@
+//! The full interface of `env`: the options, a lone `-`, the assignments and
+//! the command, and `-S` to split a single argument into several, which is
+//! how options reach `env` on a `#!` line. The arguments of the split string
+//! are parsed like the command line, so they can contain options of `env`
+//! too. The examples are from the GNU documentation of `env`.
+
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Error, FromValue, Options};
+
+/// The arguments of `-S`, split like a shell would
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SplitString(Vec<OsString>);
+
+impl FromValue for SplitString {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+        let mut args = Vec::new();
+        let mut current: Option<String> = None;
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            let quote = match c {
+                ' ' | '\t' | '\n' => {
+                    args.extend(current.take().map(OsString::from));
+                    continue;
+                }
+                '#' if current.is_none() => break,
+                '\'' | '"' => c,
+                '\\' => {
+                    let escaped = escape(option, &value, chars.next())?;
+                    current.get_or_insert_with(String::new).push(escaped);
+                    continue;
+                }
+                c => {
+                    current.get_or_insert_with(String::new).push(c);
+                    continue;
+                }
+            };
+            let word = current.get_or_insert_with(String::new);
+            loop {
+                match chars.next() {
+                    Some(c) if c == quote => break,
+                    Some('\\') if quote == '"' => word.push(escape(option, &value, chars.next())?),
+                    Some(c) => word.push(c),
+                    None => {
+                        return Err(Error::ParsingFailed {
+                            option: option.into(),
+                            value: value.into(),
+                            error: "no terminating quote".into(),
+                        })
+                    }
+                }
+            }
+        }
+        args.extend(current.map(OsString::from));
+        Ok(Self(args))
+    }
+}
+
+/// The character for the escape sequence ending in `c`
+fn escape(option: &str, value: &str, c: Option<char>) -> Result<char, Error> {
+    Ok(match c {
+        Some('_') => ' ',
+        Some('t') => '\t',
+        Some('n') => '\n',
+        Some(c @ ('\\' | '"' | '\'' | '$' | '#')) => c,
+        _ => {
+            return Err(Error::ParsingFailed {
+                option: option.into(),
+                value: value.into(),
+                error: "invalid backslash sequence".into(),
+            })
+        }
+    })
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-a ARG", "--argv0=ARG")]
+    Argv0(OsString),
+
+    #[option("-", "-i", "--ignore-environment")]
+    IgnoreEnvironment,
+
+    #[option("-0", "--null")]
+    Null,
+
+    #[option("-u NAME", "--unset=NAME")]
+    Unset(String),
+
+    #[option("-C DIR", "--chdir=DIR")]
+    Chdir(OsString),
+
+    #[option("-S S", "--split-string=S")]
+    Split(SplitString),
+
+    #[option("--block-signal[=SIG]")]
+    BlockSignal(Option<String>),
+
+    #[option("--default-signal[=SIG]")]
+    DefaultSignal(Option<String>),
+
+    #[option("--ignore-signal[=SIG]")]
+    IgnoreSignal(Option<String>),
+
+    #[option("--list-signal-handling")]
+    ListSignalHandling,
+
+    #[option("-v", "--debug")]
+    Debug,
+
+    /// The assignments followed by the command and its arguments
+    #[positional(.., last)]
+    Operands(Vec<OsString>),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Argv0(a) => Some(a))]
+    argv0: Option<OsString>,
+
+    #[map(Arg::IgnoreEnvironment => true)]
+    ignore_environment: bool,
+
+    #[map(Arg::Null => true)]
+    null: bool,
+
+    #[collect(set(Arg::Unset))]
+    unset: Vec<String>,
+
+    #[map(Arg::Chdir(d) => Some(d))]
+    chdir: Option<OsString>,
+
+    #[map(Arg::Split(s) => Some(s.0))]
+    split: Option<Vec<OsString>>,
+
+    /// The signals, where `None` is all of them
+    #[collect(map(Arg::BlockSignal(s) => s))]
+    block_signal: Vec<Option<String>>,
+
+    #[collect(map(Arg::DefaultSignal(s) => s))]
+    default_signal: Vec<Option<String>>,
+
+    #[collect(map(Arg::IgnoreSignal(s) => s))]
+    ignore_signal: Vec<Option<String>>,
+
+    #[map(Arg::ListSignalHandling => true)]
+    list_signal_handling: bool,
+
+    #[map(Arg::Debug => self.debug + 1)]
+    debug: u8,
+
+    #[set(Arg::Operands)]
+    operands: Vec<OsString>,
+}
+
+impl Settings {
+    /// Parse the arguments and then the arguments from `-S` in their place,
+    /// followed by the operands
+    fn parse_env<I: IntoIterator<Item = &'static str>>(args: I) -> Result<Self, Error> {
+        let mut settings = Self::try_parse(crate::items(args))?;
+        while let Some(split) = settings.split.take() {
+            let args: Vec<OsString> = std::iter::once("env".into())
+                .chain(split)
+                .chain(settings.operands.drain(..))
+                .collect();
+            let (inner, mask) = Self::try_parse_with_mask(args)?;
+            settings.merge_from(inner, &mask);
+        }
+        Ok(settings)
+    }
+
+    /// Split the operands into the assignments and the command line
+    fn split(&self) -> (&[OsString], &[OsString]) {
+        let n = self
+            .operands
+            .iter()
+            .take_while(|s| s.to_string_lossy().contains('='))
+            .count();
+        self.operands.split_at(n)
+    }
+
+    /// The signals of the options, with `*` for all of them
+    fn signals(signals: &[Option<String>]) -> Vec<&str> {
+        signals
+            .iter()
+            .flat_map(|s| s.as_deref().unwrap_or("*").split(','))
+            .collect()
+    }
+}
+
+#[test]
+fn clean_environment() {
+    // env -i PATH=/mybin:$PATH HOME=/ sh -c 'echo $HOME'
+    let s = Settings::parse_env([
+        "env",
+        "-i",
+        "PATH=/mybin",
+        "HOME=/",
+        "sh",
+        "-c",
+        "echo $HOME",
+    ])
+    .unwrap();
+    assert!(s.ignore_environment);
+    let (assignments, command) = s.split();
+    assert_eq!(assignments, ["PATH=/mybin", "HOME=/"]);
+    assert_eq!(command, ["sh", "-c", "echo $HOME"]);
+}
+
+#[test]
+fn lone_dash_is_ignore_environment() {
+    // env - A=1 cmd
+    let s = Settings::parse_env(["env", "-", "A=1", "cmd"]).unwrap();
+    assert!(s.ignore_environment);
+    assert_eq!(s.operands, ["A=1", "cmd"]);
+
+    // After the first operand, `-` is part of the command.
+    let s = Settings::parse_env(["env", "cat", "-"]).unwrap();
+    assert!(!s.ignore_environment);
+    assert_eq!(s.operands, ["cat", "-"]);
+}
+
+#[test]
+fn print_environment() {
+    // Without a command, the environment is printed: env -0 -u HOME
+    let s = Settings::parse_env(["env", "-0", "-u", "HOME", "--unset=PWD"]).unwrap();
+    assert!(s.null);
+    assert_eq!(s.unset, ["HOME", "PWD"]);
+    assert!(s.operands.is_empty());
+}
+
+#[test]
+fn chdir_and_argv0() {
+    // env -C /tmp -a shell bash
+    let s = Settings::parse_env(["env", "-C", "/tmp", "-a", "shell", "bash"]).unwrap();
+    assert_eq!(s.chdir.as_deref(), Some("/tmp".as_ref()));
+    assert_eq!(s.argv0.as_deref(), Some("shell".as_ref()));
+    assert_eq!(s.operands, ["bash"]);
+
+    let s = Settings::parse_env(["env", "--chdir=/", "--argv0=x", "pwd"]).unwrap();
+    assert_eq!(s.chdir.as_deref(), Some("/".as_ref()));
+    assert_eq!(s.argv0.as_deref(), Some("x".as_ref()));
+}
+
+#[test]
+fn shebang_split_string() {
+    // `#!/usr/bin/env -S perl -w -T` runs the script like
+    // env '-S perl -w -T' ./script.pl
+    let s = Settings::parse_env(["env", "-S perl -w -T", "./script.pl"]).unwrap();
+    assert_eq!(s.operands, ["perl", "-w", "-T", "./script.pl"]);
+
+    let s = Settings::parse_env(["env", "--split-string=python3 -u", "x.py"]).unwrap();
+    assert_eq!(s.operands, ["python3", "-u", "x.py"]);
+}
+
+#[test]
+fn split_string_with_options() {
+    // The split string can start with options of env:
+    // #!/usr/bin/env -S -i -u HOME A=1 cmd
+    let s = Settings::parse_env(["env", "-v", "-S -i -u HOME A=1 cmd", "arg"]).unwrap();
+    assert_eq!(s.debug, 1);
+    assert!(s.ignore_environment);
+    assert_eq!(s.unset, ["HOME"]);
+    let (assignments, command) = s.split();
+    assert_eq!(assignments, ["A=1"]);
+    assert_eq!(command, ["cmd", "arg"]);
+}
+
+#[test]
+fn split_string_quotes_and_escapes() {
+    let split = |value: &str| SplitString::from_value("-S", value.into()).map(|s| s.0);
+    assert_eq!(split("a 'b c' \"d e\"").unwrap(), ["a", "b c", "d e"]);
+    assert_eq!(split("a\\_b \"c\\td\"").unwrap(), ["a b", "c\td"]);
+    assert_eq!(split("  a   b  ").unwrap(), ["a", "b"]);
+    assert_eq!(split("a'' ''").unwrap(), ["a", ""]);
+    assert_eq!(split("cmd # a comment").unwrap(), ["cmd"]);
+    assert!(split("'unterminated").is_err());
+    assert!(split("a\\qb").is_err());
+}
+
+#[test]
+fn signals() {
+    let s = Settings::parse_env([
+        "env",
+        "--default-signal=INT,PIPE",
+        "--ignore-signal",
+        "--block-signal=TERM",
+        "--list-signal-handling",
+        "sleep",
+        "10",
+    ])
+    .unwrap();
+    assert_eq!(Settings::signals(&s.default_signal), ["INT", "PIPE"]);
+    assert_eq!(Settings::signals(&s.ignore_signal), ["*"]);
+    assert_eq!(Settings::signals(&s.block_signal), ["TERM"]);
+    assert!(s.list_signal_handling);
+    assert_eq!(s.operands, ["sleep", "10"]);
+
+    // An optional value must be attached.
+    let s = Settings::parse_env(["env", "--ignore-signal", "INT", "cmd"]).unwrap();
+    assert_eq!(Settings::signals(&s.ignore_signal), ["*"]);
+    assert_eq!(s.operands, ["INT", "cmd"]);
+}
+
+#[test]
+fn debug_count() {
+    let s = Settings::parse_env(["env", "-vv", "--debug", "true"]).unwrap();
+    assert_eq!(s.debug, 3);
+}
+
+#[test]
+fn options_end_at_the_first_operand() {
+    // Options after an assignment belong to the command.
+    let s = Settings::parse_env(["env", "A=1", "-i", "cmd"]).unwrap();
+    assert!(!s.ignore_environment);
+    let (assignments, command) = s.split();
+    assert_eq!(assignments, ["A=1"]);
+    assert_eq!(command, ["-i", "cmd"]);
+}
+
+#[test]
+fn invalid_options() {
+    assert!(Settings::parse_env(["env", "-x"]).is_err());
+    assert!(Settings::parse_env(["env", "-u"]).is_err());
+    assert!(Settings::parse_env(["env", "--null=1"]).is_err());
+    assert!(Settings::parse_env(["env", "-S", "'"]).is_err());
+}
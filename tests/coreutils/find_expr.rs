@@ -0,0 +1,241 @@
+//! `find` takes a few options, then the starting points and then an
+//! expression of primaries like `-name` that look like options. The
+//! primaries before the first operand are collected as unknown options, after
+//! it everything is taken as is. The examples are from the GNU documentation
+//! of `find`.
+
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Follow {
+    CommandLine,
+    Always,
+    #[default]
+    Never,
+}
+
+#[derive(Clone, Arguments)]
+#[arguments(collect_unknown = Primary)]
+enum Arg {
+    #[option("-H")]
+    CommandLine,
+
+    #[option("-L")]
+    Always,
+
+    #[option("-P")]
+    Never,
+
+    #[option("-D DEBUGOPTS")]
+    Debug(String),
+
+    #[option("-O LEVEL")]
+    Optimize(u8),
+
+    /// The starting points followed by the expression
+    #[positional(.., last)]
+    Rest(Vec<OsString>),
+
+    Primary(OsString),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[choice(Arg::CommandLine => Follow::CommandLine, Arg::Always => Follow::Always, Arg::Never => Follow::Never)]
+    follow: Follow,
+
+    #[collect(set(Arg::Debug))]
+    debug: Vec<String>,
+
+    #[field(default = 1)]
+    #[set(Arg::Optimize)]
+    optimize: u8,
+
+    #[collect(map(
+        Arg::Primary(p) => vec![p],
+        Arg::Rest(rest) => rest,
+    ))]
+    args: Vec<Vec<OsString>>,
+}
+
+impl Settings {
+    /// The arguments up to the first that starts an expression, or `.`
+    fn starting_points(&self) -> Vec<OsString> {
+        let points: Vec<_> = self
+            .args
+            .iter()
+            .flatten()
+            .take_while(|arg| !starts_expression(arg))
+            .cloned()
+            .collect();
+        if points.is_empty() {
+            vec![".".into()]
+        } else {
+            points
+        }
+    }
+
+    fn expression(&self) -> Vec<&OsString> {
+        self.args
+            .iter()
+            .flatten()
+            .skip_while(|arg| !starts_expression(arg))
+            .collect()
+    }
+
+    fn debug_options(&self) -> Vec<&str> {
+        self.debug.iter().flat_map(|d| d.split(',')).collect()
+    }
+}
+
+fn starts_expression(arg: &OsString) -> bool {
+    let arg = arg.to_string_lossy();
+    arg.starts_with('-') || arg == "(" || arg == "!"
+}
+
+#[test]
+fn name_and_type() {
+    // find . -name '*.c' -type f
+    let s = Settings::parse(crate::items(["find", ".", "-name", "*.c", "-type", "f"]));
+    assert_eq!(s.starting_points(), ["."]);
+    assert_eq!(s.expression(), ["-name", "*.c", "-type", "f"]);
+    assert_eq!(s.follow, Follow::Never);
+}
+
+#[test]
+fn several_starting_points() {
+    // find /usr/include /usr/local/include -maxdepth 1 -print
+    let s = Settings::parse(crate::items([
+        "find",
+        "/usr/include",
+        "/usr/local/include",
+        "-maxdepth",
+        "1",
+        "-print",
+    ]));
+    assert_eq!(s.starting_points(), ["/usr/include", "/usr/local/include"]);
+    assert_eq!(s.expression(), ["-maxdepth", "1", "-print"]);
+}
+
+#[test]
+fn no_starting_point() {
+    // The starting point is `.` if none is given: find -name foo -print
+    let s = Settings::parse(crate::items(["find", "-name", "foo", "-print"]));
+    assert_eq!(s.starting_points(), ["."]);
+    assert_eq!(s.expression(), ["-name", "foo", "-print"]);
+
+    let s = Settings::parse(crate::items(["find"]));
+    assert_eq!(s.starting_points(), ["."]);
+    assert!(s.expression().is_empty());
+}
+
+#[test]
+fn primaries_without_starting_point() {
+    // find -empty -delete
+    let s = Settings::parse(crate::items(["find", "-empty", "-delete"]));
+    assert_eq!(s.expression(), ["-empty", "-delete"]);
+
+    // Numeric arguments with a sign: find -mtime -7 -size +1M
+    let s = Settings::parse(crate::items(["find", "-mtime", "-7", "-size", "+1M"]));
+    assert_eq!(s.expression(), ["-mtime", "-7", "-size", "+1M"]);
+}
+
+#[test]
+fn symlink_options() {
+    // find -L /tmp -type l
+    let s = Settings::parse(crate::items(["find", "-L", "/tmp", "-type", "l"]));
+    assert_eq!(s.follow, Follow::Always);
+    assert_eq!(s.starting_points(), ["/tmp"]);
+    assert_eq!(s.expression(), ["-type", "l"]);
+
+    // The last of -H, -L and -P wins.
+    assert_eq!(
+        Settings::parse(crate::items(["find", "-H", "-L", "-P", "."])).follow,
+        Follow::Never
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["find", "-P", "-H", "."])).follow,
+        Follow::CommandLine
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["find", "-PL", "."])).follow,
+        Follow::Always
+    );
+}
+
+#[test]
+fn debug_and_optimization() {
+    // find -D tree,stat -O3 . -print
+    let s = Settings::parse(crate::items([
+        "find",
+        "-D",
+        "tree,stat",
+        "-O3",
+        ".",
+        "-print",
+    ]));
+    assert_eq!(s.debug_options(), ["tree", "stat"]);
+    assert_eq!(s.optimize, 3);
+    assert_eq!(s.starting_points(), ["."]);
+
+    assert_eq!(Settings::parse(crate::items(["find", "."])).optimize, 1);
+    assert!(Settings::try_parse(crate::items(["find", "-Ofast", "."])).is_err());
+    assert!(Settings::try_parse(crate::items(["find", "-D"])).is_err());
+}
+
+#[test]
+fn parentheses_and_negation() {
+    // find . \( -name '*.h' -o -name '*.c' \) -print
+    let s = Settings::parse(crate::items([
+        "find", ".", "(", "-name", "*.h", "-o", "-name", "*.c", ")", "-print",
+    ]));
+    assert_eq!(s.starting_points(), ["."]);
+    assert_eq!(
+        s.expression(),
+        ["(", "-name", "*.h", "-o", "-name", "*.c", ")", "-print"]
+    );
+
+    // find ! -name '*.o'
+    let s = Settings::parse(crate::items(["find", "!", "-name", "*.o"]));
+    assert_eq!(s.starting_points(), ["."]);
+    assert_eq!(s.expression(), ["!", "-name", "*.o"]);
+}
+
+#[test]
+fn exec_is_verbatim() {
+    // find . -name '*.tmp' -exec rm -f {} ;
+    let s = Settings::parse(crate::items([
+        "find", ".", "-name", "*.tmp", "-exec", "rm", "-f", "{}", ";",
+    ]));
+    assert_eq!(
+        s.expression(),
+        ["-name", "*.tmp", "-exec", "rm", "-f", "{}", ";"]
+    );
+
+    // The options of find are not recognized in the expression.
+    let s = Settings::parse(crate::items(["find", ".", "-exec", "ls", "-L", "{}", "+"]));
+    assert_eq!(s.follow, Follow::Never);
+    assert_eq!(s.expression(), ["-exec", "ls", "-L", "{}", "+"]);
+}
+
+#[test]
+fn double_dash() {
+    // The options can be ended explicitly: find -L -- . -print
+    let s = Settings::parse(crate::items(["find", "-L", "--", ".", "-print"]));
+    assert_eq!(s.follow, Follow::Always);
+    assert_eq!(s.starting_points(), ["."]);
+    assert_eq!(s.expression(), ["-print"]);
+}
+
+#[test]
+#[ignore = "missing: ending the options at the first unknown option"]
+fn option_like_value_of_primary() {
+    // The pattern `-L` is the value of `-name`, but after an unknown option
+    // the parser still recognizes the options of find.
+    let s = Settings::parse(crate::items(["find", "-name", "-L"]));
+    assert_eq!(s.follow, Follow::Never);
+    assert_eq!(s.expression(), ["-name", "-L"]);
+}
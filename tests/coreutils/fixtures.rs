@@ -0,0 +1,68 @@
+// The fixtures of the coreutils tests, included by each of the test crates
+// that parse them with a different item type
+
+#[path = "arch.rs"]
+mod arch;
+
+#[path = "b2sum.rs"]
+mod b2sum;
+
+#[path = "base32.rs"]
+mod base32;
+
+#[path = "basename.rs"]
+mod basename;
+
+#[path = "cat.rs"]
+mod cat;
+
+#[path = "chmod.rs"]
+mod chmod;
+
+#[path = "cp.rs"]
+mod cp;
+
+#[path = "cut.rs"]
+mod cut;
+
+#[path = "dd.rs"]
+mod dd;
+
+#[path = "env.rs"]
+mod env;
+
+#[path = "env_full.rs"]
+mod env_full;
+
+#[path = "find_expr.rs"]
+mod find_expr;
+
+#[path = "kill.rs"]
+mod kill;
+
+#[path = "mktemp.rs"]
+mod mktemp;
+
+#[path = "ls.rs"]
+mod ls;
+
+#[path = "sort_keys.rs"]
+mod sort_keys;
+
+#[path = "su.rs"]
+mod su;
+
+#[path = "tail.rs"]
+mod tail;
+
+#[path = "tar_legacy.rs"]
+mod tar_legacy;
+
+#[path = "timeout.rs"]
+mod timeout;
+
+#[path = "tr.rs"]
+mod tr;
+
+#[path = "unlink.rs"]
+mod unlink;
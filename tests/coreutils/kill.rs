@@ -0,0 +1,109 @@
+use uutils_args::{Arguments, Layers, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Send the signal SIG instead of TERM
+    #[option("-s SIG", "--signal=SIG")]
+    Signal(String),
+
+    /// List the signal names
+    #[option("-l", "--list", exclusive)]
+    List,
+
+    /// List the signal names with their numbers
+    #[option("-L", "--table", exclusive)]
+    Table,
+
+    #[positional(1..)]
+    Pid(i32),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Send,
+    List,
+    Table,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[field(default = "TERM".into())]
+    #[set(Arg::Signal)]
+    signal: String,
+
+    #[map(Arg::List => Mode::List, Arg::Table => Mode::Table)]
+    mode: Mode,
+
+    #[collect(set(Arg::Pid))]
+    pids: Vec<i32>,
+}
+
+#[test]
+fn send() {
+    let s = Settings::try_parse(crate::items(["kill", "-s", "KILL", "12", "34"])).unwrap();
+    assert_eq!(s.mode, Mode::Send);
+    assert_eq!(s.signal, "KILL");
+    assert_eq!(s.pids, [12, 34]);
+
+    let err = Settings::try_parse(crate::items(["kill", "-s", "KILL"]))
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "missing PID operand");
+}
+
+#[test]
+fn list_without_operands() {
+    let s = Settings::try_parse(crate::items(["kill", "--list"])).unwrap();
+    assert_eq!(s.mode, Mode::List);
+    assert!(s.pids.is_empty());
+
+    let s = Settings::try_parse(crate::items(["kill", "-L"])).unwrap();
+    assert_eq!(s.mode, Mode::Table);
+}
+
+#[test]
+fn arguments_after_exclusive_are_ignored() {
+    // Not even an invalid operand is an error
+    let s = Settings::try_parse(crate::items(["kill", "-l", "missing-required-operand"])).unwrap();
+    assert_eq!(s.mode, Mode::List);
+    assert!(s.pids.is_empty());
+
+    let s = Settings::try_parse(crate::items([
+        "kill", "-s", "HUP", "--table", "-s", "INT", "--bogus",
+    ]))
+    .unwrap();
+    assert_eq!(s.mode, Mode::Table);
+    assert_eq!(s.signal, "HUP");
+
+    // The arguments before it are still parsed
+    let s = Settings::try_parse(crate::items(["kill", "12", "--list"])).unwrap();
+    assert_eq!(s.mode, Mode::List);
+    assert_eq!(s.pids, [12]);
+    assert!(Settings::try_parse(crate::items(["kill", "--bogus", "--list"])).is_err());
+}
+
+#[test]
+fn exclusive_layer() {
+    let layers = Layers::default();
+    let mut s = Settings::default();
+    s.apply_layer(
+        Arg::parse_without_binname(crate::items(["-s", "HUP"])),
+        &layers,
+    )
+    .unwrap();
+    s.apply_layer(Arg::parse(crate::items(["kill", "-l", "12"])), &layers)
+        .unwrap();
+    s.finish_layers(layers).unwrap();
+    assert_eq!(s.mode, Mode::List);
+    assert_eq!(s.signal, "HUP");
+    assert!(s.pids.is_empty());
+}
+
+#[test]
+fn help() {
+    let help = Arg::help("kill");
+    assert!(help.contains("kill [OPTION]... PID...\n"));
+    assert!(help.contains("  -l, --list"));
+}
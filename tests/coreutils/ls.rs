@@ -1,5 +1,5 @@
 use std::path::PathBuf;
-use uutils_args::{Arguments, FromValue, Options};
+use uutils_args::{value_types::Pattern, Arguments, Error, FromValue, Options, Warning};
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, FromValue)]
 enum Format {
@@ -129,6 +129,7 @@ enum IndicatorStyle {
 }
 
 #[derive(Clone, Arguments)]
+#[arguments(deny_missing_help)]
 enum Arg {
     // === Files ===
     /// Do not ignore entries starting with .
@@ -144,32 +145,41 @@ enum Arg {
     Author,
 
     // === Time ===
+    /// Sort by and show the change time
     #[option("-c")]
     ChangeTime,
 
+    /// Sort by and show the access time
     #[option("-u")]
     AccessTime,
 
+    /// Show and sort by the given time instead of the modification time
     #[option("--time=WORD")]
     Time(Time),
 
     // === Sorting ===
+    /// Sort by WORD instead of name
     #[option("--sort=WORD")]
     Sort(Sort),
 
+    /// Sort by time, newest first
     #[option("-t")]
     SortTime,
 
+    /// Do not sort, list entries in directory order
     #[option("-U")]
     SortNone,
 
+    /// Sort by version numbers within the names
     #[option("-v")]
     SortVersion,
 
+    /// Sort alphabetically by extension
     #[option("-X")]
     SortExtension,
 
     // === Miscellaneous ===
+    /// Print the security context of each file
     #[option("-Z", "--context")]
     SecurityContext,
 
@@ -177,33 +187,43 @@ enum Arg {
     #[option("-B", "--ignore-backups")]
     IgnoreBackups,
 
+    /// List directories themselves, not their contents
     #[option("-d", "--directory")]
     Directory,
 
+    /// Generate output designed for Emacs' dired mode
     #[option("-D", "--dired")]
     Dired,
 
+    /// Hyperlink file names
     #[option("--hyperlink")]
     Hyperlink(When),
 
+    /// Print the index number of each file
     #[option("-i", "--inode")]
     Inode,
 
+    /// Do not list entries matching PATTERN
     #[option("-I PATTERN", "--ignore=PATTERN")]
-    Ignore(String),
+    Ignore(Pattern),
 
+    /// Reverse order while sorting
     #[option("-r", "--reverse")]
     Reverse,
 
+    /// List subdirectories recursively
     #[option("-R", "--recursive")]
     Recursive,
 
+    /// Set the output width to COLS
     #[option("-w COLS", "--width=COLS")]
     Width(u16),
 
+    /// Print the allocated size of each file, in blocks
     #[option("-s", "--size")]
     AllocationSize,
 
+    /// Do not print group names in a long listing
     #[option("-G", "--no-group")]
     NoGroup,
 
@@ -228,12 +248,15 @@ enum Arg {
     #[option("-1")]
     SingleColumn,
 
+    /// Like -l, but do not list group information
     #[option("-o")]
     LongNoGroup,
 
+    /// Like -l, but do not list the owner
     #[option("-g")]
     LongNoOwner,
 
+    /// Like -l, but list numeric user and group IDs
     #[option("-n", "--numeric-uid-gid")]
     LongNumericUidGid,
 
@@ -242,35 +265,45 @@ enum Arg {
     Format(Format),
 
     // === Indicator style ===
+    /// Append an indicator with STYLE to entry names
     #[option("--indicator-style=STYLE")]
     IndicatorStyle(IndicatorStyle),
 
+    /// Append / to directories
     #[option("-p")]
     IndicatorStyleSlash,
 
+    /// Like --classify, except do not append *
     #[option("--file-type")]
     IndicatorStyleFileType,
 
+    /// Append an indicator (one of */=>@|) to entries
     #[option("-F", "--classify[=WHEN]", default = When::Always)]
     IndicatorStyleClassify(When),
 
     // === Dereference ===
+    /// Show information for the file a symbolic link refers to
     #[option("-L", "--dereference")]
     DerefAll,
 
+    /// Follow each command line symbolic link that points to a directory
     #[option("--dereference-command-line-symlink-to-dir")]
     DerefDirArgs,
 
+    /// Follow symbolic links listed on the command line
     #[option("--dereference-command-line")]
     DerefArgs,
 
     // === Size ===
+    /// Print sizes like 1K, 234M and 2G
     #[option("-h", "--human-readable")]
     HumanReadable,
 
+    /// Use 1024-byte blocks for file system usage
     #[option("-k", "--kibibytes")]
     Kibibytes,
 
+    /// Like -h, but use powers of 1000 instead of 1024
     #[option("--si")]
     Si,
 
@@ -278,15 +311,19 @@ enum Arg {
     // BlockSize(Size),
 
     // === Quoting style ===
+    /// Use quoting STYLE for entry names
     #[option("--quoting-style=STYLE")]
     QuotingStyle(QuotingStyle),
 
+    /// Print entry names without quoting
     #[option("-N", "--literal")]
     Literal,
 
+    /// Print C-style escapes for nongraphic characters
     #[option("-h", "--escape")]
     Escape,
 
+    /// Enclose entry names in double quotes
     #[option("-Q", "--quote-name")]
     QuoteName,
 
@@ -302,9 +339,11 @@ enum Arg {
     #[option("--show-control-chars")]
     ShowControlChars,
 
+    /// End each output line with NUL, not newline
     #[option("--zero")]
     Zero,
 
+    /// Group directories before files
     #[option("--group-directories-first")]
     GroupDirectoriesFirst,
 
@@ -320,19 +359,27 @@ fn default_terminal_size() -> u16 {
     if let Some(columns) = std::env::var_os("COLUMNS") {
         match columns.to_str().and_then(|s| s.parse().ok()) {
             Some(columns) => return columns,
-            None => {
-                // TODO: Make show_error! when integrated with uutils
-                println!(
-                    "ignoring invalid width in environment variable COLUMNS: '{}'",
-                    columns.to_string_lossy()
-                );
-            }
+            None => uutils_args::warn(Warning::Custom(format!(
+                "ignoring invalid width in environment variable COLUMNS: '{}'",
+                columns.to_string_lossy()
+            ))),
         }
     }
 
     80
 }
 
+/// `dir` is `ls -C` and `vdir` is `ls -l`, while `ls` itself only uses
+/// columns when the output is a terminal.
+fn default_format(bin_name: &str) -> Format {
+    match bin_name {
+        "dir" => Format::Columns,
+        "vdir" => Format::Long,
+        _ if atty::is(atty::Stream::Stdout) => Format::Columns,
+        _ => Format::SingleColumn,
+    }
+}
+
 #[derive(Default, Options, Debug, PartialEq, Eq)]
 #[arg_type(Arg)]
 struct Settings {
@@ -344,6 +391,7 @@ struct Settings {
         Arg::SingleColumn => Format::SingleColumn,
         Arg::Format(f) => f,
     )]
+    #[field(default_by_name = default_format)]
     format: Format,
 
     #[collect(set(Arg::File))]
@@ -364,7 +412,7 @@ struct Settings {
     #[map(Arg::Reverse => true)]
     reverse: bool,
 
-    #[map(
+    #[choice(
         Arg::DerefAll => Dereference::All,
         Arg::DerefDirArgs => Dereference::DirArgs,
         Arg::DerefArgs => Dereference::Args,
@@ -372,7 +420,7 @@ struct Settings {
     dereference: Dereference,
 
     #[collect(set(Arg::Ignore))]
-    ignore_patterns: Vec<String>,
+    ignore_patterns: Vec<Pattern>,
     //
     // size_format: SizeFormat,
     //
@@ -446,7 +494,7 @@ struct Settings {
     #[field(default = '\n')]
     eol: char,
 
-    #[map(
+    #[choice(
         Arg::AlmostAll => Files::AlmostAll,
         Arg::All => Files::All,
     )]
@@ -465,9 +513,9 @@ struct Settings {
 #[test]
 fn default() {
     assert_eq!(
-        Settings::parse(["ls"]),
+        Settings::parse(crate::items(["ls"])),
         Settings {
-            format: Format::Columns,
+            format: default_format("ls"),
             files: Vec::new(),
             sort: Sort::Name,
             recursive: false,
@@ -499,80 +547,248 @@ fn default() {
     );
 }
 
+#[test]
+fn default_format_by_name() {
+    let ls = Settings::parse(crate::items(["ls"])).format;
+    let dir = Settings::parse(crate::items(["dir"])).format;
+    let vdir = Settings::parse(crate::items(["vdir"])).format;
+
+    if atty::is(atty::Stream::Stdout) {
+        assert_eq!(ls, Format::Columns);
+    } else {
+        assert_eq!(ls, Format::SingleColumn);
+    }
+    assert_eq!(dir, Format::Columns);
+    assert_eq!(vdir, Format::Long);
+
+    // The arguments are applied on top of the default
+    assert_eq!(
+        Settings::parse(crate::items(["vdir", "-C"])).format,
+        Format::Columns
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["dir", "-l"])).format,
+        Format::Long
+    );
+
+    // Without a bin name, the name from the help config is used
+    let vdir = uutils_args::HelpConfig {
+        name: "vdir",
+        ..Default::default()
+    };
+    let iter = Arg::parse_without_binname(crate::items(["-m"])).with_help_config(vdir);
+    assert_eq!(
+        Settings::try_parse_iter(iter).unwrap().format,
+        Format::Commas
+    );
+    let iter = Arg::parse_without_binname(Vec::<String>::new()).with_help_config(vdir);
+    assert_eq!(Settings::try_parse_iter(iter).unwrap().format, Format::Long);
+
+    assert_eq!(
+        Settings::initial().unwrap().format,
+        default_format(Arg::NAME)
+    );
+}
+
 #[test]
 fn color() {
-    let s = Settings::parse(["ls", "--color"]);
+    let s = Settings::parse(crate::items(["ls", "--color"]));
     assert!(s.color);
 
-    let s = Settings::parse(["ls", "--color=always"]);
+    let s = Settings::parse(crate::items(["ls", "--color=always"]));
     assert!(s.color);
 
-    let s = Settings::parse(["ls", "--color=never"]);
+    let s = Settings::parse(crate::items(["ls", "--color=never"]));
     assert!(!s.color);
 }
 
 #[test]
 fn format() {
-    let s = Settings::parse(["ls", "-l"]);
+    let s = Settings::parse(crate::items(["ls", "-l"]));
     assert_eq!(s.format, Format::Long);
 
-    let s = Settings::parse(["ls", "-m"]);
+    let s = Settings::parse(crate::items(["ls", "-m"]));
     assert_eq!(s.format, Format::Commas);
 
-    let s = Settings::parse(["ls", "--format=across"]);
+    let s = Settings::parse(crate::items(["ls", "--format=across"]));
     assert_eq!(s.format, Format::Across);
 
-    let s = Settings::parse(["ls", "--format=acr"]);
+    let s = Settings::parse(crate::items(["ls", "--format=acr"]));
     assert_eq!(s.format, Format::Across);
 
-    let s = Settings::parse(["ls", "-o"]);
+    let s = Settings::parse(crate::items(["ls", "-o"]));
     assert_eq!(s.format, Format::Long);
     assert!(s.long_no_group && !s.long_no_owner && !s.long_numeric_uid_gid);
 
-    let s = Settings::parse(["ls", "-g"]);
+    let s = Settings::parse(crate::items(["ls", "-g"]));
     assert_eq!(s.format, Format::Long);
     assert!(!s.long_no_group && s.long_no_owner && !s.long_numeric_uid_gid);
 
-    let s = Settings::parse(["ls", "-n"]);
+    let s = Settings::parse(crate::items(["ls", "-n"]));
     assert_eq!(s.format, Format::Long);
     assert!(!s.long_no_group && !s.long_no_owner && s.long_numeric_uid_gid);
 
-    let s = Settings::parse(["ls", "-og"]);
+    let s = Settings::parse(crate::items(["ls", "-og"]));
     assert_eq!(s.format, Format::Long);
     assert!(s.long_no_group && s.long_no_owner && !s.long_numeric_uid_gid);
 
-    let s = Settings::parse(["ls", "-on"]);
+    let s = Settings::parse(crate::items(["ls", "-on"]));
     assert_eq!(s.format, Format::Long);
     assert!(s.long_no_group && !s.long_no_owner && s.long_numeric_uid_gid);
 
-    let s = Settings::parse(["ls", "-onCl"]);
+    let s = Settings::parse(crate::items(["ls", "-onCl"]));
     assert_eq!(s.format, Format::Long);
     assert!(s.long_no_group && !s.long_no_owner && s.long_numeric_uid_gid);
 }
 
 #[test]
 fn time() {
-    let s = Settings::parse(["ls", "--time=access"]);
+    let s = Settings::parse(crate::items(["ls", "--time=access"]));
     assert_eq!(s.time, Time::Access);
 
-    let s = Settings::parse(["ls", "--time=a"]);
+    let s = Settings::parse(crate::items(["ls", "--time=a"]));
     assert_eq!(s.time, Time::Access);
 }
 
 #[test]
 fn classify() {
-    let s = Settings::parse(["ls", "--indicator-style=classify"]);
+    let s = Settings::parse(crate::items(["ls", "--indicator-style=classify"]));
     assert_eq!(s.indicator_style, IndicatorStyle::Classify);
 
-    let s = Settings::parse(["ls", "--classify"]);
+    let s = Settings::parse(crate::items(["ls", "--classify"]));
     assert_eq!(s.indicator_style, IndicatorStyle::Classify);
 
-    let s = Settings::parse(["ls", "--classify=always"]);
+    let s = Settings::parse(crate::items(["ls", "--classify=always"]));
     assert_eq!(s.indicator_style, IndicatorStyle::Classify);
 
-    let s = Settings::parse(["ls", "--classify=none"]);
+    let s = Settings::parse(crate::items(["ls", "--classify=none"]));
     assert_eq!(s.indicator_style, IndicatorStyle::None);
 
-    let s = Settings::parse(["ls", "-F"]);
+    let s = Settings::parse(crate::items(["ls", "-F"]));
     assert_eq!(s.indicator_style, IndicatorStyle::Classify);
 }
+
+#[test]
+fn ignore() {
+    let s = Settings::parse(crate::items(["ls", "-I", "*.rs", "--ignore=[!a-c]?"]));
+    let [rs, other] = &s.ignore_patterns[..] else {
+        panic!("expected two patterns");
+    };
+    assert_eq!(rs.as_str(), "*.rs");
+    assert!(rs.matches("main.rs") && !rs.matches("main.rsx"));
+    assert!(other.matches("dx") && !other.matches("ax"));
+
+    let Err(Error::ParsingFailed { option, value, .. }) =
+        Settings::try_parse(crate::items(["ls", "--ignore=[abc"]))
+    else {
+        panic!("expected an invalid pattern");
+    };
+    assert_eq!(option, "--ignore");
+    assert_eq!(value, "[abc");
+}
+
+#[test]
+fn ignore_control_characters() {
+    let s = Settings::parse(crate::items(["ls", "--ignore=a\nb", "-I", "\x1b\t*"]));
+    let [newline, escape] = &s.ignore_patterns[..] else {
+        panic!("expected two patterns");
+    };
+    assert_eq!(newline.as_str(), "a\nb");
+    assert!(newline.matches("a\nb"));
+    assert_eq!(escape.as_str(), "\x1b\t*");
+    assert!(escape.matches("\x1b\tfoo"));
+}
+
+#[test]
+fn attached_value_without_value() {
+    for (arg, option) in [
+        ("--rec=1", "--recursive"),
+        ("--rec=", "--recursive"),
+        ("--recursive=1", "--recursive"),
+        ("--rev=", "--reverse"),
+    ] {
+        let Err(Error::UnexpectedValue { option: got, .. }) =
+            Settings::try_parse(crate::items(["ls", arg, "dir"]))
+        else {
+            panic!("expected {arg} to be rejected");
+        };
+        assert_eq!(got, option);
+    }
+
+    let s = Settings::parse(crate::items(["ls", "--rec", "dir"]));
+    assert!(s.recursive);
+    assert_eq!(s.files, [PathBuf::from("dir")]);
+}
+
+#[test]
+fn attached_value_is_not_an_operand() {
+    let mut iter = Arg::parse(crate::items(["ls", "--rec=1", "dir"]));
+    assert!(matches!(
+        iter.next_arg(),
+        Err(Error::UnexpectedValue { .. })
+    ));
+    assert!(matches!(
+        iter.next_arg(),
+        Ok(Some(uutils_args::Argument::Custom(Arg::File(f)))) if f.as_os_str() == "dir"
+    ));
+    assert!(matches!(iter.next_arg(), Ok(None)));
+
+    let mut iter = Arg::parse(crate::items(["ls", "--re=1", "dir"]));
+    assert!(matches!(
+        iter.next_arg(),
+        Err(Error::AmbiguousOption { .. })
+    ));
+    assert!(matches!(
+        iter.next_arg(),
+        Ok(Some(uutils_args::Argument::Custom(Arg::File(f)))) if f.as_os_str() == "dir"
+    ));
+    assert!(matches!(iter.next_arg(), Ok(None)));
+}
+
+#[test]
+fn options() {
+    use uutils_args::complete::{Flag, Value};
+
+    let options = Arg::options();
+    assert_eq!(options[0].name, "All");
+    assert_eq!(
+        options[0].short,
+        [Flag {
+            flag: "a",
+            value: Value::No
+        }]
+    );
+    assert!(options[0].long.is_empty());
+
+    let width = options.iter().find(|o| o.name == "Width").unwrap();
+    assert_eq!(
+        width.short,
+        [Flag {
+            flag: "w",
+            value: Value::Required("COLS")
+        }]
+    );
+    assert_eq!(
+        width.long,
+        [Flag {
+            flag: "width",
+            value: Value::Required("COLS")
+        }]
+    );
+    assert_eq!(width.metavar, Some("COLS"));
+    assert_eq!(width.num_args, None);
+    assert!(!width.hidden);
+    assert_eq!(width.help, "Set the output width to COLS");
+
+    let file = options.iter().find(|o| o.name == "File").unwrap();
+    assert_eq!(file.metavar, Some("FILE"));
+    assert_eq!(file.num_args, Some(0..=usize::MAX));
+
+    let long: Vec<_> = options
+        .iter()
+        .flat_map(|o| o.long)
+        .map(|f| f.flag)
+        .collect();
+    assert!(long.contains(&"recursive"));
+    assert_eq!(long[long.len() - 2..], ["help", "version"]);
+}
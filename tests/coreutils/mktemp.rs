@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use uutils_args::{Arguments, Options};
+use uutils_args::{Arguments, Error, Options};
 
 #[derive(Clone, Arguments)]
 enum Arg {
@@ -19,7 +19,7 @@ enum Arg {
     #[option("-t")]
     TreatAsTemplate,
 
-    #[option("-p DIR", "--tmpdir[=DIR]", default = ".".into())]
+    #[option("-p DIR", "--tmpdir[=DIR]", mixed_arity, default = ".".into())]
     TmpDir(PathBuf),
 
     #[positional(0..=1)]
@@ -28,6 +28,7 @@ enum Arg {
 
 #[derive(Default, Options)]
 #[arg_type(Arg)]
+#[validate(Settings::validate_suffix)]
 struct Settings {
     #[map(Arg::Directory => true)]
     directory: bool,
@@ -51,37 +52,80 @@ struct Settings {
     template: String,
 }
 
+impl Settings {
+    fn validate_suffix(&self) -> Result<(), Error> {
+        if self.suffix.is_some() && !self.template.is_empty() && !self.template.ends_with('X') {
+            return Err(Error::Validation(format!(
+                "with --suffix, template '{}' must end in X",
+                self.template
+            )));
+        }
+        Ok(())
+    }
+}
+
 #[test]
 fn suffix() {
-    let s = Settings::parse(["mktemp", "--suffix=hello"]);
+    let s = Settings::parse(crate::items(["mktemp", "--suffix=hello"]));
     assert_eq!(s.suffix.unwrap(), "hello");
 
-    let s = Settings::parse(["mktemp", "--suffix="]);
+    let s = Settings::parse(crate::items(["mktemp", "--suffix="]));
     assert_eq!(s.suffix.unwrap(), "");
 
-    let s = Settings::parse(["mktemp", "--suffix="]);
+    let s = Settings::parse(crate::items(["mktemp", "--suffix="]));
     assert_eq!(s.suffix.unwrap(), "");
 
-    let s = Settings::parse(["mktemp"]);
+    let s = Settings::parse(crate::items(["mktemp"]));
     assert_eq!(s.suffix, None);
 }
 
 #[test]
 fn tmpdir() {
-    let s = Settings::parse(["mktemp", "--tmpdir"]);
+    let s = Settings::parse(crate::items(["mktemp", "--tmpdir"]));
     assert_eq!(s.tmp_dir.unwrap(), Path::new("."));
 
-    let s = Settings::parse(["mktemp", "--tmpdir="]);
+    let s = Settings::parse(crate::items(["mktemp", "--tmpdir="]));
     assert_eq!(s.tmp_dir.unwrap(), Path::new(""));
 
-    let s = Settings::parse(["mktemp", "-p", "foo"]);
+    let s = Settings::parse(crate::items(["mktemp", "-p", "foo"]));
     assert_eq!(s.tmp_dir.unwrap(), Path::new("foo"));
 
-    let s = Settings::parse(["mktemp", "-pfoo"]);
+    let s = Settings::parse(crate::items(["mktemp", "-pfoo"]));
     assert_eq!(s.tmp_dir.unwrap(), Path::new("foo"));
 
-    let s = Settings::parse(["mktemp", "-p", ""]);
+    let s = Settings::parse(crate::items(["mktemp", "-p", ""]));
     assert_eq!(s.tmp_dir.unwrap(), Path::new(""));
 
-    assert!(Settings::try_parse(["mktemp", "-p"]).is_err());
+    assert!(Settings::try_parse(crate::items(["mktemp", "-p"])).is_err());
+}
+
+#[test]
+fn suffix_requires_template_ending_in_x() {
+    let s = Settings::parse(crate::items(["mktemp", "--suffix=.txt", "fooXXX"]));
+    assert_eq!(s.template, "fooXXX");
+
+    // Each argument is valid on its own, but not in combination.
+    assert!(Settings::try_parse(crate::items(["mktemp", "foo"])).is_ok());
+    assert!(Settings::try_parse(crate::items(["mktemp", "--suffix=.txt"])).is_ok());
+
+    let Err(err) = Settings::try_parse(crate::items(["mktemp", "--suffix=.txt", "foo"])) else {
+        panic!("the template must end in X");
+    };
+    assert!(matches!(err, Error::Validation(_)));
+    assert_eq!(
+        err.to_string(),
+        "with --suffix, template 'foo' must end in X"
+    );
+}
+
+#[test]
+fn control_characters() {
+    let s = Settings::parse(crate::items(["mktemp", "--suffix=\t"]));
+    assert_eq!(s.suffix.unwrap(), "\t");
+
+    let s = Settings::parse(crate::items(["mktemp", "--suffix", "\x1b[31m\n"]));
+    assert_eq!(s.suffix.unwrap(), "\x1b[31m\n");
+
+    let s = Settings::parse(crate::items(["mktemp", "a\nb\tXXX"]));
+    assert_eq!(s.template, "a\nb\tXXX");
 }
@@ -0,0 +1,449 @@
+//! The keys of `sort`, given with `-k POS1[,POS2]` or with the obsolete
+//! `+POS1 [-POS2]`, which count from zero. Both are turned into the same
+//! [`Key`], so the rest of the utility only sees one syntax. The examples
+//! are from the GNU documentation of `sort`.
+
+use std::{ffi::OsString, path::PathBuf};
+
+use uutils_args::{Arguments, Error, FromValue, Options};
+
+const ORDERING: &str = "bdfgiMhnRrV";
+
+/// A position in a line, as fields and characters counted from 1
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Pos {
+    field: usize,
+    /// The character in the field, where 0 is the end of the field for the
+    /// end of a key
+    char: usize,
+    /// The ordering options of this part of the key, like `n` or `bn`
+    opts: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Key {
+    start: Pos,
+    /// The end of the key, or the end of the line if there is none
+    end: Option<Pos>,
+}
+
+fn invalid(option: &str, value: &str, error: &str) -> Error {
+    Error::ParsingFailed {
+        option: option.into(),
+        value: value.into(),
+        error: error.to_string().into(),
+    }
+}
+
+/// Split `F[.C][OPTS]` into its parts, with `C` as 0 if it is left out
+fn split_pos(option: &str, value: &str, pos: &str) -> Result<(usize, usize, String), Error> {
+    let opts_start = pos
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(pos.len());
+    let (numbers, opts) = pos.split_at(opts_start);
+    if let Some(opt) = opts.chars().find(|c| !ORDERING.contains(*c)) {
+        return Err(invalid(
+            option,
+            value,
+            &format!("invalid ordering option '{opt}'"),
+        ));
+    }
+    let (field, char) = numbers.split_once('.').unwrap_or((numbers, "0"));
+    let number = |n: &str| {
+        n.parse::<usize>()
+            .map_err(|_| invalid(option, value, "invalid number"))
+    };
+    Ok((number(field)?, number(char)?, opts.into()))
+}
+
+impl FromValue for Key {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+        let (start, end) = match value.split_once(',') {
+            Some((start, end)) => (start, Some(end)),
+            None => (&value[..], None),
+        };
+
+        let (field, char, opts) = split_pos(option, &value, start)?;
+        if field == 0 {
+            return Err(invalid(option, &value, "field number is zero"));
+        }
+        if start.contains('.') && char == 0 {
+            return Err(invalid(option, &value, "character offset is zero"));
+        }
+        let start = Pos {
+            field,
+            char: char.max(1),
+            opts,
+        };
+
+        let end = match end {
+            Some(end) => {
+                let (field, char, opts) = split_pos(option, &value, end)?;
+                if field == 0 {
+                    return Err(invalid(option, &value, "field number is zero"));
+                }
+                Some(Pos { field, char, opts })
+            }
+            None => None,
+        };
+        Ok(Self { start, end })
+    }
+}
+
+/// The start of an obsolete key, like `+1.2n`, counted from zero
+#[derive(Clone, Debug)]
+struct LegacyStart(Pos);
+
+impl FromValue for LegacyStart {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+        let pos = value.strip_prefix('+').unwrap_or(&value);
+        let (field, char, opts) = split_pos(option, &value, pos)?;
+        Ok(Self(Pos {
+            field: field + 1,
+            char: char + 1,
+            opts,
+        }))
+    }
+}
+
+/// The end of an obsolete key, like `-2`, counted from zero
+#[derive(Clone, Debug)]
+struct LegacyEnd(Pos);
+
+impl FromValue for LegacyEnd {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+        let (field, char, opts) = split_pos(option, &value, &value)?;
+        // `-F` ends at the end of field F counted from zero, so at the end of
+        // the previous field counted from one, but `-F.C` ends within field F.
+        let field = if char == 0 { field } else { field + 1 };
+        Ok(Self(Pos { field, char, opts }))
+    }
+}
+
+/// The separator of the fields, a single character
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Separator(char);
+
+impl FromValue for Separator {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let value = String::from_value(option, value)?;
+        let mut chars = value.chars();
+        match (chars.next(), chars.next(), &value[..]) {
+            (_, _, "\\0") => Ok(Self('\0')),
+            (Some(c), None, _) => Ok(Self(c)),
+            (None, _, _) => Err(invalid(option, &value, "empty tab")),
+            _ => Err(invalid(option, &value, "multi-character tab")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Order {
+    #[default]
+    Text,
+    Numeric,
+    GeneralNumeric,
+    HumanNumeric,
+    Month,
+    Version,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-k KEYDEF", "--key=KEYDEF")]
+    Key(Key),
+
+    #[option("+{POS1}")]
+    LegacyStart(LegacyStart),
+
+    #[option("-{POS2}")]
+    LegacyEnd(LegacyEnd),
+
+    #[option("-t SEP", "--field-separator=SEP")]
+    Separator(Separator),
+
+    #[option("-n", "--numeric-sort")]
+    Numeric,
+
+    #[option("-g", "--general-numeric-sort")]
+    GeneralNumeric,
+
+    #[option("-h", "--human-numeric-sort")]
+    HumanNumeric,
+
+    #[option("-M", "--month-sort")]
+    Month,
+
+    #[option("-V", "--version-sort")]
+    Version,
+
+    #[option("-r", "--reverse")]
+    Reverse,
+
+    #[option("-u", "--unique")]
+    Unique,
+
+    #[option("-s", "--stable")]
+    Stable,
+
+    #[option("-o FILE", "--output=FILE")]
+    Output(PathBuf),
+
+    #[option("-S SIZE", "--buffer-size=SIZE")]
+    BufferSize(String),
+
+    #[option("--parallel=N")]
+    Parallel(usize),
+
+    #[positional(..)]
+    File(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+#[try_apply(Settings::check_arg)]
+struct Settings {
+    keys: Vec<Key>,
+
+    /// Whether the last argument was the start of an obsolete key, which
+    /// the end of an obsolete key must follow
+    after_legacy_start: bool,
+
+    #[map(Arg::Separator(s) => Some(s.0))]
+    separator: Option<char>,
+
+    #[choice(
+        Arg::Numeric => Order::Numeric,
+        Arg::GeneralNumeric => Order::GeneralNumeric,
+        Arg::HumanNumeric => Order::HumanNumeric,
+        Arg::Month => Order::Month,
+        Arg::Version => Order::Version,
+    )]
+    order: Order,
+
+    #[map(Arg::Reverse => true)]
+    reverse: bool,
+
+    #[map(Arg::Unique => true)]
+    unique: bool,
+
+    #[map(Arg::Stable => true)]
+    stable: bool,
+
+    #[map(Arg::Output(o) => Some(o))]
+    output: Option<PathBuf>,
+
+    #[map(Arg::BufferSize(s) => Some(s))]
+    buffer_size: Option<String>,
+
+    #[map(Arg::Parallel(n) => Some(n))]
+    parallel: Option<usize>,
+
+    #[collect(set(Arg::File))]
+    files: Vec<PathBuf>,
+}
+
+impl Settings {
+    /// Collect the keys of both syntaxes in order
+    fn check_arg(&mut self, arg: &Arg) -> Result<(), Error> {
+        let after_legacy_start = std::mem::take(&mut self.after_legacy_start);
+        match arg {
+            Arg::Key(key) => self.keys.push(key.clone()),
+            Arg::LegacyStart(LegacyStart(start)) => {
+                self.keys.push(Key {
+                    start: start.clone(),
+                    end: None,
+                });
+                self.after_legacy_start = true;
+            }
+            Arg::LegacyEnd(LegacyEnd(end)) if after_legacy_start => {
+                self.keys.last_mut().unwrap().end = Some(end.clone());
+            }
+            Arg::LegacyEnd(_) => {
+                return Err(Error::Validation(
+                    "an obsolete key end must follow an obsolete key start".into(),
+                ))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn key(start: (usize, usize, &str), end: Option<(usize, usize, &str)>) -> Key {
+    let pos = |(field, char, opts): (usize, usize, &str)| Pos {
+        field,
+        char,
+        opts: opts.into(),
+    };
+    Key {
+        start: pos(start),
+        end: end.map(pos),
+    }
+}
+
+#[test]
+fn numeric_reverse() {
+    // sort -n -r numbers.txt
+    let s = Settings::parse(crate::items(["sort", "-n", "-r", "numbers.txt"]));
+    assert_eq!(s.order, Order::Numeric);
+    assert!(s.reverse);
+    assert_eq!(s.files, [PathBuf::from("numbers.txt")]);
+    assert!(s.keys.is_empty());
+}
+
+#[test]
+fn several_keys() {
+    // Sort on the second field numerically, then on the first:
+    // sort -k 2,2n -k 1,1 file
+    let s = Settings::parse(crate::items(["sort", "-k", "2,2n", "-k", "1,1", "file"]));
+    assert_eq!(
+        s.keys,
+        [
+            key((2, 1, ""), Some((2, 0, "n"))),
+            key((1, 1, ""), Some((1, 0, "")))
+        ]
+    );
+}
+
+#[test]
+fn characters_in_fields() {
+    // Sort the password file on the fifth field, ignoring its first two
+    // characters: sort -t : -k 5.3,5.4 /etc/passwd
+    let s = Settings::parse(crate::items([
+        "sort",
+        "-t",
+        ":",
+        "-k",
+        "5.3,5.4",
+        "/etc/passwd",
+    ]));
+    assert_eq!(s.separator, Some(':'));
+    assert_eq!(s.keys, [key((5, 3, ""), Some((5, 4, "")))]);
+
+    // The key can be attached and the options can be on both ends.
+    let s = Settings::parse(crate::items(["sort", "-k3b,3bn", "--key=1.2"]));
+    assert_eq!(
+        s.keys,
+        [key((3, 1, "b"), Some((3, 0, "bn"))), key((1, 2, ""), None)]
+    );
+}
+
+#[test]
+fn invalid_keys() {
+    for k in ["0", "1.0", "1,0", "1x", "a", "1,2q", ""] {
+        assert!(
+            Settings::try_parse(crate::items(["sort", "-k", k])).is_err(),
+            "-k {k:?} must be rejected"
+        );
+    }
+}
+
+#[test]
+fn obsolete_keys() {
+    // sort +1 -2 is the same as sort -k 2,2
+    let s = Settings::parse(crate::items(["sort", "+1", "-2", "file"]));
+    assert_eq!(s.keys, [key((2, 1, ""), Some((2, 0, "")))]);
+    assert_eq!(s.files, [PathBuf::from("file")]);
+
+    // sort +0.1 -0.3 is the same as sort -k 1.2,1.3
+    let s = Settings::parse(crate::items(["sort", "+0.1", "-0.3"]));
+    assert_eq!(s.keys, [key((1, 2, ""), Some((1, 3, "")))]);
+
+    // Without an end, the key goes to the end of the line: sort +2n
+    let s = Settings::parse(crate::items(["sort", "+2n"]));
+    assert_eq!(s.keys, [key((3, 1, "n"), None)]);
+}
+
+#[test]
+fn obsolete_and_new_keys_mix() {
+    // sort -t : +4n -k 1,1 +0 -1
+    let s = Settings::parse(crate::items([
+        "sort", "-t", ":", "+4n", "-k", "1,1", "+0", "-1",
+    ]));
+    assert_eq!(
+        s.keys,
+        [
+            key((5, 1, "n"), None),
+            key((1, 1, ""), Some((1, 0, ""))),
+            key((1, 1, ""), Some((1, 0, ""))),
+        ]
+    );
+}
+
+#[test]
+fn obsolete_end_needs_start() {
+    assert!(Settings::try_parse(crate::items(["sort", "-2"])).is_err());
+    assert!(Settings::try_parse(crate::items(["sort", "+1", "-r", "-2"])).is_err());
+    assert!(Settings::try_parse(crate::items(["sort", "-k", "1", "-2"])).is_err());
+}
+
+#[test]
+fn separators() {
+    assert_eq!(
+        Settings::parse(crate::items(["sort", "-t,"])).separator,
+        Some(',')
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["sort", "--field-separator", "\t"])).separator,
+        Some('\t')
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["sort", "-t", "\\0"])).separator,
+        Some('\0')
+    );
+    assert!(Settings::try_parse(crate::items(["sort", "-t", "ab"])).is_err());
+    assert!(Settings::try_parse(crate::items(["sort", "-t", ""])).is_err());
+}
+
+#[test]
+fn orderings() {
+    for (flag, order) in [
+        ("-g", Order::GeneralNumeric),
+        ("-h", Order::HumanNumeric),
+        ("-M", Order::Month),
+        ("-V", Order::Version),
+        ("--numeric-sort", Order::Numeric),
+    ] {
+        assert_eq!(
+            Settings::parse(crate::items(["sort", flag])).order,
+            order,
+            "{flag}"
+        );
+    }
+    assert_eq!(Settings::parse(crate::items(["sort"])).order, Order::Text);
+}
+
+#[test]
+fn other_options() {
+    // sort -u -s -o sorted.txt -S 50% --parallel=4 a b
+    let s = Settings::parse(crate::items([
+        "sort",
+        "-us",
+        "-o",
+        "sorted.txt",
+        "-S",
+        "50%",
+        "--parallel=4",
+        "a",
+        "b",
+    ]));
+    assert!(s.unique);
+    assert!(s.stable);
+    assert_eq!(s.output, Some("sorted.txt".into()));
+    assert_eq!(s.buffer_size.as_deref(), Some("50%"));
+    assert_eq!(s.parallel, Some(4));
+    assert_eq!(s.files, [PathBuf::from("a"), PathBuf::from("b")]);
+}
+
+#[test]
+fn keys_after_files() {
+    // Options can follow the files: sort data.txt -k 2 -r
+    let s = Settings::parse(crate::items(["sort", "data.txt", "-k", "2", "-r"]));
+    assert_eq!(s.keys, [key((2, 1, ""), None)]);
+    assert!(s.reverse);
+    assert_eq!(s.files, [PathBuf::from("data.txt")]);
+}
@@ -0,0 +1,68 @@
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Start a login shell
+    #[option("-", "-l", "--login")]
+    Login,
+
+    /// Pass a single command to the shell
+    #[option("-c COMMAND", "--command=COMMAND")]
+    Command(OsString),
+
+    #[positional(..=1)]
+    User(OsString),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Login => true)]
+    login: bool,
+
+    #[map(Arg::Command(c) => Some(c))]
+    command: Option<OsString>,
+
+    #[map(Arg::User(u) => Some(u))]
+    user: Option<OsString>,
+}
+
+#[test]
+fn dash_is_login() {
+    for args in [
+        &["su", "-", "root"][..],
+        &["su", "root", "-"],
+        &["su", "-l", "root"],
+        &["su", "--login", "root"],
+    ] {
+        let s = Settings::parse(crate::items(args.iter().copied()));
+        assert!(s.login, "{args:?}");
+        assert_eq!(s.user.as_deref(), Some("root".as_ref()), "{args:?}");
+    }
+
+    let s = Settings::parse(crate::items(["su", "root"]));
+    assert!(!s.login);
+}
+
+#[test]
+fn dash_as_value() {
+    let s = Settings::parse(crate::items(["su", "-c", "-"]));
+    assert!(!s.login);
+    assert_eq!(s.command.as_deref(), Some("-".as_ref()));
+    assert_eq!(s.user, None);
+}
+
+#[test]
+fn dash_after_double_dash() {
+    let s = Settings::parse(crate::items(["su", "--", "-"]));
+    assert!(!s.login);
+    assert_eq!(s.user.as_deref(), Some("-".as_ref()));
+}
+
+#[test]
+fn dash_in_help() {
+    let help = Arg::help("su");
+    assert!(help.contains("  -l, -, --login "), "{help}");
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use uutils_args::{
+    value_types::{SigNum, Sign},
+    Arguments, Error, Options,
+};
+
+/// A count for `tail`, where a count without a sign is from the end
+type Count = SigNum<true>;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-f", "--follow")]
+    Follow,
+
+    #[option("-c NUM", "--bytes=NUM")]
+    Bytes(Count),
+
+    #[option("-n NUM", "--lines=NUM", "-{NUM}", "+{NUM}")]
+    Lines(Count),
+
+    #[option("--pid=PID")]
+    Pid(u32),
+
+    #[option("-q", "--quiet", "--silent")]
+    Quiet,
+
+    #[positional(..)]
+    File(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+#[try_apply(Settings::check_arg)]
+struct Settings {
+    #[map(Arg::Follow => true)]
+    follow: bool,
+
+    #[map(Arg::Bytes(n) => Some(n))]
+    bytes: Option<Count>,
+
+    #[field(default = Count::new(Sign::Minus, 10))]
+    #[set(Arg::Lines)]
+    lines: Count,
+
+    #[map(Arg::Pid(p) => Some(p))]
+    pid: Option<u32>,
+
+    #[map(Arg::Quiet => true)]
+    quiet: bool,
+
+    #[collect(set(Arg::File))]
+    files: Vec<PathBuf>,
+}
+
+impl Settings {
+    fn check_arg(&mut self, arg: &Arg) -> Result<(), Error> {
+        match arg {
+            Arg::Pid(_) if self.pid.is_some() => {
+                Err(Error::Validation("the pid can only be given once".into()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn follow_pid() {
+    let s = Settings::parse(crate::items(["tail", "-f", "--pid=42", "log"]));
+    assert!(s.follow);
+    assert_eq!(s.pid, Some(42));
+    assert_eq!(s.files, vec![PathBuf::from("log")]);
+}
+
+#[test]
+fn pid_twice() {
+    let Err(err) = Settings::try_parse(crate::items(["tail", "--pid=1", "-f", "--pid=2"])) else {
+        panic!("a second pid must be rejected");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid use of option '--pid': the pid can only be given once"
+    );
+    let Error::Rejected { option, .. } = err else {
+        panic!("expected the option to be attached");
+    };
+    assert_eq!(option, "--pid");
+}
+
+#[test]
+fn default_lines() {
+    let s = Settings::parse(crate::items(["tail"]));
+    assert_eq!(s.lines, Count::new(Sign::Minus, 10));
+    assert!(s.bytes.is_none());
+}
+
+#[test]
+fn other_options_repeat() {
+    let s = Settings::parse(crate::items(["tail", "-n", "5", "-q", "--lines=10", "-q"]));
+    assert_eq!(s.lines, Count::new(Sign::Minus, 10));
+    assert!(s.quiet);
+}
+
+#[test]
+fn number_shorthand() {
+    let s = Settings::parse(crate::items(["tail", "-20", "log"]));
+    assert_eq!(s.lines, Count::new(Sign::Minus, 20));
+    assert_eq!(s.files, vec![PathBuf::from("log")]);
+
+    let s = Settings::parse(crate::items(["tail", "+5", "log"]));
+    assert_eq!(s.lines, Count::new(Sign::Plus, 5));
+    assert_eq!(s.files, vec![PathBuf::from("log")]);
+
+    let s = Settings::parse(crate::items(["tail", "-qf", "-5k"]));
+    assert!(s.quiet);
+    assert!(s.follow);
+    assert_eq!(s.lines, Count::new(Sign::Minus, 5 * 1024));
+    assert!(s.files.is_empty());
+}
+
+#[test]
+fn number_shorthand_and_files() {
+    let s = Settings::parse(crate::items(["tail", "-n", "3", "+x", "-", "-1"]));
+    assert_eq!(s.lines, Count::new(Sign::Minus, 1));
+    assert_eq!(s.files, vec![PathBuf::from("+x"), PathBuf::from("-")]);
+}
+
+#[test]
+fn signed_counts() {
+    let s = Settings::parse(crate::items(["tail", "-n", "+0", "-c", "-0"]));
+    assert_eq!(s.lines, Count::new(Sign::Plus, 0));
+    assert_eq!(s.bytes, Some(Count::new(Sign::Minus, 0)));
+    assert_ne!(s.lines, Count::new(Sign::Minus, 0));
+
+    let s = Settings::parse(crate::items(["tail", "--bytes=2KB"]));
+    assert_eq!(s.bytes, Some(Count::new(Sign::Minus, 2000)));
+}
+
+#[test]
+fn invalid_count() {
+    let Err(err) = Settings::try_parse(crate::items(["tail", "-n", "5x"])) else {
+        panic!("an invalid count must be rejected");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid argument '5x' for '-n': Invalid suffix"
+    );
+}
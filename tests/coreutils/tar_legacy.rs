@@ -0,0 +1,346 @@
+//! `tar` accepts its options in three styles: long options, short options
+//! and the old style, where the first argument is a cluster of options
+//! without a dash and the values of those options follow as separate
+//! arguments, like `tar cvfb archive.tar 20`. The old style is not supported
+//! yet, so those tests are ignored. The examples are from the GNU
+//! documentation of `tar`.
+
+use std::path::PathBuf;
+
+use uutils_args::{Arguments, Error, Options};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Create,
+    Extract,
+    List,
+    Append,
+    Update,
+    Diff,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-c", "--create")]
+    Create,
+
+    #[option("-x", "--extract", "--get")]
+    Extract,
+
+    #[option("-t", "--list")]
+    List,
+
+    #[option("-r", "--append")]
+    Append,
+
+    #[option("-u", "--update")]
+    Update,
+
+    #[option("-d", "--diff", "--compare")]
+    Diff,
+
+    #[option("-f ARCHIVE", "--file=ARCHIVE")]
+    File(PathBuf),
+
+    #[option("-b BLOCKS", "--blocking-factor=BLOCKS")]
+    BlockingFactor(u32),
+
+    #[option("-C DIR", "--directory=DIR")]
+    Directory(PathBuf),
+
+    #[option("-v", "--verbose")]
+    Verbose,
+
+    #[option("-z", "--gzip")]
+    Gzip,
+
+    #[option("-j", "--bzip2")]
+    Bzip2,
+
+    #[option("-J", "--xz")]
+    Xz,
+
+    #[option("--exclude=PATTERN")]
+    Exclude(String),
+
+    #[positional(..)]
+    Member(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+#[try_apply(Settings::check_mode)]
+#[validate(Settings::validate_mode)]
+struct Settings {
+    #[map(
+        Arg::Create => Some(Mode::Create),
+        Arg::Extract => Some(Mode::Extract),
+        Arg::List => Some(Mode::List),
+        Arg::Append => Some(Mode::Append),
+        Arg::Update => Some(Mode::Update),
+        Arg::Diff => Some(Mode::Diff),
+    )]
+    mode: Option<Mode>,
+
+    #[map(Arg::File(f) => Some(f))]
+    file: Option<PathBuf>,
+
+    #[field(default = 20)]
+    #[set(Arg::BlockingFactor)]
+    blocking_factor: u32,
+
+    #[map(Arg::Directory(d) => Some(d))]
+    directory: Option<PathBuf>,
+
+    #[map(Arg::Verbose => self.verbose + 1)]
+    verbose: u8,
+
+    #[choice(Arg::Gzip => Compression::Gzip, Arg::Bzip2 => Compression::Bzip2, Arg::Xz => Compression::Xz)]
+    compression: Compression,
+
+    #[collect(set(Arg::Exclude))]
+    exclude: Vec<String>,
+
+    #[collect(set(Arg::Member))]
+    members: Vec<PathBuf>,
+}
+
+const MODES: &str =
+    "You may not specify more than one '-Acdtrux', '--delete' or '--test-label' option";
+
+impl Settings {
+    fn check_mode(&mut self, arg: &Arg) -> Result<(), Error> {
+        let is_mode = matches!(
+            arg,
+            Arg::Create | Arg::Extract | Arg::List | Arg::Append | Arg::Update | Arg::Diff
+        );
+        if is_mode && self.mode.is_some() {
+            return Err(Error::Validation(MODES.into()));
+        }
+        Ok(())
+    }
+
+    fn validate_mode(&self) -> Result<(), Error> {
+        if self.mode.is_none() {
+            return Err(Error::Validation(
+                "You must specify one of the '-Acdtrux', '--delete' or '--test-label' options"
+                    .into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn create() {
+    // tar -cvf collection.tar blues folk jazz
+    let s = Settings::parse(crate::items([
+        "tar",
+        "-cvf",
+        "collection.tar",
+        "blues",
+        "folk",
+        "jazz",
+    ]));
+    assert_eq!(s.mode, Some(Mode::Create));
+    assert_eq!(s.verbose, 1);
+    assert_eq!(s.file, Some("collection.tar".into()));
+    assert_eq!(s.members, ["blues", "folk", "jazz"].map(PathBuf::from));
+}
+
+#[test]
+fn long_options() {
+    // tar --create --verbose --file=collection.tar blues folk jazz
+    let s = Settings::parse(crate::items([
+        "tar",
+        "--create",
+        "--verbose",
+        "--file=collection.tar",
+        "blues",
+    ]));
+    assert_eq!(s.mode, Some(Mode::Create));
+    assert_eq!(s.verbose, 1);
+    assert_eq!(s.file, Some("collection.tar".into()));
+
+    // The value of a long option can be the next argument.
+    let s = Settings::parse(crate::items(["tar", "--list", "--file", "collection.tar"]));
+    assert_eq!(s.mode, Some(Mode::List));
+    assert_eq!(s.file, Some("collection.tar".into()));
+
+    // Long options can be abbreviated.
+    let s = Settings::parse(crate::items(["tar", "--extr", "--fi=a.tar"]));
+    assert_eq!(s.mode, Some(Mode::Extract));
+    assert_eq!(s.file, Some("a.tar".into()));
+}
+
+#[test]
+fn extract_compressed_into_directory() {
+    // tar -xzvf archive.tar.gz -C /tmp/out
+    let s = Settings::parse(crate::items([
+        "tar",
+        "-xzvf",
+        "archive.tar.gz",
+        "-C",
+        "/tmp/out",
+    ]));
+    assert_eq!(s.mode, Some(Mode::Extract));
+    assert_eq!(s.compression, Compression::Gzip);
+    assert_eq!(s.file, Some("archive.tar.gz".into()));
+    assert_eq!(s.directory, Some("/tmp/out".into()));
+    assert!(s.members.is_empty());
+}
+
+#[test]
+fn compression() {
+    assert_eq!(
+        Settings::parse(crate::items(["tar", "-cjf", "a.tbz", "x"])).compression,
+        Compression::Bzip2
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["tar", "-cJf", "a.txz", "x"])).compression,
+        Compression::Xz
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["tar", "-cf", "a.tar", "x"])).compression,
+        Compression::None
+    );
+}
+
+#[test]
+fn options_after_members() {
+    // tar -c -f archive.tar src --exclude='*.o' -v
+    let s = Settings::parse(crate::items([
+        "tar",
+        "-c",
+        "-f",
+        "archive.tar",
+        "src",
+        "--exclude=*.o",
+        "-v",
+    ]));
+    assert_eq!(s.members, [PathBuf::from("src")]);
+    assert_eq!(s.exclude, ["*.o"]);
+    assert_eq!(s.verbose, 1);
+}
+
+#[test]
+fn blocking_factor() {
+    // tar -c -b 20 -f archive.tar x
+    let s = Settings::parse(crate::items([
+        "tar",
+        "-c",
+        "-b",
+        "20",
+        "-f",
+        "archive.tar",
+        "x",
+    ]));
+    assert_eq!(s.blocking_factor, 20);
+    // The value of `-b` is the rest of the cluster: tar -cvb40 -f a.tar
+    let s = Settings::parse(crate::items(["tar", "-cvb40", "-f", "a.tar"]));
+    assert_eq!(s.blocking_factor, 40);
+    assert_eq!(s.verbose, 1);
+    assert!(Settings::try_parse(crate::items(["tar", "-cb40f", "a.tar"])).is_err());
+    assert!(Settings::try_parse(crate::items(["tar", "-cb", "x"])).is_err());
+}
+
+#[test]
+fn stdin_and_dashes() {
+    // Read the archive from stdin: tar -xf -
+    let s = Settings::parse(crate::items(["tar", "-xf", "-"]));
+    assert_eq!(s.file, Some("-".into()));
+
+    // A member that looks like an option: tar -cf a.tar -- -weird
+    let s = Settings::parse(crate::items(["tar", "-cf", "a.tar", "--", "-weird"]));
+    assert_eq!(s.members, [PathBuf::from("-weird")]);
+}
+
+#[test]
+fn verbosity() {
+    assert_eq!(
+        Settings::parse(crate::items(["tar", "-tvvf", "a.tar"])).verbose,
+        2
+    );
+    assert_eq!(
+        Settings::parse(crate::items(["tar", "-tf", "a.tar"])).verbose,
+        0
+    );
+}
+
+#[test]
+fn one_mode() {
+    let Err(err) = Settings::try_parse(crate::items(["tar", "-c", "-x", "-f", "a.tar"])) else {
+        panic!("two modes must be rejected");
+    };
+    assert!(err.to_string().contains(MODES), "{err}");
+
+    let Err(err) = Settings::try_parse(crate::items(["tar", "-f", "a.tar"])) else {
+        panic!("a mode is required");
+    };
+    assert!(err.to_string().contains("You must specify one"), "{err}");
+}
+
+#[test]
+#[ignore = "missing: old style options, a first operand of options without a dash"]
+fn old_style() {
+    // tar cvf collection.tar blues folk jazz
+    let s = Settings::parse(crate::items([
+        "tar",
+        "cvf",
+        "collection.tar",
+        "blues",
+        "folk",
+        "jazz",
+    ]));
+    assert_eq!(s.mode, Some(Mode::Create));
+    assert_eq!(s.verbose, 1);
+    assert_eq!(s.file, Some("collection.tar".into()));
+    assert_eq!(s.members, ["blues", "folk", "jazz"].map(PathBuf::from));
+
+    // tar xf archive.tar
+    let s = Settings::parse(crate::items(["tar", "xf", "archive.tar"]));
+    assert_eq!(s.mode, Some(Mode::Extract));
+    assert!(s.members.is_empty());
+}
+
+#[test]
+#[ignore = "missing: old style options, whose values follow in the order of the options"]
+fn old_style_values_in_order() {
+    // The values of `f` and `b` are the next two arguments:
+    // tar cfb archive.tar 20 blues
+    let s = Settings::parse(crate::items(["tar", "cfb", "archive.tar", "20", "blues"]));
+    assert_eq!(s.file, Some("archive.tar".into()));
+    assert_eq!(s.blocking_factor, 20);
+    assert_eq!(s.members, [PathBuf::from("blues")]);
+
+    // tar cbf 20 archive.tar blues
+    let s = Settings::parse(crate::items(["tar", "cbf", "20", "archive.tar", "blues"]));
+    assert_eq!(s.file, Some("archive.tar".into()));
+    assert_eq!(s.blocking_factor, 20);
+}
+
+#[test]
+#[ignore = "missing: old style options, which mix with the other styles"]
+fn old_style_with_other_options() {
+    // tar xf archive.tar -C /tmp --exclude=*.o
+    let s = Settings::parse(crate::items([
+        "tar",
+        "xf",
+        "archive.tar",
+        "-C",
+        "/tmp",
+        "--exclude=*.o",
+    ]));
+    assert_eq!(s.mode, Some(Mode::Extract));
+    assert_eq!(s.directory, Some("/tmp".into()));
+    assert_eq!(s.exclude, ["*.o"]);
+}
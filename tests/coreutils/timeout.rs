@@ -0,0 +1,120 @@
+use std::{ffi::OsString, time::Duration};
+
+use uutils_args::{value_types, Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--foreground")]
+    Foreground,
+
+    #[option("-k DURATION", "--kill-after=DURATION")]
+    KillAfter(value_types::Duration),
+
+    #[option("--preserve-status")]
+    PreserveStatus,
+
+    #[option("-s SIGNAL", "--signal=SIGNAL")]
+    Signal(String),
+
+    #[option("-v", "--verbose")]
+    Verbose,
+
+    #[positional(1)]
+    Duration(value_types::Duration),
+
+    #[positional(1, command)]
+    Command(OsString),
+
+    #[positional(..)]
+    Args(Vec<OsString>),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Foreground => true)]
+    foreground: bool,
+
+    #[map(Arg::KillAfter(d) => Some(d.into()))]
+    kill_after: Option<Duration>,
+
+    #[map(Arg::PreserveStatus => true)]
+    preserve_status: bool,
+
+    #[set(Arg::Signal)]
+    signal: String,
+
+    #[map(Arg::Verbose => true)]
+    verbose: bool,
+
+    #[map(Arg::Duration(d) => d.into())]
+    duration: Duration,
+
+    #[set(Arg::Command)]
+    command: OsString,
+
+    #[set(Arg::Args)]
+    args: Vec<OsString>,
+}
+
+#[test]
+fn command_without_args() {
+    let settings = Settings::parse(crate::items(["timeout", "5", "sleep"]));
+    assert_eq!(settings.duration, Duration::from_secs(5));
+    assert_eq!(settings.command, "sleep");
+    assert!(settings.args.is_empty());
+}
+
+#[test]
+fn options_after_command() {
+    let settings = Settings::parse(crate::items(["timeout", "5", "sh", "-c", "x", "-v"]));
+    assert!(!settings.verbose);
+    assert_eq!(settings.duration, Duration::from_secs(5));
+    assert_eq!(settings.command, "sh");
+    assert_eq!(settings.args, vec!["-c", "x", "-v"]);
+}
+
+#[test]
+fn options_before_command() {
+    let settings = Settings::parse(crate::items([
+        "timeout", "-v", "-s", "KILL", "5", "sh", "-c", "x", "-v",
+    ]));
+    assert!(settings.verbose);
+    assert_eq!(settings.signal, "KILL");
+    assert_eq!(settings.duration, Duration::from_secs(5));
+    assert_eq!(settings.command, "sh");
+    assert_eq!(settings.args, vec!["-c", "x", "-v"]);
+}
+
+#[test]
+fn options_between_duration_and_command() {
+    let settings = Settings::parse(crate::items([
+        "timeout",
+        "5",
+        "--foreground",
+        "sleep",
+        "--",
+        "1",
+    ]));
+    assert!(settings.foreground);
+    assert_eq!(settings.command, "sleep");
+    assert_eq!(settings.args, vec!["--", "1"]);
+}
+
+#[test]
+fn durations() {
+    let settings = Settings::parse(crate::items(["timeout", "-k", "0.5m", "1.5", "sleep"]));
+    assert_eq!(settings.kill_after, Some(Duration::from_secs(30)));
+    assert_eq!(settings.duration, Duration::from_millis(1500));
+
+    assert!(Settings::try_parse(crate::items(["timeout", "-1", "sleep"])).is_err());
+    assert!(
+        Settings::try_parse(crate::items(["timeout", "--kill-after=x", "5", "sleep"])).is_err()
+    );
+}
+
+#[test]
+fn missing_command() {
+    assert!(Settings::try_parse(crate::items(["timeout", "5"])).is_err());
+    assert!(Settings::try_parse(crate::items(["timeout"])).is_err());
+}
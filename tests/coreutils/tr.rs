@@ -0,0 +1,93 @@
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-c", "-C", "--complement")]
+    Complement,
+
+    #[option("-d", "--delete")]
+    Delete,
+
+    #[option("-s", "--squeeze-repeats")]
+    Squeeze,
+
+    #[option("-t", "--truncate-set1")]
+    Truncate,
+
+    #[positional(1, value_name = "SET1")]
+    First(String),
+
+    #[positional(0..=1, value_name = "SET2")]
+    Second(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Complement => true)]
+    complement: bool,
+
+    #[map(Arg::Delete => true)]
+    delete: bool,
+
+    #[map(Arg::Squeeze => true)]
+    squeeze: bool,
+
+    #[map(Arg::Truncate => true)]
+    truncate: bool,
+
+    #[set(Arg::First)]
+    set1: String,
+
+    #[map(Arg::Second(s) => Some(s))]
+    set2: Option<String>,
+}
+
+fn error(args: &'static [&'static str]) -> String {
+    match Settings::try_parse(crate::items(args.iter().copied())) {
+        Ok(_) => panic!("expected an error for {args:?}"),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn one_set() {
+    let s = Settings::parse(crate::items(["tr", "-d", "abc"]));
+    assert!(s.delete);
+    assert_eq!(s.set1, "abc");
+    assert_eq!(s.set2, None);
+}
+
+#[test]
+fn two_sets() {
+    let s = Settings::parse(crate::items(["tr", "-cs", "a-z", "\\n"]));
+    assert!(s.complement && s.squeeze);
+    assert!(!s.truncate);
+    assert_eq!(s.set1, "a-z");
+    assert_eq!(s.set2.as_deref(), Some("\\n"));
+
+    let s = Settings::parse(crate::items(["tr", "-t", "--", "-a", "b"]));
+    assert!(s.truncate);
+    assert_eq!(s.set1, "-a");
+    assert_eq!(s.set2.as_deref(), Some("b"));
+}
+
+#[test]
+fn extra_operand() {
+    assert_eq!(error(&["tr", "a", "b", "c"]), "extra operand 'c'");
+}
+
+#[test]
+fn missing_operand() {
+    assert_eq!(error(&["tr"]), "missing SET1 operand");
+    assert_eq!(error(&["tr", "-d"]), "missing SET1 operand");
+}
+
+#[test]
+fn help() {
+    let help = Arg::help("tr");
+    assert!(help.contains("tr [OPTION]... SET1 [SET2]\n"));
+
+    let meta = Arg::options().iter().find(|o| o.name == "Second").unwrap();
+    assert_eq!(meta.metavar, Some("SET2"));
+}
@@ -0,0 +1,58 @@
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[positional(1, count_only)]
+    File(OsString),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::File)]
+    file: OsString,
+}
+
+fn error(args: &'static [&'static str]) -> String {
+    match Settings::try_parse(crate::items(args.iter().copied())) {
+        Ok(_) => panic!("expected an error for {args:?}"),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn one_operand() {
+    assert_eq!(Settings::parse(crate::items(["unlink", "foo"])).file, "foo");
+    assert_eq!(
+        Settings::parse(crate::items(["unlink", "--", "-foo"])).file,
+        "-foo"
+    );
+}
+
+#[test]
+fn missing_operand() {
+    assert_eq!(error(&["unlink"]), "missing operand");
+}
+
+#[test]
+fn extra_operand() {
+    assert_eq!(error(&["unlink", "foo", "bar"]), "extra operand 'bar'");
+    assert_eq!(
+        error(&["unlink", "foo", "bar", "baz"]),
+        "extra operand 'bar'"
+    );
+}
+
+#[test]
+fn operand_count() {
+    let count = |args: &'static [&'static str]| {
+        let mut iter = Arg::parse(crate::items(args.iter().copied())).with_metadata();
+        while let Ok(Some(_)) = iter.next_arg() {}
+        iter.metadata().unwrap().operand_count()
+    };
+    assert_eq!(count(&["unlink"]), 0);
+    assert_eq!(count(&["unlink", "foo"]), 1);
+    assert_eq!(count(&["unlink", "foo", "bar"]), 1);
+}
@@ -0,0 +1,13 @@
+//! The coreutils tests with `Cow<OsStr>` arguments, see tests/coreutils.rs
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+/// The arguments as the item type that the fixtures are parsed with
+fn items(args: impl IntoIterator<Item = &'static str>) -> Vec<Cow<'static, OsStr>> {
+    args.into_iter()
+        .map(|arg| Cow::Borrowed(OsStr::new(arg)))
+        .collect()
+}
+
+include!("coreutils/fixtures.rs");
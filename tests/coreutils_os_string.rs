@@ -0,0 +1,10 @@
+//! The coreutils tests with `OsString` arguments, see tests/coreutils.rs
+
+use std::ffi::OsString;
+
+/// The arguments as the item type that the fixtures are parsed with
+fn items(args: impl IntoIterator<Item = &'static str>) -> Vec<OsString> {
+    args.into_iter().map(OsString::from).collect()
+}
+
+include!("coreutils/fixtures.rs");
@@ -0,0 +1,8 @@
+//! The coreutils tests with `String` arguments, see tests/coreutils.rs
+
+/// The arguments as the item type that the fixtures are parsed with
+fn items(args: impl IntoIterator<Item = &'static str>) -> Vec<String> {
+    args.into_iter().map(String::from).collect()
+}
+
+include!("coreutils/fixtures.rs");
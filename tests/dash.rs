@@ -0,0 +1,157 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use uutils_args::{Argument, Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-d DELIM", "--delimiter=DELIM")]
+    Delimiter(String),
+
+    #[option("-o FILE", "--output=FILE")]
+    Output(PathBuf),
+
+    #[option("-c[WHEN]", "--color[=WHEN]")]
+    Color(Option<OsString>),
+
+    #[option("-s")]
+    Silent,
+
+    #[positional(..)]
+    File(PathBuf),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Delimiter(d) => Some(d))]
+    delimiter: Option<String>,
+
+    #[map(Arg::Output(o) => Some(o))]
+    output: Option<PathBuf>,
+
+    #[map(Arg::Color(c) => Some(c))]
+    color: Option<Option<OsString>>,
+
+    #[map(Arg::Silent => true)]
+    silent: bool,
+
+    #[collect(set(Arg::File))]
+    files: Vec<PathBuf>,
+}
+
+fn parse(args: &[&str]) -> Settings {
+    let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+    Settings::try_parse(args).unwrap()
+}
+
+#[test]
+fn required_value() {
+    for args in [
+        &["cut", "-d", "-"][..],
+        &["cut", "-d-"],
+        &["cut", "-sd", "-"],
+        &["cut", "-sd-"],
+        &["cut", "--delimiter", "-"],
+        &["cut", "--delimiter=-"],
+        &["cut", "--delim=-"],
+    ] {
+        let s = parse(args);
+        assert_eq!(s.delimiter.as_deref(), Some("-"), "{args:?}");
+        assert!(s.files.is_empty(), "{args:?}");
+    }
+
+    for args in [&["sort", "-o", "-"][..], &["sort", "--output=-"]] {
+        let s = parse(args);
+        assert_eq!(s.output, Some(PathBuf::from("-")), "{args:?}");
+    }
+}
+
+#[test]
+fn required_value_is_not_an_option() {
+    let s = parse(&["cut", "-d", "-s", "--", "-"]);
+    assert_eq!(s.delimiter.as_deref(), Some("-s"));
+    assert!(!s.silent);
+    assert_eq!(s.files, [PathBuf::from("-")]);
+
+    let s = parse(&["cut", "-d", "--"]);
+    assert_eq!(s.delimiter.as_deref(), Some("--"));
+}
+
+#[test]
+fn optional_value() {
+    // Only an attached value belongs to the option
+    let s = parse(&["ls", "--color", "-"]);
+    assert_eq!(s.color, Some(None));
+    assert_eq!(s.files, [PathBuf::from("-")]);
+
+    let s = parse(&["ls", "-c", "-"]);
+    assert_eq!(s.color, Some(None));
+    assert_eq!(s.files, [PathBuf::from("-")]);
+
+    let s = parse(&["ls", "--color=-"]);
+    assert_eq!(s.color, Some(Some("-".into())));
+    assert!(s.files.is_empty());
+
+    let s = parse(&["ls", "-c-"]);
+    assert_eq!(s.color, Some(Some("-".into())));
+    assert!(s.files.is_empty());
+}
+
+#[test]
+fn positional() {
+    let s = parse(&["cat", "-", "-s", "-", "--", "-"]);
+    assert!(s.silent);
+    assert_eq!(s.files, vec![PathBuf::from("-"); 3]);
+}
+
+#[derive(Clone, Arguments)]
+enum Xargs {
+    #[option("-d DELIM")]
+    Delimiter(String),
+
+    #[option("-s")]
+    Silent,
+
+    /// Mark the end of the arguments for this command
+    #[option("--", literal)]
+    End,
+
+    #[positional(..)]
+    Word(String),
+}
+
+fn xargs(args: &[&str]) -> Vec<Xargs> {
+    let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+    let mut iter = Xargs::parse(args);
+    let mut parsed = Vec::new();
+    while let Some(arg) = iter.next_arg().unwrap() {
+        if let Argument::Custom(arg) = arg {
+            parsed.push(arg);
+        }
+    }
+    parsed
+}
+
+#[test]
+fn claimed_double_dash() {
+    let parsed = xargs(&["xargs", "a", "--", "-s", "b"]);
+    assert!(matches!(
+        &parsed[..],
+        [Xargs::Word(a), Xargs::End, Xargs::Silent, Xargs::Word(b)] if a == "a" && b == "b"
+    ));
+}
+
+#[test]
+fn claimed_double_dash_as_value() {
+    let parsed = xargs(&["xargs", "-d", "--"]);
+    assert!(matches!(&parsed[..], [Xargs::Delimiter(d)] if d == "--"));
+
+    let parsed = xargs(&["xargs", "-d--"]);
+    assert!(matches!(&parsed[..], [Xargs::Delimiter(d)] if d == "--"));
+}
+
+#[test]
+fn claimed_double_dash_in_help() {
+    let help = Xargs::help("xargs");
+    assert!(help.contains("      -- "), "{help}");
+}
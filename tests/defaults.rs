@@ -1,4 +1,6 @@
-use uutils_args::{Arguments, Options};
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Error, Options, ParseConfig, ValueSource};
 
 #[test]
 fn true_default() {
@@ -45,3 +47,76 @@ fn env_var_string() {
     std::env::remove_var("FOO");
     assert_eq!(Settings::parse(["test"]).foo, "");
 }
+
+#[test]
+fn invalid_value_names_its_source() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-w COLS", "--width=COLS")]
+        Width(u16),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Width)]
+        #[field(env = "COLUMNS")]
+        width: u16,
+    }
+
+    fn columns(name: &str) -> Option<OsString> {
+        (name == "COLUMNS").then(|| "1x0".into())
+    }
+    let config = ParseConfig {
+        env: columns,
+        ..ParseConfig::default()
+    };
+
+    let Err(cli) = Settings::try_parse(["test", "--width=1x0"]) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        cli.to_string(),
+        "invalid argument '1x0' for '--width': invalid digit found in string"
+    );
+
+    let Err(env) = Settings::try_parse_with_config(["test"], config) else {
+        panic!("expected an error");
+    };
+    assert!(matches!(
+        &env,
+        Error::FromSource {
+            source: ValueSource::Env { var },
+            ..
+        } if var == "COLUMNS"
+    ));
+    assert_eq!(
+        env.to_string(),
+        "invalid value '1x0' for environment variable COLUMNS: invalid digit found in string"
+    );
+}
+
+#[test]
+fn invalid_positional_default() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[positional(.., default = "none")]
+        Count(u8),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[collect(set(Arg::Count))]
+        counts: Vec<u8>,
+    }
+
+    assert_eq!(Settings::parse(["test", "3"]).counts, [3]);
+    let Err(err) = Settings::try_parse(["test"]) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid default value 'none': invalid digit found in string"
+    );
+}
@@ -0,0 +1,111 @@
+use uutils_args::{Arguments, Error, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Select only these fields
+    #[option("-f LIST", "--fields=LIST", delimiter = ',')]
+    Fields(Vec<u32>),
+
+    /// Do not list entries matching PATTERN
+    #[option("--hide=PATTERN", delimiter = ',', skip_empty)]
+    Hide(Vec<String>),
+
+    /// Ignore entries matching PATTERN
+    #[option("--ignore=PATTERN", delimiter = ',')]
+    Ignore(Vec<String>),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::Fields)]
+    fields: Vec<u32>,
+
+    #[collect(map(Arg::Hide(patterns) => patterns))]
+    hide: Vec<Vec<String>>,
+
+    #[set(Arg::Ignore)]
+    ignore: Vec<String>,
+}
+
+fn parse(args: &'static [&'static str]) -> Result<Settings, Error> {
+    Settings::try_parse(args.iter().copied())
+}
+
+#[test]
+fn numbers() {
+    let s = parse(&["cut", "-f", "1,3,5"]).unwrap();
+    assert_eq!(s.fields, [1, 3, 5]);
+
+    let s = parse(&["cut", "--fields=2"]).unwrap();
+    assert_eq!(s.fields, [2]);
+
+    let s = parse(&["cut", "-f4,1"]).unwrap();
+    assert_eq!(s.fields, [4, 1]);
+}
+
+#[test]
+fn invalid_item() {
+    let err = parse(&["cut", "-f", "1,x,3"]).err().unwrap();
+    assert!(matches!(
+        &err,
+        Error::InvalidItem { option, index: 1, .. } if option == "-f"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "invalid item 2 'x' for '-f': invalid digit found in string"
+    );
+
+    // Only the first failure is reported
+    let err = parse(&["cut", "--fields=y,x"]).err().unwrap();
+    assert!(matches!(err, Error::InvalidItem { index: 0, .. }));
+}
+
+#[test]
+fn patterns() {
+    let s = parse(&["ls", "--hide=*.o,*.a", "--hide=core"]).unwrap();
+    assert_eq!(s.hide, [vec!["*.o", "*.a"], vec!["core"]]);
+
+    // Each item is kept as is, including other delimiters
+    let s = parse(&["ls", "--ignore=a b;c"]).unwrap();
+    assert_eq!(s.ignore, ["a b;c"]);
+}
+
+#[test]
+fn empty_items() {
+    // With `skip_empty`, a trailing comma is ignored
+    let s = parse(&["ls", "--hide=*.o,", "--hide=,,x"]).unwrap();
+    assert_eq!(s.hide, [vec!["*.o"], vec!["x"]]);
+    let s = parse(&["ls", "--hide="]).unwrap();
+    assert_eq!(s.hide, [Vec::<String>::new()]);
+
+    // Otherwise it is an error
+    let err = parse(&["ls", "--ignore=*.o,"]).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid item 2 '' for '--ignore': the item is empty"
+    );
+    assert!(parse(&["cut", "-f", "1,,2"]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn non_unicode_items() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    let value = OsString::from_vec(b"--ignore=a,b\xff".to_vec());
+    let err = Settings::try_parse([OsString::from("ls"), value])
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::InvalidItem { index: 1, error, .. } if matches!(*error, Error::NonUnicodeValue(_))
+    ));
+}
+
+#[test]
+fn help() {
+    let help = Arg::help("cut");
+    assert!(help.contains("-f LIST, --fields=LIST"));
+}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+
+use uutils_args::{complete, Arguments, Options, ParseConfig};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Output the last lines
+    #[option("-n NUM", "--lines=NUM")]
+    Lines(usize),
+
+    /// Keep trying to open a file
+    #[option(
+        "--retry",
+        deprecated = "--retry ignored; --retry is useful only when following"
+    )]
+    Retry,
+
+    /// Follow the file
+    #[option("--follow-name", hidden, deprecated = "use --follow=name instead")]
+    FollowName,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::Lines)]
+    lines: usize,
+
+    #[map(Arg::Retry => true)]
+    retry: bool,
+
+    #[map(Arg::FollowName => true)]
+    follow_name: bool,
+}
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(bin_name: &str, warning: &str) {
+    WARNINGS.with(|w| w.borrow_mut().push(format!("{bin_name}: {warning}")));
+}
+
+/// Parse the arguments and return the warnings that were reported
+fn parse(args: &'static [&'static str]) -> (Settings, Vec<String>) {
+    let config = ParseConfig {
+        warn: record,
+        ..ParseConfig::default()
+    };
+    let settings = Settings::try_parse_with_config(args.iter().copied(), config).unwrap();
+    (settings, WARNINGS.with(|w| w.take()))
+}
+
+#[test]
+fn warning() {
+    let (s, warnings) = parse(&["tail", "--retry", "-n", "5"]);
+    assert!(s.retry);
+    assert_eq!(s.lines, 5);
+    assert_eq!(
+        warnings,
+        ["tail: --retry ignored; --retry is useful only when following"]
+    );
+
+    // Every use is reported, also when abbreviated
+    let (s, warnings) = parse(&["tail", "--ret", "--follow-name", "--retry"]);
+    assert!(s.retry && s.follow_name);
+    assert_eq!(
+        warnings,
+        [
+            "tail: --retry ignored; --retry is useful only when following",
+            "tail: use --follow=name instead",
+            "tail: --retry ignored; --retry is useful only when following",
+        ]
+    );
+}
+
+#[test]
+fn no_warning() {
+    let (s, warnings) = parse(&["tail", "-n", "3"]);
+    assert!(!s.retry);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn left_out_of_help_and_completion() {
+    let help = Arg::help("tail");
+    assert!(help.contains("--lines"));
+    assert!(!help.contains("--retry"));
+    assert!(!help.contains("--follow-name"));
+
+    let fish = complete::render(&Arg::complete(), "fish");
+    assert!(fish.contains("-l lines"));
+    assert!(!fish.contains("retry"));
+
+    let meta = Arg::options().iter().find(|o| o.name == "Retry").unwrap();
+    assert!(meta.hidden);
+}
+
+#[test]
+fn documented_in_man() {
+    let man = complete::render(&Arg::complete(), "man");
+    assert!(man.contains("\\fB\\-\\-retry\\fR (deprecated)\nKeep trying to open a file\n"));
+
+    // Hidden options stay hidden
+    assert!(!man.contains("follow\\-name"));
+}
@@ -0,0 +1,101 @@
+//! Parsing must not depend on the arguments containing a bin name.
+
+use uutils_args::{Arguments, Error, HelpConfig, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-a")]
+    A,
+
+    #[positional(1..)]
+    File(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::A => true)]
+    a: bool,
+
+    #[collect(set(Arg::File))]
+    files: Vec<String>,
+}
+
+#[derive(Clone, Arguments)]
+enum OptionalArg {
+    #[option("-a")]
+    A,
+
+    #[positional(..)]
+    File(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(OptionalArg)]
+struct OptionalSettings {
+    #[map(OptionalArg::A => true)]
+    a: bool,
+
+    #[collect(set(OptionalArg::File))]
+    files: Vec<String>,
+}
+
+fn assert_missing_file(result: Result<Settings, Error>) {
+    let Err(err) = result else {
+        panic!("expected an error for the missing file");
+    };
+    let Error::MissingPositionalArguments(missing) = &err else {
+        panic!("expected missing positional arguments, got {err}");
+    };
+    assert_eq!(missing, &["FILE"]);
+    assert_eq!(err.to_string(), "missing FILE operand");
+}
+
+#[test]
+fn empty() {
+    assert_missing_file(Settings::try_parse(Vec::<String>::new()));
+
+    let s = OptionalSettings::try_parse(Vec::<String>::new()).unwrap();
+    assert!(!s.a);
+    assert!(s.files.is_empty());
+}
+
+#[test]
+fn only_bin_name() {
+    assert_missing_file(Settings::try_parse(["prog"]));
+
+    let s = OptionalSettings::try_parse(["prog"]).unwrap();
+    assert!(!s.a);
+    assert!(s.files.is_empty());
+}
+
+#[test]
+fn bin_name_of_empty_args() {
+    let iter = Arg::parse(Vec::<String>::new());
+    assert_eq!(iter.bin_name(), Arg::NAME);
+    assert!(iter
+        .help()
+        .contains(&format!("Usage:\n  {} [OPTION]... FILE...", Arg::NAME)));
+
+    let config = HelpConfig {
+        name: "other",
+        ..HelpConfig::default()
+    };
+    let iter = Arg::parse(Vec::<String>::new()).with_help_config(config);
+    assert_eq!(iter.bin_name(), "other");
+    assert!(iter.help().contains("Usage:\n  other [OPTION]... FILE..."));
+    assert!(iter.version().starts_with("other "));
+
+    let iter = Arg::parse(["prog"]);
+    assert_eq!(iter.bin_name(), "prog");
+}
+
+#[test]
+fn empty_with_config() {
+    let config = HelpConfig {
+        name: "other",
+        ..HelpConfig::default()
+    };
+    assert_missing_file(Settings::try_parse_with_help(Vec::<String>::new(), config));
+    assert!(OptionalSettings::try_parse_with_help(Vec::<String>::new(), config).is_ok());
+}
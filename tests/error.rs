@@ -0,0 +1,233 @@
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Error, Options};
+
+#[derive(Arguments, Clone)]
+#[arguments(exit_code = 2)]
+enum Arg {
+    #[option("-n NUM", "--lines=NUM")]
+    Lines(u64),
+    #[option("--follow")]
+    Follow,
+    #[option("--follow-name")]
+    FollowName,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Lines(n) => n)]
+    lines: u64,
+}
+
+fn error(args: &[&str]) -> Error {
+    let Err(err) = Settings::try_parse(args.iter().map(OsString::from).collect::<Vec<_>>()) else {
+        panic!("expected an error for {args:?}");
+    };
+    err
+}
+
+#[test]
+fn missing_value() {
+    assert_eq!(
+        error(&["tail", "--lines"]).to_string(),
+        "option '--lines' requires an argument"
+    );
+    // An abbreviation is named in full
+    assert_eq!(
+        error(&["tail", "--lin"]).to_string(),
+        "option '--lines' requires an argument"
+    );
+    assert_eq!(
+        error(&["tail", "-n"]).to_string(),
+        "option requires an argument -- 'n'"
+    );
+    assert!(matches!(
+        error(&["tail", "-n"]),
+        Error::MissingValue { option: Some(o) } if o == "-n"
+    ));
+    assert_eq!(
+        Error::MissingValue { option: None }.to_string(),
+        "option requires an argument"
+    );
+}
+
+#[test]
+fn ambiguous_option() {
+    assert_eq!(
+        error(&["tail", "--fol"]).to_string(),
+        "option '--fol' is ambiguous; possibilities:\n  - --follow\n  - --follow-name"
+    );
+}
+
+#[test]
+fn parsing_failed() {
+    assert_eq!(
+        error(&["tail", "-n", "x"]).to_string(),
+        "invalid argument 'x' for '-n': invalid digit found in string"
+    );
+}
+
+#[test]
+fn unexpected_option() {
+    assert_eq!(error(&["tail", "-x"]).to_string(), "invalid option -- 'x'");
+    assert_eq!(
+        error(&["tail", "--xyz"]).to_string(),
+        "unrecognized option '--xyz'"
+    );
+}
+
+#[test]
+fn exit_code() {
+    assert_eq!(error(&["tail", "-x"]).exit_code(), 1);
+    assert_eq!(Arg::EXIT_CODE, 2);
+}
+
+#[test]
+fn help_and_version_are_returned() {
+    let s = Settings::try_parse(["tail", "-n", "3"]).unwrap();
+    assert_eq!(s.lines, 3);
+
+    // Nothing after the help is parsed, so the invalid option is ignored
+    match error(&["tail", "--help", "-x"]) {
+        Error::DisplayHelp(text) => assert!(text.contains("tail [OPTION]...")),
+        err => panic!("expected the help, got {err}"),
+    }
+
+    let err = error(&["tail", "--version"]);
+    assert!(
+        matches!(&err, Error::DisplayVersion(text) if text.contains(env!("CARGO_PKG_VERSION")))
+    );
+    assert!(err.to_string().ends_with('\n'));
+    assert_eq!(err.exit_code(), 0);
+}
+
+#[test]
+fn source() {
+    use std::error::Error as _;
+
+    assert!(error(&["tail", "-n", "x"]).source().is_some());
+    assert!(error(&["tail", "-x"]).source().is_none());
+}
+
+#[test]
+fn from_lexopt_parsing_failed() {
+    use uutils_args::lexopt::ValueExt;
+
+    let lexopt_err = OsString::from("x").parse::<u32>().unwrap_err();
+    let err = Error::from(lexopt_err);
+    let Error::ParsingFailed { option, value, .. } = &err else {
+        panic!("expected a parsing error");
+    };
+    assert!(option.is_empty());
+    assert_eq!(value, "x");
+    assert_eq!(
+        err.to_string(),
+        "invalid argument 'x': invalid digit found in string"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn parsing_failed_non_unicode() {
+    use std::os::unix::ffi::OsStringExt;
+
+    let value = OsString::from_vec(vec![b'1', 0xff]);
+    let Err(err) =
+        Settings::try_parse([OsString::from("tail"), OsString::from("-n"), value.clone()])
+    else {
+        panic!("expected an error");
+    };
+    let Error::ParsingFailed {
+        option, value: v, ..
+    } = &err
+    else {
+        panic!("expected a parsing error, got {err}");
+    };
+    assert_eq!(option, "-n");
+    assert_eq!(v, &value);
+    assert_eq!(
+        err.to_string(),
+        "invalid argument '1\u{FFFD}' for '-n': invalid digit found in string"
+    );
+}
+
+#[test]
+fn control_characters_are_escaped() {
+    assert_eq!(
+        error(&["tail", "-n", "1\t2\n"]).to_string(),
+        "invalid argument '1\\t2\\n' for '-n': invalid digit found in string"
+    );
+    assert_eq!(
+        error(&["tail", "--\x1b[2J"]).to_string(),
+        "unrecognized option '--\\x1b[2J'"
+    );
+    assert_eq!(
+        error(&["tail", "-\x07"]).to_string(),
+        "invalid option -- '\\x07'"
+    );
+    assert_eq!(
+        Error::ExtraOperand("a\rb".into()).to_string(),
+        "extra operand 'a\\rb'"
+    );
+    assert_eq!(
+        Error::InvalidValue {
+            option: "--color".into(),
+            value: "\x1b]0;title\x07".into(),
+            expected: vec!["auto".into()],
+        }
+        .to_string(),
+        "invalid argument '\\x1b]0;title\\x07' for '--color'\nValid arguments are:\n  - auto"
+    );
+}
+
+#[test]
+fn values_are_kept_unescaped() {
+    let Error::ParsingFailed { value, .. } = error(&["tail", "-n", "\x1b\n"]) else {
+        panic!("expected a parsing error");
+    };
+    assert_eq!(value, "\x1b\n");
+}
+
+#[test]
+fn with_source() {
+    use std::error::Error as _;
+    use uutils_args::{FromValue, ValueSource};
+
+    let err = || u16::from_value("", "1x0".into()).unwrap_err();
+
+    let cli = err().with_source(ValueSource::Cli {
+        flag: "--width".into(),
+    });
+    assert_eq!(
+        cli.to_string(),
+        "invalid argument '1x0' for '--width': invalid digit found in string"
+    );
+
+    let config = err().with_source(ValueSource::Config {
+        layer: "user".into(),
+    });
+    assert_eq!(
+        config.to_string(),
+        "invalid value '1x0' for user configuration: invalid digit found in string"
+    );
+
+    // The first source is kept.
+    let twice = config.with_source(ValueSource::Default);
+    assert!(matches!(
+        twice,
+        Error::FromSource {
+            source: ValueSource::Config { .. },
+            ..
+        }
+    ));
+
+    let custom = Error::Custom("not a width".into()).with_source(ValueSource::Env {
+        var: "COLUMNS".into(),
+    });
+    assert_eq!(
+        custom.to_string(),
+        "invalid value for environment variable COLUMNS: not a width"
+    );
+    assert!(custom.source().is_some());
+}
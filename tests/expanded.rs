@@ -0,0 +1,93 @@
+use std::ffi::OsString;
+
+use uutils_args::{Argument, Arguments, Error, Options};
+
+#[derive(Clone, Debug, PartialEq, Eq, Arguments)]
+enum Arg {
+    #[option("-a", "--all", "--almost-everything")]
+    All,
+
+    #[option("-w N", "--width=N")]
+    Width(u16),
+
+    #[positional(1..)]
+    File(OsString),
+}
+
+#[derive(Default, Debug, PartialEq, Eq, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+
+    #[map(Arg::Width(w) => w)]
+    width: u16,
+
+    #[collect(set(Arg::File))]
+    files: Vec<OsString>,
+}
+
+fn expanded(args: &[&str]) -> Vec<Result<Argument<Arg>, Error>> {
+    let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+    Arg::parse(args).expanded().collect()
+}
+
+#[test]
+fn same_as_parse() {
+    let args = ["ls", "--alm", "-w", "10", "a", "--width=20", "b"];
+
+    let custom: Vec<Arg> = expanded(&args)
+        .into_iter()
+        .map(|arg| match arg {
+            Ok(Argument::Custom(arg)) => arg,
+            _ => panic!("expected only custom arguments"),
+        })
+        .collect();
+    assert_eq!(
+        custom,
+        [
+            Arg::All,
+            Arg::Width(10),
+            Arg::File("a".into()),
+            Arg::Width(20),
+            Arg::File("b".into()),
+        ]
+    );
+
+    let mut settings = Settings::default();
+    for arg in custom {
+        match arg {
+            Arg::All => settings.all = true,
+            Arg::Width(w) => settings.width = w,
+            Arg::File(f) => settings.files.push(f),
+        }
+    }
+    assert_eq!(settings, Settings::try_parse(args).unwrap());
+}
+
+#[test]
+fn missing_positional() {
+    let mut args = expanded(&["ls", "-a"]).into_iter();
+    assert!(matches!(args.next(), Some(Ok(Argument::Custom(Arg::All)))));
+    assert!(matches!(
+        args.next(),
+        Some(Err(Error::MissingPositionalArguments(_)))
+    ));
+    assert!(args.next().is_none());
+}
+
+#[test]
+fn stops_after_error() {
+    let args = expanded(&["ls", "-x", "a"]);
+    assert!(matches!(&args[..], [Err(Error::UnexpectedOption(_))]));
+}
+
+#[test]
+fn flag_of_last_argument() {
+    let mut args = Arg::parse(["ls", "--wid=3", "a"]).expanded();
+    assert!(matches!(
+        args.next(),
+        Some(Ok(Argument::Custom(Arg::Width(3))))
+    ));
+    assert_eq!(args.inner().flag(), "--width");
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-d DELIM")]
+    Delimiter(String),
+
+    #[option("-o DELIM")]
+    OutputDelimiter(Option<String>),
+
+    #[option("--color[=WHEN]")]
+    Color(Option<String>),
+
+    #[option("--width[=N]", default = 80)]
+    Width(u16),
+
+    #[positional(1)]
+    Pattern(Option<String>),
+
+    #[positional(.., last)]
+    Files(Vec<PathBuf>),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Delimiter(d) => Some(d))]
+    delimiter: Option<String>,
+
+    #[map(Arg::OutputDelimiter(d) => d)]
+    output_delimiter: Option<String>,
+
+    #[map(Arg::Color(c) => Some(c))]
+    color: Option<Option<String>>,
+
+    #[map(Arg::Width(w) => w)]
+    width: u16,
+
+    #[map(Arg::Pattern(p) => p)]
+    pattern: Option<String>,
+
+    #[map(Arg::Files(f) => f)]
+    files: Vec<PathBuf>,
+}
+
+#[test]
+fn single_values() {
+    let s = Settings::try_parse(["cut", "-d", ",", "-o", ";", "pat"]).unwrap();
+    assert_eq!(s.delimiter.unwrap(), ",");
+    assert_eq!(s.output_delimiter.unwrap(), ";");
+    assert_eq!(s.pattern.unwrap(), "pat");
+    assert!(s.files.is_empty());
+}
+
+#[test]
+fn optional_values() {
+    let s = Settings::try_parse(["cut", "--color", "--width", "p"]).unwrap();
+    assert_eq!(s.color, Some(None));
+    assert_eq!(s.width, 80);
+
+    let s = Settings::try_parse(["cut", "--color=auto", "--width=3", "p"]).unwrap();
+    assert_eq!(s.color, Some(Some("auto".into())));
+    assert_eq!(s.width, 3);
+}
+
+#[test]
+fn collected_values() {
+    let s = Settings::try_parse(["cut", "p", "a", "-d", "b"]).unwrap();
+    assert!(s.delimiter.is_none());
+    assert_eq!(s.files, [PathBuf::from("a"), "-d".into(), "b".into()]);
+}
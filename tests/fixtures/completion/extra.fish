@@ -0,0 +1,2 @@
+# Units of --dir, written by hand
+complete -c extra -l dir -f -a 'K M G'
@@ -0,0 +1,140 @@
+use uutils_args::{complete, Arguments, Error, Options};
+
+#[derive(Clone, Arguments)]
+enum Common {
+    /// Print debugging information
+    #[option("--debug")]
+    Debug,
+
+    /// Set the log level
+    #[option("-L LEVEL", "--log-level=LEVEL")]
+    LogLevel(u8),
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Include all entries
+    #[option("-a", "--all")]
+    All,
+
+    /// Separate the fields with DELIM
+    #[option("-d DELIM", "--delimiter=DELIM")]
+    Delimiter(String),
+
+    #[flatten]
+    Common(Common),
+
+    #[positional(..)]
+    File(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+
+    #[set(Arg::Delimiter)]
+    delimiter: String,
+
+    #[map(Arg::Common(Common::Debug) => true)]
+    debug: bool,
+
+    #[map(Arg::Common(Common::LogLevel(level)) => level)]
+    log_level: u8,
+
+    #[collect(set(Arg::File))]
+    files: Vec<String>,
+}
+
+fn parse(args: &'static [&'static str]) -> Result<Settings, Error> {
+    Settings::try_parse(args.iter().copied())
+}
+
+#[test]
+fn local_and_flattened() {
+    let s = parse(&["prog", "-a", "--debug", "-L", "3", "file", "-d:"]).unwrap();
+    assert!(s.all);
+    assert!(s.debug);
+    assert_eq!(s.log_level, 3);
+    assert_eq!(s.delimiter, ":");
+    assert_eq!(s.files, ["file"]);
+
+    let s = parse(&["prog", "--log-level=5", "--all"]).unwrap();
+    assert!(s.all);
+    assert!(!s.debug);
+    assert_eq!(s.log_level, 5);
+}
+
+#[test]
+fn cluster() {
+    // A flattened option with a value takes the rest of the cluster
+    let s = parse(&["prog", "-aL2"]).unwrap();
+    assert!(s.all);
+    assert_eq!(s.log_level, 2);
+
+    let s = parse(&["prog", "-ad", "L2"]).unwrap();
+    assert_eq!(s.delimiter, "L2");
+    assert_eq!(s.log_level, 0);
+}
+
+#[test]
+fn abbreviated() {
+    let s = parse(&["prog", "--deb", "--log", "4", "--del=,"]).unwrap();
+    assert!(s.debug);
+    assert_eq!(s.log_level, 4);
+    assert_eq!(s.delimiter, ",");
+
+    // The local and flattened options are abbreviated together
+    assert!(matches!(
+        parse(&["prog", "--de"]),
+        Err(Error::AmbiguousOption { option, candidates })
+            if option == "de" && candidates == ["delimiter", "debug"]
+    ));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        parse(&["prog", "--log-level=x"]).err().unwrap().to_string(),
+        "invalid argument 'x' for '--log-level': invalid digit found in string"
+    );
+    assert_eq!(
+        parse(&["prog", "--debug=1"]).err().unwrap().to_string(),
+        "option '--debug' doesn't allow an argument"
+    );
+    assert!(matches!(
+        parse(&["prog", "-x"]),
+        Err(Error::UnexpectedOption(_))
+    ));
+}
+
+#[test]
+fn help() {
+    let help = Arg::help("prog");
+    let all = help.find("--all").unwrap();
+    let debug = help.find("--debug").unwrap();
+    let version = help.find("--version").unwrap();
+    assert!(all < debug && debug < version);
+    assert!(help.contains("  -L LEVEL, --log-level=LEVEL\n"));
+    assert!(help.contains("Print debugging information"));
+
+    let short_help = Arg::short_help("prog");
+    assert!(short_help.contains("\n      --debug\n"));
+}
+
+#[test]
+fn completion() {
+    let fish = complete::render(&Arg::complete(), "fish");
+    assert!(fish.contains("-l debug"));
+    assert!(fish.contains("-s L -l log-level"));
+
+    // The help of the flattened enum is not merged
+    let command = Arg::complete();
+    let help_options = command
+        .args
+        .iter()
+        .filter(|arg| arg.long.iter().any(|flag| flag.flag == "help"))
+        .count();
+    assert_eq!(help_options, 1);
+}
@@ -0,0 +1,181 @@
+use uutils_args::{
+    getopt::{normalize, GetoptSpec, NormalizedToken},
+    ParseMode,
+};
+
+fn flag(canonical: &str) -> NormalizedToken {
+    NormalizedToken::flag(canonical, None)
+}
+
+fn value(canonical: &str, value: &str) -> NormalizedToken {
+    NormalizedToken::flag(canonical, Some(value))
+}
+
+fn operand(value: &str) -> NormalizedToken {
+    NormalizedToken::Operand(value.into())
+}
+
+/// The tokens and error messages of normalizing `args` for `spec`
+fn run(spec: &GetoptSpec, args: &[&str]) -> (Vec<NormalizedToken>, Vec<String>) {
+    let normalized = normalize(spec, args.iter().copied(), true);
+    let errors = normalized.errors.iter().map(ToString::to_string).collect();
+    (normalized.tokens, errors)
+}
+
+fn tokens(spec: &GetoptSpec, args: &[&str]) -> Vec<NormalizedToken> {
+    let (tokens, errors) = run(spec, args);
+    assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+    tokens
+}
+
+// getopt ab:c:: -a -b val arg
+#[test]
+fn separate_options() {
+    let spec = GetoptSpec::new("ab:c::");
+    assert_eq!(
+        tokens(&spec, &["-a", "-b", "val", "arg"]),
+        [flag("-a"), value("-b", "val"), operand("arg")]
+    );
+}
+
+// getopt ab: -abval
+#[test]
+fn cluster_with_value() {
+    let spec = GetoptSpec::new("ab:");
+    assert_eq!(tokens(&spec, &["-abval"]), [flag("-a"), value("-b", "val")]);
+}
+
+// getopt ab: -b -a
+#[test]
+fn required_value_looks_like_option() {
+    let spec = GetoptSpec::new("ab:");
+    assert_eq!(tokens(&spec, &["-b", "-a"]), [value("-b", "-a")]);
+}
+
+// getopt c:: -c foo
+// getopt c:: -cfoo
+#[test]
+fn optional_value() {
+    let spec = GetoptSpec::new("c::");
+    assert_eq!(tokens(&spec, &["-c", "foo"]), [flag("-c"), operand("foo")]);
+    assert_eq!(tokens(&spec, &["-cfoo"]), [value("-c", "foo")]);
+
+    let spec = GetoptSpec::new("").long("color::");
+    assert_eq!(
+        tokens(&spec, &["--color", "auto"]),
+        [flag("--color"), operand("auto")]
+    );
+    assert_eq!(tokens(&spec, &["--color=auto"]), [value("--color", "auto")]);
+}
+
+// getopt -o v -l verbose,file: -- --verb --file=x y --file z
+#[test]
+fn long_options() {
+    let spec = GetoptSpec::new("v").long("verbose,file:");
+    assert_eq!(
+        tokens(&spec, &["--verb", "--file=x", "y", "--file", "z"]),
+        [
+            flag("--verbose"),
+            value("--file", "x"),
+            value("--file", "z"),
+            operand("y")
+        ]
+    );
+}
+
+// getopt -o '' -l foo,foobar -- --foo
+#[test]
+fn exact_long_option() {
+    let spec = GetoptSpec::new("").long("foo,foobar");
+    assert_eq!(tokens(&spec, &["--foo"]), [flag("--foo")]);
+    assert_eq!(tokens(&spec, &["--foob"]), [flag("--foobar")]);
+}
+
+// getopt -o '' -l verbose,version -- --ver -- x
+#[test]
+fn ambiguous_long_option() {
+    let spec = GetoptSpec::new("").long("verbose version");
+    let (tokens, errors) = run(&spec, &["--ver", "x"]);
+    assert_eq!(tokens, [operand("x")]);
+    assert_eq!(
+        errors,
+        ["option '--ver' is ambiguous; possibilities:\n  - --verbose\n  - --version"]
+    );
+}
+
+// getopt a -x -a --xyz=1 -a
+#[test]
+fn unrecognized_options_continue() {
+    let spec = GetoptSpec::new("a");
+    let (tokens, errors) = run(&spec, &["-x", "-a", "--xyz=1", "-ya"]);
+    assert_eq!(tokens, [flag("-a"), flag("-a")]);
+    assert_eq!(
+        errors,
+        [
+            "invalid option -- 'x'",
+            "unrecognized option '--xyz'",
+            "invalid option -- 'y'"
+        ]
+    );
+}
+
+// getopt ab: -a -b
+#[test]
+fn missing_value() {
+    let spec = GetoptSpec::new("ab:").long("file:");
+    let (tokens, errors) = run(&spec, &["-a", "-b"]);
+    assert_eq!(tokens, [flag("-a")]);
+    assert_eq!(errors, ["option requires an argument -- 'b'"]);
+
+    let (_, errors) = run(&spec, &["--file"]);
+    assert_eq!(errors, ["option '--file' requires an argument"]);
+}
+
+// getopt -o '' -l verbose -- --verbose=yes -- x
+#[test]
+fn unexpected_value() {
+    let spec = GetoptSpec::new("").long("verbose");
+    let (tokens, errors) = run(&spec, &["--verbose=yes", "x"]);
+    assert_eq!(tokens, [operand("x")]);
+    assert_eq!(errors, ["option '--verbose' doesn't allow an argument"]);
+}
+
+// getopt a x -a -- -a - y
+#[test]
+fn permute() {
+    let spec = GetoptSpec::new("a");
+    assert_eq!(
+        tokens(&spec, &["x", "-a", "--", "-a", "-", "y"]),
+        [
+            flag("-a"),
+            operand("x"),
+            operand("-a"),
+            operand("-"),
+            operand("y")
+        ]
+    );
+}
+
+// getopt +a -a x -a
+// POSIXLY_CORRECT=1 getopt a -a x -a
+#[test]
+fn posix() {
+    let spec = GetoptSpec::new("+a");
+    assert_eq!(spec.mode(), ParseMode::Posix);
+    assert_eq!(
+        tokens(&spec, &["-a", "x", "-a", "--"]),
+        [flag("-a"), operand("x"), operand("-a"), operand("--")]
+    );
+
+    let spec = GetoptSpec::new("a").parse_mode(ParseMode::Posix);
+    assert_eq!(
+        tokens(&spec, &["-a", "x", "-a"]),
+        [flag("-a"), operand("x"), operand("-a")]
+    );
+}
+
+#[test]
+fn no_arguments() {
+    let spec = GetoptSpec::new("ab:");
+    assert_eq!(tokens(&spec, &[]), []);
+}
@@ -0,0 +1,343 @@
+use uutils_args::{Argument, Arguments, FromValue};
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Arg {
+    /// Do not ignore entries starting with .
+    #[option("-a", "--all")]
+    All,
+
+    /// Sort by file size, largest first
+    #[option("-S", category = "Sorting")]
+    SortSize,
+
+    /// Use a long listing format
+    #[option("-l", category = "Format")]
+    Long,
+
+    /// Reverse order while sorting
+    #[option("-r", "--reverse", category = "Sorting")]
+    Reverse,
+
+    #[positional(..)]
+    File(String),
+}
+
+#[test]
+fn categories() {
+    let help = Arg::help("ls");
+    let (_, help) = help.split_once("Usage:\n").unwrap();
+    assert_eq!(
+        help,
+        "  ls [OPTION]... [FILE]...
+
+Sorting:
+  -S                Sort by file size, largest first
+  -r, --reverse     Reverse order while sorting
+
+Format:
+  -l                Use a long listing format
+
+Options:
+  -a, --all         Do not ignore entries starting with .
+      --help        Display this help message
+      --version     Display version information
+"
+    );
+}
+
+#[test]
+fn category_in_completion() {
+    let categories: Vec<_> = Arg::complete()
+        .args
+        .iter()
+        .map(|arg| arg.category)
+        .collect();
+    assert_eq!(
+        categories,
+        [
+            None,
+            Some("Sorting"),
+            Some("Format"),
+            Some("Sorting"),
+            None,
+            None
+        ]
+    );
+}
+
+#[test]
+fn default_and_possible_values() {
+    #[derive(Clone, Default, FromValue)]
+    #[value_extras(Display)]
+    enum When {
+        #[default]
+        #[value("always", "yes")]
+        Always,
+        #[value("auto")]
+        Auto,
+        #[value("never", "no")]
+        Never,
+    }
+
+    #[derive(Clone, FromValue)]
+    enum Signal {
+        #[value("HUP")]
+        Hup,
+        #[value("KILL")]
+        Kill,
+    }
+
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Colorize the output
+        #[option("--color[=WHEN]", default = When::Always)]
+        Color(When),
+
+        /// Send SIGNAL on timeout
+        #[option("-s SIGNAL", hide_possible_values)]
+        Signal(Signal),
+
+        // `PathBuf` does not implement `Display`, so the expression is shown.
+        /// Use DIR for the temporary file
+        #[option("-p DIR", "--tmpdir[=DIR]", mixed_arity, default = ".".into())]
+        TmpDir(std::path::PathBuf),
+
+        /// Use NUM columns
+        #[option("-w NUM")]
+        Width(u16),
+    }
+
+    let help = Arg::help("test");
+    let (_, help) = help.split_once("Options:\n").unwrap();
+    assert_eq!(
+        help,
+        "      --color[=WHEN]
+                    Colorize the output
+                    [default: always]
+                    [possible values: always, yes, auto, never, no]
+  -s SIGNAL         Send SIGNAL on timeout
+  -p DIR, --tmpdir[=DIR]
+                    Use DIR for the temporary file
+                    [default: \".\".into()]
+  -w NUM            Use NUM columns
+      --help        Display this help message
+      --version     Display version information
+"
+    );
+}
+
+#[test]
+fn short_and_long_help() {
+    #[derive(Clone, Arguments)]
+    #[arguments(
+        help = ["--help"],
+        short_help = ["-h", "--usage"],
+        file = "tests/fixtures/help/dir.md"
+    )]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Do not ignore entries starting with .
+        #[option("-a", "--all")]
+        All,
+
+        /// Sort by file size, largest first
+        #[option("-S", category = "Sorting")]
+        SortSize,
+
+        #[positional(..)]
+        File(String),
+    }
+
+    let short = Arg::short_help("dir");
+    assert_eq!(
+        short,
+        "Usage:
+  dir [OPTION]... [FILE]...
+
+Sorting:
+  -S
+
+Options:
+  -a, --all
+      --help
+  -h, --usage
+      --version
+
+Try 'dir --help' for more information.
+"
+    );
+
+    let long = Arg::help("dir");
+    assert!(long.contains("List directory contents in columns."));
+    assert!(long.contains("  -a, --all         Do not ignore entries starting with ."));
+    assert!(long.contains("  -h, --usage       Display a short help message"));
+    assert!(long.contains("Equivalent to"));
+    assert!(!short.contains("Equivalent to"));
+    assert!(!short.contains("Do not ignore"));
+
+    let kind = |args: &'static [&'static str]| Arg::parse(args.to_vec()).next_arg().unwrap();
+    assert!(matches!(kind(&["dir", "--help"]), Some(Argument::Help)));
+    assert!(matches!(kind(&["dir", "-h"]), Some(Argument::ShortHelp)));
+    assert!(matches!(
+        kind(&["dir", "--usage"]),
+        Some(Argument::ShortHelp)
+    ));
+    assert!(matches!(kind(&["dir", "--us"]), Some(Argument::ShortHelp)));
+}
+
+#[test]
+fn value_name() {
+    use uutils_args::complete::Value;
+
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Use SIZE bytes
+        #[option(
+            "-s NUM",
+            "--size=SIZE",
+            "--max[=NUM]",
+            value_name = "SIZE",
+            mixed_arity,
+            default = 0
+        )]
+        Size(u64),
+    }
+
+    let help = Arg::help("test");
+    assert!(help.contains("  -s SIZE, --size=SIZE, --max[=SIZE]\n"));
+
+    let command = Arg::complete();
+    let values: Vec<_> = command.args[0]
+        .short
+        .iter()
+        .chain(&command.args[0].long)
+        .map(|flag| match flag.value {
+            Value::Required(name) | Value::Optional(name) => name,
+            Value::No => panic!("expected a value"),
+        })
+        .collect();
+    assert_eq!(values, ["SIZE", "SIZE", "SIZE"]);
+}
+
+#[test]
+fn help_flag_spelling() {
+    #[derive(Clone, Arguments)]
+    #[arguments(help = ["--help", "-?"], short_help = ["-h"], version = ["--version", "-V"])]
+    #[allow(dead_code)]
+    enum Arg {
+        #[option("-a", "--all")]
+        All,
+    }
+
+    let mut iter = Arg::parse(["ls", "--hel"]);
+    assert!(matches!(iter.next_arg(), Ok(Some(Argument::Help))));
+    assert_eq!(iter.flag(), "--help");
+    assert_eq!(iter.bin_name(), "ls");
+
+    let mut iter = Arg::parse(["ls", "-a?"]);
+    assert!(matches!(
+        iter.next_arg(),
+        Ok(Some(Argument::Custom(Arg::All)))
+    ));
+    assert!(matches!(iter.next_arg(), Ok(Some(Argument::Help))));
+    assert_eq!(iter.flag(), "-?");
+
+    let mut iter = Arg::parse(["ls", "-ah"]);
+    iter.next_arg().unwrap();
+    assert!(matches!(iter.next_arg(), Ok(Some(Argument::ShortHelp))));
+    assert_eq!(iter.flag(), "-h");
+
+    let mut iter = Arg::parse(["ls", "-V"]);
+    assert!(matches!(iter.next_arg(), Ok(Some(Argument::Version))));
+    assert_eq!(iter.flag(), "-V");
+}
+
+#[test]
+fn help_text() {
+    #[derive(Clone, Arguments)]
+    #[arguments(file = "tests/fixtures/help/dir.md")]
+    #[allow(dead_code)]
+    enum Arg {
+        /// Do not ignore entries starting with `.`, which are hidden by
+        /// default, and also list the implied entries `.` and `..` when
+        /// **combined** with other options
+        #[option("-a", "--all")]
+        All,
+
+        #[option("-w COLS", "--width=COLS", default = 80)]
+        Width(u16),
+
+        #[option("--quoting-style=WORD")]
+        QuotingStyle(String),
+
+        #[option("-n NUM", default = 1)]
+        Num(u8),
+
+        /// Sort by WORD instead of name
+        #[option("--sort=WORD", category = "Sorting", default = "name".into())]
+        Sort(String),
+
+        #[positional(..)]
+        File(String),
+    }
+
+    let help = Arg::help("dir");
+    let (_, help) = help.split_once('\n').unwrap();
+    assert_eq!(
+        help,
+        "List directory contents in columns.
+
+Usage:
+  dir [OPTION]... [FILE]...
+
+Sorting:
+      --sort=WORD   Sort by WORD instead of name
+                    [default: name]
+
+Options:
+  -a, --all         Do not ignore entries starting with \x1b[38;5;250m.\x1b[0m, which are hidden by 
+                    default, and also list the implied entries \x1b[38;5;250m.\x1b[0m and \x1b[38;5;250m..\x1b[0m when \x1b[1m
+                    combined\x1b[0m with other options
+  -w COLS, --width=COLS
+                    [default: 80]
+      --quoting-style=WORD
+  -n NUM            [default: 1]
+      --help        Display this help message
+      --version     Display version information
+
+Equivalent to \x1b[38;5;250mls -C -b\x1b[0m.
+"
+    );
+
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Cp {
+        /// Copy directories recursively
+        #[option("-r")]
+        Recursive,
+
+        #[positional(1..)]
+        Source(String),
+
+        #[positional(from_end = 1)]
+        Dest(String),
+    }
+
+    let help = Cp::help("cp");
+    let (_, help) = help.split_once('\n').unwrap();
+    assert_eq!(
+        help,
+        "
+Usage:
+  cp [OPTION]... SOURCE... DEST
+
+Options:
+  -r                Copy directories recursively
+      --help        Display this help message
+      --version     Display version information
+"
+    );
+}
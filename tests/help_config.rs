@@ -0,0 +1,107 @@
+use uutils_args::{complete, Arguments, HelpConfig, Options};
+
+#[derive(Clone, Arguments)]
+#[arguments(file = "tests/fixtures/help/ls.md")]
+enum Arg {
+    /// Show all
+    #[option("-a", "--all")]
+    All,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+}
+
+const DIR: HelpConfig = HelpConfig {
+    name: "dir",
+    file: include_str!("fixtures/help/dir.md"),
+    after_help: "",
+};
+
+#[test]
+fn default_help() {
+    let help = Arg::help("ls");
+    assert!(help.starts_with(&format!("{} ", Arg::NAME)));
+    assert!(help.contains("List directory contents."));
+    assert!(help.contains("Usage:\n  ls [OPTION]...\n"));
+    assert!(help.contains("Colors are used by default"));
+}
+
+#[test]
+fn help_from_config() {
+    let ls = Arg::help("ls");
+    let dir = Arg::help_with_config("dir", &DIR);
+    assert_ne!(ls, dir);
+
+    assert!(dir.starts_with("dir "));
+    assert!(dir.contains("List directory contents in columns."));
+    assert!(dir.contains("Usage:\n  dir [OPTION]... [FILE]...\n"));
+    assert!(dir.contains("--all"));
+    assert!(dir.contains("Equivalent to"));
+    assert!(!dir.contains("Colors are used by default"));
+}
+
+#[test]
+fn after_help_overrides_file() {
+    let config = HelpConfig {
+        after_help: "Equivalent to `ls -l -b`.",
+        ..DIR
+    };
+    let help = Arg::help_with_config("vdir", &config);
+    assert!(help.contains("List directory contents in columns."));
+    assert!(help.contains("ls -l -b"));
+    assert!(!help.contains("ls -C -b"));
+
+    // Only the text after the options is replaced.
+    let config = HelpConfig {
+        after_help: "Some text.",
+        ..HelpConfig::default()
+    };
+    let help = Arg::help_with_config("ls", &config);
+    assert!(help.contains("List directory contents."));
+    assert!(help.contains("Some text."));
+    assert!(!help.contains("Colors are used by default"));
+}
+
+#[test]
+fn version_from_config() {
+    assert!(Arg::version().starts_with(&format!("{} ", Arg::NAME)));
+    assert!(Arg::version_with_config(&DIR).starts_with("dir "));
+}
+
+#[test]
+fn parse_with_help() {
+    let s = Settings::try_parse_with_help(["dir", "-a"], DIR).unwrap();
+    assert!(s.all);
+
+    let iter = Arg::parse(["dir"]).with_help_config(DIR);
+    assert!(iter.help().contains("List directory contents in columns."));
+    assert!(iter.version().starts_with("dir "));
+}
+
+#[test]
+fn complete_from_config() {
+    let c = Arg::complete_with_config(&DIR);
+    assert_eq!(c.name, "dir");
+    assert_eq!(c.summary, "List directory contents in columns.");
+    assert_eq!(c.after_options, "Equivalent to ls -C -b.");
+
+    let c = Arg::complete();
+    assert_eq!(c.name, Arg::NAME);
+    assert_eq!(c.summary, "List directory contents.");
+
+    assert!(
+        complete::render(&Arg::complete_with_config(&DIR), "fish").starts_with("complete -c dir ")
+    );
+}
+
+#[test]
+fn complete_text_generated_once() {
+    let first = Arg::complete_with_config(&DIR);
+    let second = Arg::complete_with_config(&DIR);
+    assert!(std::ptr::eq(first.summary, second.summary));
+    assert!(std::ptr::eq(first.after_options, second.after_options));
+}
@@ -0,0 +1,85 @@
+use std::{cell::RefCell, ffi::OsString};
+
+use uutils_args::ParseConfig;
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(bin_name: &str, warning: &str) {
+    WARNINGS.with(|w| w.borrow_mut().push(format!("{bin_name}: {warning}")));
+}
+
+fn config(env: fn(&str) -> Option<OsString>) -> ParseConfig {
+    ParseConfig {
+        env,
+        warn: record,
+        ..ParseConfig::default()
+    }
+}
+
+fn no_env(_: &str) -> Option<OsString> {
+    None
+}
+
+/// The resolved width and the warnings reported while resolving it
+fn width(detected: Option<usize>, env: fn(&str) -> Option<OsString>) -> (usize, Vec<String>) {
+    let width = config(env).help_width("ls", detected);
+    (width, WARNINGS.with(|w| w.take()))
+}
+
+#[test]
+fn resolution_order() {
+    fn columns(name: &str) -> Option<OsString> {
+        (name == "COLUMNS").then(|| "100".into())
+    }
+
+    assert_eq!(width(Some(120), columns), (120, vec![]));
+    assert_eq!(width(None, columns), (100, vec![]));
+    assert_eq!(width(None, no_env), (80, vec![]));
+}
+
+#[test]
+fn floor() {
+    fn narrow(_: &str) -> Option<OsString> {
+        Some("5".into())
+    }
+
+    assert_eq!(width(Some(1), no_env).0, ParseConfig::MIN_HELP_WIDTH);
+    assert_eq!(width(None, narrow).0, ParseConfig::MIN_HELP_WIDTH);
+}
+
+#[test]
+fn unknown_widths() {
+    fn zero(_: &str) -> Option<OsString> {
+        Some("0".into())
+    }
+    fn huge(_: &str) -> Option<OsString> {
+        Some("100000".into())
+    }
+    fn invalid(_: &str) -> Option<OsString> {
+        Some("wide".into())
+    }
+
+    assert_eq!(width(Some(0), no_env), (80, vec![]));
+    assert_eq!(width(Some(1 << 40), no_env), (80, vec![]));
+
+    for (env, value) in [
+        (zero as fn(&str) -> _, "0"),
+        (huge, "100000"),
+        (invalid, "wide"),
+    ] {
+        assert_eq!(
+            width(Some(0), env),
+            (
+                80,
+                vec![format!(
+                    "ls: ignoring invalid value in environment variable COLUMNS: '{value}'"
+                )]
+            )
+        );
+    }
+
+    // The environment is not read if the width is known
+    assert_eq!(width(Some(90), invalid), (90, vec![]));
+}
@@ -0,0 +1,198 @@
+//! The parsed result must not depend on the item type of the iterator that
+//! is passed to `parse`.
+//!
+//! The coreutils fixtures are checked with every item type as well, by the
+//! `coreutils_*` test crates.
+
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+
+use uutils_args::{Arguments, FromValue, Options};
+
+/// Parse the arguments given as `&str`, `String`, `OsString` and
+/// `Cow<OsStr>` and run the same assertions on each result.
+macro_rules! for_each_item_type {
+    ($settings:ty, $args:expr, |$s:ident| $body:expr) => {{
+        let args: &'static [&'static str] = &$args;
+
+        let $s = <$settings>::try_parse(args.iter().copied());
+        $body;
+
+        let $s = <$settings>::try_parse(args.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+        $body;
+
+        let $s = <$settings>::try_parse(args.iter().map(OsString::from).collect::<Vec<_>>());
+        $body;
+
+        let $s = <$settings>::try_parse(
+            args.iter()
+                .map(|a| Cow::Borrowed(OsStr::new(a)))
+                .collect::<Vec<_>>(),
+        );
+        $body;
+    }};
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, FromValue)]
+enum When {
+    #[value("always", "yes")]
+    Always,
+    #[default]
+    #[value("auto")]
+    Auto,
+    #[value("never", "no")]
+    Never,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-a", "--all")]
+    All,
+
+    #[option("-w N", "--width=N")]
+    Width(u64),
+
+    #[option("-m MSG", "--message=MSG")]
+    Message(String),
+
+    #[option("--color[=WHEN]", default = When::Always)]
+    Color(When),
+
+    #[positional(1)]
+    First(String),
+
+    #[positional(last, ..)]
+    Rest(Vec<OsString>),
+}
+
+#[derive(Default, Options, Debug, PartialEq, Eq)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+
+    #[set(Arg::Width)]
+    width: u64,
+
+    #[set(Arg::Message)]
+    message: String,
+
+    #[set(Arg::Color)]
+    color: When,
+
+    #[set(Arg::First)]
+    first: String,
+
+    #[set(Arg::Rest)]
+    rest: Vec<OsString>,
+}
+
+#[test]
+fn flags() {
+    for_each_item_type!(Settings, ["test", "-a", "x"], |s| {
+        assert!(s.unwrap().all)
+    });
+    for_each_item_type!(Settings, ["test", "--all", "x"], |s| {
+        assert!(s.unwrap().all)
+    });
+    for_each_item_type!(Settings, ["test", "--al", "x"], |s| {
+        assert!(s.unwrap().all)
+    });
+    for_each_item_type!(Settings, ["test", "x"], |s| assert!(!s.unwrap().all));
+}
+
+#[test]
+fn values() {
+    for_each_item_type!(Settings, ["test", "-w", "10", "x"], |s| {
+        assert_eq!(s.unwrap().width, 10)
+    });
+    for_each_item_type!(Settings, ["test", "-w10", "x"], |s| {
+        assert_eq!(s.unwrap().width, 10)
+    });
+    for_each_item_type!(Settings, ["test", "--width=10", "x"], |s| {
+        assert_eq!(s.unwrap().width, 10)
+    });
+    for_each_item_type!(Settings, ["test", "--message", "-a", "x"], |s| {
+        assert_eq!(s.unwrap().message, "-a")
+    });
+    for_each_item_type!(Settings, ["test", "--message=", "x"], |s| {
+        assert_eq!(s.unwrap().message, "")
+    });
+    for_each_item_type!(Settings, ["test", "--width=ten", "x"], |s| {
+        assert!(s.is_err())
+    });
+    for_each_item_type!(Settings, ["test", "--width"], |s| assert!(s.is_err()));
+}
+
+#[test]
+fn optional_values() {
+    for_each_item_type!(Settings, ["test", "x"], |s| {
+        assert_eq!(s.unwrap().color, When::Auto)
+    });
+    for_each_item_type!(Settings, ["test", "--color", "x"], |s| {
+        assert_eq!(s.unwrap().color, When::Always)
+    });
+    for_each_item_type!(Settings, ["test", "--color=no", "x"], |s| {
+        assert_eq!(s.unwrap().color, When::Never)
+    });
+    for_each_item_type!(Settings, ["test", "--color=n", "x"], |s| {
+        assert_eq!(s.unwrap().color, When::Never)
+    });
+}
+
+#[test]
+fn positionals() {
+    for_each_item_type!(Settings, ["test", "x", "y", "-a"], |s| {
+        let s = s.unwrap();
+        assert_eq!(s.first, "x");
+        assert!(!s.all);
+        assert_eq!(s.rest, vec!["y", "-a"]);
+    });
+    for_each_item_type!(Settings, ["test", "-a", "--", "-x"], |s| {
+        let s = s.unwrap();
+        assert!(s.all);
+        assert_eq!(s.first, "-x");
+        assert!(s.rest.is_empty());
+    });
+    for_each_item_type!(Settings, ["test"], |s| assert!(s.is_err()));
+}
+
+#[test]
+fn same_result() {
+    let args = ["test", "-aw5", "--color", "-m", "hi", "x", "y"];
+    let expected = Settings::try_parse(args).unwrap();
+    for_each_item_type!(
+        Settings,
+        ["test", "-aw5", "--color", "-m", "hi", "x", "y"],
+        |s| { assert_eq!(s.unwrap(), expected) }
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let invalid = OsStr::from_bytes(b"\xff\xfe").to_os_string();
+
+    let s = Settings::try_parse([OsString::from("test"), OsString::from("x"), invalid.clone()])
+        .unwrap();
+    assert_eq!(s.rest, vec![invalid.clone()]);
+
+    let s = Settings::try_parse([
+        Cow::Borrowed(OsStr::new("test")),
+        Cow::Borrowed(OsStr::new("x")),
+        Cow::Owned(invalid.clone()),
+    ])
+    .unwrap();
+    assert_eq!(s.rest, vec![invalid.clone()]);
+
+    // String values must reject invalid unicode for every item type.
+    assert!(Settings::try_parse([
+        OsString::from("test"),
+        OsString::from("-m"),
+        invalid.clone(),
+        OsString::from("x"),
+    ])
+    .is_err());
+}
@@ -0,0 +1,100 @@
+use uutils_args::{Arguments, Error, FromValue, Layers, Options};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, FromValue)]
+enum Format {
+    #[default]
+    #[value("long")]
+    Long,
+    #[value("commas")]
+    Commas,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--format=WORD")]
+    Format(Format),
+
+    #[option("-I PATTERN")]
+    Ignore(String),
+
+    #[positional(1)]
+    Source(String),
+
+    #[positional(0..=1, default = "out")]
+    Dest(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::Format)]
+    format: Format,
+
+    #[collect(set(Arg::Ignore))]
+    ignore: Vec<String>,
+
+    #[set(Arg::Source)]
+    source: String,
+
+    #[set(Arg::Dest)]
+    dest: String,
+}
+
+fn layered(
+    config: &'static [&'static str],
+    cli: &'static [&'static str],
+) -> Result<Settings, Error> {
+    let layers = Layers::default();
+    let mut settings = Settings::initial()?;
+    settings.apply_layer(Arg::parse_without_binname(config.iter().copied()), &layers)?;
+    settings.apply_layer(Arg::parse(cli.iter().copied()), &layers)?;
+    settings.finish_layers(layers)?;
+    Ok(settings)
+}
+
+#[test]
+fn later_layer_wins() {
+    let s = layered(
+        &["--format=long", "-I", "*.o"],
+        &["ls", "--format=commas", "a"],
+    )
+    .unwrap();
+    assert_eq!(s.format, Format::Commas);
+    assert_eq!(s.ignore, ["*.o"]);
+
+    let s = layered(&["--format=commas"], &["ls", "a"]).unwrap();
+    assert_eq!(s.format, Format::Commas);
+}
+
+#[test]
+fn positional_in_later_layer() {
+    let s = layered(&["-I", "*~"], &["ls", "a", "b"]).unwrap();
+    assert_eq!(s.source, "a");
+    assert_eq!(s.dest, "b");
+
+    // The positional arguments continue where the earlier layer stopped
+    let s = layered(&["a"], &["ls", "b"]).unwrap();
+    assert_eq!(s.source, "a");
+    assert_eq!(s.dest, "b");
+}
+
+#[test]
+fn defaults_and_missing_after_last_layer() {
+    let s = layered(&[], &["ls", "a"]).unwrap();
+    assert_eq!(s.dest, "out");
+
+    assert_eq!(
+        layered(&["--format=long"], &["ls"])
+            .err()
+            .unwrap()
+            .to_string(),
+        "missing SOURCE operand"
+    );
+    assert_eq!(
+        layered(&["a", "b"], &["ls", "c"])
+            .err()
+            .unwrap()
+            .to_string(),
+        "extra operand 'c'"
+    );
+}
@@ -0,0 +1,78 @@
+use uutils_args::{Arguments, Options, SetMask};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-w N", "--width=N")]
+    Width(u16),
+
+    #[option("--color", "--no-color")]
+    Color,
+
+    #[option("-q")]
+    Quiet,
+
+    #[option("-I PATTERN")]
+    Ignore(String),
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Width(w) => w)]
+    width: u16,
+
+    #[map(Arg::Color => true)]
+    color: bool,
+
+    #[map(Arg::Quiet => true)]
+    quiet: bool,
+
+    #[collect(set(Arg::Ignore))]
+    ignore: Vec<String>,
+
+    /// Not set by any argument
+    name: String,
+}
+
+#[test]
+fn arguments_override_config() {
+    let mut settings = Settings {
+        width: 100,
+        color: true,
+        quiet: false,
+        ignore: vec!["*.o".into()],
+        name: "config".into(),
+    };
+
+    let (cli, mask) = Settings::try_parse_with_mask(["ls", "-w", "40", "-q", "-I", "*~"]).unwrap();
+    assert_eq!(mask.fields(), ["width", "quiet", "ignore"]);
+
+    settings.merge_from(cli, &mask);
+    assert_eq!(
+        settings,
+        Settings {
+            width: 40,
+            color: true,
+            quiet: true,
+            ignore: vec!["*.o".into(), "*~".into()],
+            name: "config".into(),
+        }
+    );
+}
+
+#[test]
+fn empty_mask() {
+    let mut settings = Settings {
+        width: 100,
+        ..Settings::default()
+    };
+    let cli = Settings::try_parse(["ls", "-w", "40"]).unwrap();
+    settings.merge_from(cli, &SetMask::default());
+    assert_eq!(settings.width, 100);
+}
+
+#[test]
+fn fields_of() {
+    assert_eq!(Settings::fields_of(&Arg::Color), ["color"]);
+    assert_eq!(Settings::fields_of(&Arg::Ignore("x".into())), ["ignore"]);
+}
@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+use uutils_args::{ArgKind, Arguments};
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Arg {
+    #[option("-l")]
+    Long,
+
+    #[option("-a", "--all")]
+    All,
+
+    #[option("-h")]
+    Human,
+
+    #[option("-w N", "--width=N")]
+    Width(u64),
+
+    #[option("-c[WHEN]", "--color[=WHEN]")]
+    Color(Option<String>),
+
+    #[positional(..)]
+    File(String),
+}
+
+fn spans(args: &'static [&'static str]) -> Vec<(ArgKind, Range<usize>)> {
+    let mut iter = Arg::parse(args.iter().copied()).with_metadata();
+    let mut count = 0;
+    while iter.next_arg().unwrap().is_some() {
+        count += 1;
+    }
+    let spans = iter.metadata().unwrap().spans().to_vec();
+    assert_eq!(spans.len(), count);
+    spans
+}
+
+fn short(position: usize) -> ArgKind {
+    ArgKind::Short { position }
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut iter = Arg::parse(["test", "-l"]);
+    while iter.next_arg().unwrap().is_some() {}
+    assert!(iter.metadata().is_none());
+}
+
+#[test]
+fn cluster() {
+    assert_eq!(
+        spans(&["test", "-lah"]),
+        [(short(1), 1..2), (short(2), 1..2), (short(3), 1..2)]
+    );
+    assert_eq!(
+        spans(&["test", "-la", "-h"]),
+        [(short(1), 1..2), (short(2), 1..2), (short(1), 2..3)]
+    );
+}
+
+#[test]
+fn attached_values() {
+    assert_eq!(spans(&["test", "-w80"]), [(short(1), 1..2)]);
+    assert_eq!(spans(&["test", "--width=80"]), [(ArgKind::Long, 1..2)]);
+    assert_eq!(
+        spans(&["test", "-lw80", "-a"]),
+        [(short(1), 1..2), (short(2), 1..2), (short(1), 2..3)]
+    );
+    // The value of `-w` is `l`, which is not a separate option.
+    assert!(Arg::parse(["test", "-wl"]).next_arg().is_err());
+}
+
+#[test]
+fn separate_values() {
+    assert_eq!(spans(&["test", "-w", "80"]), [(short(1), 1..3)]);
+    assert_eq!(spans(&["test", "--width", "80"]), [(ArgKind::Long, 1..3)]);
+    assert_eq!(
+        spans(&["test", "-lw", "80", "-a"]),
+        [(short(1), 1..2), (short(2), 1..3), (short(1), 3..4)]
+    );
+    assert_eq!(
+        spans(&["test", "--width", "80", "80"]),
+        [(ArgKind::Long, 1..3), (ArgKind::Positional, 3..4)]
+    );
+}
+
+#[test]
+fn optional_values() {
+    assert_eq!(
+        spans(&["test", "-c", "-l"]),
+        [(short(1), 1..2), (short(1), 2..3)]
+    );
+    assert_eq!(spans(&["test", "-cl"]), [(short(1), 1..2)]);
+    assert_eq!(
+        spans(&["test", "--color", "x"]),
+        [(ArgKind::Long, 1..2), (ArgKind::Positional, 2..3)]
+    );
+}
+
+#[test]
+fn positionals() {
+    assert_eq!(
+        spans(&["test", "a", "-l", "b"]),
+        [
+            (ArgKind::Positional, 1..2),
+            (short(1), 2..3),
+            (ArgKind::Positional, 3..4)
+        ]
+    );
+    assert_eq!(
+        spans(&["test", "-l", "--", "-a", "--"]),
+        [
+            (short(1), 1..2),
+            (ArgKind::Positional, 3..4),
+            (ArgKind::Positional, 4..5)
+        ]
+    );
+}
+
+#[test]
+fn without_binname() {
+    let mut iter = Arg::parse_without_binname(["-la", "x"]).with_metadata();
+    while iter.next_arg().unwrap().is_some() {}
+    assert_eq!(
+        iter.metadata().unwrap().spans(),
+        [
+            (short(1), 0..1),
+            (short(2), 0..1),
+            (ArgKind::Positional, 1..2)
+        ]
+    );
+}
+
+#[test]
+fn operand_count() {
+    let count = |args: &'static [&'static str]| {
+        let mut iter = Arg::parse(args.iter().copied()).with_metadata();
+        while iter.next_arg().unwrap().is_some() {}
+        iter.metadata().unwrap().operand_count()
+    };
+    assert_eq!(count(&["test"]), 0);
+    assert_eq!(count(&["test", "-l", "a", "-w", "80", "b"]), 2);
+    assert_eq!(count(&["test", "a", "--", "-l", "--all"]), 3);
+}
+
+#[test]
+fn recorded_args() {
+    #[derive(Clone, Arguments)]
+    #[allow(dead_code)]
+    enum Timeout {
+        #[option("--foreground", record)]
+        Foreground,
+
+        #[option("-k DURATION", "--kill-after=DURATION", record)]
+        KillAfter(String),
+
+        #[option("-s SIGNAL", "--signal=SIGNAL")]
+        Signal(String),
+
+        #[option("-v", "--verbose")]
+        Verbose,
+
+        #[positional(..)]
+        Command(String),
+    }
+
+    let recorded = |args: &'static [&'static str]| {
+        let mut iter = Timeout::parse(args.iter().copied()).with_metadata();
+        while iter.next_arg().unwrap().is_some() {}
+        iter.metadata().unwrap().recorded_args().to_vec()
+    };
+
+    assert!(recorded(&["timeout", "-v", "--signal", "KILL", "5", "sleep"]).is_empty());
+    assert_eq!(
+        recorded(&["timeout", "--foreground", "-v", "5", "sleep"]),
+        ["--foreground"]
+    );
+
+    // Attached values
+    assert_eq!(
+        recorded(&["timeout", "--kill-after=3", "5"]),
+        ["--kill-after=3"]
+    );
+    assert_eq!(recorded(&["timeout", "-k3", "5"]), ["-k3"]);
+    assert_eq!(recorded(&["timeout", "-vk3", "5"]), ["-k3"]);
+
+    // Separate values
+    assert_eq!(
+        recorded(&["timeout", "--kill-after", "3", "5"]),
+        ["--kill-after", "3"]
+    );
+    assert_eq!(recorded(&["timeout", "-k", "3", "5"]), ["-k", "3"]);
+    assert_eq!(recorded(&["timeout", "-vk", "3", "5"]), ["-k", "3"]);
+
+    // Abbreviations are kept as typed
+    assert_eq!(
+        recorded(&["timeout", "--fore", "--kill=3", "-s", "INT", "5"]),
+        ["--fore", "--kill=3"]
+    );
+}
@@ -0,0 +1,101 @@
+//! The derive macros must work in a crate without optional package metadata.
+//!
+//! ```
+//! use uutils_args::{Arguments, Options};
+//!
+//! #[derive(Clone, Arguments)]
+//! enum Arg {
+//!     #[option("-a")]
+//!     A,
+//! }
+//!
+//! #[derive(Default, Options)]
+//! #[arg_type(Arg)]
+//! struct Settings {
+//!     #[map(Arg::A => true)]
+//!     a: bool,
+//! }
+//!
+//! assert!(Settings::parse(["minimal", "-a"]).a);
+//! ```
+
+use uutils_args::{Arguments, FromValue, Options};
+
+#[derive(Clone, FromValue)]
+enum When {
+    #[value]
+    Always,
+    #[value]
+    Never,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Do the thing
+    #[option("-a", "--all")]
+    All,
+
+    /// When to do it
+    #[option("--when=WHEN")]
+    When(When),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+
+    #[map(Arg::When(When::Always) => true, Arg::When(When::Never) => false)]
+    when: bool,
+}
+
+/// Entry point like the ones of the uutils utilities, which keep their
+/// argument types private.
+pub fn uumain(args: impl IntoIterator<Item = std::ffi::OsString> + 'static) -> i32 {
+    let settings = Settings::parse(args);
+    i32::from(!(settings.all && settings.when))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{uumain, Arg, Settings};
+    use uutils_args::{complete, Arguments, Options};
+
+    #[test]
+    fn module_level() {
+        let settings = Settings::parse(["minimal", "-a", "--when=always"]);
+        assert!(settings.all);
+        assert!(settings.when);
+
+        assert_eq!(Arg::NAME, "minimal");
+        assert_eq!(Arg::version(), "minimal 0.0.0");
+        assert!(Arg::help("minimal").contains("--all"));
+        assert!(complete::render(&Arg::complete(), "fish").contains("-l when"));
+
+        assert_eq!(
+            uumain(["minimal".into(), "-a".into(), "--when=a".into()]),
+            0
+        );
+        assert_eq!(uumain(["minimal".into(), "--when=n".into()]), 1);
+    }
+
+    #[test]
+    fn function_level() {
+        #[derive(Clone, Arguments)]
+        enum Arg {
+            #[option("-b")]
+            B,
+        }
+
+        #[derive(Default, Options)]
+        #[arg_type(Arg)]
+        struct Settings {
+            #[map(Arg::B => true)]
+            b: bool,
+        }
+
+        assert!(Settings::parse(["minimal", "-b"]).b);
+        assert_eq!(Arg::complete().version, "0.0.0");
+    }
+}
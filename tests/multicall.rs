@@ -0,0 +1,118 @@
+use uutils_args::{Arguments, Error, Options};
+
+#[derive(Arguments, Clone)]
+enum Arg {
+    #[option("-a")]
+    All,
+
+    #[positional(1)]
+    Source(String),
+
+    #[positional(0..=1)]
+    Dest(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    all: bool,
+
+    #[set(Arg::Source)]
+    source: String,
+
+    #[set(Arg::Dest)]
+    dest: String,
+}
+
+#[test]
+fn without_binname() {
+    let settings = Settings::parse_without_binname(["-a", "foo", "bar"]);
+    assert!(settings.all);
+    assert_eq!(settings.source, "foo");
+    assert_eq!(settings.dest, "bar");
+}
+
+#[test]
+fn without_binname_missing_positional() {
+    assert!(Settings::try_parse_without_binname(["-a"]).is_err());
+}
+
+#[test]
+fn multicall_delegation() {
+    // The multicall binary gets the full argv and strips its own name and
+    // the name of the utility before delegating.
+    let argv = ["coreutils", "util", "-a", "foo"];
+    assert_eq!(argv[1], "util");
+    let rest: Vec<String> = argv[2..].iter().map(ToString::to_string).collect();
+
+    let settings = Settings::parse_without_binname(rest);
+    assert!(settings.all);
+    assert_eq!(settings.source, "foo");
+    assert_eq!(settings.dest, "");
+}
+
+#[test]
+fn initial_positional_idx() {
+    // A wrapper that has already handled the first operand itself.
+    let iter = Arg::parse_without_binname(["bar", "-a"]).with_initial_positional_idx(1);
+    let settings = Settings::try_parse_iter(iter).unwrap();
+    assert!(settings.all);
+    assert_eq!(settings.source, "");
+    assert_eq!(settings.dest, "bar");
+
+    let iter = Arg::parse_without_binname(["bar", "baz"]).with_initial_positional_idx(1);
+    assert!(Settings::try_parse_iter(iter).is_err());
+}
+
+#[test]
+fn help_without_binname() {
+    let iter = Arg::parse_without_binname(["--help"]);
+    assert!(iter.help().contains(&format!(
+        "Usage:\n  {} [OPTION]... SOURCE [DEST]",
+        Arg::NAME
+    )));
+
+    let iter = Arg::parse(["util", "--help"]);
+    assert!(iter
+        .help()
+        .contains("Usage:\n  util [OPTION]... SOURCE [DEST]"));
+}
+
+#[test]
+fn with_name() {
+    let settings = Settings::try_parse_with_name("cp", ["-a", "foo", "bar"]).unwrap();
+    assert!(settings.all);
+    assert_eq!(settings.source, "foo");
+    assert_eq!(settings.dest, "bar");
+
+    let iter = Arg::parse_with_name("cp", ["--help"]);
+    assert_eq!(iter.bin_name(), "cp");
+    assert!(iter
+        .help()
+        .contains("Usage:\n  cp [OPTION]... SOURCE [DEST]"));
+
+    match Settings::try_parse_with_name("cp", ["--help"]) {
+        Err(Error::DisplayHelp(text)) => assert!(text.contains("  cp [OPTION]...")),
+        _ => panic!("expected the help"),
+    }
+}
+
+#[test]
+fn with_name_in_errors() {
+    // The exit path is checked in a copy of this test binary.
+    if std::env::var_os("MULTICALL_WITH_NAME").is_some() {
+        Settings::parse_with_name("cp", ["a", "b", "c"]);
+        return;
+    }
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "with_name_in_errors", "--nocapture"])
+        .env("MULTICALL_WITH_NAME", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "cp: extra operand 'c'\nTry 'cp --help' for more information.\n"
+    );
+}
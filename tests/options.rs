@@ -1,6 +1,6 @@
 use std::ffi::OsString;
 
-use uutils_args::{Arguments, FromValue, Options};
+use uutils_args::{Arguments, Error, FromValue, Options, ParseConfig};
 
 #[test]
 fn string_option() {
@@ -113,7 +113,7 @@ fn enum_with_complex_from_value() {
             } else {
                 Err(uutils_args::Error::ParsingFailed {
                     option: option.to_string(),
-                    value,
+                    value: value.into(),
                     error: "Failure!".into(),
                 })
             }
@@ -152,8 +152,13 @@ fn color() {
 
     #[derive(Arguments, Clone)]
     enum Arg {
+        // `None` if the value is left out
         #[option("--color[=WHEN]")]
         Color(Option<Color>),
+
+        // The `default` if the value is left out
+        #[option("--colour[=WHEN]", default = Color::Always)]
+        Colour(Color),
     }
 
     #[derive(Default, Options)]
@@ -162,6 +167,7 @@ fn color() {
         #[map(
             Arg::Color(Some(c)) => c.clone(),
             Arg::Color(None) => Color::Always,
+            Arg::Colour(c) => c.clone(),
         )]
         color: Color,
     }
@@ -180,7 +186,12 @@ fn color() {
         Color::Never
     );
     assert_eq!(Settings::parse(["test", "--color=auto"]).color, Color::Auto);
-    assert_eq!(Settings::parse(["test", "--color"]).color, Color::Always)
+    assert_eq!(Settings::parse(["test", "--color"]).color, Color::Always);
+    assert_eq!(Settings::parse(["test", "--colour"]).color, Color::Always);
+    assert_eq!(
+        Settings::parse(["test", "--colour=never"]).color,
+        Color::Never
+    );
 }
 
 #[test]
@@ -343,12 +354,92 @@ fn ls_classify() {
     assert!(Settings::try_parse(["test", "-Falways"]).is_err());
 }
 
+#[test]
+fn short_optional_value() {
+    #[derive(FromValue, Default, Clone, PartialEq, Eq, Debug)]
+    enum When {
+        #[value]
+        Never,
+        #[default]
+        #[value]
+        Auto,
+        #[value]
+        Always,
+    }
+
+    #[derive(Clone, Arguments)]
+    enum Arg {
+        #[option("-F[WHEN]", "--classify[=WHEN]", default = When::Always)]
+        Classify(When),
+
+        #[option("-H[WHEN]", "--hyperlink[=WHEN]")]
+        Hyperlink(Option<When>),
+
+        #[option("-l")]
+        Long,
+    }
+
+    #[derive(Options, Default)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Classify)]
+        classify: When,
+
+        #[map(Arg::Hyperlink(w) => Some(w))]
+        hyperlink: Option<Option<When>>,
+
+        #[map(Arg::Long => true)]
+        long: bool,
+    }
+
+    let parse = |args: &[&'static str]| Settings::try_parse(args.to_vec());
+
+    assert_eq!(parse(&["test", "-F"]).unwrap().classify, When::Always);
+    assert_eq!(parse(&["test", "-Fnever"]).unwrap().classify, When::Never);
+    assert_eq!(parse(&["test", "-F=never"]).unwrap().classify, When::Never);
+
+    let s = parse(&["test", "-lFnever"]).unwrap();
+    assert!(s.long);
+    assert_eq!(s.classify, When::Never);
+
+    // The value must be attached, so the next word is not consumed
+    assert!(parse(&["test", "-F", "never"]).is_err());
+
+    // The rest of the cluster is the value, not more options
+    assert!(parse(&["test", "-Fl"]).is_err());
+
+    // An attached empty value is parsed, not replaced by the default
+    assert_eq!(
+        parse(&["test", "--classify="]).err().unwrap().to_string(),
+        "invalid argument '' for '--classify'\nValid arguments are:\n  - never\n  - auto\n  - always"
+    );
+    assert!(matches!(
+        parse(&["test", "--hyperlink="]),
+        Err(Error::InvalidValue { value, .. }) if value.is_empty()
+    ));
+
+    // Without a `default`, a left out value is `None`
+    assert_eq!(parse(&["test"]).unwrap().hyperlink, None);
+    assert_eq!(parse(&["test", "-H"]).unwrap().hyperlink, Some(None));
+    assert_eq!(
+        parse(&["test", "-Hnever"]).unwrap().hyperlink,
+        Some(Some(When::Never))
+    );
+    assert_eq!(
+        parse(&["test", "--hyperlink=auto", "-l"])
+            .unwrap()
+            .hyperlink,
+        Some(Some(When::Auto))
+    );
+}
+
 #[test]
 fn mktemp_tmpdir() {
     #[derive(Clone, Arguments)]
     enum Arg {
         #[option(
             "-p DIR", "--tmpdir[=DIR]",
+            mixed_arity,
             default = String::from("/tmp"),
         )]
         TmpDir(String),
@@ -396,6 +487,461 @@ fn infer_value() {
         Foo::Deck
     );
 
-    Foo::from_value("--foo", OsString::from("l")).unwrap_err();
-    Foo::from_value("--foo", OsString::from("de")).unwrap_err();
+    let Err(Error::AmbiguousValue {
+        option,
+        value,
+        candidates,
+    }) = Foo::from_value("--foo", OsString::from("l"))
+    else {
+        panic!("expected an ambiguous value");
+    };
+    assert_eq!(option, "--foo");
+    assert_eq!(value, "l");
+    assert_eq!(candidates, ["long", "link"]);
+
+    let Err(Error::AmbiguousValue { candidates, .. }) =
+        Foo::from_value("--foo", OsString::from("de"))
+    else {
+        panic!("expected an ambiguous value");
+    };
+    assert_eq!(candidates, ["deck", "desk"]);
+
+    let err = Foo::from_value("--foo", OsString::from("x")).unwrap_err();
+    let Error::InvalidValue {
+        option,
+        value,
+        expected,
+    } = &err
+    else {
+        panic!("expected an invalid value");
+    };
+    assert_eq!(option, "--foo");
+    assert_eq!(value, "x");
+    assert_eq!(expected, &["long", "link", "deck", "desk"]);
+    assert_eq!(
+        err.to_string(),
+        "invalid argument 'x' for '--foo'\nValid arguments are:\n  - long\n  - link\n  - deck\n  - desk"
+    );
+
+    // The empty value is not an abbreviation of every value
+    assert!(matches!(
+        Foo::from_value("--foo", OsString::new()),
+        Err(Error::InvalidValue { value, .. }) if value.is_empty()
+    ));
+}
+
+#[test]
+fn value_extras_round_trip() {
+    #[derive(FromValue, PartialEq, Eq, Debug, Clone, Copy)]
+    #[value_extras(FromStr, Display)]
+    enum Sort {
+        #[value("name")]
+        Name,
+        #[value("size")]
+        Size,
+        #[value("time", "mtime", "modification")]
+        Time,
+        #[value]
+        Version,
+    }
+
+    for sort in [Sort::Name, Sort::Size, Sort::Time, Sort::Version] {
+        assert_eq!(sort.to_string().parse::<Sort>().unwrap(), sort);
+    }
+
+    // The first key is the canonical one.
+    assert_eq!(Sort::Time.to_string(), "time");
+    assert_eq!(Sort::Version.to_string(), "version");
+    assert_eq!("mtime".parse::<Sort>().unwrap(), Sort::Time);
+}
+
+#[test]
+fn value_extras_abbreviation() {
+    #[derive(FromValue, PartialEq, Eq, Debug)]
+    #[value_extras(FromStr)]
+    enum Abbreviated {
+        #[value("always")]
+        Always,
+        #[value("auto")]
+        Auto,
+        #[value("never")]
+        Never,
+    }
+
+    #[derive(FromValue, PartialEq, Eq, Debug)]
+    #[value_extras(FromStr, exact)]
+    enum Exact {
+        #[value("always")]
+        Always,
+        #[value("auto")]
+        Auto,
+        #[value("never")]
+        Never,
+    }
+
+    assert_eq!("nev".parse::<Abbreviated>().unwrap(), Abbreviated::Never);
+    assert_eq!("al".parse::<Abbreviated>().unwrap(), Abbreviated::Always);
+    assert!(matches!(
+        "a".parse::<Abbreviated>(),
+        Err(Error::AmbiguousValue { .. })
+    ));
+    assert!(matches!(
+        "nope".parse::<Abbreviated>(),
+        Err(Error::InvalidValue { .. })
+    ));
+
+    assert_eq!("never".parse::<Exact>().unwrap(), Exact::Never);
+    assert!(matches!(
+        "nev".parse::<Exact>(),
+        Err(Error::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        "al".parse::<Exact>(),
+        Err(Error::InvalidValue { .. })
+    ));
+
+    // Argument parsing keeps accepting abbreviations either way.
+    assert_eq!(
+        Exact::from_value("--color", OsString::from("nev")).unwrap(),
+        Exact::Never
+    );
+}
+
+#[test]
+fn value_fallback() {
+    #[derive(FromValue, PartialEq, Eq, Debug, Clone)]
+    #[value_extras(FromStr, Display)]
+    enum Date {
+        #[value("now")]
+        Now,
+        #[value("noon")]
+        Noon,
+        #[value(fallback)]
+        Other(String),
+    }
+
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-d DATE", "--date=DATE")]
+        Date(Date),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::Date(d) => Some(d))]
+        date: Option<Date>,
+    }
+
+    // Exact and unambiguous matches win over the fallback.
+    assert_eq!(
+        Settings::try_parse(["test", "--date=now"]).unwrap().date,
+        Some(Date::Now)
+    );
+    assert_eq!(
+        Settings::try_parse(["test", "-d", "noo"]).unwrap().date,
+        Some(Date::Noon)
+    );
+    assert_eq!(
+        Settings::try_parse(["test", "--date=no"]).unwrap().date,
+        Some(Date::Other("no".into()))
+    );
+
+    assert_eq!(
+        Settings::try_parse(["test", "--date=2 days ago"])
+            .unwrap()
+            .date,
+        Some(Date::Other("2 days ago".into()))
+    );
+
+    assert_eq!(
+        "next week".parse::<Date>().unwrap(),
+        Date::Other("next week".into())
+    );
+    assert_eq!(Date::Other("next week".into()).to_string(), "next week");
+    assert_eq!(Date::Noon.to_string(), "noon");
+}
+
+#[test]
+fn value_fallback_ambiguous() {
+    #[derive(FromValue, PartialEq, Eq, Debug)]
+    enum Date {
+        #[value("today")]
+        Today,
+        #[value("tomorrow")]
+        Tomorrow,
+        #[value(fallback)]
+        Other(String),
+    }
+
+    // A prefix of both keywords is not an error, but a free-form value.
+    assert_eq!(
+        Date::from_value("--date", "to".into()).unwrap(),
+        Date::Other("to".into())
+    );
+    assert_eq!(
+        Date::from_value("--date", "tom".into()).unwrap(),
+        Date::Tomorrow
+    );
+    assert_eq!(
+        Date::from_value("--date", "today".into()).unwrap(),
+        Date::Today
+    );
+}
+
+#[test]
+fn value_fallback_os_string() {
+    #[derive(FromValue, PartialEq, Eq, Debug)]
+    enum Format {
+        #[value("json")]
+        Json,
+        #[value(fallback)]
+        Custom(OsString),
+    }
+
+    assert_eq!(
+        Format::from_value("--format", "js".into()).unwrap(),
+        Format::Json
+    );
+    assert_eq!(
+        Format::from_value("--format", "%n %s".into()).unwrap(),
+        Format::Custom("%n %s".into())
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        let value = OsString::from_vec(vec![b'%', 0xff]);
+        assert_eq!(
+            Format::from_value("--format", value.clone()).unwrap(),
+            Format::Custom(value)
+        );
+    }
+}
+
+#[test]
+fn many_ambiguous_candidates() {
+    #[derive(Arguments, Clone)]
+    #[allow(dead_code)]
+    enum Arg {
+        #[option("--sa")]
+        Sa,
+        #[option("--sb")]
+        Sb,
+        #[option("--sc")]
+        Sc,
+        #[option("--sd")]
+        Sd,
+        #[option("--se")]
+        Se,
+        #[option("--sf")]
+        Sf,
+        #[option("--sg")]
+        Sg,
+        #[option("--sh")]
+        Sh,
+        #[option("--si")]
+        Si,
+        #[option("--sj")]
+        Sj,
+        #[option("--sk")]
+        Sk,
+        #[option("--sl")]
+        Sl,
+        #[option("--sm")]
+        Sm,
+        #[option("--sn")]
+        Sn,
+        #[option("--so")]
+        So,
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {}
+
+    let Err(err) = Settings::try_parse(["test", "--s"]) else {
+        panic!("expected an error");
+    };
+    let uutils_args::Error::AmbiguousOption { option, candidates } = &err else {
+        panic!("expected an ambiguous option error");
+    };
+    assert_eq!(option, "s");
+    assert_eq!(candidates.len(), 15);
+    assert_eq!(candidates[0], "sa");
+    assert_eq!(candidates[14], "so");
+
+    assert_eq!(
+        err.to_string(),
+        "option '--s' is ambiguous; possibilities:\n  - --sa\n  - --sb\n  - --sc\n  - --sd\n  - --se\n  - --sf\n  - --sg\n  - --sh\n  and 7 more"
+    );
+
+    let config = ParseConfig {
+        candidate_limit: 3,
+        ..ParseConfig::default()
+    };
+    assert_eq!(
+        err.display_with(&config).to_string(),
+        "option '--s' is ambiguous; possibilities:\n  - --sa\n  - --sb\n  - --sc\n  and 12 more"
+    );
+}
+
+#[test]
+fn flag_spelling() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-c NUM", "--bytes=NUM")]
+        Bytes(u64),
+
+        #[positional(..)]
+        File(OsString),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    #[try_apply_named(Settings::record_flag)]
+    struct Settings {
+        #[map(Arg::Bytes(n) => Some(n))]
+        bytes: Option<u64>,
+        #[collect(set(Arg::File))]
+        files: Vec<OsString>,
+        flags: Vec<String>,
+    }
+
+    impl Settings {
+        fn record_flag(&mut self, _arg: &Arg, flag: &str) -> Result<(), uutils_args::Error> {
+            self.flags.push(flag.to_string());
+            Ok(())
+        }
+    }
+
+    let s = Settings::parse(["head", "-c", "1", "--bytes=2", "--by", "3", "-c4", "foo"]);
+    assert_eq!(s.bytes, Some(4));
+    assert_eq!(s.files, ["foo"]);
+    assert_eq!(s.flags, ["-c", "--bytes", "--bytes", "-c", "FILE"]);
+}
+
+#[test]
+fn flag_spelling_in_error() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-c NUM", "--bytes=NUM")]
+        Bytes(u64),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    #[try_apply(Settings::check_arg)]
+    struct Settings {
+        #[map(Arg::Bytes(n) => Some(n))]
+        bytes: Option<u64>,
+    }
+
+    impl Settings {
+        fn check_arg(&mut self, arg: &Arg) -> Result<(), uutils_args::Error> {
+            match arg {
+                Arg::Bytes(0) => Err(uutils_args::Error::Validation("must be positive".into())),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    let message = |args: [&'static str; 3]| match Settings::try_parse(args) {
+        Ok(_) => panic!("expected an error"),
+        Err(err) => err.to_string(),
+    };
+    assert_eq!(
+        message(["head", "-c", "0"]),
+        "invalid use of option '-c': must be positive"
+    );
+    assert_eq!(
+        message(["head", "--bytes", "0"]),
+        "invalid use of option '--bytes': must be positive"
+    );
+}
+
+#[test]
+fn number_option() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-n NUM", "-{NUM}")]
+        Lines(u64),
+
+        #[option("+{PAGE}")]
+        Page(u64),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::Lines(n) => n)]
+        lines: u64,
+
+        #[map(Arg::Page(p) => p)]
+        page: u64,
+    }
+
+    let s = Settings::try_parse(["pr", "-15", "+3"]).unwrap();
+    assert_eq!(s.lines, 15);
+    assert_eq!(s.page, 3);
+
+    let Err(err) = Settings::try_parse(["pr", "-5x"]) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "invalid argument '5x' for '-NUM': invalid digit found in string"
+    );
+}
+
+#[test]
+fn max_occurrences() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("--suffix=SUFF", max_occurrences = 1)]
+        Suffix(String),
+        #[option("-d DELIM", "--delimiter=DELIM", max_occurrences = 2)]
+        Delimiter(String),
+        #[option("-t", max_occurrences = 1)]
+        Tab,
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Suffix)]
+        suffix: String,
+        #[set(Arg::Delimiter)]
+        #[map(Arg::Tab => "\t".into())]
+        delimiter: String,
+    }
+
+    let s = Settings::try_parse(["test", "--suffix=.txt"]).unwrap();
+    assert_eq!(s.suffix, ".txt");
+
+    let err = Settings::try_parse(["test", "--suffix=.txt", "--suffix=.md"])
+        .err()
+        .unwrap();
+    assert!(matches!(
+        &err,
+        Error::TooManyOccurrences { option, max: 1 } if option == "--suffix"
+    ));
+    assert_eq!(err.to_string(), "option '--suffix' can only be given once");
+
+    // Exactly at the limit, through both spellings
+    let s = Settings::try_parse(["test", "-d", ",", "--delimiter=:"]).unwrap();
+    assert_eq!(s.delimiter, ":");
+
+    let err = Settings::try_parse(["test", "-d,", "--del=:", "-d;"])
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "option '--delimiter' can be given at most 2 times"
+    );
+
+    // Other variants for the same setting are counted separately
+    let s = Settings::try_parse(["test", "-d,", "-t", "-d:"]).unwrap();
+    assert_eq!(s.delimiter, ":");
+    assert!(Settings::try_parse(["test", "-tt"]).is_err());
 }
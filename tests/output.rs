@@ -0,0 +1,47 @@
+use std::io::{self, ErrorKind, Write};
+
+use uutils_args::write_info;
+
+/// A writer that fails with the given kind of error
+struct Failing(ErrorKind);
+
+impl Write for Failing {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(self.0.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn written() {
+    let mut out = Vec::new();
+    assert_eq!(write_info(&mut out, "Usage: test\n"), 0);
+    assert_eq!(out, b"Usage: test\n");
+}
+
+#[test]
+fn broken_pipe() {
+    assert_eq!(write_info(&mut Failing(ErrorKind::BrokenPipe), "help"), 0);
+}
+
+#[test]
+fn other_error() {
+    assert_eq!(
+        write_info(&mut Failing(ErrorKind::PermissionDenied), "help"),
+        1
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn closed_pipe() {
+    use std::os::unix::net::UnixStream;
+
+    let (mut writer, reader) = UnixStream::pair().unwrap();
+    drop(reader);
+    let text = "help\n".repeat(100_000);
+    assert_eq!(write_info(&mut writer, &text), 0);
+}
@@ -0,0 +1,83 @@
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Options, ParseConfig};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-l")]
+    Long,
+
+    #[positional(..)]
+    File(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Long => true)]
+    long: bool,
+
+    #[collect(set(Arg::File))]
+    files: Vec<String>,
+}
+
+#[derive(Clone, Arguments)]
+#[arguments(parse_mode = posix)]
+enum PosixArg {
+    #[option("-l")]
+    Long,
+
+    #[positional(..)]
+    File(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(PosixArg)]
+struct PosixSettings {
+    #[map(PosixArg::Long => true)]
+    long: bool,
+
+    #[collect(set(PosixArg::File))]
+    files: Vec<String>,
+}
+
+fn posixly_correct(name: &str) -> Option<OsString> {
+    (name == "POSIXLY_CORRECT").then(|| "1".into())
+}
+
+fn empty_env(_: &str) -> Option<OsString> {
+    None
+}
+
+fn config(env: fn(&str) -> Option<OsString>) -> ParseConfig {
+    ParseConfig {
+        env,
+        ..ParseConfig::default()
+    }
+}
+
+#[test]
+fn permute() {
+    let s = Settings::try_parse_with_config(["prog", "file", "-l"], config(empty_env)).unwrap();
+    assert!(s.long);
+    assert_eq!(s.files, ["file"]);
+}
+
+#[test]
+fn posix() {
+    let s = PosixSettings::try_parse_with_config(
+        ["prog", "-l", "file", "-l", "--", "x"],
+        config(empty_env),
+    )
+    .unwrap();
+    assert!(s.long);
+    assert_eq!(s.files, ["file", "-l", "--", "x"]);
+}
+
+#[test]
+fn posixly_correct_env() {
+    let s =
+        Settings::try_parse_with_config(["prog", "file", "-l"], config(posixly_correct)).unwrap();
+    assert!(!s.long);
+    assert_eq!(s.files, ["file", "-l"]);
+}
@@ -132,3 +132,385 @@ fn last2() {
     let settings = Settings::parse(["test", "--", "-a"]);
     assert_eq!(settings.foo, vec!["-a"]);
 }
+
+#[test]
+fn first_and_rest() {
+    // Like `basename NAME [SUFFIX]`
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[positional(1..=2)]
+        Operand(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[first(Arg::Operand)]
+        name: String,
+        #[rest(Arg::Operand)]
+        suffix: Vec<String>,
+    }
+
+    let settings = Settings::parse(["basename", "foobar"]);
+    assert_eq!(settings.name, "foobar");
+    assert!(settings.suffix.is_empty());
+
+    let settings = Settings::parse(["basename", "foobar", "bar"]);
+    assert_eq!(settings.name, "foobar");
+    assert_eq!(settings.suffix, ["bar"]);
+
+    assert!(Settings::try_parse(["basename"]).is_err());
+    assert!(Settings::try_parse(["basename", "a", "b", "c"]).is_err());
+}
+
+#[test]
+fn first_and_rest_of_many() {
+    // Like `cp SOURCE... DEST`, but with the destination first
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-v")]
+        Verbose,
+        #[positional(2..)]
+        File(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::Verbose => true)]
+        verbose: bool,
+        #[first(Arg::File)]
+        dest: String,
+        #[rest(Arg::File)]
+        sources: Vec<String>,
+    }
+
+    let settings = Settings::parse(["cp", "d", "-v", "a", "b"]);
+    assert!(settings.verbose);
+    assert_eq!(settings.dest, "d");
+    assert_eq!(settings.sources, ["a", "b"]);
+}
+
+#[test]
+fn one_then_any() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[positional(1)]
+        Pattern(String),
+        #[positional(..)]
+        File(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Pattern)]
+        pattern: String,
+        #[collect(set(Arg::File))]
+        files: Vec<String>,
+    }
+
+    let settings = Settings::parse(["grep", "foo"]);
+    assert_eq!(settings.pattern, "foo");
+    assert!(settings.files.is_empty());
+
+    let settings = Settings::parse(["grep", "foo", "a", "b", "c"]);
+    assert_eq!(settings.pattern, "foo");
+    assert_eq!(settings.files, ["a", "b", "c"]);
+
+    assert!(Settings::try_parse(["grep"]).is_err());
+}
+
+#[test]
+fn two_then_at_least_one() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[positional(2)]
+        Pair(String),
+        #[positional(1..)]
+        Rest(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[collect(set(Arg::Pair))]
+        pair: Vec<String>,
+        #[collect(set(Arg::Rest))]
+        rest: Vec<String>,
+    }
+
+    let settings = Settings::parse(["test", "a", "b", "c", "d"]);
+    assert_eq!(settings.pair, ["a", "b"]);
+    assert_eq!(settings.rest, ["c", "d"]);
+
+    assert!(Settings::try_parse(["test", "a", "b"]).is_err());
+}
+
+#[test]
+fn default_is_never_missing() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[positional(1, default = "-")]
+        Input(String),
+        #[positional(0..=1, default = "out")]
+        Output(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Input)]
+        input: String,
+        #[set(Arg::Output)]
+        output: String,
+    }
+
+    let settings = Settings::parse(["uniq"]);
+    assert_eq!(settings.input, "-");
+    assert_eq!(settings.output, "out");
+
+    let settings = Settings::parse(["uniq", "a"]);
+    assert_eq!(settings.input, "a");
+    assert_eq!(settings.output, "out");
+
+    let settings = Settings::parse(["uniq", "a", "b"]);
+    assert_eq!(settings.input, "a");
+    assert_eq!(settings.output, "b");
+}
+
+#[test]
+fn default_then_required() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[positional(0..=1, default = "-")]
+        Input(String),
+        #[positional(1)]
+        Name(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Input)]
+        input: String,
+        #[set(Arg::Name)]
+        name: String,
+    }
+
+    let err = Settings::try_parse(["test"]).err().unwrap();
+    assert_eq!(err.to_string(), "missing NAME operand");
+
+    // The first operand goes to the first positional argument, so the
+    // default is not used.
+    let err = Settings::try_parse(["test", "a"]).err().unwrap();
+    assert_eq!(err.to_string(), "missing NAME operand");
+
+    let settings = Settings::parse(["test", "a", "b"]);
+    assert_eq!(settings.input, "a");
+    assert_eq!(settings.name, "b");
+}
+
+/// Every way to place `-s` and `-S SUFFIX` around the operands `a` and `b`
+fn interleavings() -> Vec<Vec<&'static str>> {
+    let options: [&[&str]; 2] = [&["-s"], &["-S", "~"]];
+    let mut all = Vec::new();
+    // The number of operands before each of the options
+    for s in 0..=2 {
+        for suffix in 0..=2 {
+            for s_first in [true, false] {
+                let mut args = vec!["test"];
+                for i in 0..=2 {
+                    let mut here = Vec::new();
+                    if s == i {
+                        here.push(options[0]);
+                    }
+                    if suffix == i {
+                        here.push(options[1]);
+                    }
+                    if !s_first {
+                        here.reverse();
+                    }
+                    args.extend(here.into_iter().flatten());
+                    if i < 2 {
+                        args.push(["a", "b"][i]);
+                    }
+                }
+                all.push(args);
+            }
+        }
+    }
+    all
+}
+
+#[test]
+fn options_between_two_positionals() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-s")]
+        Symbolic,
+        #[option("-S SUFFIX")]
+        Suffix(String),
+        #[positional(1)]
+        Target(String),
+        #[positional(1)]
+        Link(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::Symbolic => true)]
+        symbolic: bool,
+        #[set(Arg::Suffix)]
+        suffix: String,
+        #[set(Arg::Target)]
+        target: String,
+        #[set(Arg::Link)]
+        link: String,
+    }
+
+    for args in interleavings() {
+        let settings = Settings::try_parse(args.clone()).unwrap();
+        assert!(settings.symbolic, "{args:?}");
+        assert_eq!(settings.suffix, "~", "{args:?}");
+        assert_eq!(settings.target, "a", "{args:?}");
+        assert_eq!(settings.link, "b", "{args:?}");
+    }
+}
+
+#[test]
+fn options_within_bounded_positional() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-s")]
+        Symbolic,
+        #[option("-S SUFFIX")]
+        Suffix(String),
+        #[positional(2..=2)]
+        File(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::Symbolic => true)]
+        symbolic: bool,
+        #[set(Arg::Suffix)]
+        suffix: String,
+        #[collect(set(Arg::File))]
+        files: Vec<String>,
+    }
+
+    for args in interleavings() {
+        let settings = Settings::try_parse(args.clone()).unwrap();
+        assert!(settings.symbolic, "{args:?}");
+        assert_eq!(settings.suffix, "~", "{args:?}");
+        assert_eq!(settings.files, ["a", "b"], "{args:?}");
+    }
+
+    let mut args = interleavings().swap_remove(0);
+    args.push("c");
+    assert!(Settings::try_parse(args).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn non_unicode_path_operands() {
+    use std::{
+        ffi::OsString,
+        os::unix::ffi::{OsStrExt, OsStringExt},
+        path::PathBuf,
+    };
+
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("-v")]
+        Verbose,
+        #[positional(1)]
+        Target(PathBuf),
+        #[positional(.., last)]
+        Rest(Vec<PathBuf>),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[set(Arg::Target)]
+        target: PathBuf,
+        #[set(Arg::Rest)]
+        rest: Vec<PathBuf>,
+    }
+
+    let bytes = |b: &[u8]| OsString::from_vec(b.to_vec());
+    let target = bytes(b"a\xff\xfeb");
+    let first = bytes(b"\x80");
+    let second = bytes(b"-\xc3(");
+    let settings = Settings::try_parse([
+        OsString::from("test"),
+        OsString::from("-v"),
+        target.clone(),
+        first.clone(),
+        second.clone(),
+    ])
+    .unwrap();
+    assert_eq!(
+        settings.target.into_os_string().into_vec(),
+        target.as_bytes()
+    );
+    assert_eq!(
+        settings
+            .rest
+            .into_iter()
+            .map(|p| p.into_os_string().into_vec())
+            .collect::<Vec<_>>(),
+        [first.as_bytes(), second.as_bytes()]
+    );
+
+    // And the same after the end of the options
+    let settings =
+        Settings::try_parse([OsString::from("test"), OsString::from("--"), target.clone()])
+            .unwrap();
+    assert_eq!(
+        settings.target.into_os_string().into_vec(),
+        target.as_bytes()
+    );
+}
+
+#[test]
+fn from_end_before_exclusive() {
+    #[derive(Arguments, Clone)]
+    enum Arg {
+        #[option("--list", exclusive)]
+        List,
+        #[positional(1..)]
+        Source(String),
+        #[positional(from_end = 1)]
+        Dest(String),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::List => true)]
+        list: bool,
+        #[collect(set(Arg::Source))]
+        sources: Vec<String>,
+        #[set(Arg::Dest)]
+        dest: String,
+    }
+
+    // The operands held back for the end are not lost
+    let settings = Settings::try_parse(["test", "a", "--list"]).unwrap();
+    assert!(settings.list);
+    assert!(settings.sources.is_empty());
+    assert_eq!(settings.dest, "a");
+
+    let settings = Settings::try_parse(["test", "a", "b", "c", "--list", "d"]).unwrap();
+    assert_eq!(settings.sources, ["a", "b"]);
+    assert_eq!(settings.dest, "c");
+
+    let settings = Settings::try_parse(["test", "--list", "a"]).unwrap();
+    assert!(settings.sources.is_empty());
+    assert_eq!(settings.dest, "");
+}
@@ -0,0 +1,150 @@
+use std::ffi::OsString;
+
+use uutils_args::{Arguments, Options, ParseConfig, ValueSource};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-w COLS", "--width=COLS")]
+    Width(u16),
+
+    #[option("--format=WORD")]
+    Format(String),
+
+    #[option("--color")]
+    Color,
+
+    #[option("-T SIZE", "--tabsize=SIZE")]
+    TabSize(u8),
+
+    #[positional(0..=1, default = ".")]
+    Dir(String),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::Width)]
+    #[field(env = "COLUMNS")]
+    width: u16,
+
+    #[set(Arg::Format)]
+    format: String,
+
+    #[map(Arg::Color => true)]
+    color: bool,
+
+    #[set(Arg::TabSize)]
+    #[field(env = "TABSIZE", ignore_invalid_env, default = 8)]
+    tab_size: u8,
+
+    #[set(Arg::Dir)]
+    dir: String,
+}
+
+fn columns(name: &str) -> Option<OsString> {
+    (name == "COLUMNS").then(|| "120".into())
+}
+
+fn no_env(_name: &str) -> Option<OsString> {
+    None
+}
+
+fn parse(
+    args: &'static [&'static str],
+    env: fn(&str) -> Option<OsString>,
+) -> (Settings, uutils_args::Provenance) {
+    let config = ParseConfig {
+        env,
+        ..ParseConfig::default()
+    };
+    let iter = Arg::parse(args.iter().copied()).with_config(config);
+    Settings::try_parse_iter_with_provenance(iter).unwrap()
+}
+
+#[test]
+fn was_provided() {
+    let (s, provenance) = parse(&["ls", "--width=40"], no_env);
+    assert_eq!(s.width, 40);
+    assert!(provenance.was_provided("width"));
+    assert!(!provenance.was_provided("format"));
+
+    let (s, provenance) = parse(&["ls", "--color"], no_env);
+    assert_eq!(s.width, 0);
+    assert!(!provenance.was_provided("width"));
+    assert!(provenance.was_provided("color"));
+}
+
+#[test]
+fn sources() {
+    let (s, provenance) = parse(&["ls", "-w", "40", "--form=long", "src"], columns);
+    assert_eq!(s.width, 40);
+    assert_eq!(
+        provenance.source("width"),
+        ValueSource::Cli { flag: "-w".into() }
+    );
+    assert_eq!(
+        provenance.source("format"),
+        ValueSource::Cli {
+            flag: "--format".into()
+        }
+    );
+    assert_eq!(
+        provenance.source("dir"),
+        ValueSource::Cli { flag: "DIR".into() }
+    );
+    assert_eq!(provenance.source("color"), ValueSource::Default);
+
+    let (s, provenance) = parse(&["ls"], columns);
+    assert_eq!(s.width, 120);
+    assert_eq!(
+        provenance.source("width"),
+        ValueSource::Env {
+            var: "COLUMNS".into()
+        }
+    );
+    assert!(!provenance.was_provided("width"));
+
+    // The default of a positional argument was not given
+    assert_eq!(s.dir, ".");
+    assert!(!provenance.was_provided("dir"));
+    assert_eq!(provenance.source("dir"), ValueSource::Default);
+}
+
+#[test]
+fn ignored_env() {
+    fn bogus_tabsize(name: &str) -> Option<OsString> {
+        match name {
+            "COLUMNS" => Some("120".into()),
+            "TABSIZE" => Some("wide".into()),
+            _ => None,
+        }
+    }
+
+    // The invalid value is not used, so the field has its default
+    let (s, provenance) = parse(&["ls"], bogus_tabsize);
+    assert_eq!(s.tab_size, 8);
+    assert_eq!(provenance.source("tab_size"), ValueSource::Default);
+    assert_eq!(
+        provenance.source("width"),
+        ValueSource::Env {
+            var: "COLUMNS".into()
+        }
+    );
+
+    let (s, provenance) = parse(&["ls"], |name| (name == "TABSIZE").then(|| "4".into()));
+    assert_eq!(s.tab_size, 4);
+    assert_eq!(
+        provenance.source("tab_size"),
+        ValueSource::Env {
+            var: "TABSIZE".into()
+        }
+    );
+}
+
+#[test]
+fn env_fields() {
+    assert_eq!(
+        Settings::ENV_FIELDS,
+        [("width", "COLUMNS"), ("tab_size", "TABSIZE")]
+    );
+}
@@ -0,0 +1,93 @@
+use std::ffi::OsString;
+
+use uutils_args::{Argument, Arguments, Error};
+
+#[derive(Clone, Debug, PartialEq, Eq, Arguments)]
+enum Arg {
+    #[option("-i", "--ignore-environment")]
+    IgnoreEnvironment,
+
+    #[option("-u NAME", "--unset=NAME")]
+    Unset(String),
+
+    #[positional(..)]
+    Command(OsString),
+}
+
+fn custom(arg: Result<Argument<Arg>, Error>) -> Arg {
+    match arg {
+        Ok(Argument::Custom(arg)) => arg,
+        Err(err) => panic!("unexpected error: {err}"),
+        Ok(_) => panic!("expected a custom argument"),
+    }
+}
+
+fn os(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn options_then_command() {
+    let mut iter = Arg::parse(["env", "-i", "-u", "HOME", "ls", "-l", "--all"]);
+    let mut options = Vec::new();
+    for arg in &mut iter {
+        match custom(arg) {
+            Arg::Command(command) => {
+                assert_eq!(command, "ls");
+                break;
+            }
+            arg => options.push(arg),
+        }
+    }
+    assert_eq!(options, [Arg::IgnoreEnvironment, Arg::Unset("HOME".into())]);
+    assert_eq!(iter.remaining().unwrap(), os(&["-l", "--all"]));
+}
+
+#[test]
+fn collect() {
+    let args: Vec<Arg> = Arg::parse(["env", "-i", "ls"]).map(custom).collect();
+    assert_eq!(args, [Arg::IgnoreEnvironment, Arg::Command("ls".into())]);
+}
+
+#[test]
+fn double_dash_not_read() {
+    let mut iter = Arg::parse(["env", "-i", "--", "-u", "ls"]);
+    assert_eq!(custom(iter.next().unwrap()), Arg::IgnoreEnvironment);
+    assert_eq!(iter.remaining().unwrap(), os(&["--", "-u", "ls"]));
+}
+
+#[test]
+fn double_dash_read() {
+    let mut iter = Arg::parse(["env", "-i", "--", "ls", "-u", "x"]);
+    assert_eq!(custom(iter.next().unwrap()), Arg::IgnoreEnvironment);
+    assert_eq!(custom(iter.next().unwrap()), Arg::Command("ls".into()));
+    assert_eq!(iter.remaining().unwrap(), os(&["-u", "x"]));
+}
+
+#[test]
+fn error_mid_stream() {
+    let mut iter = Arg::parse(["env", "-i", "--foo", "-u", "HOME", "ls"]);
+    assert_eq!(custom(iter.next().unwrap()), Arg::IgnoreEnvironment);
+    assert!(iter.next().unwrap().is_err());
+    assert_eq!(custom(iter.next().unwrap()), Arg::Unset("HOME".into()));
+    assert_eq!(iter.remaining().unwrap(), os(&["ls"]));
+}
+
+#[test]
+fn error_then_remaining() {
+    let mut iter = Arg::parse(["env", "--foo", "-u", "HOME"]);
+    assert!(iter.next().unwrap().is_err());
+    assert_eq!(iter.remaining().unwrap(), os(&["-u", "HOME"]));
+}
+
+#[test]
+fn inside_cluster() {
+    let mut iter = Arg::parse(["env", "-iu", "HOME"]);
+    assert_eq!(custom(iter.next().unwrap()), Arg::IgnoreEnvironment);
+    assert!(iter.remaining().is_err());
+}
+
+#[test]
+fn bin_name() {
+    assert_eq!(Arg::parse(["env", "-i"]).bin_name(), "env");
+}
@@ -0,0 +1,55 @@
+use uutils_args::{resolve_long, Error};
+
+const OPTIONS: [&str; 5] = ["reverse", "recursive", "re", "size", "si"];
+
+#[test]
+fn exact_match() {
+    assert_eq!(resolve_long("reverse", &OPTIONS).unwrap(), "reverse");
+    assert_eq!(resolve_long("si", &OPTIONS).unwrap(), "si");
+}
+
+#[test]
+fn exact_match_wins_over_prefix() {
+    // `re` is also a prefix of `reverse` and `recursive`.
+    assert_eq!(resolve_long("re", &OPTIONS).unwrap(), "re");
+}
+
+#[test]
+fn unique_prefix() {
+    assert_eq!(resolve_long("rev", &OPTIONS).unwrap(), "reverse");
+    assert_eq!(resolve_long("rec", &OPTIONS).unwrap(), "recursive");
+    assert_eq!(resolve_long("siz", &OPTIONS).unwrap(), "size");
+}
+
+#[test]
+fn ambiguous_prefix() {
+    let Err(Error::AmbiguousOption { option, candidates }) = resolve_long("r", &OPTIONS) else {
+        panic!("expected an ambiguous option");
+    };
+    assert_eq!(option, "r");
+    assert_eq!(candidates, ["reverse", "recursive", "re"]);
+
+    let Err(Error::AmbiguousOption { option, candidates }) = resolve_long("s", &OPTIONS) else {
+        panic!("expected an ambiguous option");
+    };
+    assert_eq!(option, "s");
+    assert_eq!(candidates, ["size", "si"]);
+}
+
+#[test]
+fn unknown() {
+    let Err(Error::UnexpectedOption(option)) = resolve_long("x", &OPTIONS) else {
+        panic!("expected an unknown option");
+    };
+    assert_eq!(option, "--x");
+
+    // Only a prefix of an option matches, not the other way around.
+    assert!(matches!(
+        resolve_long("sizes", &OPTIONS),
+        Err(Error::UnexpectedOption(_))
+    ));
+    assert!(matches!(
+        resolve_long("reverse", &[]),
+        Err(Error::UnexpectedOption(_))
+    ));
+}
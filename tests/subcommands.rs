@@ -0,0 +1,204 @@
+use uutils_args::{complete, Arguments, Error, Options};
+
+#[derive(Clone, Arguments)]
+enum AddArg {
+    /// Add the file even if it is ignored
+    #[option("-f", "--force")]
+    Force,
+
+    #[positional(1..)]
+    File(String),
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Options)]
+#[arg_type(AddArg)]
+struct AddSettings {
+    #[map(AddArg::Force => true)]
+    force: bool,
+
+    #[collect(set(AddArg::File))]
+    files: Vec<String>,
+}
+
+#[derive(Clone, Arguments)]
+enum RemoveArg {
+    /// Remove directories recursively
+    #[option("-r", "--recursive")]
+    Recursive,
+
+    #[positional(1..)]
+    File(String),
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Options)]
+#[arg_type(RemoveArg)]
+struct RemoveSettings {
+    #[map(RemoveArg::Recursive => true)]
+    recursive: bool,
+
+    #[collect(set(RemoveArg::File))]
+    files: Vec<String>,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Explain what is being done
+    #[option("-v", "--verbose")]
+    Verbose,
+
+    /// Add files to the index
+    #[subcommand]
+    Add(AddSettings),
+
+    /// Remove files from the index
+    #[subcommand("remove", "rm")]
+    Remove(RemoveSettings),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Command {
+    #[default]
+    None,
+    Add(AddSettings),
+    Remove(RemoveSettings),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::Verbose => true)]
+    verbose: bool,
+
+    #[map(Arg::Add(s) => Command::Add(s), Arg::Remove(s) => Command::Remove(s))]
+    command: Command,
+}
+
+#[test]
+fn subcommand() {
+    let s = Settings::try_parse(["git", "add", "-f", "a", "b"]).unwrap();
+    assert!(!s.verbose);
+    assert_eq!(
+        s.command,
+        Command::Add(AddSettings {
+            force: true,
+            files: vec!["a".into(), "b".into()],
+        })
+    );
+
+    let s = Settings::try_parse(["git", "rm", "-r", "dir"]).unwrap();
+    assert_eq!(
+        s.command,
+        Command::Remove(RemoveSettings {
+            recursive: true,
+            files: vec!["dir".into()],
+        })
+    );
+}
+
+#[test]
+fn global_option() {
+    for args in [
+        ["git", "-v", "add", "a"],
+        ["git", "--verbose", "remove", "a"],
+    ] {
+        let s = Settings::try_parse(args).unwrap();
+        assert!(s.verbose);
+        assert_ne!(s.command, Command::None);
+    }
+
+    // After the subcommand, the options are parsed by the subcommand
+    assert!(matches!(
+        Settings::try_parse(["git", "add", "-v", "a"]),
+        Err(Error::UnexpectedOption(_))
+    ));
+}
+
+#[test]
+fn arguments_passed_as_is() {
+    // An operand that looks like a subcommand is an operand of the subcommand
+    let s = Settings::try_parse(["git", "add", "--", "-f", "rm"]).unwrap();
+    assert_eq!(
+        s.command,
+        Command::Add(AddSettings {
+            force: false,
+            files: vec!["-f".into(), "rm".into()],
+        })
+    );
+}
+
+#[test]
+fn abbreviated() {
+    let s = Settings::try_parse(["git", "ad", "a"]).unwrap();
+    assert!(matches!(s.command, Command::Add(_)));
+
+    let s = Settings::try_parse(["git", "rem", "a"]).unwrap();
+    assert!(matches!(s.command, Command::Remove(_)));
+
+    // A prefix of more than one name is ambiguous
+    let s = Settings::try_parse(["git", "r", "a"]);
+    assert!(matches!(
+        s,
+        Err(Error::AmbiguousSubcommand { name, candidates })
+            if name == "r" && candidates == ["remove", "rm"]
+    ));
+}
+
+#[test]
+fn unknown() {
+    let err = Settings::try_parse(["git", "commit"]).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "unrecognized command 'commit'\nValid commands are:\n  - add\n  - remove\n  - rm"
+    );
+}
+
+#[test]
+fn missing() {
+    let err = Settings::try_parse(["git", "-v"]).err().unwrap();
+    assert_eq!(err.to_string(), "missing COMMAND operand");
+
+    // The subcommand checks its own arguments
+    let err = Settings::try_parse(["git", "add"]).err().unwrap();
+    assert_eq!(err.to_string(), "missing FILE operand");
+}
+
+#[test]
+fn help() {
+    let help = Arg::help("git");
+    assert!(help.contains("git [OPTION]... COMMAND [ARGS]...\n"));
+    assert!(help.contains("\nCommands:\n  add               Add files to the index\n"));
+    assert!(help.contains("  remove, rm        Remove files from the index\n"));
+}
+
+#[test]
+fn completion() {
+    let fish = complete::render(&Arg::complete(), "fish");
+    assert!(fish.contains("add"));
+    assert!(fish.contains("rm"));
+
+    let meta = Arg::options().iter().find(|o| o.name == "Remove").unwrap();
+    assert_eq!(meta.metavar, Some("COMMAND"));
+}
+
+#[test]
+fn subcommand_help() {
+    let Err(Error::DisplayHelp(help)) = Settings::try_parse(["git", "add", "--help"]) else {
+        panic!("expected the help of the subcommand");
+    };
+    assert!(help.contains("git add [OPTION]... FILE...\n"));
+    assert!(help.contains("  -f, --force"));
+    assert!(!help.contains("--verbose"));
+}
+
+#[test]
+fn subcommand_with_name() {
+    let s = Settings::try_parse_with_name("mygit", ["-v", "rm", "a"]).unwrap();
+    assert!(s.verbose);
+    assert!(matches!(s.command, Command::Remove(_)));
+
+    let Err(Error::DisplayHelp(help)) = Settings::try_parse_with_name("mygit", ["add", "--help"])
+    else {
+        panic!("expected the help of the subcommand");
+    };
+    assert!(help.contains("mygit add [OPTION]... FILE...\n"));
+}
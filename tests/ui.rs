@@ -0,0 +1,10 @@
+//! Attributes that the derives reject, with the error and where it points.
+//!
+//! Run with `TRYBUILD=overwrite` to update the expected errors after a
+//! change to a message.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,19 @@
+// The attributes of a field are checked too.
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-a")]
+    All,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[map(Arg::All => true)]
+    #[field(defualt = true)]
+    all: bool,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// A short flag must be a single character.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-ab")]
+    All,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+// A `default` is a single value, so it cannot be given to a positional
+// argument that collects its values.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[positional(last, .., default = "-")]
+    Files(Vec<String>),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// The items of an option with a `delimiter` are collected into a `Vec`.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--fields=LIST", delimiter = ',')]
+    Fields(u32),
+}
+
+fn main() {}
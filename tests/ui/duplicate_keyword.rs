@@ -0,0 +1,13 @@
+// Every keyword belongs to one option.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option(keyword = "skip=N")]
+    Skip(u64),
+    #[option(keyword = "seek=N", keyword = "skip=N")]
+    Seek(u64),
+}
+
+fn main() {}
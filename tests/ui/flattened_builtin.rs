@@ -0,0 +1,18 @@
+// The help and version options cannot be options of a flattened enum.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+#[arguments(help = ["--usage"])]
+enum Common {
+    #[option("--help")]
+    Help,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[flatten]
+    Common(Common),
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+// A flattened enum cannot have an option of the enum that flattens it.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Common {
+    #[option("-d", "--debug")]
+    Debug,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-d DELIM")]
+    Delimiter(String),
+    #[flatten]
+    Common(Common),
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// Two flattened enums cannot have an option in common.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Common {
+    #[option("--debug")]
+    Debug,
+}
+
+#[derive(Clone, Arguments)]
+enum Trace {
+    #[option("--debug")]
+    Debug,
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[flatten]
+    Common(Common),
+    #[flatten]
+    Trace(Trace),
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// The values of a `from_end` positional argument are split off from those
+// of the unbounded positional argument right before it.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[positional(1)]
+    Source(String),
+    #[positional(from_end = 1)]
+    Dest(String),
+}
+
+fn main() {}
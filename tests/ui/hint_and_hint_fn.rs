@@ -0,0 +1,15 @@
+// The values to complete come either from a `hint` or from a `hint_fn`.
+
+use uutils_args::Arguments;
+
+fn users() -> Vec<String> {
+    vec!["root".into()]
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--owner=OWNER", hint = Username, hint_fn = users)]
+    Owner(String),
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+// Only a field that is read from the environment can ignore an invalid
+// environment variable.
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-w COLS")]
+    Width(u16),
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::Width)]
+    #[field(ignore_invalid_env, default = 80)]
+    width: u16,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// A keyword option is an operand like `bs=1M` and cannot have flags.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--block-size=BYTES", keyword = "bs=BYTES")]
+    BlockSize(u64),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// The range of a positional argument cannot be empty.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[positional(3..1)]
+    File(String),
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+// Extra completions are read from a file that exists.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+#[arguments(completion_extra = "completions/missing.fish")]
+enum Arg {
+    #[option("-a")]
+    All,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+// The help file must exist.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+#[arguments(file = "does/not/exist.md")]
+enum Arg {
+    #[option("-a")]
+    All,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Variants take their value in a single unnamed field.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-w WIDTH")]
+    Width { width: u64 },
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+// An exhaustive `choice` must list every variant of the type of the field.
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--quiet")]
+    Quiet,
+    #[option("--status")]
+    Status,
+}
+
+#[derive(Default)]
+enum CheckOutput {
+    #[default]
+    Warn,
+    Quiet,
+    Status,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[choice(
+        exhaustive,
+        Arg::Quiet => CheckOutput::Quiet,
+        Arg::Status => CheckOutput::Status,
+    )]
+    check_output: CheckOutput,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+// A variant is either an option or a positional argument.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-f FILE")]
+    #[positional]
+    File(String),
+}
+
+fn main() {}
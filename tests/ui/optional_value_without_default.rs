@@ -0,0 +1,12 @@
+// An optional value needs a `default` for when it is left out, unless the
+// field is like `Option<T>`.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--color[=WHEN]")]
+    Color(String),
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// No positional argument can follow one that takes any number of values,
+// because it would never get a value.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[positional(..)]
+    Source(String),
+    #[positional(1)]
+    Dest(String),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Unknown arguments in an attribute are not ignored.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("-a", hiden)]
+    All,
+}
+
+fn main() {}
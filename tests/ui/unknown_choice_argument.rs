@@ -0,0 +1,25 @@
+// The arguments in a `choice` must be variants of the argument type.
+
+use uutils_args::{Arguments, Options};
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    #[option("--quiet")]
+    Quiet,
+}
+
+#[derive(Default)]
+enum CheckOutput {
+    #[default]
+    Warn,
+    Quiet,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[choice(Arg::Quiet => CheckOutput::Quiet, Arg::Warn => CheckOutput::Warn)]
+    check_output: CheckOutput,
+}
+
+fn main() {}
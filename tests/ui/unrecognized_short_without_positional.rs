@@ -0,0 +1,13 @@
+// Unrecognized short options are operands, so there must be a positional
+// argument to take them.
+
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+#[arguments(unrecognized_short_as_positional)]
+enum Arg {
+    #[option("-R")]
+    Recursive,
+}
+
+fn main() {}
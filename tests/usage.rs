@@ -0,0 +1,91 @@
+use uutils_args::Arguments;
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Mktemp {
+    #[option("-d", "--directory")]
+    Directory,
+
+    #[positional(0..=1)]
+    Template(String),
+}
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Unlink {
+    #[positional(1)]
+    File(String),
+}
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Shred {
+    #[positional(1..)]
+    File(String),
+}
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Ls {
+    #[positional(..)]
+    File(String),
+}
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+enum Timeout {
+    #[positional(1)]
+    Duration(String),
+
+    #[positional(1, command)]
+    Command(String),
+
+    #[positional(..)]
+    Args(Vec<String>),
+}
+
+#[derive(Clone, Arguments)]
+#[allow(dead_code)]
+#[arguments(file = "tests/fixtures/help/dir.md")]
+enum Dir {
+    #[positional(..)]
+    File(String),
+}
+
+fn usage<T: Arguments>(name: &str) -> String {
+    let help = T::help(name);
+    let (_, usage) = help.split_once("Usage:\n").unwrap();
+    usage.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn optional() {
+    assert_eq!(usage::<Mktemp>("mktemp"), "  mktemp [OPTION]... [TEMPLATE]");
+    assert_eq!(Mktemp::complete().usage, "[OPTION]... [TEMPLATE]");
+}
+
+#[test]
+fn required() {
+    assert_eq!(usage::<Unlink>("unlink"), "  unlink [OPTION]... FILE");
+    assert_eq!(Unlink::complete().usage, "[OPTION]... FILE");
+}
+
+#[test]
+fn open_ended() {
+    assert_eq!(usage::<Shred>("shred"), "  shred [OPTION]... FILE...");
+    assert_eq!(usage::<Ls>("ls"), "  ls [OPTION]... [FILE]...");
+    assert_eq!(Ls::complete().usage, "[OPTION]... [FILE]...");
+}
+
+#[test]
+fn after_command() {
+    assert_eq!(
+        usage::<Timeout>("timeout"),
+        "  timeout [OPTION]... DURATION COMMAND [ARGS]..."
+    );
+}
+
+#[test]
+fn from_help_file() {
+    assert_eq!(usage::<Dir>("vdir"), "  vdir [OPTION]... [FILE]...");
+}
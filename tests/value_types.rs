@@ -0,0 +1,467 @@
+use std::ffi::OsString;
+
+use uutils_args::{
+    complete::ValueHint,
+    value_types::{
+        Duration, Pattern, Percentage, Ratio, RelativeOrAbsoluteDate, SigNum, Sign, TimeStyleValue,
+    },
+    Arguments, Error, FromValue, Options,
+};
+
+fn time_style(value: &str) -> Result<TimeStyleValue, Error> {
+    TimeStyleValue::from_value("--time-style", OsString::from(value))
+}
+
+fn percentage<const BARE: bool, const OVER: bool>(
+    value: &str,
+) -> Result<Percentage<BARE, OVER>, Error> {
+    Percentage::from_value("--output", OsString::from(value))
+}
+
+fn ratio(value: &str) -> Result<Ratio, Error> {
+    Ratio::from_value("--ratio", OsString::from(value))
+}
+
+fn count(value: &str) -> Result<SigNum, Error> {
+    SigNum::from_value("-c", OsString::from(value))
+}
+
+fn pattern(value: &str) -> Result<Pattern, Error> {
+    Pattern::from_value("--ignore", OsString::from(value))
+}
+
+fn duration(value: &str) -> Result<std::time::Duration, Error> {
+    Duration::from_value("--sleep-interval", OsString::from(value)).map(Into::into)
+}
+
+fn date(value: &str) -> Result<RelativeOrAbsoluteDate, Error> {
+    RelativeOrAbsoluteDate::from_value("--date", OsString::from(value))
+}
+
+#[test]
+fn time_style_keywords() {
+    assert_eq!(time_style("full-iso").unwrap(), TimeStyleValue::FullIso);
+    assert_eq!(time_style("long-iso").unwrap(), TimeStyleValue::LongIso);
+    assert_eq!(time_style("iso").unwrap(), TimeStyleValue::Iso);
+    assert_eq!(time_style("locale").unwrap(), TimeStyleValue::Locale);
+}
+
+#[test]
+fn time_style_abbreviations() {
+    assert_eq!(time_style("f").unwrap(), TimeStyleValue::FullIso);
+    assert_eq!(time_style("long").unwrap(), TimeStyleValue::LongIso);
+    assert_eq!(time_style("i").unwrap(), TimeStyleValue::Iso);
+    assert_eq!(time_style("loc").unwrap(), TimeStyleValue::Locale);
+
+    let Err(Error::AmbiguousValue {
+        option,
+        value,
+        candidates,
+    }) = time_style("l")
+    else {
+        panic!("expected an ambiguous value");
+    };
+    assert_eq!(option, "--time-style");
+    assert_eq!(value, "l");
+    assert_eq!(candidates, ["long-iso", "locale"]);
+}
+
+#[test]
+fn time_style_format() {
+    assert_eq!(
+        time_style("+%Y-%m-%d").unwrap(),
+        TimeStyleValue::Format("%Y-%m-%d".into())
+    );
+    assert_eq!(time_style("+").unwrap(), TimeStyleValue::Format("".into()));
+    assert_eq!(
+        time_style("+iso").unwrap(),
+        TimeStyleValue::Format("iso".into())
+    );
+}
+
+#[test]
+fn time_style_invalid() {
+    for value in ["", "posix", "isox", "%Y"] {
+        assert!(matches!(
+            time_style(value),
+            Err(Error::ParsingFailed { .. }) | Err(Error::AmbiguousValue { .. })
+        ));
+    }
+    let Err(Error::ParsingFailed { option, value, .. }) = time_style("posix") else {
+        panic!("expected a parsing error");
+    };
+    assert_eq!(option, "--time-style");
+    assert_eq!(value, "posix");
+}
+
+#[test]
+fn time_style_hint() {
+    let ValueHint::Strings(keywords) = TimeStyleValue::value_hint() else {
+        panic!("expected the keywords as hint");
+    };
+    assert_eq!(keywords, ["full-iso", "long-iso", "iso", "locale"]);
+}
+
+#[test]
+fn date_epoch() {
+    assert_eq!(date("@0").unwrap(), RelativeOrAbsoluteDate::Epoch(0));
+    assert_eq!(
+        date("@1700000000").unwrap(),
+        RelativeOrAbsoluteDate::Epoch(1_700_000_000)
+    );
+    assert_eq!(
+        date("@-86400").unwrap(),
+        RelativeOrAbsoluteDate::Epoch(-86400)
+    );
+
+    for value in ["@", "@abc", "@1.5", "@ 1"] {
+        let Err(Error::ParsingFailed { option, .. }) = date(value) else {
+            panic!("expected a parsing error for {value:?}");
+        };
+        assert_eq!(option, "--date");
+    }
+}
+
+#[test]
+fn date_absolute() {
+    assert_eq!(
+        date("2024-02-29").unwrap(),
+        RelativeOrAbsoluteDate::Absolute {
+            year: 2024,
+            month: 2,
+            day: 29,
+            time: None,
+        }
+    );
+    assert_eq!(
+        date("2000-12-31 23:59").unwrap(),
+        RelativeOrAbsoluteDate::Absolute {
+            year: 2000,
+            month: 12,
+            day: 31,
+            time: Some((23, 59, 0)),
+        }
+    );
+    assert_eq!(
+        date("1970-01-01 00:00:59").unwrap(),
+        RelativeOrAbsoluteDate::Absolute {
+            year: 1970,
+            month: 1,
+            day: 1,
+            time: Some((0, 0, 59)),
+        }
+    );
+}
+
+#[test]
+fn date_absolute_invalid() {
+    for value in [
+        "2024-00-01",
+        "2024-13-01",
+        "2024-01-00",
+        "2024-04-31",
+        "2023-02-29",
+        "1900-02-29",
+        "2024-01-01 24:00",
+        "2024-01-01 12:60",
+        "2024-01-01 12:00:60",
+    ] {
+        let Err(Error::ParsingFailed {
+            option, value: v, ..
+        }) = date(value)
+        else {
+            panic!("expected a parsing error for {value:?}");
+        };
+        assert_eq!(option, "--date");
+        assert_eq!(v, value);
+    }
+}
+
+#[test]
+fn date_passthrough() {
+    for value in [
+        "",
+        "now",
+        "yesterday",
+        "next thursday",
+        "2 days ago",
+        "2024-1-1",
+        "24-01-01",
+        "2024-01-01T12:00",
+        "2024-01-01 12:00 +0200",
+        "2024-01-01 1:00",
+        "2024-01-01 12",
+        "2024/01/01",
+    ] {
+        assert_eq!(
+            date(value).unwrap(),
+            RelativeOrAbsoluteDate::Other(value.into())
+        );
+    }
+}
+
+#[test]
+fn in_arguments() {
+    #[derive(Clone, Arguments)]
+    enum Arg {
+        #[option("-d DATE", "--date=DATE")]
+        Date(RelativeOrAbsoluteDate),
+
+        #[option("--time-style=STYLE")]
+        TimeStyle(TimeStyleValue),
+    }
+
+    #[derive(Default, Options)]
+    #[arg_type(Arg)]
+    struct Settings {
+        #[map(Arg::Date(d) => Some(d))]
+        date: Option<RelativeOrAbsoluteDate>,
+        #[map(Arg::TimeStyle(s) => Some(s))]
+        time_style: Option<TimeStyleValue>,
+    }
+
+    let s = Settings::try_parse(["touch", "-d", "@10", "--time-style=long"]).unwrap();
+    assert_eq!(s.date, Some(RelativeOrAbsoluteDate::Epoch(10)));
+    assert_eq!(s.time_style, Some(TimeStyleValue::LongIso));
+
+    assert!(Settings::try_parse(["touch", "--date=2024-13-01"]).is_err());
+}
+
+#[test]
+fn percentage_valid() {
+    let p = |value| percentage::<true, false>(value).unwrap().fraction();
+    assert_eq!(p("0%"), 0.0);
+    assert_eq!(p("50%"), 0.5);
+    assert_eq!(p("100%"), 1.0);
+    assert_eq!(p("12.5%"), 0.125);
+    assert_eq!(p("50"), 0.5);
+    assert_eq!(p("-0"), 0.0);
+    assert_eq!(percentage::<true, false>("50%").unwrap().percent(), 50.0);
+}
+
+#[test]
+fn percentage_invalid() {
+    for value in [
+        "", "%", "abc", "50%%", "5 0%", "inf", "nan", "1e2", "+5%", ".", "-1%", "100.5%",
+    ] {
+        let Err(Error::ParsingFailed {
+            option, value: v, ..
+        }) = percentage::<true, false>(value)
+        else {
+            panic!("expected a parsing error for {value:?}");
+        };
+        assert_eq!(option, "--output");
+        assert_eq!(v, value);
+    }
+
+    let err = percentage::<true, false>("-5%").unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "invalid argument '-5%' for '--output': Percentage cannot be negative"
+    );
+    let err = percentage::<true, false>("101").unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "invalid argument '101' for '--output': Percentage cannot be more than 100%"
+    );
+}
+
+#[test]
+fn percentage_options() {
+    assert!(percentage::<false, false>("50").is_err());
+    assert_eq!(percentage::<false, false>("50%").unwrap().fraction(), 0.5);
+
+    assert!(percentage::<true, false>("150%").is_err());
+    assert_eq!(percentage::<true, true>("150%").unwrap().fraction(), 1.5);
+    assert!(percentage::<true, true>("-150%").is_err());
+}
+
+#[test]
+fn percentage_display() {
+    assert_eq!(percentage::<true, false>("50").unwrap().to_string(), "50%");
+    assert_eq!(
+        percentage::<true, false>("12.5%").unwrap().to_string(),
+        "12.5%"
+    );
+}
+
+#[test]
+fn ratio_valid() {
+    assert_eq!(
+        ratio("16/9").unwrap(),
+        Ratio {
+            numerator: 16,
+            denominator: 9
+        }
+    );
+    assert_eq!(ratio("0/1").unwrap().as_f64(), 0.0);
+    assert_eq!(ratio("3/2").unwrap().as_f64(), 1.5);
+    assert_eq!(ratio("3/2").unwrap().to_string(), "3/2");
+}
+
+#[test]
+fn ratio_invalid() {
+    for value in [
+        "", "1", "/", "1/", "/2", "1/2/3", "a/b", "-1/2", "1/-2", "1.5/2", " 1/2",
+    ] {
+        let Err(Error::ParsingFailed {
+            option, value: v, ..
+        }) = ratio(value)
+        else {
+            panic!("expected a parsing error for {value:?}");
+        };
+        assert_eq!(option, "--ratio");
+        assert_eq!(v, value);
+    }
+
+    assert_eq!(
+        ratio("1/0").unwrap_err().to_string(),
+        "invalid argument '1/0' for '--ratio': Denominator cannot be zero"
+    );
+}
+
+#[test]
+fn pattern_matches() {
+    let matches = |p: &str, s: &str| pattern(p).unwrap().matches(s);
+    assert!(matches("*", ""));
+    assert!(matches("*", ".hidden"));
+    assert!(matches("*.rs", "lib.rs"));
+    assert!(!matches("*.rs", "lib.rs.bak"));
+    assert!(matches("a*b*c", "aXbYbZc"));
+    assert!(!matches("a*b*c", "aXbY"));
+    assert!(matches("?", "ü"));
+    assert!(!matches("?", ""));
+    assert!(matches("\\*", "*"));
+    assert!(!matches("\\*", "x"));
+    assert!(matches("[a-c]x", "bx"));
+    assert!(!matches("[!a-c]x", "bx"));
+    assert!(matches("[^a-c]x", "dx"));
+    assert!(matches("[]]", "]"));
+    assert!(matches("[a-]", "-"));
+    assert!(matches("[[:digit:]]*", "1abc"));
+    assert!(!matches("[[:digit:]]*", "abc"));
+    assert!(matches("*~", "file~"));
+}
+
+#[test]
+fn pattern_invalid() {
+    for (value, error) in [
+        ("[abc", "Unclosed '[' in pattern"),
+        ("[", "Unclosed '[' in pattern"),
+        ("[]", "Unclosed '[' in pattern"),
+        ("[[:alpha:]", "Unclosed '[' in pattern"),
+        ("[z-a]", "Invalid range 'z-a' in pattern"),
+        ("[[:foo:]]", "Invalid character class 'foo' in pattern"),
+        ("abc\\", "Pattern cannot end with a backslash"),
+    ] {
+        let Err(Error::ParsingFailed {
+            option,
+            value: v,
+            error: e,
+        }) = pattern(value)
+        else {
+            panic!("expected a parsing error for {value:?}");
+        };
+        assert_eq!(option, "--ignore");
+        assert_eq!(v, value);
+        assert_eq!(e.to_string(), error);
+    }
+}
+
+#[test]
+fn pattern_display() {
+    assert_eq!(pattern("[a-z]*").unwrap().to_string(), "[a-z]*");
+    assert_eq!(pattern("*.rs").unwrap(), pattern("*.rs").unwrap());
+    assert!(matches!(Pattern::value_hint(), ValueHint::Unknown));
+}
+
+#[test]
+fn signed_count() {
+    assert_eq!(count("100").unwrap(), SigNum::new(Sign::Plus, 100));
+    assert_eq!(count("+100").unwrap(), SigNum::new(Sign::Plus, 100));
+    assert_eq!(count("-100").unwrap(), SigNum::new(Sign::Minus, 100));
+    assert_eq!(count("-0").unwrap().sign(), Sign::Minus);
+    assert_eq!(count("0").unwrap().to_string(), "+0");
+
+    let bare: SigNum<true> = SigNum::from_value("-n", "7".into()).unwrap();
+    assert_eq!(bare, SigNum::new(Sign::Minus, 7));
+}
+
+#[test]
+fn signed_count_suffixes() {
+    let magnitude = |value: &str| count(value).unwrap().magnitude();
+    assert_eq!(magnitude("2b"), 1024);
+    assert_eq!(magnitude("1k"), 1024);
+    assert_eq!(magnitude("1K"), 1024);
+    assert_eq!(magnitude("1KiB"), 1024);
+    assert_eq!(magnitude("1KB"), 1000);
+    assert_eq!(magnitude("-3M"), 3 * 1024 * 1024);
+    assert_eq!(magnitude("1GB"), 1_000_000_000);
+    assert_eq!(magnitude("1E"), 1 << 60);
+}
+
+#[test]
+fn signed_count_errors() {
+    for (value, error) in [
+        ("", "Invalid count"),
+        ("+", "Invalid count"),
+        ("--5", "Invalid count"),
+        ("K", "Invalid count"),
+        ("5x", "Invalid suffix"),
+        ("5KiBB", "Invalid suffix"),
+        ("16E", "Value too large"),
+        (
+            "99999999999999999999999999999999999999999",
+            "Value too large",
+        ),
+    ] {
+        assert_eq!(
+            count(value).unwrap_err().to_string(),
+            format!("invalid argument '{value}' for '-c': {error}"),
+        );
+    }
+}
+
+#[test]
+fn duration_valid() {
+    use std::time::Duration as Std;
+
+    assert_eq!(duration("5").unwrap(), Std::from_secs(5));
+    assert_eq!(duration("5s").unwrap(), Std::from_secs(5));
+    assert_eq!(duration("0.5m").unwrap(), Std::from_secs(30));
+    assert_eq!(duration("1.5h").unwrap(), Std::from_secs(90 * 60));
+    assert_eq!(duration("2d").unwrap(), Std::from_secs(2 * 24 * 60 * 60));
+    assert_eq!(duration("1e3").unwrap(), Std::from_secs(1000));
+    assert_eq!(duration(".25").unwrap(), Std::from_millis(250));
+    assert_eq!(duration("0").unwrap(), Std::ZERO);
+    assert_eq!(duration("-0").unwrap(), Std::ZERO);
+}
+
+#[test]
+fn duration_saturates() {
+    assert_eq!(duration("inf").unwrap(), std::time::Duration::MAX);
+    assert_eq!(duration("1e400").unwrap(), std::time::Duration::MAX);
+    assert_eq!(duration("1e300d").unwrap(), std::time::Duration::MAX);
+}
+
+#[test]
+fn duration_invalid() {
+    for value in ["-1", "-0.5m", "abc", "", "s", "5x", "5 s", "nan", "-inf"] {
+        assert!(duration(value).is_err(), "{value:?} must be rejected");
+    }
+    assert_eq!(
+        duration("-1").unwrap_err().to_string(),
+        "invalid argument '-1' for '--sleep-interval': Duration cannot be negative"
+    );
+    assert_eq!(
+        duration("abc").unwrap_err().to_string(),
+        "invalid argument 'abc' for '--sleep-interval': Invalid duration"
+    );
+}
+
+#[test]
+fn duration_display() {
+    let d = Duration::from_value("", OsString::from("1.5m")).unwrap();
+    assert_eq!(d.to_string(), "90s");
+    assert_eq!(d.get(), std::time::Duration::from_secs(90));
+}
@@ -0,0 +1,71 @@
+use uutils_args::{Arguments, HelpConfig, Version};
+
+#[derive(Clone, Arguments)]
+enum Plain {}
+
+#[derive(Clone, Arguments)]
+#[arguments(version_extra = concat!("features: ", "color"))]
+enum Extra {}
+
+#[test]
+fn default_rendering() {
+    let version = env!("CARGO_PKG_VERSION");
+    assert_eq!(Plain::version(), format!("{} {version}", Plain::NAME));
+    assert_eq!(
+        Plain::version_info(),
+        Version {
+            name: Plain::NAME,
+            version,
+            extra: None,
+        }
+    );
+}
+
+#[test]
+fn extra() {
+    let version = env!("CARGO_PKG_VERSION");
+    let info = Extra::version_info();
+    assert_eq!(info.extra.as_deref(), Some("features: color"));
+    assert_eq!(
+        Extra::version(),
+        format!("{} {version}\nfeatures: color", Extra::NAME)
+    );
+}
+
+#[test]
+fn name_from_config() {
+    let config = HelpConfig {
+        name: "dir",
+        ..HelpConfig::default()
+    };
+    let info = Plain::version_info_with_config(&config);
+    assert_eq!(info.name, "dir");
+    assert_eq!(
+        Plain::parse(["dir"])
+            .with_help_config(config)
+            .version_info(),
+        info
+    );
+}
+
+#[test]
+fn json() {
+    let info = Version {
+        name: "ls",
+        version: "0.1.0",
+        extra: None,
+    };
+    assert_eq!(
+        info.to_json(),
+        r#"{"name":"ls","version":"0.1.0","extra":null}"#
+    );
+
+    let info = Version {
+        extra: Some("git \"abc\"\nfeatures:\tcolor".into()),
+        ..info
+    };
+    assert_eq!(
+        info.to_json(),
+        r#"{"name":"ls","version":"0.1.0","extra":"git \"abc\"\nfeatures:\tcolor"}"#
+    );
+}
@@ -0,0 +1,185 @@
+use std::{cell::RefCell, ffi::OsString};
+
+use uutils_args::{Arguments, Error, FromValue, Options, ParseConfig, Warning};
+
+/// A tab size that warns about sizes over 32, but still accepts them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct TabSize(usize);
+
+impl FromValue for TabSize {
+    fn from_value(option: &str, value: OsString) -> Result<Self, Error> {
+        let size = usize::from_value(option, value)?;
+        if size > 32 {
+            uutils_args::warn(Warning::Custom(format!("large tab size {size}")));
+        }
+        Ok(Self(size))
+    }
+}
+
+#[derive(Clone, Arguments)]
+enum Arg {
+    /// Assume tab stops at each SIZE
+    #[option("-T SIZE", "--tabsize=SIZE")]
+    TabSize(TabSize),
+
+    /// Set the output width
+    #[option("-w COLS", "--width=COLS")]
+    Width(u16),
+
+    /// Keep trying to open a file
+    #[option("--retry", deprecated = "--retry is ignored")]
+    Retry,
+}
+
+#[derive(Default, Options)]
+#[arg_type(Arg)]
+struct Settings {
+    #[set(Arg::TabSize)]
+    tab_size: TabSize,
+
+    #[set(Arg::Width)]
+    #[field(env = "COLUMNS", ignore_invalid_env, default = 80)]
+    width: u16,
+
+    #[map(Arg::Retry => true)]
+    retry: bool,
+}
+
+fn bogus_columns(name: &str) -> Option<OsString> {
+    (name == "COLUMNS").then(|| "wide".into())
+}
+
+fn parse(
+    env: fn(&str) -> Option<OsString>,
+    args: &'static [&'static str],
+) -> (Result<Settings, Error>, Vec<Warning>) {
+    let config = ParseConfig {
+        env,
+        ..ParseConfig::default()
+    };
+    Settings::try_parse_iter_with_warnings(Arg::parse(args.iter().copied()).with_config(config))
+}
+
+#[test]
+fn invalid_env() {
+    let (s, warnings) = parse(bogus_columns, &["ls"]);
+    assert_eq!(s.unwrap().width, 80);
+    assert_eq!(
+        warnings,
+        [Warning::InvalidEnv {
+            var: "COLUMNS".into(),
+            value: "wide".into()
+        }]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "ignoring invalid value in environment variable COLUMNS: 'wide'"
+    );
+
+    // A valid value is used without a warning
+    let (s, warnings) = parse(|_| Some("100".into()), &["ls"]);
+    assert_eq!(s.unwrap().width, 100);
+    assert!(warnings.is_empty());
+
+    // The arguments still override the environment
+    let (s, _) = parse(bogus_columns, &["ls", "-w", "60"]);
+    assert_eq!(s.unwrap().width, 60);
+}
+
+#[test]
+fn in_order() {
+    let (s, warnings) = parse(bogus_columns, &["ls", "--retry", "-T", "40", "--retry"]);
+    let s = s.unwrap();
+    assert!(s.retry);
+    assert_eq!(s.tab_size, TabSize(40));
+    let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        [
+            "ignoring invalid value in environment variable COLUMNS: 'wide'",
+            "--retry is ignored",
+            "large tab size 40",
+            "--retry is ignored",
+        ]
+    );
+}
+
+#[test]
+fn returned_with_error() {
+    let (s, warnings) = parse(|_| None, &["ls", "--retry", "-w", "x"]);
+    assert!(matches!(s, Err(Error::ParsingFailed { .. })));
+    assert_eq!(
+        warnings,
+        [Warning::Deprecated {
+            option: "--retry".into(),
+            message: "--retry is ignored".into(),
+        }]
+    );
+}
+
+thread_local! {
+    static REPORTED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(bin_name: &str, warning: &str) {
+    REPORTED.with(|r| r.borrow_mut().push(format!("{bin_name}: {warning}")));
+}
+
+#[test]
+fn reported_without_collecting() {
+    let config = ParseConfig {
+        env: bogus_columns,
+        warn: record,
+        ..ParseConfig::default()
+    };
+    let s = Settings::try_parse_with_config(["ls", "-T", "33"], config).unwrap();
+    assert_eq!(s.width, 80);
+    assert_eq!(
+        REPORTED.with(|r| r.take()),
+        [
+            "ls: ignoring invalid value in environment variable COLUMNS: 'wide'",
+            "ls: large tab size 33",
+        ]
+    );
+}
+
+#[derive(Clone, Arguments)]
+enum ColorArg {
+    #[option("--color")]
+    Color,
+}
+
+#[derive(Default, Options)]
+#[arg_type(ColorArg)]
+#[validate(ColorSettings::check)]
+struct ColorSettings {
+    #[map(ColorArg::Color => true)]
+    color: bool,
+}
+
+impl ColorSettings {
+    fn check(&self) -> Result<(), Error> {
+        if self.color {
+            uutils_args::warn(Warning::Custom("color is not supported".into()));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn reported_from_validate() {
+    let config = ParseConfig {
+        warn: record,
+        ..ParseConfig::default()
+    };
+    let iter = ColorArg::parse(["ls", "--color"]).with_config(config);
+    let (s, _) = ColorSettings::try_parse_iter_with_provenance(iter).unwrap();
+    assert!(s.color);
+    assert_eq!(REPORTED.with(|r| r.take()), ["ls: color is not supported"]);
+
+    // The warning of a parse with a mask is not left for the next parse
+    let (s, _) = ColorSettings::try_parse_with_mask(["ls", "--color"]).unwrap();
+    assert!(s.color);
+    ColorSettings::try_parse_with_config(["dir"], config).unwrap();
+    assert!(REPORTED.with(|r| r.take()).is_empty());
+}